@@ -1,13 +1,16 @@
 use std::path::{Path, PathBuf};
 use std::env;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 use std::collections::HashMap;
 use log::{info, warn, error, debug};
 use serde::{Serialize, Deserialize};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
 
-use crate::disk_analyzer::{DiskAnalyzer};
+use crate::disk_analyzer::{DiskAnalyzer, format_file_size};
+use crate::migration_service::MigrationService;
+use crate::file_operations::{detect_link_type, LinkType};
 use crate::performance_optimizer::{PerformanceOptimizer, PerformanceConfig};
 
 /// AppData 分析器配置
@@ -15,10 +18,83 @@ use crate::performance_optimizer::{PerformanceOptimizer, PerformanceConfig};
 pub struct AppDataConfig {
     #[serde(rename = "minSizeThreshold")]
     pub min_size_threshold: u64,  // 最小大小阈值（默认1GB）
+    /// 最大扫描深度（默认2层）。深度从 AppData 根下的 Local/LocalLow/Roaming 算起：
+    /// 一级项目为第 1 层，其中的内容为第 2 层，依此类推。一级项目的大小总是完整递归统计，
+    /// 该深度只限制查找最近修改时间的范围，更深处的写入不影响项目的闲置天数
     #[serde(rename = "maxDepth")]
-    pub max_depth: usize,          // 最大扫描深度（默认2层）
+    pub max_depth: usize,
     #[serde(rename = "sortOrder")]
     pub sort_order: SortOrder,     // 排序方式
+    // 新增：按子树（Local/LocalLow/Roaming）设置的大文件夹阈值，未指定时回退到 min_size_threshold
+    #[serde(default)]
+    pub thresholds: HashMap<String, u64>,
+    // 新增：只保留超过指定天数未修改的项目（用于发现废弃的应用数据）
+    #[serde(rename = "minDaysSinceModified", default)]
+    pub min_days_since_modified: Option<u64>,
+    // 新增：计算迁移推荐分时大小、闲置时间和分类三项的权重
+    #[serde(rename = "scoreWeights", default)]
+    pub score_weights: MigrationScoreWeights,
+}
+
+/// 迁移推荐分各项的权重，按权重之和归一化，全部为 0 时分数为 0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationScoreWeights {
+    pub size: f64,
+    pub staleness: f64,
+    pub category: f64,
+}
+
+impl Default for MigrationScoreWeights {
+    fn default() -> Self {
+        Self { size: 0.5, staleness: 0.3, category: 0.2 }
+    }
+}
+
+/// 闲置天数达到该值时闲置得分为 0.5，越久越接近 1
+const STALENESS_REFERENCE_DAYS: f64 = 90.0;
+
+impl AppDataConfig {
+    /// 项目是否满足“超过 N 天未修改”的筛选条件（未设置时全部保留）
+    pub fn passes_age_filter(&self, item: &AppDataFirstLevelItem) -> bool {
+        match self.min_days_since_modified {
+            Some(min_days) => item.days_since_modified.is_some_and(|days| days >= min_days),
+            None => true,
+        }
+    }
+
+    /// 计算项目的迁移推荐分（0~1）：越大、越久未修改、越不像系统数据的项目分数越高，
+    /// 已迁移的项目为 0
+    pub fn migration_score(&self, item: &AppDataFirstLevelItem) -> f64 {
+        let weights = &self.score_weights;
+        let total_weight = weights.size.max(0.0) + weights.staleness.max(0.0) + weights.category.max(0.0);
+        if item.is_symlink || total_weight <= 0.0 {
+            return 0.0;
+        }
+
+        // 以所在子树的阈值为参照，达到阈值时大小得分为 0.5
+        let threshold = self.threshold_for(&item.parent_type).max(1) as f64;
+        let size_score = item.size as f64 / (item.size as f64 + threshold);
+        let staleness_score = item.days_since_modified
+            .map_or(0.0, |days| days as f64 / (days as f64 + STALENESS_REFERENCE_DAYS));
+        let category_score = if AppDataAnalyzer::blocklist_match(Path::new(&item.path)).is_some() {
+            0.0
+        } else {
+            category_migration_score(&item.category)
+        };
+
+        (weights.size.max(0.0) * size_score
+            + weights.staleness.max(0.0) * staleness_score
+            + weights.category.max(0.0) * category_score) / total_weight
+    }
+
+    /// 获取指定子树的大文件夹阈值
+    pub fn threshold_for(&self, parent_type: &str) -> u64 {
+        self.thresholds
+            .get(parent_type)
+            .copied()
+            .unwrap_or(self.min_size_threshold)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,12 +110,15 @@ impl Default for AppDataConfig {
             min_size_threshold: 1024 * 1024 * 1024, // 1GB
             max_depth: 2,
             sort_order: SortOrder::Desc,
+            thresholds: HashMap::new(),
+            min_days_since_modified: None,
+            score_weights: MigrationScoreWeights::default(),
         }
     }
 }
 
 /// AppData 一级项目信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppDataFirstLevelItem {
     pub path: String,
     pub name: String,
@@ -52,10 +131,28 @@ pub struct AppDataFirstLevelItem {
     pub is_large: bool,
     #[serde(rename = "sizePercentage")]
     pub size_percentage: f64,
+    // 新增：修改/访问时间，以及距今未修改的天数
+    #[serde(rename = "lastModified", default)]
+    pub last_modified: Option<SystemTime>,
+    #[serde(rename = "lastAccessed", default)]
+    pub last_accessed: Option<SystemTime>,
+    #[serde(rename = "daysSinceModified", default)]
+    pub days_since_modified: Option<u64>,
+    // 新增：按文件夹名称推断的分类（Browser、Developer、Games 等，未知为 Other）
+    #[serde(default)]
+    pub category: String,
+    // 新增：项目已被迁移，原位置是指向其他磁盘的链接（符号链接或目录联接）
+    #[serde(rename = "isSymlink", default)]
+    pub is_symlink: bool,
+    #[serde(rename = "linkTarget", default)]
+    pub link_target: Option<String>,
+    // 新增：迁移推荐分（0~1），越高越适合迁移
+    #[serde(rename = "migrationScore", default)]
+    pub migration_score: f64,
 }
 
 /// AppData 迁移选项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppDataMigrationOptions {
     #[serde(rename = "sourceItems")]
     pub source_items: Vec<String>, // 要迁移的项目路径列表
@@ -65,6 +162,176 @@ pub struct AppDataMigrationOptions {
     pub create_symlink: bool,      // 是否创建符号链接
     #[serde(rename = "deleteSource")]
     pub delete_source: bool,       // 是否删除源文件
+    #[serde(rename = "allowRisky", default)]
+    pub allow_risky: bool,         // 新增：是否允许迁移黑名单中的系统关键目录
+    #[serde(rename = "targetLayout", default)]
+    pub target_layout: TargetLayout, // 新增：目标盘上的目录布局
+}
+
+/// AppData 迁移计划中单个项目的验证结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppDataPlanItem {
+    pub path: String,
+    pub valid: bool,
+    pub message: String,
+    /// 项目大小（字节），不存在时为 0
+    pub size: u64,
+}
+
+/// AppData 迁移计划的整体验证结果（试运行，不做任何修改）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppDataPlanValidation {
+    pub valid: bool,
+    pub items: Vec<AppDataPlanItem>,
+    pub summary: String,
+    pub target_drive_valid: bool,
+    pub target_drive: String,
+    /// 所有选中项目的总大小
+    pub total_bytes: u64,
+    /// 含 20% 缓冲的所需空间
+    pub required_bytes: u64,
+    /// 目标磁盘可用空间（无法获取时为 None，此时不做空间检查）
+    pub available_bytes: Option<u64>,
+    /// 导致整体无效的原因（空间不足、存在无效项目等）
+    pub reasons: Vec<String>,
+    /// 不影响有效性的提示（例如对应程序似乎仍在运行）
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// AppData 项目在目标盘上的目录布局
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TargetLayout {
+    /// `<目标盘>/<名称>`，不同子目录下的同名项目会冲突
+    Flat,
+    /// `<目标盘>/AppData/<Local|LocalLow|Roaming>/<名称>`
+    #[default]
+    PreserveSubtree,
+    /// `<目标盘>/<文件夹>/<名称>`，例如统一放在 `MovedAppData` 下
+    UnderNamedFolder(String),
+}
+
+/// 无法从当前用户目录推断时使用的用户配置文件根目录
+const DEFAULT_USERS_ROOT: &str = "C:\\Users";
+
+/// 迁移后会导致 Windows 异常的 AppData 目录（相对于 AppData 目录）
+pub const APPDATA_BLOCKLIST: &[&str] = &[
+    "Local/Microsoft/Windows",
+    "Local/Microsoft/WindowsApps",
+    "Local/Microsoft/Credentials",
+    "Local/Packages",
+    "Local/ConnectedDevicesPlatform",
+    "Roaming/Microsoft/Windows",
+    "Roaming/Microsoft/Credentials",
+    "Roaming/Microsoft/Protect",
+    "Roaming/Microsoft/SystemCertificates",
+];
+
+/// 未命中任何分类规则时使用的分类
+pub const OTHER_CATEGORY: &str = "Other";
+
+/// AppData 一级项目的分类规则（相对于一级项目，按顺序匹配，先写更具体的规则）
+///
+/// 多级规则（如 `Google/Chrome`）要求一级项目名称匹配且其下存在对应子目录。
+pub const APPDATA_CATEGORIES: &[(&str, &str)] = &[
+    ("Google/Chrome", "Browser"),
+    ("Microsoft/Edge", "Browser"),
+    ("Mozilla/Firefox", "Browser"),
+    ("BraveSoftware", "Browser"),
+    ("Opera Software", "Browser"),
+    ("Vivaldi", "Browser"),
+    ("Microsoft/VSCode", "Developer"),
+    ("Microsoft/VisualStudio", "Developer"),
+    ("Code", "Developer"),
+    ("JetBrains", "Developer"),
+    ("npm", "Developer"),
+    ("npm-cache", "Developer"),
+    ("pip", "Developer"),
+    ("Docker", "Developer"),
+    ("Steam", "Games"),
+    ("Epic", "Games"),
+    ("EpicGamesLauncher", "Games"),
+    ("Battle.net", "Games"),
+    ("Riot Games", "Games"),
+    ("Ubisoft Game Launcher", "Games"),
+    ("Microsoft/Teams", "Communication"),
+    ("discord", "Communication"),
+    ("Slack", "Communication"),
+    ("Zoom", "Communication"),
+    ("Telegram Desktop", "Communication"),
+    ("Tencent", "Communication"),
+    ("Spotify", "Media"),
+    ("Adobe", "Media"),
+    ("obs-studio", "Media"),
+    ("Temp", "Cache"),
+    ("CrashDumps", "Cache"),
+];
+
+/// 分类对迁移推荐分的贡献：游戏、开发工具等数据通常体积大且迁移安全，缓存和未知分类居中
+fn category_migration_score(category: &str) -> f64 {
+    match category {
+        "Games" => 1.0,
+        "Developer" | "Media" => 0.8,
+        "Browser" | "Communication" => 0.6,
+        "Cache" => 0.4,
+        _ => 0.5,
+    }
+}
+
+/// 程序名与 AppData 文件夹名做前缀匹配时要求的最短长度，避免过短的名称误报
+const MIN_PROCESS_PREFIX_MATCH_LEN: usize = 4;
+
+/// 获取当前运行中的进程名称（尽力而为，无法读取时返回空列表）
+pub fn running_process_names() -> Vec<String> {
+    use sysinfo::{ProcessExt, System, SystemExt};
+
+    let mut system = System::new();
+    system.refresh_processes();
+    system.processes().values()
+        .map(|process| process.name().to_string())
+        .collect()
+}
+
+/// 归一化名称用于比较：去掉 `.exe` 后缀，只保留字母数字并转为小写
+fn normalize_app_name(name: &str) -> String {
+    let lower = name.to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// 查找与 AppData 项目文件夹名匹配的运行中进程（名称相同，或较长者以较短者开头）
+pub fn find_running_app_process(item: &Path, processes: &[String]) -> Option<String> {
+    let folder = normalize_app_name(&item.file_name()?.to_string_lossy());
+    if folder.is_empty() {
+        return None;
+    }
+
+    processes.iter()
+        .find(|process| {
+            let process_name = normalize_app_name(process);
+            if process_name.is_empty() {
+                return false;
+            }
+            let (shorter, longer) = if process_name.len() < folder.len() {
+                (&process_name, &folder)
+            } else {
+                (&folder, &process_name)
+            };
+            shorter == longer || (shorter.len() >= MIN_PROCESS_PREFIX_MATCH_LEN && longer.starts_with(shorter.as_str()))
+        })
+        .cloned()
+}
+
+/// AppData 分类汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppDataCategorySummary {
+    pub category: String,
+    pub size: u64,
+    #[serde(rename = "itemCount")]
+    pub item_count: usize,
 }
 
 /// AppData 信息结构体
@@ -90,6 +357,9 @@ pub struct AppDataInfo {
     pub large_items: Vec<AppDataFirstLevelItem>, // 1GB以上项目列表
     #[serde(rename = "scanTimeMs")]
     pub scan_time_ms: u64,
+    // 新增：已迁移项目的总大小，这部分数据已不占用本盘空间
+    #[serde(rename = "alreadyFreedBytes", default)]
+    pub already_freed_bytes: u64,
 }
 
 /// 扫描事件类型 - 用于流式扫描
@@ -109,25 +379,57 @@ pub enum ScanEvent {
     ScanError { error: String, path: String },
 }
 
+/// 流式扫描选项
+#[derive(Debug, Clone)]
+pub struct StreamingScanOptions {
+    /// 同时计算大小的一级项目数量上限（三个子树共享，至少为 1）
+    pub concurrency: usize,
+    /// 相邻两个事件之间的转发间隔，为 None 时不节流
+    pub event_throttle: Option<Duration>,
+}
+
+impl Default for StreamingScanOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            event_throttle: None,
+        }
+    }
+}
+
 /// 流式扫描器 - 支持并发扫描和实时事件推送
 pub struct StreamingAppDataScanner {
     analyzer: AppDataAnalyzer,
     event_tx: mpsc::UnboundedSender<ScanEvent>,
+    // 新增：并发上限和事件节流配置
+    options: StreamingScanOptions,
 }
 
 impl StreamingAppDataScanner {
     /// 创建新的流式扫描器
     pub fn new(analyzer: AppDataAnalyzer, event_tx: mpsc::UnboundedSender<ScanEvent>) -> Self {
-        Self { analyzer, event_tx }
+        Self { analyzer, event_tx, options: StreamingScanOptions::default() }
+    }
+
+    /// 设置并发上限和事件节流
+    pub fn with_options(mut self, options: StreamingScanOptions) -> Self {
+        self.options = options;
+        self
     }
 
     /// 执行并发流式扫描
     pub async fn scan_appdata_streaming(&self) -> Result<AppDataInfo, String> {
-        info!("开始并发流式扫描AppData目录");
-        let start_time = Instant::now();
-        
-        // 获取AppData路径
         let appdata_path = AppDataAnalyzer::get_appdata_path()?;
+        self.scan_appdata_streaming_at(&appdata_path).await
+    }
+
+    /// 以指定目录作为 AppData 根目录执行并发流式扫描
+    ///
+    /// 事件按 Local、LocalLow、Roaming 的顺序转发，每个子树内部保证
+    /// DirectoryStarted → ItemFound… → DirectoryCompleted 的顺序，最后发送 ScanCompleted。
+    pub async fn scan_appdata_streaming_at(&self, appdata_path: &Path) -> Result<AppDataInfo, String> {
+        info!("开始并发流式扫描AppData目录，并发上限: {}", self.options.concurrency.max(1));
+        let start_time = Instant::now();
         
         // 扫描三个主要子目录（并发执行，但实时推送事件）
         let local_path = appdata_path.join("Local");
@@ -145,61 +447,51 @@ impl StreamingAppDataScanner {
         let (tx3, mut rx3) = mpsc::unbounded_channel::<ScanEvent>();
         
         let main_tx = self.event_tx.clone();
+        let event_throttle = self.options.event_throttle.filter(|throttle| !throttle.is_zero());
         
-        // 启动事件转发任务
+        // 启动事件转发任务：按子树顺序转发，子树内部保持原有顺序
         let forward_task = tokio::spawn(async move {
             let mut total_items = 0;
             let mut total_size = 0u64;
             
-            // 转发Local事件
-            while let Some(event) = rx1.recv().await {
-                if let ScanEvent::ItemFound { .. } = &event {
-                    total_items += 1;
-                }
-                if let Err(_) = main_tx.send(event) {
-                    break;
-                }
-            }
-            
-            // 转发LocalLow事件
-            while let Some(event) = rx2.recv().await {
-                if let ScanEvent::ItemFound { ref item } = &event {
-                    total_items += 1;
-                    total_size += item.size;
-                }
-                if let Err(_) = main_tx.send(event) {
-                    break;
-                }
-            }
-            
-            // 转发Roaming事件
-            while let Some(event) = rx3.recv().await {
-                if let ScanEvent::ItemFound { ref item } = &event {
-                    total_items += 1;
-                    total_size += item.size;
-                }
-                if let Err(_) = main_tx.send(event) {
-                    break;
+            for rx in [&mut rx1, &mut rx2, &mut rx3] {
+                while let Some(event) = rx.recv().await {
+                    if let ScanEvent::ItemFound { ref item } = &event {
+                        total_items += 1;
+                        total_size += item.size;
+                    }
+                    if main_tx.send(event).is_err() {
+                        break;
+                    }
+                    if let Some(throttle) = event_throttle {
+                        tokio::time::sleep(throttle).await;
+                    }
                 }
             }
             
             (total_items, total_size)
         });
         
+        // 三个子树共享同一个信号量，限制同时计算大小的项目数量
+        let semaphore = Arc::new(Semaphore::new(self.options.concurrency.max(1)));
+        
         // 创建并发扫描任务
         let analyzer_clone = self.analyzer.clone();
+        let permits = semaphore.clone();
         let local_task = tokio::spawn(async move {
-            Self::scan_directory_with_events(&analyzer_clone, &local_path, "Local", tx1).await
+            Self::scan_directory_with_events(&analyzer_clone, &local_path, "Local", tx1, permits).await
         });
         
         let analyzer_clone = self.analyzer.clone();
+        let permits = semaphore.clone();
         let local_low_task = tokio::spawn(async move {
-            Self::scan_directory_with_events(&analyzer_clone, &local_low_path, "LocalLow", tx2).await
+            Self::scan_directory_with_events(&analyzer_clone, &local_low_path, "LocalLow", tx2, permits).await
         });
         
         let analyzer_clone = self.analyzer.clone();
+        let permits = semaphore;
         let roaming_task = tokio::spawn(async move {
-            Self::scan_directory_with_events(&analyzer_clone, &roaming_path, "Roaming", tx3).await
+            Self::scan_directory_with_events(&analyzer_clone, &roaming_path, "Roaming", tx3, permits).await
         });
         
         // 等待所有扫描任务完成
@@ -231,6 +523,8 @@ impl StreamingAppDataScanner {
         all_items.extend(local_items);
         all_items.extend(local_low_items);
         all_items.extend(roaming_items);
+        let already_freed_bytes = self.analyzer.detect_already_migrated(&mut all_items);
+        self.analyzer.assign_migration_scores(&mut all_items);
         
         Ok(AppDataInfo {
             local_path: local_path_str,
@@ -242,18 +536,20 @@ impl StreamingAppDataScanner {
             total_size,
             first_level_items: all_items.clone(),
             large_items: all_items.into_iter()
-                .filter(|item| item.size >= self.analyzer.config.min_size_threshold)
+                .filter(|item| self.analyzer.is_migration_candidate(item))
                 .collect(),
             scan_time_ms,
+            already_freed_bytes,
         })
     }
     
-    /// 扫描单个目录并发送事件
+    /// 扫描单个目录并发送事件，每个一级项目在取得信号量许可后于阻塞线程中计算大小
     async fn scan_directory_with_events(
         analyzer: &AppDataAnalyzer,
         path: &Path,
         parent_type: &str,
         event_tx: mpsc::UnboundedSender<ScanEvent>,
+        semaphore: Arc<Semaphore>,
     ) -> Result<(Vec<AppDataFirstLevelItem>, u64), String> {
         
         // 发送目录开始事件
@@ -265,29 +561,74 @@ impl StreamingAppDataScanner {
         let mut items = Vec::new();
         let mut total_size = 0u64;
         
-        // 扫描目录内容
-        match analyzer.scan_first_level_items(path, parent_type).await {
-            Ok((dir_items, dir_size)) => {
-                total_size = dir_size;
-                
-                // 逐个发送项目发现事件
-                for item in &dir_items {
-                    event_tx.send(ScanEvent::ItemFound {
-                        item: item.clone(),
-                    }).map_err(|e| format!("发送项目发现事件失败: {}", e))?;
+        let entries = if path.exists() {
+            match std::fs::read_dir(path) {
+                Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+                Err(e) => {
+                    let error = format!("读取目录失败: {}", e);
+                    // 发送扫描错误事件
+                    event_tx.send(ScanEvent::ScanError {
+                        error: error.clone(),
+                        path: path.display().to_string(),
+                    }).map_err(|e| format!("发送错误事件失败: {}", e))?;
+                    
+                    return Err(error);
                 }
-                
-                items = dir_items;
             }
-            Err(e) => {
-                // 发送扫描错误事件
-                event_tx.send(ScanEvent::ScanError {
-                    error: e.clone(),
-                    path: path.display().to_string(),
-                }).map_err(|e| format!("发送错误事件失败: {}", e))?;
-                
-                return Err(e);
+        } else {
+            warn!("目录不存在，跳过扫描: {}", path.display());
+            Vec::new()
+        };
+        
+        // 许可在派发前获取，因此同时运行的计算任务不会超过并发上限
+        let mut tasks = JoinSet::new();
+        for entry_path in entries {
+            let permit = semaphore.clone().acquire_owned().await
+                .map_err(|e| format!("获取扫描许可失败: {}", e))?;
+            let config = analyzer.config.clone();
+            let parent_type = parent_type.to_string();
+            tasks.spawn_blocking(move || {
+                let _permit = permit;
+                AppDataAnalyzer::build_first_level_item(&config, &entry_path, &parent_type)
+            });
+        }
+        
+        // 按完成顺序逐个发送项目发现事件
+        while let Some(joined) = tasks.join_next().await {
+            let mut item = match joined {
+                Ok(Ok(item)) => item,
+                Ok(Err(e)) => {
+                    warn!("获取项目信息失败: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("项目大小计算任务失败: {}", e);
+                    continue;
+                }
+            };
+            
+            let on_drive_size = AppDataAnalyzer::on_drive_size(Path::new(&item.path), item.size);
+            total_size += on_drive_size;
+            item.size_percentage = if total_size > 0 {
+                (on_drive_size as f64 / total_size as f64) * 100.0
+            } else {
+                0.0
+            };
+            
+            // 按修改时间筛选废弃项目（总大小仍包含全部项目）
+            if !analyzer.config.passes_age_filter(&item) {
+                continue;
             }
+            
+            event_tx.send(ScanEvent::ItemFound {
+                item: item.clone(),
+            }).map_err(|e| format!("发送项目发现事件失败: {}", e))?;
+            items.push(item);
+        }
+        
+        match analyzer.config.sort_order {
+            SortOrder::Desc => items.sort_by(|a, b| b.size.cmp(&a.size)),
+            SortOrder::Asc => items.sort_by(|a, b| a.size.cmp(&b.size)),
         }
         
         // 发送目录完成事件
@@ -301,6 +642,88 @@ impl StreamingAppDataScanner {
     }
 }
 
+/// 判断链接目标是否位于与链接不同的磁盘（Windows 上比较盘符）
+#[cfg(target_os = "windows")]
+fn is_off_drive(link: &Path, target: &Path) -> bool {
+    use std::path::{Component, Prefix};
+
+    // `\\?\D:\` 与 `D:\` 视为同一磁盘
+    let drive = |path: &Path| match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => Some((letter.to_ascii_uppercase() as char).to_string()),
+            _ => Some(prefix.as_os_str().to_string_lossy().to_uppercase()),
+        },
+        _ => None,
+    };
+    drive(link) != drive(target)
+}
+
+/// 判断链接目标是否位于与链接不同的磁盘（Unix 上比较所在设备）
+#[cfg(not(target_os = "windows"))]
+fn is_off_drive(link: &Path, target: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let link_device = link.parent().and_then(|parent| std::fs::metadata(parent).ok()).map(|m| m.dev());
+    let target_device = std::fs::metadata(target).ok().map(|m| m.dev());
+    matches!((link_device, target_device), (Some(link_device), Some(target_device)) if link_device != target_device)
+}
+
+/// 分页读取一级项目时未指定每页数量所用的默认值
+pub const DEFAULT_APPDATA_PAGE_SIZE: usize = 50;
+
+/// 一页 AppData 一级项目
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDataItemsPage {
+    pub items: Vec<AppDataFirstLevelItem>,
+    pub offset: usize,
+    /// 最近一次扫描的项目总数
+    pub total: usize,
+    pub has_more: bool,
+}
+
+/// 保存最近一次扫描的全部一级项目，供界面按页懒加载
+#[derive(Default)]
+pub struct AppDataItemPager {
+    items: std::sync::RwLock<Option<Vec<AppDataFirstLevelItem>>>,
+}
+
+impl AppDataItemPager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存一次扫描的项目：三个子树的项目合并后整体按大小排序，大小相同时按路径排序以保证分页稳定
+    pub fn store(&self, items: &[AppDataFirstLevelItem], sort_order: &SortOrder) {
+        let mut sorted = items.to_vec();
+        sorted.sort_by(|a, b| {
+            let by_size = match sort_order {
+                SortOrder::Desc => b.size.cmp(&a.size),
+                SortOrder::Asc => a.size.cmp(&b.size),
+            };
+            by_size.then_with(|| a.path.cmp(&b.path))
+        });
+        debug!("缓存AppData扫描结果供分页读取: {} 个项目", sorted.len());
+        *self.items.write().unwrap_or_else(|e| e.into_inner()) = Some(sorted);
+    }
+
+    /// 读取从 `offset` 开始的至多 `limit` 个项目（`limit` 为 0 时取默认每页数量），尚未扫描时返回错误
+    pub fn page(&self, offset: usize, limit: usize) -> Result<AppDataItemsPage, String> {
+        let items = self.items.read().unwrap_or_else(|e| e.into_inner());
+        let items = items.as_ref().ok_or_else(|| "尚未扫描AppData目录".to_string())?;
+
+        let limit = if limit == 0 { DEFAULT_APPDATA_PAGE_SIZE } else { limit };
+        let start = offset.min(items.len());
+        let end = start.saturating_add(limit).min(items.len());
+        Ok(AppDataItemsPage {
+            items: items[start..end].to_vec(),
+            offset: start,
+            total: items.len(),
+            has_more: end < items.len(),
+        })
+    }
+}
+
 /// 缓存的扫描结果
 #[derive(Debug, Clone)]
 pub struct CachedScanResult {
@@ -312,24 +735,25 @@ pub struct CachedScanResult {
 /// AppData 分析器（性能优化版本）
 #[derive(Clone)]
 pub struct AppDataAnalyzer {
-    disk_analyzer: DiskAnalyzer,
     config: AppDataConfig,
     performance_optimizer: Arc<PerformanceOptimizer>,
     scan_cache: Arc<tokio::sync::RwLock<HashMap<String, CachedScanResult>>>,
+    #[cfg(test)]
+    off_drive_detector: fn(&Path, &Path) -> bool, // 新增：判断链接目标是否位于其他磁盘（测试中可替换）
 }
 
 impl AppDataAnalyzer {
     /// 创建新的 AppData 分析器（性能优化版本）
     pub fn new() -> Self {
-        let mut disk_analyzer = DiskAnalyzer::new();
-        disk_analyzer.set_max_depth(2); // AppData扫描限制深度为2层
-        
         // 创建性能优化器配置
         let perf_config = PerformanceConfig {
             max_memory_usage_mb: 150, // 限制为150MB以满足NFR-2要求
             cache_size: 500, // 缓存500个目录信息
             max_concurrent_operations: 3, // 限制并发操作数
+            worker_threads: 2, // 限制扫描线程数
             batch_size: 50, // 每批处理50个项目
+            migration_throughput_bytes_per_sec: 50 * 1024 * 1024,
+            copy_buffer_size: 1024 * 1024,
             cleanup_interval_seconds: 60, // 1分钟清理一次
             enable_memory_monitoring: true,
             enable_caching: true,
@@ -339,18 +763,17 @@ impl AppDataAnalyzer {
         let performance_optimizer = Arc::new(PerformanceOptimizer::new(perf_config));
         
         Self {
-            disk_analyzer,
             config: AppDataConfig::default(),
             performance_optimizer: performance_optimizer.clone(),
             scan_cache: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            #[cfg(test)]
+            off_drive_detector: is_off_drive,
         }
     }
 
     /// 设置配置
     pub fn set_config(&mut self, config: AppDataConfig) {
-        self.disk_analyzer.set_max_depth(config.max_depth);
-        self.disk_analyzer.set_large_folder_threshold(config.min_size_threshold);
-        self.config = config.clone();
+        self.config = config;
     }
 
     /// 获取当前用户的 AppData 路径
@@ -368,13 +791,315 @@ impl AppDataAnalyzer {
         Ok(appdata_path)
     }
 
+    /// 获取指定用户的 AppData 路径（管理员管理多个账户时使用）
+    ///
+    /// `user` 可以是用户名（在用户配置文件根目录下查找），也可以是配置文件目录的完整路径。
+    pub fn get_appdata_path_for_user(user: &str) -> Result<PathBuf, String> {
+        let user = user.trim();
+        let profile = Path::new(user);
+        if profile.is_absolute() {
+            return Self::appdata_path_for_profile(profile);
+        }
+        Self::appdata_path_for_user_in(&Self::users_root(), user)
+    }
+
+    /// 用户配置文件根目录：当前用户目录的上级（通常为 C:\Users）
+    fn users_root() -> PathBuf {
+        env::var("USERPROFILE").ok()
+            .and_then(|profile| Path::new(&profile).parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_USERS_ROOT))
+    }
+
+    /// 在指定的用户配置文件根目录下解析用户的 AppData 路径，用户名只能是单级名称
+    pub fn appdata_path_for_user_in(users_root: &Path, username: &str) -> Result<PathBuf, String> {
+        let mut components = Path::new(username).components();
+        let is_plain = matches!(
+            (components.next(), components.next()),
+            (Some(std::path::Component::Normal(_)), None)
+        );
+        if !is_plain {
+            return Err(format!("无效的用户名: {}", username));
+        }
+        Self::appdata_path_for_profile(&users_root.join(username))
+    }
+
+    /// 解析配置文件目录下的 AppData 路径，并确认当前进程有权读取
+    pub fn appdata_path_for_profile(profile: &Path) -> Result<PathBuf, String> {
+        if !profile.is_dir() {
+            return Err(format!("用户配置文件目录不存在: {}", profile.display()));
+        }
+
+        let appdata_path = profile.join("AppData");
+        if !appdata_path.is_dir() {
+            return Err(format!("AppData目录不存在: {}", appdata_path.display()));
+        }
+
+        // 访问其他用户的配置文件通常需要管理员权限
+        std::fs::read_dir(&appdata_path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                format!("没有权限访问 {}，请以管理员身份运行", appdata_path.display())
+            } else {
+                format!("无法读取AppData目录 {}: {}", appdata_path.display(), e)
+            }
+        })?;
+
+        Ok(appdata_path)
+    }
+
+    /// 获取路径中 `AppData` 之后的各级名称
+    fn components_under_appdata(path: &Path) -> Option<Vec<String>> {
+        let components: Vec<String> = path.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+
+        let appdata_index = components.iter().rposition(|c| c.eq_ignore_ascii_case("AppData"))?;
+        Some(components[appdata_index + 1..].to_vec())
+    }
+
+    /// 从源路径推断所属的 AppData 子目录类型（Local/LocalLow/Roaming）
+    pub fn subtree_of(path: &Path) -> Option<&'static str> {
+        const SUBTREES: [&str; 3] = ["Local", "LocalLow", "Roaming"];
+
+        let first = Self::components_under_appdata(path)?.into_iter().next()?;
+        SUBTREES.iter().find(|subtree| subtree.eq_ignore_ascii_case(&first)).copied()
+    }
+
+    /// 检查路径是否命中黑名单（本身、位于其中或包含黑名单目录），返回命中的条目
+    pub fn blocklist_match(path: &Path) -> Option<&'static str> {
+        let components = Self::components_under_appdata(path)?;
+        if components.is_empty() {
+            return None;
+        }
+
+        APPDATA_BLOCKLIST.iter().copied().find(|entry| {
+            let blocked: Vec<&str> = entry.split('/').collect();
+            components.iter()
+                .zip(blocked.iter())
+                .all(|(component, blocked)| component.eq_ignore_ascii_case(blocked))
+        })
+    }
+
+    /// 按 APPDATA_CATEGORIES 推断一级项目的分类
+    pub fn classify_category(item_path: &Path) -> String {
+        let name = match item_path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => return OTHER_CATEGORY.to_string(),
+        };
+
+        APPDATA_CATEGORIES.iter()
+            .find(|(pattern, _)| {
+                let mut components = pattern.split('/');
+                let matches_name = components.next().is_some_and(|first| first.eq_ignore_ascii_case(&name));
+                let rest: PathBuf = components.collect();
+                matches_name && (rest.as_os_str().is_empty() || item_path.join(rest).exists())
+            })
+            .map(|(_, category)| category.to_string())
+            .unwrap_or_else(|| OTHER_CATEGORY.to_string())
+    }
+
+    /// 按分类汇总项目大小，按大小降序排列
+    pub fn category_sizes(items: &[AppDataFirstLevelItem]) -> Vec<AppDataCategorySummary> {
+        let mut totals: HashMap<&str, (u64, usize)> = HashMap::new();
+        for item in items {
+            let category = if item.category.is_empty() { OTHER_CATEGORY } else { item.category.as_str() };
+            let entry = totals.entry(category).or_default();
+            entry.0 += item.size;
+            entry.1 += 1;
+        }
+
+        let mut summaries: Vec<AppDataCategorySummary> = totals.into_iter()
+            .map(|(category, (size, item_count))| AppDataCategorySummary {
+                category: category.to_string(),
+                size,
+                item_count,
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.category.cmp(&b.category)));
+        summaries
+    }
+
+    /// 项目是否可作为迁移候选：在本盘占用的大小达到所在子树的阈值且尚未迁移
+    pub fn is_migration_candidate(&self, item: &AppDataFirstLevelItem) -> bool {
+        !item.is_symlink && item.is_large
+    }
+
+    /// 标记已迁移的项目：原位置是链接且指向其他磁盘时设置 `is_symlink` 和 `link_target`，
+    /// 返回这些项目的总大小（即已释放的空间）
+    pub fn detect_already_migrated(&self, items: &mut [AppDataFirstLevelItem]) -> u64 {
+        for item in items.iter_mut() {
+            let path = Path::new(&item.path);
+            if detect_link_type(path).is_none() {
+                continue;
+            }
+            let target = match std::fs::read_link(path) {
+                Ok(target) if target.is_relative() => path.parent().map(|parent| parent.join(&target)).unwrap_or(target),
+                Ok(target) => target,
+                Err(e) => {
+                    warn!("读取链接目标失败 {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if self.target_is_off_drive(path, &target) {
+                debug!("发现已迁移的项目: {} -> {}", path.display(), target.display());
+                item.is_symlink = true;
+                item.is_large = false;
+                item.link_target = Some(target.to_string_lossy().to_string());
+            }
+        }
+
+        Self::already_freed_bytes(items)
+    }
+
+    /// 判断链接目标是否位于其他磁盘（测试中可替换）
+    #[cfg(test)]
+    fn target_is_off_drive(&self, link: &Path, target: &Path) -> bool {
+        (self.off_drive_detector)(link, target)
+    }
+
+    #[cfg(not(test))]
+    fn target_is_off_drive(&self, link: &Path, target: &Path) -> bool {
+        is_off_drive(link, target)
+    }
+
+    /// 按当前配置为每个项目计算迁移推荐分
+    pub fn assign_migration_scores(&self, items: &mut [AppDataFirstLevelItem]) {
+        for item in items.iter_mut() {
+            item.migration_score = self.config.migration_score(item);
+        }
+    }
+
+    /// 已迁移项目的总大小
+    fn already_freed_bytes(items: &[AppDataFirstLevelItem]) -> u64 {
+        items.iter().filter(|item| item.is_symlink).map(|item| item.size).sum()
+    }
+
+    /// 验证单个待迁移项目，返回 (是否有效, 说明)
+    pub fn validate_migration_item(source: &Path, allow_risky: bool) -> (bool, String) {
+        if !source.exists() {
+            return (false, "源路径不存在".to_string());
+        }
+
+        if !source.is_dir() && !source.is_file() {
+            return (false, "路径既不是文件也不是目录".to_string());
+        }
+
+        if detect_link_type(source).is_some() {
+            return (false, "项目已迁移（源位置是链接）".to_string());
+        }
+
+        if let Some(entry) = Self::blocklist_match(source) {
+            if !allow_risky {
+                return (false, format!("系统关键目录（{}），迁移可能导致Windows异常", entry));
+            }
+            warn!("已允许迁移系统关键目录: {} (命中: {})", source.display(), entry);
+            return (true, format!("路径有效（已允许迁移系统关键目录: {}）", entry));
+        }
+
+        (true, "路径有效".to_string())
+    }
+
+    /// 试运行验证整个迁移计划：逐项检查存在性、黑名单和系统保护目录，统计总大小，
+    /// 并检查目标磁盘的可用空间是否足够容纳全部项目（含 20% 缓冲，与迁移预检查一致）。
+    /// 项目对应的程序似乎仍在运行时（见 [`find_running_app_process`]）只给出警告，不影响有效性
+    pub fn validate_migration_plan(
+        options: &AppDataMigrationOptions,
+        service: &MigrationService,
+        available_bytes: Option<u64>,
+        running_processes: &[String],
+    ) -> AppDataPlanValidation {
+        let size_analyzer = DiskAnalyzer::new();
+        let mut plan = AppDataPlanValidation {
+            target_drive_valid: true,
+            target_drive: options.target_drive.clone(),
+            available_bytes,
+            ..Default::default()
+        };
+
+        for source_item in &options.source_items {
+            let source = Path::new(source_item);
+            let (mut valid, mut message) = Self::validate_migration_item(source, options.allow_risky);
+            if valid {
+                if let Some(reason) = service.protection_reason(source) {
+                    valid = false;
+                    message = reason;
+                }
+            }
+
+            if let Some(process) = find_running_app_process(source, running_processes) {
+                plan.warnings.push(format!(
+                    "{} 可能正在被运行中的程序 {} 使用，建议先关闭该程序再迁移",
+                    source_item, process
+                ));
+            }
+
+            let size = size_analyzer.measure_path(source).map_or(0, |totals| totals.size);
+            plan.total_bytes += size;
+            plan.items.push(AppDataPlanItem { path: source_item.clone(), valid, message, size });
+        }
+
+        let valid_count = plan.items.iter().filter(|item| item.valid).count();
+        if valid_count < plan.items.len() {
+            plan.reasons.push(format!("{} 个项目无法迁移", plan.items.len() - valid_count));
+        }
+
+        plan.required_bytes = plan.total_bytes + plan.total_bytes / 5;
+        if let Some(available) = available_bytes {
+            if plan.required_bytes > available {
+                plan.reasons.push(format!(
+                    "目标磁盘空间不足。需要: {} (含20%缓冲)，可用: {}",
+                    format_file_size(plan.required_bytes),
+                    format_file_size(available)
+                ));
+            }
+        }
+
+        plan.valid = plan.reasons.is_empty();
+        plan.summary = format!(
+            "验证完成：{}/{} 个项目有效，共 {}",
+            valid_count, plan.items.len(), format_file_size(plan.total_bytes)
+        );
+        plan
+    }
+
+    /// 按布局构建迁移目标路径，各布局的路径形式见 [`TargetLayout`]
+    ///
+    /// PreserveSubtree 无法识别子目录类型时退回为 `<目标盘>/<名称>`；
+    /// UnderNamedFolder 的文件夹名称为空或包含 `..`、根目录等非普通路径组件时返回 None。
+    pub fn build_migration_target(target_drive: &Path, source: &Path, layout: &TargetLayout) -> Option<PathBuf> {
+        let name = source.file_name()?;
+
+        match layout {
+            TargetLayout::Flat => Some(target_drive.join(name)),
+            TargetLayout::PreserveSubtree => Some(match Self::subtree_of(source) {
+                Some(subtree) => target_drive.join("AppData").join(subtree).join(name),
+                None => target_drive.join(name),
+            }),
+            TargetLayout::UnderNamedFolder(folder) => {
+                let folder = Path::new(folder.trim());
+                let is_plain = folder.components().next().is_some()
+                    && folder.components().all(|c| matches!(c, std::path::Component::Normal(_)));
+                is_plain.then(|| target_drive.join(folder).join(name))
+            }
+        }
+    }
+
     /// 扫描 AppData 目录（性能优化版本）
     pub async fn scan_appdata(&self) -> Result<AppDataInfo, String> {
-        info!("开始扫描AppData目录（性能优化版本）");
+        let appdata_path = Self::get_appdata_path()?;
+        self.scan_appdata_at(&appdata_path).await
+    }
+
+    /// 扫描调用方指定的 AppData 根目录（例如其他用户的配置文件目录）
+    pub async fn scan_appdata_at(&self, base: &Path) -> Result<AppDataInfo, String> {
+        info!("开始扫描AppData目录（性能优化版本）: {}", base.display());
         let start_time = std::time::Instant::now();
         
-        // 检查缓存
-        let cache_key = "appdata_scan".to_string();
+        if !base.is_dir() {
+            return Err(format!("AppData目录不存在: {}", base.display()));
+        }
+        
+        // 检查缓存（按根目录区分）
+        let cache_key = format!("appdata_scan:{}", base.display());
         {
             let cache = self.scan_cache.read().await;
             if let Some(cached_result) = cache.get(&cache_key) {
@@ -391,10 +1116,11 @@ impl AppDataAnalyzer {
                         total_size: cached_result.total_size,
                         first_level_items: cached_result.items.clone(),
                         large_items: cached_result.items.iter()
-                            .filter(|item| item.size >= self.config.min_size_threshold)
+                            .filter(|item| self.is_migration_candidate(item))
                             .cloned()
                             .collect(),
                         scan_time_ms: 0, // 缓存命中，时间为0
+                        already_freed_bytes: Self::already_freed_bytes(&cached_result.items),
                     });
                 }
             }
@@ -404,13 +1130,10 @@ impl AppDataAnalyzer {
         let initial_stats = self.performance_optimizer.get_performance_stats();
         debug!("扫描前性能统计: {:?}", initial_stats);
         
-        // 获取AppData路径
-        let appdata_path = Self::get_appdata_path()?;
-        
         // 扫描三个主要子目录（使用任务池并发执行以提高性能）
-        let local_path = appdata_path.join("Local");
-        let local_low_path = appdata_path.join("LocalLow");
-        let roaming_path = appdata_path.join("Roaming");
+        let local_path = base.join("Local");
+        let local_low_path = base.join("LocalLow");
+        let roaming_path = base.join("Roaming");
         
         info!("开始并发扫描AppData子目录");
         
@@ -462,6 +1185,8 @@ impl AppDataAnalyzer {
         all_first_level_items.write().await.extend(local_items);
         all_first_level_items.write().await.extend(local_low_items);
         all_first_level_items.write().await.extend(roaming_items);
+        let already_freed_bytes = self.detect_already_migrated(&mut all_first_level_items.write().await);
+        self.assign_migration_scores(&mut all_first_level_items.write().await);
         
         // 计算总大小
         let total_size = local_size + local_low_size + roaming_size;
@@ -470,7 +1195,7 @@ impl AppDataAnalyzer {
         let items = all_first_level_items.read().await;
         let large_items: Vec<AppDataFirstLevelItem> = items
             .iter()
-            .filter(|item| item.size >= self.config.min_size_threshold)
+            .filter(|item| self.is_migration_candidate(item))
             .cloned()
             .collect();
         
@@ -519,6 +1244,7 @@ impl AppDataAnalyzer {
             first_level_items: items.clone(),
             large_items,
             scan_time_ms,
+            already_freed_bytes,
         })
     }
 
@@ -550,17 +1276,20 @@ impl AppDataAnalyzer {
                         let name = entry.file_name().to_string_lossy().to_string();
                         
                         // 同步获取基本信息（不调用异步函数）
-                        match AppDataAnalyzer::get_item_info_sync(&entry_path) {
-                            Ok((size, item_type)) => {
-                                total_size += size;
+                        match AppDataAnalyzer::get_item_info_sync(&entry_path, self.config.max_depth) {
+                            Ok((size, item_type, newest_modified)) => {
+                                // 链接项目的目标不在本盘，只计入项目自身的大小
+                                let on_drive_size = Self::on_drive_size(&entry_path, size);
+                                total_size += on_drive_size;
                                 
-                                let is_large = size >= self.config.min_size_threshold;
+                                let is_large = on_drive_size >= self.config.threshold_for(parent_type);
                                 let size_percentage = if total_size > 0 {
-                                    (size as f64 / total_size as f64) * 100.0
+                                    (on_drive_size as f64 / total_size as f64) * 100.0
                                 } else {
                                     0.0
                                 };
                                 
+                                let (last_modified, last_accessed, days_since_modified) = Self::item_times(&entry_path, newest_modified);
                                 let item = AppDataFirstLevelItem {
                                     path: entry_path.to_string_lossy().to_string(),
                                     name,
@@ -569,6 +1298,11 @@ impl AppDataAnalyzer {
                                     parent_type: parent_type.to_string(),
                                     is_large,
                                     size_percentage,
+                                    last_modified,
+                                    last_accessed,
+                                    days_since_modified,
+                                    category: Self::classify_category(&entry_path),
+                                    ..Default::default()
                                 };
                                 
                                 items.push(item);
@@ -593,6 +1327,9 @@ impl AppDataAnalyzer {
             return self.scan_first_level_items_fallback(path, parent_type).await;
         }
         
+        // 按修改时间筛选废弃项目（总大小仍包含全部项目）
+        items.retain(|item| self.config.passes_age_filter(item));
+
         // 按大小排序（根据配置）- 使用高效的排序算法
         match self.config.sort_order {
             SortOrder::Desc => {
@@ -627,16 +1364,19 @@ impl AppDataAnalyzer {
                             
                             // 获取文件/目录信息
                             match self.get_item_info(&entry_path).await {
-                                Ok((size, item_type)) => {
-                                    total_size += size;
+                                Ok((size, item_type, newest_modified)) => {
+                                    // 链接项目的目标不在本盘，只计入项目自身的大小
+                                    let on_drive_size = Self::on_drive_size(&entry_path, size);
+                                    total_size += on_drive_size;
                                     
-                                    let is_large = size >= self.config.min_size_threshold;
+                                    let is_large = on_drive_size >= self.config.threshold_for(parent_type);
                                     let size_percentage = if total_size > 0 {
-                                        (size as f64 / total_size as f64) * 100.0
+                                        (on_drive_size as f64 / total_size as f64) * 100.0
                                     } else {
                                         0.0
                                     };
                                     
+                                    let (last_modified, last_accessed, days_since_modified) = Self::item_times(&entry_path, newest_modified);
                                     let item = AppDataFirstLevelItem {
                                         path: entry_path.to_string_lossy().to_string(),
                                         name,
@@ -645,6 +1385,11 @@ impl AppDataAnalyzer {
                                         parent_type: parent_type.to_string(),
                                         is_large,
                                         size_percentage,
+                                        last_modified,
+                                        last_accessed,
+                                        days_since_modified,
+                                        category: Self::classify_category(&entry_path),
+                                        ..Default::default()
                                     };
                                     
                                     items.push(item);
@@ -666,6 +1411,9 @@ impl AppDataAnalyzer {
             }
         }
         
+        // 按修改时间筛选废弃项目（总大小仍包含全部项目）
+        items.retain(|item| self.config.passes_age_filter(item));
+
         // 按大小排序（根据配置）
         match self.config.sort_order {
             SortOrder::Desc => {
@@ -679,43 +1427,41 @@ impl AppDataAnalyzer {
         Ok((items, total_size))
     }
 
-    /// 获取项目信息（大小和类型）
-    async fn get_item_info(&self, path: &Path) -> Result<(u64, String), String> {
-        if path.is_file() {
-            // 文件：获取大小
-            return match std::fs::metadata(path) {
-                Ok(metadata) => Ok((metadata.len(), "file".to_string())),
-                Err(e) => Err(format!("获取文件元数据失败: {}", e)),
-            };
-        } else if path.is_dir() {
-            // 目录：使用DiskAnalyzer扫描获取总大小
-            return match self.disk_analyzer.scan_directory_async(path).await {
-                Ok(dir_info) => Ok((dir_info.size, "directory".to_string())),
-                Err(e) => {
-                    warn!("扫描目录失败 {}: {}，返回0大小", path.display(), e);
-                    Ok((0, "directory".to_string()))
-                }
-            };
-        } else {
-            return Err("未知项目类型".to_string());
-        }
+    /// 获取项目信息（大小、类型和最近修改时间），在阻塞线程上遍历
+    async fn get_item_info(&self, path: &Path) -> Result<(u64, String, Option<SystemTime>), String> {
+        let path = path.to_path_buf();
+        let max_depth = self.config.max_depth;
+        tokio::task::spawn_blocking(move || Self::get_item_info_sync(&path, max_depth))
+            .await
+            .map_err(|e| format!("获取项目信息失败: {}", e))?
     }
 
-    /// 同步获取项目信息（用于优化扫描）
-    pub fn get_item_info_sync(path: &Path) -> Result<(u64, String), String> {
+    /// 同步获取项目信息（用于优化扫描），返回（大小, 类型, 最近修改时间）
+    ///
+    /// 目录大小完整递归统计；最近修改时间只取自不超过 max_depth 层的目录（一级项目本身为第 1 层）。
+    /// 一级项目本身是链接时返回其目标的大小，目标不存在时大小为 0；目录内部的链接不跟随。
+    pub fn get_item_info_sync(path: &Path, max_depth: usize) -> Result<(u64, String, Option<SystemTime>), String> {
+        if let Some(link_type) = detect_link_type(path) {
+            if std::fs::metadata(path).is_err() {
+                warn!("链接目标不存在: {}", path.display());
+                let item_type = if link_type == LinkType::Junction { "directory" } else { "file" };
+                return Ok((0, item_type.to_string(), None));
+            }
+        }
+
         if path.is_file() {
             // 文件：获取大小
             match std::fs::metadata(path) {
-                Ok(metadata) => Ok((metadata.len(), "file".to_string())),
+                Ok(metadata) => Ok((metadata.len(), "file".to_string(), metadata.modified().ok())),
                 Err(e) => Err(format!("获取文件元数据失败: {}", e)),
             }
         } else if path.is_dir() {
-            // 目录：使用同步扫描获取总大小（简化版）
-            match Self::get_directory_size_sync(path) {
-                Ok(size) => Ok((size, "directory".to_string())),
+            // 目录：使用同步扫描获取总大小
+            match Self::directory_totals(path, 1, max_depth) {
+                Ok((size, newest_modified)) => Ok((size, "directory".to_string(), newest_modified)),
                 Err(e) => {
                     warn!("扫描目录失败 {}: {}，返回0大小", path.display(), e);
-                    Ok((0, "directory".to_string()))
+                    Ok((0, "directory".to_string(), None))
                 }
             }
         } else {
@@ -723,31 +1469,94 @@ impl AppDataAnalyzer {
         }
     }
 
-    /// 同步获取目录大小（简化版）
-    pub fn get_directory_size_sync(path: &Path) -> Result<u64, String> {
+    /// 同步构建单个一级项目（不含占比，由调用方根据累计大小计算）
+    fn build_first_level_item(config: &AppDataConfig, entry_path: &Path, parent_type: &str) -> Result<AppDataFirstLevelItem, String> {
+        let (size, item_type, newest_modified) = Self::get_item_info_sync(entry_path, config.max_depth)
+            .map_err(|e| format!("{}: {}", entry_path.display(), e))?;
+        let (last_modified, last_accessed, days_since_modified) = Self::item_times(entry_path, newest_modified);
+
+        Ok(AppDataFirstLevelItem {
+            path: entry_path.to_string_lossy().to_string(),
+            name: entry_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+            size,
+            item_type,
+            parent_type: parent_type.to_string(),
+            is_large: Self::on_drive_size(entry_path, size) >= config.threshold_for(parent_type),
+            last_modified,
+            last_accessed,
+            days_since_modified,
+            category: Self::classify_category(entry_path),
+            ..Default::default()
+        })
+    }
+
+    /// 项目在本盘实际占用的大小：项目本身是链接（符号链接或目录联接）时几乎不占空间，计为 0
+    fn on_drive_size(path: &Path, size: u64) -> u64 {
+        if detect_link_type(path).is_some() {
+            0
+        } else {
+            size
+        }
+    }
+
+    /// 读取项目的（修改时间, 访问时间, 距今未修改天数）
+    ///
+    /// 修改时间取项目本身与 `newest_modified`（遍历时找到的最新修改时间）中较新的一个，
+    /// 只在深层目录中写入数据的应用不会被误判为已废弃。
+    fn item_times(path: &Path, newest_modified: Option<SystemTime>) -> (Option<SystemTime>, Option<SystemTime>, Option<u64>) {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return (None, None, None),
+        };
+
+        let last_modified = metadata.modified().ok().max(newest_modified);
+        let last_accessed = metadata.accessed().ok();
+        let days_since_modified = last_modified.map(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .map(|age| age.as_secs() / 86_400)
+                .unwrap_or(0)
+        });
+
+        (last_modified, last_accessed, days_since_modified)
+    }
+
+    /// 统计位于第 depth 层的目录的完整大小，以及其中最新的修改时间
+    ///
+    /// 修改时间只取自不超过 max_depth 层的目录（目录本身及其中的文件），更深的目录只统计大小。
+    fn directory_totals(path: &Path, depth: usize, max_depth: usize) -> Result<(u64, Option<SystemTime>), String> {
+        let entries = std::fs::read_dir(path).map_err(|e| format!("读取目录失败: {}", e))?;
+        let within_depth = depth <= max_depth;
         let mut total_size = 0u64;
-        
-        match std::fs::read_dir(path) {
-            Ok(entries) => {
-                for entry in entries {
-                    if let Ok(entry) = entry {
-                        let entry_path = entry.path();
-                        if entry_path.is_file() {
-                            if let Ok(metadata) = std::fs::metadata(&entry_path) {
-                                total_size += metadata.len();
-                            }
-                        } else if entry_path.is_dir() {
-                            // 递归获取子目录大小（限制深度以避免性能问题）
-                            if let Ok(sub_size) = Self::get_directory_size_sync(&entry_path) {
-                                total_size += sub_size;
-                            }
-                        }
-                    }
+        let mut newest_modified = if within_depth {
+            std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+        } else {
+            None
+        };
+
+        for entry in entries.flatten() {
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+            // 不跟随链接（符号链接或目录联接），其目标可能在其他磁盘上
+            if file_type.is_symlink() {
+                continue;
+            }
+            if file_type.is_dir() {
+                if let Ok((sub_size, sub_modified)) = Self::directory_totals(&entry.path(), depth + 1, max_depth) {
+                    total_size += sub_size;
+                    newest_modified = newest_modified.max(sub_modified);
+                }
+            } else if let Ok(metadata) = entry.metadata() {
+                total_size += metadata.len();
+                if within_depth {
+                    newest_modified = newest_modified.max(metadata.modified().ok());
                 }
-                Ok(total_size)
             }
-            Err(e) => Err(format!("读取目录失败: {}", e)),
         }
+
+        Ok((total_size, newest_modified))
     }
 
     /// 格式化文件大小
@@ -779,6 +1588,84 @@ impl AppDataAnalyzer {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_classify_category() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["Google/Chrome", "Microsoft/VSCode", "Steam", "JetBrains", "Discord", "UnknownVendor", "Microsoft/Unrelated"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::create_dir_all(root.join("Mozilla")).unwrap();
+
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("Google")), "Browser");
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("Microsoft")), "Developer");
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("Steam")), "Games");
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("JetBrains")), "Developer");
+        // 名称匹配不区分大小写
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("Discord")), "Communication");
+        // 多级规则要求子目录存在
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("Mozilla")), OTHER_CATEGORY);
+        assert_eq!(AppDataAnalyzer::classify_category(&root.join("UnknownVendor")), OTHER_CATEGORY);
+    }
+
+    #[test]
+    fn test_category_sizes() {
+        let item = |category: &str, size: u64| AppDataFirstLevelItem {
+            category: category.to_string(),
+            size,
+            ..Default::default()
+        };
+        let items = vec![item("Browser", 300), item("Games", 1000), item("Browser", 200), item("", 50)];
+
+        let summaries = AppDataAnalyzer::category_sizes(&items);
+        let totals: Vec<(&str, u64, usize)> = summaries.iter()
+            .map(|summary| (summary.category.as_str(), summary.size, summary.item_count))
+            .collect();
+        assert_eq!(totals, vec![("Games", 1000, 1), ("Browser", 500, 2), (OTHER_CATEGORY, 50, 1)]);
+    }
+
+    #[test]
+    fn test_migration_score_ranks_large_stale_non_system_items_first() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let item = |name: &str, category: &str, size: u64, days: u64| AppDataFirstLevelItem {
+            path: format!("/home/user/AppData/Local/{}", name),
+            name: name.to_string(),
+            parent_type: "Local".to_string(),
+            category: category.to_string(),
+            size,
+            days_since_modified: Some(days),
+            ..Default::default()
+        };
+        let config = AppDataConfig::default();
+        let score = |item: &AppDataFirstLevelItem| config.migration_score(item);
+
+        let stale_game = item("Steam", "Games", 20 * GB, 400);
+        let stale_unknown = item("OldTool", OTHER_CATEGORY, 5 * GB, 200);
+        let active_browser = item("Google", "Browser", 2 * GB, 1);
+        let small_recent = item("Notes", OTHER_CATEGORY, 10 * 1024 * 1024, 0);
+        // 同样大且久未修改，但位于系统目录（包含黑名单目录）
+        let system = item("Microsoft", OTHER_CATEGORY, 20 * GB, 400);
+        let migrated = AppDataFirstLevelItem { is_symlink: true, ..stale_game.clone() };
+
+        assert!(score(&stale_game) > score(&stale_unknown));
+        assert!(score(&stale_unknown) > score(&active_browser));
+        assert!(score(&active_browser) > score(&small_recent));
+        assert!(score(&stale_game) > score(&system));
+        assert_eq!(score(&migrated), 0.0);
+        for item in [&stale_game, &stale_unknown, &active_browser, &small_recent, &system] {
+            assert!((0.0..=1.0).contains(&score(item)));
+        }
+
+        // 只看闲置时间时，久未修改的小项目排在刚使用过的大项目之前
+        let staleness_only = AppDataConfig {
+            score_weights: MigrationScoreWeights { size: 0.0, staleness: 1.0, category: 0.0 },
+            ..AppDataConfig::default()
+        };
+        let old_small = item("Archive", OTHER_CATEGORY, 1024, 365);
+        assert!(staleness_only.migration_score(&old_small) > staleness_only.migration_score(&active_browser));
+        assert!(score(&old_small) < score(&active_browser));
+    }
+
     #[test]
     fn test_appdata_config_default() {
         let config = AppDataConfig::default();
@@ -824,6 +1711,7 @@ mod tests {
             parent_type: "Local".to_string(),
             is_large: true,
             size_percentage: 50.0,
+            ..Default::default()
         };
 
         assert_eq!(item.name, "TestApp");
@@ -831,4 +1719,552 @@ mod tests {
         assert!(item.is_large);
         assert_eq!(item.parent_type, "Local");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_min_days_since_modified_filter() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let now = filetime::FileTime::now().unix_seconds();
+        for (name, days) in [("Abandoned", 400), ("Dormant", 45), ("Active", 0)] {
+            let dir = temp_dir.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("data.bin"), vec![0u8; 16]).unwrap();
+            let mtime = filetime::FileTime::from_unix_time(now - days * 86_400, 0);
+            filetime::set_file_mtime(dir.join("data.bin"), mtime).unwrap();
+            filetime::set_file_mtime(&dir, mtime).unwrap();
+        }
+
+        let mut analyzer = AppDataAnalyzer::new();
+        let (all_items, all_size) = analyzer.scan_first_level_items(temp_dir.path(), "Local").await.unwrap();
+        assert_eq!(all_items.len(), 3);
+        let abandoned = all_items.iter().find(|item| item.name == "Abandoned").unwrap();
+        assert_eq!(abandoned.days_since_modified, Some(400));
+        assert!(abandoned.last_modified.is_some());
+
+        analyzer.set_config(AppDataConfig {
+            min_days_since_modified: Some(30),
+            ..Default::default()
+        });
+        let (stale_items, stale_size) = analyzer.scan_first_level_items(temp_dir.path(), "Local").await.unwrap();
+        let mut names: Vec<_> = stale_items.iter().map(|item| item.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Abandoned", "Dormant"]);
+        assert_eq!(stale_size, all_size);
+    }
+
+    #[tokio::test]
+    async fn test_deep_write_keeps_item_active() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let now = filetime::FileTime::now().unix_seconds();
+        let old = filetime::FileTime::from_unix_time(now - 400 * 86_400, 0);
+        let app = temp_dir.path().join("Browser");
+        let profile = app.join("User Data");
+        std::fs::create_dir_all(&profile).unwrap();
+        std::fs::write(app.join("settings.json"), b"{}").unwrap();
+        // 应用只在深层目录中写入数据，一级目录本身的修改时间很旧
+        std::fs::write(profile.join("History"), vec![0u8; 64]).unwrap();
+        filetime::set_file_mtime(app.join("settings.json"), old).unwrap();
+        filetime::set_file_mtime(&profile, old).unwrap();
+        filetime::set_file_mtime(&app, old).unwrap();
+
+        let mut analyzer = AppDataAnalyzer::new();
+        let (items, _) = analyzer.scan_first_level_items(temp_dir.path(), "Local").await.unwrap();
+        assert_eq!(items[0].days_since_modified, Some(0));
+
+        // 只查看一级目录本身时，深层写入不可见
+        analyzer.set_config(AppDataConfig { max_depth: 1, ..Default::default() });
+        let (items, _) = analyzer.scan_first_level_items(temp_dir.path(), "Local").await.unwrap();
+        assert_eq!(items[0].days_since_modified, Some(400));
+    }
+
+    #[tokio::test]
+    async fn test_per_subtree_large_threshold() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let app_dir = temp_dir.path().join("GameData");
+        std::fs::create_dir_all(&app_dir).unwrap();
+        std::fs::write(app_dir.join("save.dat"), vec![0u8; 4096]).unwrap();
+
+        let mut analyzer = AppDataAnalyzer::new();
+        analyzer.set_config(AppDataConfig {
+            min_size_threshold: 1024 * 1024,
+            thresholds: HashMap::from([
+                ("LocalLow".to_string(), 1024),
+                ("Local".to_string(), 8192),
+            ]),
+            ..Default::default()
+        });
+
+        let (low_items, _) = analyzer.scan_first_level_items(temp_dir.path(), "LocalLow").await.unwrap();
+        assert_eq!(low_items.len(), 1);
+        assert!(low_items[0].is_large, "LocalLow 阈值下应为大文件夹");
+
+        let (local_items, _) = analyzer.scan_first_level_items(temp_dir.path(), "Local").await.unwrap();
+        assert_eq!(local_items.len(), 1);
+        assert!(!local_items[0].is_large, "Local 阈值下不应为大文件夹");
+
+        // 未配置的子树回退到 min_size_threshold
+        assert_eq!(analyzer.config.threshold_for("Roaming"), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_appdata_config_thresholds_default_empty() {
+        let config: AppDataConfig = serde_json::from_str(
+            r#"{"minSizeThreshold": 1024, "maxDepth": 2, "sortOrder": "desc"}"#
+        ).unwrap();
+        assert!(config.thresholds.is_empty());
+        assert_eq!(config.threshold_for("Local"), 1024);
+    }
+
+    #[tokio::test]
+    async fn test_scan_appdata_at_custom_base() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("AppData");
+        let fixtures = [
+            ("Local", "ChromeCache", 4096usize),
+            ("Local", "Temp", 1024),
+            ("LocalLow", "UnityGame", 2048),
+            ("Roaming", "Code", 512),
+        ];
+        for (subtree, name, size) in fixtures {
+            let dir = base.join(subtree).join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("data.bin"), vec![0u8; size]).unwrap();
+        }
+
+        let analyzer = AppDataAnalyzer::new();
+        let info = analyzer.scan_appdata_at(&base).await.unwrap();
+
+        let mut found: Vec<(String, String, u64)> = info.first_level_items.iter()
+            .map(|item| (item.parent_type.clone(), item.name.clone(), item.size))
+            .collect();
+        found.sort();
+        let mut expected: Vec<(String, String, u64)> = fixtures.iter()
+            .map(|(subtree, name, size)| (subtree.to_string(), name.to_string(), *size as u64))
+            .collect();
+        expected.sort();
+        assert_eq!(found, expected);
+
+        assert_eq!(info.local_size, 4096 + 1024);
+        assert_eq!(info.local_low_size, 2048);
+        assert_eq!(info.roaming_size, 512);
+        assert_eq!(info.local_path, base.join("Local").to_string_lossy());
+
+        assert!(analyzer.scan_appdata_at(&temp_dir.path().join("missing")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_item_pages_follow_sort_order() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("AppData");
+        let subtrees = ["Local", "LocalLow", "Roaming"];
+        for i in 0..120usize {
+            let dir = base.join(subtrees[i % 3]).join(format!("App{:03}", i));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("data.bin"), vec![0u8; (i % 40 + 1) * 100]).unwrap();
+        }
+
+        let analyzer = AppDataAnalyzer::new();
+        let info = analyzer.scan_appdata_at(&base).await.unwrap();
+        let pager = AppDataItemPager::new();
+        assert!(pager.page(0, 10).is_err());
+        pager.store(&info.first_level_items, &SortOrder::Desc);
+
+        let mut paged = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = pager.page(offset, 25).unwrap();
+            assert_eq!(page.offset, offset);
+            assert_eq!(page.total, 120);
+            assert!(page.items.len() <= 25);
+            offset += page.items.len();
+            paged.extend(page.items);
+            if !page.has_more {
+                break;
+            }
+        }
+
+        // 各页拼接后覆盖全部项目，且跨页整体按大小降序（大小相同按路径）
+        assert_eq!(paged.len(), 120);
+        assert!(paged.windows(2).all(|w| w[0].size > w[1].size || (w[0].size == w[1].size && w[0].path < w[1].path)),
+                "分页结果应按大小降序");
+        let third = pager.page(50, 25).unwrap();
+        assert_eq!(third.items.iter().map(|item| &item.path).collect::<Vec<_>>(),
+                   paged[50..75].iter().map(|item| &item.path).collect::<Vec<_>>());
+
+        let past_end = pager.page(500, 25).unwrap();
+        assert!(past_end.items.is_empty());
+        assert!(!past_end.has_more);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinked_item_excluded_from_on_drive_size() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("AppData");
+        let real = base.join("Local").join("RealApp");
+        std::fs::create_dir_all(&real).unwrap();
+        std::fs::write(real.join("data.bin"), vec![0u8; 1000]).unwrap();
+
+        // 指向其他位置的大目录：一级项目本身是链接，另有一个链接藏在普通目录内部
+        let big_target = temp_dir.path().join("Elsewhere").join("BigApp");
+        std::fs::create_dir_all(&big_target).unwrap();
+        std::fs::write(big_target.join("data.bin"), vec![0u8; 50_000]).unwrap();
+        std::os::unix::fs::symlink(&big_target, base.join("Local").join("BigApp")).unwrap();
+        std::os::unix::fs::symlink(&big_target, real.join("nested-link")).unwrap();
+        // 目标已被删除的链接
+        std::os::unix::fs::symlink(temp_dir.path().join("missing"), base.join("Local").join("Dangling")).unwrap();
+
+        let mut analyzer = AppDataAnalyzer::new();
+        analyzer.set_config(AppDataConfig { min_size_threshold: 10_000, ..AppDataConfig::default() });
+        let info = analyzer.scan_appdata_at(&base).await.unwrap();
+
+        assert_eq!(info.local_size, 1000);
+        assert_eq!(info.total_size, 1000);
+        let item = |name: &str| info.first_level_items.iter().find(|item| item.name == name).unwrap();
+        assert_eq!(item("RealApp").size, 1000);
+        assert_eq!(item("BigApp").size, 50_000);
+        assert!(!item("BigApp").is_large);
+        assert_eq!(item("Dangling").size, 0);
+        assert!(info.large_items.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinked_item_detected_as_already_migrated() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("AppData");
+        let kept = base.join("Local").join("KeptApp");
+        std::fs::create_dir_all(&kept).unwrap();
+        std::fs::write(kept.join("data.bin"), vec![0u8; 2048]).unwrap();
+
+        // 模拟此前已迁移到其他磁盘的项目
+        let moved_target = temp_dir.path().join("OtherDrive").join("MovedApp");
+        std::fs::create_dir_all(&moved_target).unwrap();
+        std::fs::write(moved_target.join("data.bin"), vec![0u8; 4096]).unwrap();
+        std::fs::create_dir_all(base.join("Roaming")).unwrap();
+        std::os::unix::fs::symlink(&moved_target, base.join("Roaming").join("MovedApp")).unwrap();
+
+        let mut analyzer = AppDataAnalyzer::new();
+        analyzer.set_config(AppDataConfig { min_size_threshold: 0, ..AppDataConfig::default() });
+        analyzer.off_drive_detector = |_, target| target.components().any(|c| c.as_os_str() == "OtherDrive");
+        let info = analyzer.scan_appdata_at(&base).await.unwrap();
+
+        let moved = info.first_level_items.iter().find(|item| item.name == "MovedApp").unwrap();
+        assert!(moved.is_symlink);
+        assert_eq!(moved.link_target.as_deref(), Some(moved_target.to_string_lossy().as_ref()));
+        assert!(!info.first_level_items.iter().find(|item| item.name == "KeptApp").unwrap().is_symlink);
+        assert_eq!(info.already_freed_bytes, 4096);
+
+        // 已迁移的项目不再作为迁移候选
+        let candidates: Vec<&str> = info.large_items.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(candidates, vec!["KeptApp"]);
+        let (valid, message) = AppDataAnalyzer::validate_migration_item(&base.join("Roaming").join("MovedApp"), false);
+        assert!(!valid, "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_max_depth_keeps_full_item_sizes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("AppData");
+        let item = base.join("Local").join("DeepApp");
+        let level2 = item.join("level2");
+        let level3 = level2.join("level3");
+        std::fs::create_dir_all(&level3).unwrap();
+        std::fs::write(item.join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(level2.join("b.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(level3.join("c.bin"), vec![0u8; 400]).unwrap();
+        std::fs::create_dir_all(base.join("Local").join("Small")).unwrap();
+        std::fs::write(base.join("Local").join("Small").join("s.bin"), vec![0u8; 50]).unwrap();
+        std::fs::write(base.join("Local").join("top.bin"), vec![0u8; 10]).unwrap();
+        // 只有第 3 层的文件是最近写入的
+        let old = filetime::FileTime::from_unix_time(filetime::FileTime::now().unix_seconds() - 100 * 86_400, 0);
+        for path in [item.join("a.bin"), level2.join("b.bin"), level3.clone(), level2.clone(), item.clone()] {
+            filetime::set_file_mtime(&path, old).unwrap();
+        }
+
+        let scan = |max_depth: usize| {
+            let base = base.clone();
+            async move {
+                let mut analyzer = AppDataAnalyzer::new();
+                analyzer.set_config(AppDataConfig { max_depth, ..Default::default() });
+                analyzer.scan_appdata_at(&base).await.unwrap()
+            }
+        };
+        let find = |info: &AppDataInfo, name: &str| {
+            info.first_level_items.iter().find(|item| item.name == name).unwrap().clone()
+        };
+
+        // 任何深度下一级项目的大小都完整汇总，子树大小为一级项目之和
+        for max_depth in [0, 1, 2, 3, usize::MAX] {
+            let info = scan(max_depth).await;
+            assert_eq!(find(&info, "DeepApp").size, 100 + 200 + 400, "max_depth = {}", max_depth);
+            assert_eq!(find(&info, "Small").size, 50);
+            assert_eq!(find(&info, "top.bin").size, 10);
+            assert_eq!(info.local_size, 100 + 200 + 400 + 50 + 10);
+        }
+
+        // 深度只限制查找最近修改时间的范围
+        assert_eq!(find(&scan(3).await, "DeepApp").days_since_modified, Some(0));
+        assert_eq!(find(&scan(2).await, "DeepApp").days_since_modified, Some(100));
+    }
+
+    #[tokio::test]
+    async fn test_appdata_path_for_other_user() {
+        let users_root = tempfile::TempDir::new().unwrap();
+        let item = users_root.path().join("alice").join("AppData").join("Roaming").join("Slack");
+        std::fs::create_dir_all(&item).unwrap();
+        std::fs::write(item.join("data.bin"), vec![0u8; 2048]).unwrap();
+        std::fs::create_dir_all(users_root.path().join("bob")).unwrap();
+
+        let appdata = AppDataAnalyzer::appdata_path_for_user_in(users_root.path(), "alice").unwrap();
+        assert_eq!(appdata, users_root.path().join("alice").join("AppData"));
+        assert_eq!(AppDataAnalyzer::get_appdata_path_for_user(&users_root.path().join("alice").to_string_lossy()).unwrap(), appdata);
+
+        let info = AppDataAnalyzer::new().scan_appdata_at(&appdata).await.unwrap();
+        let slack = info.first_level_items.iter().find(|item| item.name == "Slack").unwrap();
+        assert_eq!((slack.parent_type.as_str(), slack.size), ("Roaming", 2048));
+
+        // 没有 AppData、不存在的用户和越界的用户名都会被拒绝
+        assert!(AppDataAnalyzer::appdata_path_for_user_in(users_root.path(), "bob").unwrap_err().contains("AppData目录不存在"));
+        assert!(AppDataAnalyzer::appdata_path_for_user_in(users_root.path(), "carol").is_err());
+        for invalid in ["", "..", "../alice", "alice/AppData"] {
+            assert!(AppDataAnalyzer::appdata_path_for_user_in(users_root.path(), invalid).unwrap_err().contains("无效的用户名"), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_build_migration_target_preserves_subtree() {
+        let target_drive = Path::new("/mnt/d");
+
+        let local = Path::new("/home/test/AppData/Local/TestApp");
+        assert_eq!(AppDataAnalyzer::subtree_of(local), Some("Local"));
+        assert_eq!(
+            AppDataAnalyzer::build_migration_target(target_drive, local, &TargetLayout::PreserveSubtree).unwrap(),
+            Path::new("/mnt/d/AppData/Local/TestApp")
+        );
+
+        let other = Path::new("/home/test/Documents/TestApp");
+        assert_eq!(AppDataAnalyzer::subtree_of(other), None);
+        assert_eq!(
+            AppDataAnalyzer::build_migration_target(target_drive, other, &TargetLayout::PreserveSubtree).unwrap(),
+            Path::new("/mnt/d/TestApp")
+        );
+    }
+
+    #[test]
+    fn test_migration_plan_exceeding_free_space_is_invalid() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut source_items = Vec::new();
+        for (name, size) in [("AppA", 600usize), ("AppB", 400)] {
+            let item = temp_dir.path().join(name);
+            std::fs::create_dir_all(&item).unwrap();
+            std::fs::write(item.join("data.bin"), vec![0u8; size]).unwrap();
+            source_items.push(item.display().to_string());
+        }
+        let options = AppDataMigrationOptions {
+            source_items,
+            target_drive: temp_dir.path().display().to_string(),
+            ..Default::default()
+        };
+        let service = MigrationService::new();
+
+        // 空间充足：1000 字节加 20% 缓冲为 1200 字节
+        let plan = AppDataAnalyzer::validate_migration_plan(&options, &service, Some(2000), &[]);
+        assert!(plan.valid, "{:?}", plan.reasons);
+        assert_eq!(plan.total_bytes, 1000);
+        assert_eq!(plan.required_bytes, 1200);
+        assert_eq!(plan.items.iter().map(|item| item.size).collect::<Vec<_>>(), vec![600, 400]);
+
+        // 单项都有效，但总量超出目标磁盘可用空间
+        let plan = AppDataAnalyzer::validate_migration_plan(&options, &service, Some(1100), &[]);
+        assert!(!plan.valid);
+        assert!(plan.items.iter().all(|item| item.valid));
+        assert!(plan.reasons.iter().any(|reason| reason.contains("空间不足")), "{:?}", plan.reasons);
+
+        // 系统保护目录中的项目被标记为无效
+        service.add_protected_path(temp_dir.path().join("AppB"));
+        let plan = AppDataAnalyzer::validate_migration_plan(&options, &service, None, &[]);
+        assert!(!plan.valid);
+        assert!(plan.items[0].valid);
+        assert!(!plan.items[1].valid && plan.items[1].message.contains("系统保护目录"));
+    }
+
+    #[test]
+    fn test_running_app_produces_warning() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut source_items = Vec::new();
+        for name in ["Discord", "Code", "Zoom"] {
+            let item = temp_dir.path().join(name);
+            std::fs::create_dir_all(&item).unwrap();
+            source_items.push(item.display().to_string());
+        }
+        let options = AppDataMigrationOptions {
+            source_items,
+            target_drive: temp_dir.path().display().to_string(),
+            ..Default::default()
+        };
+        let processes = vec![
+            "explorer.exe".to_string(),
+            "DiscordPTB.exe".to_string(),
+            "CODE.EXE".to_string(),
+            "Zo.exe".to_string(),
+        ];
+
+        let plan = AppDataAnalyzer::validate_migration_plan(&options, &MigrationService::new(), None, &processes);
+        assert!(plan.valid, "{:?}", plan.reasons);
+        assert_eq!(plan.warnings.len(), 2, "{:?}", plan.warnings);
+        assert!(plan.warnings[0].contains("DiscordPTB.exe"));
+        assert!(plan.warnings[1].contains("CODE.EXE"));
+
+        let plan = AppDataAnalyzer::validate_migration_plan(&options, &MigrationService::new(), None, &[]);
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_build_migration_target_for_each_layout() {
+        let target_drive = Path::new("/mnt/d");
+        let local = Path::new("/home/test/AppData/Local/TestApp");
+        let roaming = Path::new("/home/test/AppData/Roaming/ChatApp");
+
+        let targets = |layout: TargetLayout| -> Vec<PathBuf> {
+            [local, roaming].iter()
+                .map(|item| AppDataAnalyzer::build_migration_target(target_drive, item, &layout).unwrap())
+                .collect()
+        };
+
+        assert_eq!(targets(TargetLayout::Flat), vec![
+            PathBuf::from("/mnt/d/TestApp"),
+            PathBuf::from("/mnt/d/ChatApp"),
+        ]);
+        assert_eq!(targets(TargetLayout::PreserveSubtree), vec![
+            PathBuf::from("/mnt/d/AppData/Local/TestApp"),
+            PathBuf::from("/mnt/d/AppData/Roaming/ChatApp"),
+        ]);
+        assert_eq!(targets(TargetLayout::UnderNamedFolder("MovedAppData".to_string())), vec![
+            PathBuf::from("/mnt/d/MovedAppData/TestApp"),
+            PathBuf::from("/mnt/d/MovedAppData/ChatApp"),
+        ]);
+
+        for invalid in ["", "  ", "../escape", "/absolute"] {
+            let layout = TargetLayout::UnderNamedFolder(invalid.to_string());
+            assert_eq!(AppDataAnalyzer::build_migration_target(target_drive, local, &layout), None, "{:?}", invalid);
+        }
+        assert_eq!(
+            serde_json::to_string(&TargetLayout::UnderNamedFolder("MovedAppData".to_string())).unwrap(),
+            r#"{"underNamedFolder":"MovedAppData"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_same_named_items_from_different_subtrees() {
+        use crate::migration_service::{MigrationService, MigrationOptions};
+
+        let source_root = tempfile::TempDir::new().unwrap();
+        let target_drive = tempfile::TempDir::new().unwrap();
+
+        let local_item = source_root.path().join("AppData").join("Local").join("SharedApp");
+        let roaming_item = source_root.path().join("AppData").join("Roaming").join("SharedApp");
+        for (item, content) in [(&local_item, "local"), (&roaming_item, "roaming")] {
+            std::fs::create_dir_all(item).unwrap();
+            std::fs::write(item.join("settings.json"), content).unwrap();
+        }
+
+        let service = MigrationService::new();
+        for item in [&local_item, &roaming_item] {
+            let target = AppDataAnalyzer::build_migration_target(target_drive.path(), item, &TargetLayout::PreserveSubtree).unwrap();
+            std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+
+            let result = service.migrate_folder(MigrationOptions {
+                source_path: item.display().to_string(),
+                target_path: target.display().to_string(),
+                ..Default::default()
+            }).await.unwrap();
+            assert!(result.success, "{}", result.message);
+        }
+
+        let appdata_target = target_drive.path().join("AppData");
+        assert_eq!(
+            std::fs::read_to_string(appdata_target.join("Local").join("SharedApp").join("settings.json")).unwrap(),
+            "local"
+        );
+        assert_eq!(
+            std::fs::read_to_string(appdata_target.join("Roaming").join("SharedApp").join("settings.json")).unwrap(),
+            "roaming"
+        );
+    }
+
+    #[test]
+    fn test_blocklisted_items_rejected_by_default() {
+        let appdata = tempfile::TempDir::new().unwrap();
+        let packages = appdata.path().join("AppData").join("Local").join("Packages");
+        let windows = appdata.path().join("AppData").join("Roaming").join("Microsoft").join("Windows").join("Start Menu");
+        let microsoft = appdata.path().join("AppData").join("Local").join("Microsoft");
+        for dir in [&packages, &windows, &microsoft] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+
+        // 黑名单目录本身、其子目录及包含它的上级目录都应被拒绝
+        for dir in [&packages, &windows, &microsoft] {
+            let (valid, message) = AppDataAnalyzer::validate_migration_item(dir, false);
+            assert!(!valid, "{} 应被拒绝", dir.display());
+            assert!(message.contains("系统关键目录"));
+        }
+    }
+
+    #[test]
+    fn test_blocklisted_items_allowed_with_override() {
+        let appdata = tempfile::TempDir::new().unwrap();
+        let packages = appdata.path().join("AppData").join("Local").join("Packages");
+        std::fs::create_dir_all(&packages).unwrap();
+
+        let (valid, _) = AppDataAnalyzer::validate_migration_item(&packages, true);
+        assert!(valid);
+
+        let normal = appdata.path().join("AppData").join("Local").join("SomeApp");
+        std::fs::create_dir_all(&normal).unwrap();
+        assert_eq!(AppDataAnalyzer::blocklist_match(&normal), None);
+        assert!(AppDataAnalyzer::validate_migration_item(&normal, false).0);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_scan_delivers_all_events_for_any_concurrency() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let layout = [("Local", 5usize), ("LocalLow", 2), ("Roaming", 3)];
+        for (subtree, count) in layout {
+            for i in 0..count {
+                let dir = temp_dir.path().join(subtree).join(format!("App{}", i));
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(dir.join("data.bin"), vec![0u8; 1024 * (i + 1)]).unwrap();
+            }
+        }
+
+        for concurrency in [1, 2, 16] {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let options = StreamingScanOptions { concurrency, event_throttle: None };
+            let scanner = StreamingAppDataScanner::new(AppDataAnalyzer::new(), tx).with_options(options);
+            let info = scanner.scan_appdata_streaming_at(temp_dir.path()).await.unwrap();
+            drop(scanner);
+
+            let mut events = Vec::new();
+            while let Some(event) = rx.recv().await {
+                events.push(event);
+            }
+
+            // 每个子树的事件连续出现：开始 → 全部项目 → 完成，最后是扫描完成事件
+            let mut cursor = 0;
+            for (subtree, count) in layout {
+                assert!(matches!(&events[cursor], ScanEvent::DirectoryStarted { name, .. } if name == subtree));
+                for event in &events[cursor + 1..cursor + 1 + count] {
+                    assert!(matches!(event, ScanEvent::ItemFound { item } if item.parent_type == subtree));
+                }
+                cursor += count + 1;
+                assert!(matches!(&events[cursor], ScanEvent::DirectoryCompleted { name, item_count, .. }
+                    if name == subtree && *item_count == count));
+                cursor += 1;
+            }
+            assert!(matches!(&events[cursor], ScanEvent::ScanCompleted { total_items: 10, total_size, .. }
+                if *total_size == info.total_size));
+            assert_eq!(events.len(), cursor + 1, "并发上限 {} 时事件数量不符", concurrency);
+            assert_eq!(info.first_level_items.len(), 10);
+        }
+    }
+}
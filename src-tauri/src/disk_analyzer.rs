@@ -1,11 +1,43 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::time::SystemTime;
-use log::{info, error, warn};
+use log::{debug, info, error, warn};
 use serde::{Serialize, Deserialize};
+use crate::performance_optimizer::PerformanceOptimizer;
+use crate::util::is_within;
+use crate::types::CancellationReason;
+
+pub mod export;
+pub mod diff;
+pub mod compact;
+
+/// 递归扫描时每个目录最多处理的条目数
+const MAX_ENTRIES_PER_DIR: usize = 1000;
+
+/// 快速模式下，小于阈值 1/PRUNE_SIZE_RATIO 的目录不保留子目录明细
+const PRUNE_SIZE_RATIO: u64 = 10;
+
+/// 未配置性能优化器时的文件批大小
+const DEFAULT_FILE_BATCH_SIZE: usize = 100;
+
+/// 平滑扫描速度时保留的最近进度采样点数
+const SPEED_SAMPLE_WINDOW: usize = 6;
+
+/// 扫描预算用尽时子目录扫描返回的错误信息（上层据此停止深入而非记录错误）
+const SCAN_BUDGET_EXCEEDED: &str = "扫描预算已用尽";
+
+/// 扫描被取消时返回的错误信息前缀，其后拼接取消原因
+pub const SCAN_CANCELLED: &str = "扫描已取消";
+
+/// 扫描状态：运行中
+const SCAN_STATE_RUNNING: u8 = 0;
+/// 扫描状态：已暂停
+const SCAN_STATE_PAUSED: u8 = 1;
+/// 扫描状态：已取消
+const SCAN_STATE_CANCELLED: u8 = 2;
 
 /// 目录信息结构体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DirectoryInfo {
     pub path: String,
     pub name: String,
@@ -15,6 +47,66 @@ pub struct DirectoryInfo {
     pub last_modified: Option<SystemTime>,
     pub is_large_folder: bool,      // 新增：标识大文件夹
     pub size_percentage: f64,       // 新增：相对父目录的占比
+    #[serde(default)]
+    pub budget_exceeded: bool,      // 新增：因扫描预算用尽，该目录（或其子目录）的结果不完整
+    #[serde(default)]
+    pub size_on_disk: Option<u64>,  // 新增：实际占用的磁盘空间（稀疏/压缩文件小于 size），未启用统计时为 None
+}
+
+/// 递归统计路径得到的总量
+///
+/// 除大小和文件数外还记录目录数与最新修改时间，整体可作为目录树的指纹：
+/// 任何层级的条目增删、重命名或文件内容改写都会使其发生变化。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathTotals {
+    pub size: u64,
+    pub file_count: u64,
+    pub directory_count: u64,
+    pub newest_modified: Option<SystemTime>,
+}
+
+impl PathTotals {
+    /// 计入一个条目的修改时间
+    fn record_modified(&mut self, metadata: &fs::Metadata) {
+        if let Ok(modified) = metadata.modified() {
+            self.newest_modified = self.newest_modified.max(Some(modified));
+        }
+    }
+}
+
+/// 缓存的完整扫描结果
+#[derive(Debug, Clone)]
+pub struct CachedScan {
+    pub directory: DirectoryInfo,
+    pub summary: ScanSummary,
+    /// 影响扫描结果的扫描设置，设置不同时缓存不可复用
+    pub settings_key: String,
+    /// 扫描开始前目录树的指纹，与磁盘上的当前指纹一致时缓存才可复用
+    pub fingerprint: PathTotals,
+}
+
+/// 递归统计路径失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeasureError {
+    /// 统计过程中扫描被取消
+    Cancelled(CancellationReason),
+    /// 起始路径无法读取
+    Io(String),
+}
+
+impl std::fmt::Display for MeasureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MeasureError::Cancelled(reason) => write!(f, "{}: {}", SCAN_CANCELLED, reason.description()),
+            MeasureError::Io(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<MeasureError> for String {
+    fn from(error: MeasureError) -> Self {
+        error.to_string()
+    }
 }
 
 /// 扫描进度信息
@@ -31,16 +123,102 @@ pub struct ScanProgress {
     pub scan_speed: f64,            // 新增：扫描速度（文件/秒）
     pub start_time: Option<SystemTime>, // 新增：扫描开始时间
     pub large_folders_found: u64,   // 新增：发现的大文件夹数量
+    #[serde(default)]
+    pub cancellation_reason: Option<CancellationReason>, // 新增：扫描被取消的原因（未取消时为 None）
+}
+
+/// 单个文件的路径和大小
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntrySize {
+    pub path: String,
+    pub size: u64,
+}
+
+/// 文件大小直方图的分桶数
+pub const SIZE_HISTOGRAM_BUCKETS: usize = 8;
+
+/// 直方图各桶的上界（不含），最后一个桶收纳不小于 1GB 的文件
+const SIZE_HISTOGRAM_BOUNDS: [u64; SIZE_HISTOGRAM_BUCKETS - 1] = [
+    1024,
+    10 * 1024,
+    100 * 1024,
+    1024 * 1024,
+    10 * 1024 * 1024,
+    100 * 1024 * 1024,
+    1024 * 1024 * 1024,
+];
+
+/// 直方图各桶的显示名称
+const SIZE_HISTOGRAM_LABELS: [&str; SIZE_HISTOGRAM_BUCKETS] = [
+    "0-1KB", "1-10KB", "10-100KB", "100KB-1MB", "1-10MB", "10-100MB", "100MB-1GB", ">1GB",
+];
+
+/// 计算文件大小所在的直方图桶
+pub fn size_histogram_bucket(size: u64) -> usize {
+    SIZE_HISTOGRAM_BOUNDS.iter()
+        .position(|bound| size < *bound)
+        .unwrap_or(SIZE_HISTOGRAM_BUCKETS - 1)
+}
+
+/// 将文件大小直方图格式化为多行文本，每行为 `区间: 文件数 (占比)`
+pub fn format_histogram(histogram: &[u64; SIZE_HISTOGRAM_BUCKETS]) -> String {
+    let total: u64 = histogram.iter().sum();
+    SIZE_HISTOGRAM_LABELS.iter()
+        .zip(histogram.iter())
+        .map(|(label, count)| format!("{:>10}: {} ({:.1}%)", label, count, get_size_percentage(*count, total)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 一次扫描的汇总统计，在遍历过程中顺带累计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanSummary {
+    /// 实际读取过内容的目录数（含扫描根目录）
+    pub total_directories: u64,
+    pub total_files: u64,
+    /// 读取过内容的最深目录层级（扫描根目录为第 0 层）
+    pub max_depth_reached: usize,
+    /// 各层级读取过的目录数，下标为层级
+    pub directories_by_depth: Vec<u64>,
+    pub largest_file: Option<FileEntrySize>,
+    pub large_folders_found: u64,
+    /// 按大小区间统计的文件数（0-1KB、1-10KB……>1GB），用于判断空间是被大量小文件还是少数大文件占用
+    #[serde(default)]
+    pub size_histogram: [u64; SIZE_HISTOGRAM_BUCKETS],
+}
+
+/// 扫描目录的结果及其汇总统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryScanWithSummary {
+    pub directory: DirectoryInfo,
+    pub summary: ScanSummary,
 }
 
 /// 磁盘分析器
 #[derive(Clone)]
 pub struct DiskAnalyzer {
     max_depth: usize,
-    cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    scan_state: std::sync::Arc<std::sync::atomic::AtomicU8>, // 运行中/已暂停/已取消
+    cancel_reason: std::sync::Arc<std::sync::atomic::AtomicU8>, // 新增：取消原因（CancellationReason 编码，0 表示未取消）
     progress_info: std::sync::Arc<std::sync::Mutex<ScanProgress>>,
     large_folder_threshold: u64, // 新增：大文件夹阈值（默认1GB）
     is_c_drive_mode: bool,       // 新增：是否为C盘专项扫描模式
+    count_before_scan: bool,     // 新增：扫描前预先统计条目总数
+    scanned_entries: std::sync::Arc<std::sync::atomic::AtomicU64>, // 新增：本次扫描已处理的条目数
+    expected_entries: std::sync::Arc<std::sync::atomic::AtomicU64>, // 新增：预统计得到的条目总数（0表示未知）
+    progress_sender: Option<tokio::sync::mpsc::UnboundedSender<ScanProgress>>, // 新增：流式扫描的进度推送通道
+    performance_optimizer: Option<std::sync::Arc<PerformanceOptimizer>>, // 新增：提供工作线程数和批大小
+    prune_small_folders: bool,   // 新增：只关注大文件夹的快速模式
+    max_scan_files: Option<u64>, // 新增：扫描预算（文件数），超出后停止深入
+    max_scan_bytes: Option<u64>, // 新增：扫描预算（字节数），超出后停止深入
+    budget_files: std::sync::Arc<std::sync::atomic::AtomicU64>, // 新增：本次扫描已计入预算的文件数
+    budget_bytes: std::sync::Arc<std::sync::atomic::AtomicU64>, // 新增：本次扫描已计入预算的字节数
+    speed_samples: std::sync::Arc<std::sync::Mutex<std::collections::VecDeque<(std::time::Instant, u64)>>>, // 新增：最近的（时间, 已处理数）采样，用于平滑扫描速度
+    include_hidden: bool,        // 新增：是否统计隐藏文件（Unix 下为点文件）
+    include_system: bool,        // 新增：是否统计系统文件（仅 Windows 有系统属性）
+    allowed_scan_roots: Option<Vec<PathBuf>>, // 新增：允许扫描的根目录（None 表示不限制）
+    summary: std::sync::Arc<std::sync::Mutex<ScanSummary>>, // 新增：本次扫描的汇总统计
+    compute_size_on_disk: bool,  // 新增：是否额外统计实际占用的磁盘空间
 }
 
 impl Default for DiskAnalyzer {
@@ -54,9 +232,26 @@ impl DiskAnalyzer {
     pub fn new() -> Self {
         Self {
             max_depth: 3, // 默认最大深度为3层
-            cancel_flag: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            scan_state: std::sync::Arc::new(std::sync::atomic::AtomicU8::new(SCAN_STATE_RUNNING)),
+            cancel_reason: std::sync::Arc::new(std::sync::atomic::AtomicU8::new(0)),
             large_folder_threshold: 1024 * 1024 * 1024, // 默认1GB
             is_c_drive_mode: false,
+            count_before_scan: false,
+            scanned_entries: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            expected_entries: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            progress_sender: None,
+            performance_optimizer: None,
+            prune_small_folders: false,
+            max_scan_files: None,
+            max_scan_bytes: None,
+            budget_files: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            budget_bytes: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            speed_samples: std::sync::Arc::new(std::sync::Mutex::new(std::collections::VecDeque::with_capacity(SPEED_SAMPLE_WINDOW))),
+            include_hidden: true,
+            include_system: true,
+            allowed_scan_roots: None,
+            summary: std::sync::Arc::new(std::sync::Mutex::new(ScanSummary::default())),
+            compute_size_on_disk: false,
             progress_info: std::sync::Arc::new(std::sync::Mutex::new(ScanProgress {
                 current_path: String::new(),
                 processed_files: 0,
@@ -69,6 +264,7 @@ impl DiskAnalyzer {
                 scan_speed: 0.0,
                 start_time: None,
                 large_folders_found: 0,
+                cancellation_reason: None,
             })),
         }
     }
@@ -88,6 +284,157 @@ impl DiskAnalyzer {
         self.is_c_drive_mode = enabled;
     }
 
+    /// 设置是否在扫描前预先统计条目总数
+    ///
+    /// 预统计需要额外遍历一次目录树，但能让进度百分比和剩余时间准确。
+    pub fn set_count_before_scan(&mut self, enabled: bool) {
+        self.count_before_scan = enabled;
+    }
+
+    /// 设置是否裁剪小目录的子目录明细（仍会累计其大小）
+    pub fn set_prune_small_folders(&mut self, enabled: bool) {
+        self.prune_small_folders = enabled;
+    }
+
+    /// 设置是否统计实际占用的磁盘空间（与资源管理器的“占用空间”对应，每个文件多一次系统调用）
+    pub fn set_compute_size_on_disk(&mut self, enabled: bool) {
+        self.compute_size_on_disk = enabled;
+    }
+
+    /// 设置是否统计隐藏文件（关闭后与资源管理器默认显示的大小一致）
+    pub fn set_include_hidden(&mut self, enabled: bool) {
+        self.include_hidden = enabled;
+    }
+
+    /// 设置是否统计系统文件
+    pub fn set_include_system(&mut self, enabled: bool) {
+        self.include_system = enabled;
+    }
+
+    /// 设置允许扫描的根目录，扫描路径必须等于或位于其中之一；传入 None 取消限制
+    pub fn set_allowed_scan_roots(&mut self, roots: Option<Vec<PathBuf>>) {
+        self.allowed_scan_roots = roots;
+    }
+
+    /// 检查扫描路径是否合适：须位于允许的扫描根目录内；
+    /// 未开启C盘专项模式时拒绝整个磁盘根目录和 `protected` 中的系统目录，并建议可扫描的子文件夹
+    pub fn check_scan_root(&self, path: &Path, protected: &[PathBuf]) -> Result<(), String> {
+        if let Some(roots) = &self.allowed_scan_roots {
+            if !roots.iter().any(|root| is_within(path, root)) {
+                let allowed: Vec<String> = roots.iter().map(|root| root.display().to_string()).collect();
+                return Err(format!("{} 不在允许的扫描范围内，允许的目录: {}", path.display(), allowed.join(", ")));
+            }
+        }
+
+        if self.is_c_drive_mode {
+            return Ok(());
+        }
+
+        let reason = if is_drive_root(path) {
+            format!("不建议扫描整个磁盘根目录 {}（耗时长且大多无访问权限）", path.display())
+        } else if let Some(system_dir) = protected.iter().find(|dir| is_within(path, dir)) {
+            format!("{} 属于系统目录 {}，扫描结果无法迁移", path.display(), system_dir.display())
+        } else {
+            return Ok(());
+        };
+
+        let suggestions = Self::suggest_scan_subfolders(path, protected);
+        let hint = if suggestions.is_empty() {
+            "请选择其中的子文件夹".to_string()
+        } else {
+            format!("请选择其中的子文件夹，例如: {}", suggestions.join(", "))
+        };
+        Err(format!("{}。{}，或开启C盘专项扫描模式", reason, hint))
+    }
+
+    /// 列出可代替扫描的子文件夹（跳过系统目录和隐藏目录，最多5个）
+    fn suggest_scan_subfolders(path: &Path, protected: &[PathBuf]) -> Vec<String> {
+        let mut suggestions: Vec<String> = fs::read_dir(path)
+            .map(|entries| {
+                entries.flatten()
+                    .map(|entry| entry.path())
+                    .filter(|child| child.is_dir())
+                    .filter(|child| !child.file_name().is_some_and(|name| name.to_string_lossy().starts_with(['.', '$'])))
+                    .filter(|child| !protected.iter().any(|dir| is_within(child, dir)))
+                    .map(|child| child.display().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        suggestions.sort();
+        suggestions.truncate(5);
+        suggestions
+    }
+
+    /// 按隐藏/系统文件设置判断条目是否计入扫描，被排除的目录连同其内容一起跳过
+    fn is_entry_included(&self, entry: &fs::DirEntry) -> bool {
+        if self.include_hidden && self.include_system {
+            return true;
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::fs::MetadataExt;
+            const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+            const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+            let attributes = match entry.metadata() {
+                Ok(metadata) => metadata.file_attributes(),
+                Err(_) => return true,
+            };
+            (self.include_hidden || attributes & FILE_ATTRIBUTE_HIDDEN == 0)
+                && (self.include_system || attributes & FILE_ATTRIBUTE_SYSTEM == 0)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            self.include_hidden || !entry.file_name().to_string_lossy().starts_with('.')
+        }
+    }
+
+    /// 快速模式下，远小于大文件夹阈值且不含大子文件夹的非根目录可以丢弃子目录明细
+    fn should_prune(&self, total_size: u64, subdirectories: &[DirectoryInfo], depth: usize) -> bool {
+        self.prune_small_folders
+            && depth > 0
+            && total_size.saturating_mul(PRUNE_SIZE_RATIO) < self.large_folder_threshold
+            && !subdirectories.iter().any(|subdir| subdir.is_large_folder)
+    }
+
+    /// 设置扫描预算：文件数或总大小任一超出后停止深入，结果标记为 `budget_exceeded`
+    pub fn set_scan_budget(&mut self, max_files: Option<u64>, max_bytes: Option<u64>) {
+        self.max_scan_files = max_files;
+        self.max_scan_bytes = max_bytes;
+    }
+
+    /// 本次扫描是否已用尽预算
+    fn budget_exhausted(&self) -> bool {
+        let files = self.budget_files.load(std::sync::atomic::Ordering::Relaxed);
+        let bytes = self.budget_bytes.load(std::sync::atomic::Ordering::Relaxed);
+        self.max_scan_files.is_some_and(|max| files >= max)
+            || self.max_scan_bytes.is_some_and(|max| bytes >= max)
+    }
+
+    /// 将文件计入扫描预算
+    fn charge_budget(&self, files: u64, bytes: u64) {
+        self.budget_files.fetch_add(files, std::sync::atomic::Ordering::Relaxed);
+        self.budget_bytes.fetch_add(bytes, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// 设置共享的性能优化器，扫描时从中读取工作线程数和批大小
+    pub fn set_performance_optimizer(&mut self, optimizer: std::sync::Arc<PerformanceOptimizer>) {
+        self.performance_optimizer = Some(optimizer);
+    }
+
+    /// 当前扫描使用的（工作线程数, 批大小）；未设置优化器时单线程扫描
+    fn scan_settings(&self) -> (usize, usize) {
+        match &self.performance_optimizer {
+            Some(optimizer) => {
+                let settings = optimizer.scan_settings();
+                (settings.worker_threads.max(1), settings.batch_size.max(1))
+            }
+            None => (1, DEFAULT_FILE_BATCH_SIZE),
+        }
+    }
+
     /// 获取C盘路径
     pub fn get_c_drive_path() -> PathBuf {
         PathBuf::from("C:\\")
@@ -98,14 +445,30 @@ impl DiskAnalyzer {
         path.to_string_lossy().to_uppercase().starts_with("C:\\")
     }
 
-    /// 取消当前扫描
-    pub fn cancel_scan(&self) {
-        self.cancel_flag.store(true, std::sync::atomic::Ordering::Relaxed);
+    /// 取消当前扫描，并记录取消原因
+    pub fn cancel_scan(&self, reason: CancellationReason) {
+        // 先记录原因再设置取消状态，扫描线程看到取消时总能读到原因
+        self.cancel_reason.store(reason.to_u8(), std::sync::atomic::Ordering::Relaxed);
+        self.scan_state.store(SCAN_STATE_CANCELLED, std::sync::atomic::Ordering::Release);
+        self.progress_info.lock().unwrap().cancellation_reason = Some(reason);
+        info!("扫描已取消，原因: {}", reason.description());
+    }
+
+    /// 获取本次扫描的取消原因，未取消时返回 None
+    pub fn cancellation_reason(&self) -> Option<CancellationReason> {
+        CancellationReason::from_u8(self.cancel_reason.load(std::sync::atomic::Ordering::Relaxed))
+    }
+
+    /// 扫描被取消时返回的错误信息，格式为 `扫描已取消: 原因`
+    fn cancelled_error(&self) -> String {
+        let reason = self.cancellation_reason().unwrap_or_default();
+        format!("{}: {}", SCAN_CANCELLED, reason.description())
     }
 
     /// 重置取消标志
     pub fn reset_cancel_flag(&self) {
-        self.cancel_flag.store(false, std::sync::atomic::Ordering::Relaxed);
+        self.scan_state.store(SCAN_STATE_RUNNING, std::sync::atomic::Ordering::Relaxed);
+        self.cancel_reason.store(0, std::sync::atomic::Ordering::Relaxed);
         // 重置进度信息
         let mut progress = self.progress_info.lock().unwrap();
         *progress = ScanProgress {
@@ -120,12 +483,48 @@ impl DiskAnalyzer {
             scan_speed: 0.0,
             start_time: None,
             large_folders_found: 0,
+            cancellation_reason: None,
         };
+        drop(progress);
+        self.speed_samples.lock().unwrap().clear();
+    }
+
+    /// 暂停当前扫描（仅在运行中时生效）
+    pub fn pause_scan(&self) -> bool {
+        self.scan_state.compare_exchange(
+            SCAN_STATE_RUNNING,
+            SCAN_STATE_PAUSED,
+            std::sync::atomic::Ordering::Relaxed,
+            std::sync::atomic::Ordering::Relaxed,
+        ).is_ok()
+    }
+
+    /// 恢复已暂停的扫描
+    pub fn resume_scan(&self) -> bool {
+        self.scan_state.compare_exchange(
+            SCAN_STATE_PAUSED,
+            SCAN_STATE_RUNNING,
+            std::sync::atomic::Ordering::Relaxed,
+            std::sync::atomic::Ordering::Relaxed,
+        ).is_ok()
+    }
+
+    /// 检查扫描是否已暂停
+    pub fn is_paused(&self) -> bool {
+        self.scan_state.load(std::sync::atomic::Ordering::Relaxed) == SCAN_STATE_PAUSED
+    }
+
+    /// 暂停期间阻塞等待，直到恢复或取消
+    fn wait_while_paused(&self) {
+        while self.is_paused() {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
     }
 
-    /// 检查是否已取消
+    /// 检查是否已取消（暂停期间会先等待恢复）
     fn is_cancelled(&self) -> bool {
-        self.cancel_flag.load(std::sync::atomic::Ordering::Relaxed)
+        self.wait_while_paused();
+        self.scan_state.load(std::sync::atomic::Ordering::Acquire) == SCAN_STATE_CANCELLED
     }
 
     /// 获取当前扫描进度
@@ -146,21 +545,37 @@ impl DiskAnalyzer {
             0.0
         };
 
-        // 计算扫描速度和预计剩余时间
-        if let Some(start_time) = progress.start_time {
-            if let Ok(elapsed) = start_time.elapsed() {
-                let elapsed_secs = elapsed.as_secs();
-                if elapsed_secs > 0 {
-                    progress.scan_speed = processed_files as f64 / elapsed_secs as f64;
-                    if progress.scan_speed > 0.0 && total_files > processed_files {
-                        let remaining_files = total_files - processed_files;
-                        progress.estimated_time_remaining = (remaining_files as f64 / progress.scan_speed) as u64;
-                    }
-                }
+        // 计算扫描速度（最近几次更新的滑动平均）和预计剩余时间
+        if let Some(speed) = self.record_speed_sample(processed_files, std::time::Instant::now()) {
+            progress.scan_speed = speed;
+            if speed > 0.0 && total_files > processed_files {
+                let remaining_files = total_files - processed_files;
+                progress.estimated_time_remaining = (remaining_files as f64 / speed) as u64;
             }
         }
     }
 
+    /// 记录一次进度采样，返回窗口内的平均扫描速度（文件/秒），采样不足时返回 None
+    ///
+    /// 已处理数回退（如按目录统计的进度进入新目录）时重新开始采样。
+    fn record_speed_sample(&self, processed_files: u64, at: std::time::Instant) -> Option<f64> {
+        let mut samples = self.speed_samples.lock().unwrap();
+        if samples.back().is_some_and(|&(_, last)| processed_files < last) {
+            samples.clear();
+        }
+        if samples.len() == SPEED_SAMPLE_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back((at, processed_files));
+
+        let (&(first_at, first_count), &(last_at, last_count)) = (samples.front()?, samples.back()?);
+        let elapsed = last_at.saturating_duration_since(first_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some((last_count - first_count) as f64 / elapsed)
+    }
+
     /// 更新目录进度
     fn update_directory_progress(&self, current_directory: &str, processed_dirs: u64, total_dirs: u64) {
         let mut progress = self.progress_info.lock().unwrap();
@@ -169,6 +584,72 @@ impl DiskAnalyzer {
         progress.total_directories = total_dirs;
     }
 
+    /// 流式扫描时推送当前进度快照（进度不超过100%）
+    fn emit_progress(&self, current_path: &str) {
+        let Some(sender) = &self.progress_sender else {
+            return;
+        };
+
+        let scanned = self.scanned_entries.load(std::sync::atomic::Ordering::Relaxed);
+        let expected = self.expected_entries.load(std::sync::atomic::Ordering::Relaxed);
+        self.update_progress(current_path, scanned.min(expected), expected);
+
+        let snapshot = self.get_scan_progress();
+        let _ = sender.send(snapshot);
+    }
+
+    /// 将一个已读取目录的统计计入本次扫描的汇总
+    fn record_directory_summary(
+        &self,
+        depth: usize,
+        files: u64,
+        largest_file: Option<(u64, PathBuf)>,
+        size_histogram: &[u64; SIZE_HISTOGRAM_BUCKETS],
+        is_large_folder: bool,
+    ) {
+        let mut summary = self.summary.lock().unwrap();
+        summary.total_directories += 1;
+        summary.total_files += files;
+        summary.max_depth_reached = summary.max_depth_reached.max(depth);
+        if summary.directories_by_depth.len() <= depth {
+            summary.directories_by_depth.resize(depth + 1, 0);
+        }
+        summary.directories_by_depth[depth] += 1;
+        if let Some((size, path)) = largest_file {
+            if summary.largest_file.as_ref().is_none_or(|largest| size > largest.size) {
+                summary.largest_file = Some(FileEntrySize { path: path.to_string_lossy().to_string(), size });
+            }
+        }
+        if is_large_folder {
+            summary.large_folders_found += 1;
+        }
+        for (total, count) in summary.size_histogram.iter_mut().zip(size_histogram) {
+            *total += count;
+        }
+    }
+
+    /// 获取最近一次扫描的汇总统计
+    pub fn scan_summary(&self) -> ScanSummary {
+        self.summary.lock().unwrap().clone()
+    }
+
+    /// 复制一个拥有独立汇总统计的分析器，其余状态（进度、取消标志等）仍与当前分析器共享
+    fn with_own_summary(&self) -> Self {
+        Self {
+            summary: std::sync::Arc::new(std::sync::Mutex::new(ScanSummary::default())),
+            ..self.clone()
+        }
+    }
+
+    /// 扫描目录并返回汇总统计
+    pub async fn scan_directory_with_summary(&self, path: &Path) -> Result<DirectoryScanWithSummary, String> {
+        let scan = self.with_own_summary();
+        let directory = scan.scan_directory_async(path).await?;
+        let summary = scan.scan_summary();
+        debug!("文件大小分布 {}:\n{}", path.display(), format_histogram(&summary.size_histogram));
+        Ok(DirectoryScanWithSummary { directory, summary })
+    }
+
     /// 发现大文件夹
     fn increment_large_folders_found(&self) {
         let mut progress = self.progress_info.lock().unwrap();
@@ -201,8 +682,44 @@ impl DiskAnalyzer {
         result
     }
 
+    /// 流式扫描目录：每完成一个目录即通过 `progress_tx` 推送进度，结束时推送100%
+    ///
+    /// 为保证进度单调递增，流式扫描总是先预统计条目总数。
+    pub async fn scan_directory_streaming(
+        &self,
+        path: &Path,
+        progress_tx: tokio::sync::mpsc::UnboundedSender<ScanProgress>,
+    ) -> Result<DirectoryInfo, String> {
+        let mut analyzer = self.clone();
+        analyzer.count_before_scan = true;
+        analyzer.progress_sender = Some(progress_tx.clone());
+
+        let result = analyzer.scan_directory_async(path).await;
+
+        if result.is_ok() {
+            let mut progress = analyzer.get_scan_progress();
+            progress.current_path = path.display().to_string();
+            progress.processed_files = progress.total_files;
+            progress.progress = 100.0;
+            progress.estimated_time_remaining = 0;
+            let _ = progress_tx.send(progress);
+        }
+
+        result
+    }
+
     /// 扫描目录（同步版本）
+    ///
+    /// 汇总统计先累计在本次扫描独立的计数器中，结束后才写回，克隆出的分析器并发扫描时互不干扰。
     pub fn scan_directory(&self, path: &Path) -> Result<DirectoryInfo, String> {
+        let scan = self.with_own_summary();
+        let result = scan.scan_directory_counted(path);
+        *self.summary.lock().unwrap() = scan.scan_summary();
+        result
+    }
+
+    /// 扫描目录，汇总统计计入 `self.summary`（调用方保证其为本次扫描独有）
+    fn scan_directory_counted(&self, path: &Path) -> Result<DirectoryInfo, String> {
         info!("磁盘分析器: 开始同步扫描目录 {}", path.display());
         
         // 初始化进度信息
@@ -212,19 +729,104 @@ impl DiskAnalyzer {
             progress.current_path = path.display().to_string();
         }
         
+        self.budget_files.store(0, std::sync::atomic::Ordering::Relaxed);
+        self.budget_bytes.store(0, std::sync::atomic::Ordering::Relaxed);
+        self.speed_samples.lock().unwrap().clear();
+
+        if self.is_cancelled() {
+            return Err(self.cancelled_error());
+        }
+        let Some(optimizer) = self.performance_optimizer.clone().filter(|optimizer| optimizer.caching_enabled()) else {
+            return self.scan_directory_uncached(path);
+        };
+
+        // 指纹在扫描前记录，扫描期间发生的变化会让下次读取缓存时失效
+        let settings_key = self.scan_cache_key(path);
+        let fingerprint = match self.measure_path(path) {
+            Ok(fingerprint) => Some(fingerprint),
+            Err(e @ MeasureError::Cancelled(_)) => return Err(e.into()),
+            Err(e) => {
+                debug!("无法计算目录指纹，跳过扫描缓存 {}: {}", path.display(), e);
+                None
+            }
+        };
+
+        if let Some(cached) = fingerprint.and_then(|fingerprint| {
+            optimizer.cached_scan::<CachedScan>(path, |scan| {
+                scan.settings_key == settings_key && scan.fingerprint == fingerprint
+            })
+        }) {
+            info!("磁盘分析器: 目录未变化，使用缓存的扫描结果 {}", path.display());
+            self.progress_info.lock().unwrap().large_folders_found = cached.summary.large_folders_found;
+            *self.summary.lock().unwrap() = cached.summary.clone();
+            let file_count = cached.directory.file_count;
+            self.update_progress(&path.display().to_string(), file_count, file_count);
+            return Ok(cached.directory.clone());
+        }
+
+        let result = self.scan_directory_uncached(path);
+        if let (Ok(info), Some(fingerprint)) = (&result, fingerprint) {
+            // 预算用尽的结果不完整，不缓存
+            if !info.budget_exceeded {
+                optimizer.cache_scan(path, info.file_count, info.size, info.is_large_folder, CachedScan {
+                    directory: info.clone(),
+                    summary: self.scan_summary(),
+                    settings_key,
+                    fingerprint,
+                });
+            }
+        }
+        result
+    }
+
+    /// 影响扫描结果的设置，作为扫描缓存的校验键
+    fn scan_cache_key(&self, path: &Path) -> String {
+        format!(
+            "{:?}",
+            (
+                self.max_depth,
+                self.large_folder_threshold,
+                self.is_c_drive_mode || Self::is_c_drive_path(path),
+                self.prune_small_folders,
+                self.max_scan_files,
+                self.max_scan_bytes,
+                self.include_hidden,
+                self.include_system,
+                self.compute_size_on_disk,
+            )
+        )
+    }
+
+    /// 不经缓存执行一次完整扫描
+    fn scan_directory_uncached(&self, path: &Path) -> Result<DirectoryInfo, String> {
         // C盘专项扫描模式处理
         if self.is_c_drive_mode || Self::is_c_drive_path(path) {
             info!("启用C盘专项扫描模式");
-            return self.scan_c_drive_directory(path);
+            return self.scan_c_drive_directory(path).map(Self::with_root_percentage);
         }
         
-        let result = self.scan_directory_recursive(path, 0);
+        self.scanned_entries.store(0, std::sync::atomic::Ordering::Relaxed);
+        self.expected_entries.store(0, std::sync::atomic::Ordering::Relaxed);
+        if self.count_before_scan {
+            let expected = self.count_entries(path, 0);
+            info!("预统计完成: {} 共 {} 个条目", path.display(), expected);
+            self.expected_entries.store(expected, std::sync::atomic::Ordering::Relaxed);
+            self.update_progress(&path.display().to_string(), 0, expected);
+        }
+        
+        let result = self.scan_directory_recursive(path, 0).map(Self::with_root_percentage);
         match &result {
             Ok(info) => {
                 info!("磁盘分析器: 扫描完成 {} (文件数: {}, 大小: {})",
                       path.display(), info.file_count, info.size);
                 // 扫描完成时更新最终进度
-                self.update_progress(&path.display().to_string(), info.file_count, info.file_count);
+                let expected = self.expected_entries.load(std::sync::atomic::Ordering::Relaxed);
+                if expected > 0 {
+                    let scanned = self.scanned_entries.load(std::sync::atomic::Ordering::Relaxed);
+                    self.update_progress(&path.display().to_string(), scanned, expected);
+                } else {
+                    self.update_progress(&path.display().to_string(), info.file_count, info.file_count);
+                }
             }
             Err(e) => {
                 error!("磁盘分析器: 扫描失败 {}: {}", path.display(), e);
@@ -233,6 +835,12 @@ impl DiskAnalyzer {
         result
     }
 
+    /// 扫描根目录占整个扫描结果的 100%（子目录占比在递归时已相对各自父目录计算）
+    fn with_root_percentage(mut info: DirectoryInfo) -> DirectoryInfo {
+        info.size_percentage = if info.size > 0 { 100.0 } else { 0.0 };
+        info
+    }
+
     /// C盘专项扫描模式
     fn scan_c_drive_directory(&self, path: &Path) -> Result<DirectoryInfo, String> {
         info!("C盘专项扫描: 开始扫描 {}", path.display());
@@ -271,6 +879,7 @@ impl DiskAnalyzer {
                 last_modified: None,
                 is_large_folder: false,
                 size_percentage: 0.0,
+                ..Default::default()
             });
         }
         
@@ -281,7 +890,11 @@ impl DiskAnalyzer {
     /// 优化的目录扫描（适用于大目录）
     fn scan_directory_optimized(&self, path: &Path, depth: usize) -> Result<DirectoryInfo, String> {
         if self.is_cancelled() {
-            return Err("扫描已取消".to_string());
+            return Err(self.cancelled_error());
+        }
+
+        if depth > 0 && self.budget_exhausted() {
+            return Err(SCAN_BUDGET_EXCEEDED.to_string());
         }
 
         if depth > self.max_depth {
@@ -297,6 +910,7 @@ impl DiskAnalyzer {
                 last_modified: None,
                 is_large_folder: false,
                 size_percentage: 0.0,
+                ..Default::default()
             });
         }
 
@@ -326,6 +940,7 @@ impl DiskAnalyzer {
                         last_modified: None,
                         is_large_folder: false,
                         size_percentage: 0.0,
+                        ..Default::default()
                     });
                 }
                 error!("读取目录失败 {}: {}", path.display(), e);
@@ -341,9 +956,19 @@ impl DiskAnalyzer {
         };
 
         let mut entry_count = 0;
+        let mut budget_exceeded = false;
+        let mut direct_files: u64 = 0;
+        let mut size_on_disk: u64 = 0;
+        let mut largest_file: Option<(u64, PathBuf)> = None;
+        let mut size_histogram = [0u64; SIZE_HISTOGRAM_BUCKETS];
         for entry in entries {
             if self.is_cancelled() {
-                return Err("扫描已取消".to_string());
+                return Err(self.cancelled_error());
+            }
+
+            if self.budget_exhausted() {
+                budget_exceeded = true;
+                break;
             }
 
             if entry_count >= max_entries {
@@ -363,6 +988,9 @@ impl DiskAnalyzer {
                     continue;
                 }
             };
+            if !self.is_entry_included(&entry) {
+                continue;
+            }
 
             let entry_path = entry.path();
             
@@ -370,13 +998,19 @@ impl DiskAnalyzer {
                 match self.scan_directory_optimized(&entry_path, depth + 1) {
                     Ok(subdir_info) => {
                         total_size += subdir_info.size;
+                        size_on_disk += subdir_info.size_on_disk.unwrap_or(0);
                         file_count += subdir_info.file_count + 1;
+                        budget_exceeded |= subdir_info.budget_exceeded;
                         subdirectories.push(subdir_info);
                     }
                     Err(e) => {
-                        if e == "扫描已取消" {
+                        if e.starts_with(SCAN_CANCELLED) {
                             return Err(e);
                         }
+                        if e == SCAN_BUDGET_EXCEEDED {
+                            budget_exceeded = true;
+                            continue;
+                        }
                         error!("扫描子目录失败 {}: {}", entry_path.display(), e);
                         continue;
                     }
@@ -386,6 +1020,15 @@ impl DiskAnalyzer {
                     Ok(metadata) => {
                         total_size += metadata.len();
                         file_count += 1;
+                        direct_files += 1;
+                        if self.compute_size_on_disk {
+                            size_on_disk += allocated_size(&entry_path, &metadata);
+                        }
+                        if largest_file.as_ref().is_none_or(|(size, _)| metadata.len() > *size) {
+                            largest_file = Some((metadata.len(), entry_path.clone()));
+                        }
+                        size_histogram[size_histogram_bucket(metadata.len())] += 1;
+                        self.charge_budget(1, metadata.len());
                     }
                     Err(e) => {
                         if e.kind() == std::io::ErrorKind::PermissionDenied {
@@ -406,9 +1049,13 @@ impl DiskAnalyzer {
 
         // 按大小排序子目录
         subdirectories.sort_by(|a, b| b.size.cmp(&a.size));
+        if self.should_prune(total_size, &subdirectories, depth) {
+            subdirectories = Vec::new();
+        }
 
         // 计算大文件夹标识和占比
         let is_large_folder = total_size >= self.large_folder_threshold;
+        self.record_directory_summary(depth, direct_files, largest_file, &size_histogram, is_large_folder);
         let mut subdirs_with_percentage = subdirectories;
         if total_size > 0 {
             for subdir in &mut subdirs_with_percentage {
@@ -425,6 +1072,8 @@ impl DiskAnalyzer {
             last_modified,
             is_large_folder,
             size_percentage: 0.0,
+            budget_exceeded,
+            size_on_disk: self.compute_size_on_disk.then_some(size_on_disk),
         })
     }
 
@@ -432,7 +1081,12 @@ impl DiskAnalyzer {
     fn scan_directory_recursive(&self, path: &Path, depth: usize) -> Result<DirectoryInfo, String> {
         // 检查是否已取消
         if self.is_cancelled() {
-            return Err("扫描已取消".to_string());
+            return Err(self.cancelled_error());
+        }
+
+        // 预算用尽后不再深入子目录
+        if depth > 0 && self.budget_exhausted() {
+            return Err(SCAN_BUDGET_EXCEEDED.to_string());
         }
 
         // 更新当前处理的目录
@@ -452,6 +1106,7 @@ impl DiskAnalyzer {
                 last_modified: None,
                 is_large_folder: false,
                 size_percentage: 0.0,
+                ..Default::default()
             });
         }
 
@@ -467,6 +1122,9 @@ impl DiskAnalyzer {
         let mut file_count: u64 = 0;
         let mut subdirectories: Vec<DirectoryInfo> = Vec::new();
         let mut processed_entries: u64 = 0;
+        let (worker_threads, batch_size) = self.scan_settings();
+        let mut pending_dirs: Vec<PathBuf> = Vec::new();
+        let mut file_batch: Vec<fs::DirEntry> = Vec::with_capacity(batch_size);
 
         // 读取目录条目
         let entries = match fs::read_dir(path) {
@@ -482,19 +1140,30 @@ impl DiskAnalyzer {
                     last_modified: None,
                     is_large_folder: false,
                     size_percentage: 0.0,
+                    ..Default::default()
                 });
             }
         };
 
         // 限制处理的条目数量，避免扫描过多文件
         let mut entry_count = 0;
-        const MAX_ENTRIES_PER_DIR: usize = 1000;
+        let mut budget_exceeded = false;
+        let mut direct_files: u64 = 0;
+        let mut size_on_disk: u64 = 0;
+        let mut largest_file: Option<(u64, PathBuf)> = None;
+        let mut size_histogram = [0u64; SIZE_HISTOGRAM_BUCKETS];
+        let expected_entries = self.expected_entries.load(std::sync::atomic::Ordering::Relaxed);
 
         // 处理每个条目
         for entry in entries {
             // 检查是否已取消
             if self.is_cancelled() {
-                return Err("扫描已取消".to_string());
+                return Err(self.cancelled_error());
+            }
+
+            if self.budget_exhausted() {
+                budget_exceeded = true;
+                break;
             }
 
             // 限制条目数量
@@ -506,7 +1175,13 @@ impl DiskAnalyzer {
 
             // 更新进度
             processed_entries += 1;
-            if processed_entries % 100 == 0 { // 每100个条目更新一次进度
+            let scanned_entries = self.scanned_entries.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+            if expected_entries > 0 {
+                // 已预统计总数时，按全局已处理条目计算进度
+                if scanned_entries % 100 == 0 {
+                    self.update_progress(&path.display().to_string(), scanned_entries, expected_entries);
+                }
+            } else if processed_entries % 100 == 0 { // 每100个条目更新一次进度
                 self.update_progress(&path.display().to_string(), processed_entries, MAX_ENTRIES_PER_DIR as u64);
             }
 
@@ -517,36 +1192,54 @@ impl DiskAnalyzer {
                     continue;
                 }
             };
+            if !self.is_entry_included(&entry) {
+                continue;
+            }
 
             let entry_path = entry.path();
             
             if entry_path.is_dir() {
-                // 递归扫描子目录
-                match self.scan_directory_recursive(&entry_path, depth + 1) {
-                    Ok(subdir_info) => {
-                        total_size += subdir_info.size;
-                        file_count += subdir_info.file_count + 1; // +1 为目录本身
-                        subdirectories.push(subdir_info);
-                    }
-                    Err(e) => {
-                        if e == "扫描已取消" {
-                            return Err(e);
-                        }
-                        error!("扫描子目录失败 {}: {}", entry_path.display(), e);
-                        continue;
-                    }
-                }
+                // 子目录在条目读取完后统一扫描
+                pending_dirs.push(entry_path);
             } else {
-                // 处理文件
-                match entry.metadata() {
-                    Ok(metadata) => {
-                        total_size += metadata.len();
-                        file_count += 1;
+                // 文件按批处理（文件数立即计入预算，大小在批次汇总后计入）
+                file_batch.push(entry);
+                self.charge_budget(1, 0);
+                if file_batch.len() >= batch_size {
+                    let (batch_bytes, batch_files) = self.sum_file_batch(&mut file_batch, &mut largest_file, &mut size_histogram, &mut size_on_disk);
+                    total_size += batch_bytes;
+                    file_count += batch_files;
+                    direct_files += batch_files;
+                    self.charge_budget(0, batch_bytes);
+                }
+            }
+        }
+        let (batch_bytes, batch_files) = self.sum_file_batch(&mut file_batch, &mut largest_file, &mut size_histogram, &mut size_on_disk);
+        total_size += batch_bytes;
+        file_count += batch_files;
+        direct_files += batch_files;
+        self.charge_budget(0, batch_bytes);
+
+        // 递归扫描子目录（顶层目录按工作线程数并行）
+        let workers = if depth == 0 { worker_threads } else { 1 };
+        for (dir_path, result) in pending_dirs.iter().zip(self.scan_subdirectories(&pending_dirs, depth + 1, workers)) {
+            match result {
+                Ok(subdir_info) => {
+                    total_size += subdir_info.size;
+                    size_on_disk += subdir_info.size_on_disk.unwrap_or(0);
+                    file_count += subdir_info.file_count + 1; // +1 为目录本身
+                    budget_exceeded |= subdir_info.budget_exceeded;
+                    subdirectories.push(subdir_info);
+                }
+                Err(e) => {
+                    if e.starts_with(SCAN_CANCELLED) {
+                        return Err(e);
                     }
-                    Err(e) => {
-                        error!("获取文件元数据失败 {}: {}", entry_path.display(), e);
+                    if e == SCAN_BUDGET_EXCEEDED {
+                        budget_exceeded = true;
                         continue;
                     }
+                    error!("扫描子目录失败 {}: {}", dir_path.display(), e);
                 }
             }
         }
@@ -558,12 +1251,19 @@ impl DiskAnalyzer {
 
         // 按大小排序子目录（从大到小）
         subdirectories.sort_by(|a, b| b.size.cmp(&a.size));
+        if self.should_prune(total_size, &subdirectories, depth) {
+            subdirectories = Vec::new();
+        }
 
         // 计算是否为大型文件夹
         let is_large_folder = total_size >= self.large_folder_threshold;
         if is_large_folder {
             self.increment_large_folders_found();
         }
+        self.record_directory_summary(depth, direct_files, largest_file, &size_histogram, is_large_folder);
+
+        // 目录完成后推送进度（仅流式扫描）
+        self.emit_progress(&path_str);
 
         // 计算子目录的相对占比
         let mut subdirs_with_percentage = subdirectories;
@@ -581,10 +1281,105 @@ impl DiskAnalyzer {
             subdirectories: subdirs_with_percentage,
             last_modified,
             is_large_folder,
-            size_percentage: 0.0, // 根目录的占比设为0，由上层计算
+            size_percentage: 0.0, // 占比由父目录计算，扫描根目录由 with_root_percentage 设为100%
+            budget_exceeded,
+            size_on_disk: self.compute_size_on_disk.then_some(size_on_disk),
         })
     }
 
+    /// 汇总一批文件的大小和数量，并清空批次；同时更新目前遇到的最大文件、大小直方图，
+    /// 启用时累计实际占用的磁盘空间
+    fn sum_file_batch(
+        &self,
+        batch: &mut Vec<fs::DirEntry>,
+        largest_file: &mut Option<(u64, PathBuf)>,
+        size_histogram: &mut [u64; SIZE_HISTOGRAM_BUCKETS],
+        size_on_disk: &mut u64,
+    ) -> (u64, u64) {
+        let mut total_size = 0;
+        let mut file_count = 0;
+        for entry in batch.drain(..) {
+            match entry.metadata() {
+                Ok(metadata) => {
+                    total_size += metadata.len();
+                    file_count += 1;
+                    if largest_file.as_ref().is_none_or(|(size, _)| metadata.len() > *size) {
+                        *largest_file = Some((metadata.len(), entry.path()));
+                    }
+                    size_histogram[size_histogram_bucket(metadata.len())] += 1;
+                    if self.compute_size_on_disk {
+                        *size_on_disk += allocated_size(&entry.path(), &metadata);
+                    }
+                }
+                Err(e) => {
+                    error!("获取文件元数据失败 {}: {}", entry.path().display(), e);
+                }
+            }
+        }
+        (total_size, file_count)
+    }
+
+    /// 扫描一组子目录，结果顺序与输入一致
+    ///
+    /// workers 大于1时由多个线程从共享索引领取目录并行扫描。
+    fn scan_subdirectories(&self, dirs: &[PathBuf], depth: usize, workers: usize) -> Vec<Result<DirectoryInfo, String>> {
+        let workers = workers.min(dirs.len());
+        if workers <= 1 {
+            return dirs.iter()
+                .map(|dir| self.scan_directory_recursive(dir, depth))
+                .collect();
+        }
+
+        let next_index = std::sync::atomic::AtomicUsize::new(0);
+        let results: Vec<std::sync::Mutex<Option<Result<DirectoryInfo, String>>>> =
+            dirs.iter().map(|_| std::sync::Mutex::new(None)).collect();
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next_index.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    if index >= dirs.len() {
+                        break;
+                    }
+                    let result = self.scan_directory_recursive(&dirs[index], depth);
+                    *results[index].lock().unwrap() = Some(result);
+                });
+            }
+        });
+
+        results.into_iter()
+            .map(|slot| slot.into_inner().unwrap().unwrap_or_else(|| Err("扫描线程异常退出".to_string())))
+            .collect()
+    }
+
+    /// 预统计条目总数（与递归扫描采用相同的深度和条目数限制）
+    fn count_entries(&self, path: &Path, depth: usize) -> u64 {
+        if self.is_cancelled() || depth > self.max_depth {
+            return 0;
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+
+        let mut count = 0;
+        for entry in entries.take(MAX_ENTRIES_PER_DIR) {
+            if entry.as_ref().is_ok_and(|entry| !self.is_entry_included(entry)) {
+                continue;
+            }
+            count += 1;
+            if let Ok(entry) = entry {
+                let entry_path = entry.path();
+                if entry_path.is_dir() {
+                    count += self.count_entries(&entry_path, depth + 1);
+                }
+            }
+        }
+
+        count
+    }
+
     /// 获取目录的简要信息（不递归）
     pub async fn get_directory_info(&self, path: &Path) -> Result<DirectoryInfo, String> {
         let path_str = path.to_string_lossy().to_string();
@@ -614,34 +1409,188 @@ impl DiskAnalyzer {
             last_modified,
             is_large_folder: size >= self.large_folder_threshold,
             size_percentage: 0.0,
+            ..Default::default()
         })
     }
 
     /// 计算目录总大小
     fn calculate_directory_size(&self, path: &Path) -> Result<u64, String> {
-        let mut total_size = 0u64;
+        Ok(self.measure_path(path)?.size)
+    }
+
+    /// 完整统计路径的总字节数和文件数，不构建 DirectoryInfo
+    ///
+    /// 不受扫描深度和条目数限制，遵循隐藏/系统文件设置，可通过 cancel_scan 取消。
+    /// 符号链接和目录联接按链接本身计数而不进入其目标，因此链接成环或指向
+    /// 其他磁盘时都不会重复统计；起始路径本身是链接时同样不跟随。
+    /// 起始路径无法读取时返回错误，更深层无法读取的目录记录警告后跳过。
+    pub fn measure_path(&self, path: &Path) -> Result<PathTotals, MeasureError> {
+        let metadata = fs::symlink_metadata(path)
+            .map_err(|e| MeasureError::Io(format!("获取路径元数据失败: {}", e)))?;
+        let mut totals = PathTotals::default();
+        totals.record_modified(&metadata);
+        if !metadata.is_dir() {
+            totals.size = metadata.len();
+            totals.file_count = 1;
+            return Ok(totals);
+        }
+
+        let mut pending = vec![path.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            if self.is_cancelled() {
+                return Err(MeasureError::Cancelled(self.cancellation_reason().unwrap_or_default()));
+            }
+
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if dir == path => return Err(MeasureError::Io(format!("读取目录失败: {}", e))),
+                Err(e) => {
+                    warn!("读取目录失败 {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
 
+            for entry in entries.flatten().filter(|entry| self.is_entry_included(entry)) {
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        warn!("获取条目元数据失败 {}: {}", entry.path().display(), e);
+                        continue;
+                    }
+                };
+                totals.record_modified(&metadata);
+                // DirEntry::metadata 不跟随链接，链接按自身计为文件
+                if metadata.is_dir() {
+                    totals.directory_count += 1;
+                    pending.push(entry.path());
+                } else {
+                    totals.size += metadata.len();
+                    totals.file_count += 1;
+                }
+            }
+        }
+
+        Ok(totals)
+    }
+
+    /// 浅层扫描：只列出直接子目录，每个子目录给出完整的递归大小但不包含更深层的明细
+    ///
+    /// 用于首次渲染时快速展示顶层结构，之后再按需对子目录深入扫描；可通过 cancel_scan 取消。
+    pub fn scan_shallow(&self, path: &Path) -> Result<DirectoryInfo, String> {
         let entries = fs::read_dir(path)
             .map_err(|e| format!("读取目录失败: {}", e))?;
 
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("读取条目失败: {}", e))?;
+        let mut total_size = 0u64;
+        let mut file_count = 0u64;
+        let mut subdirectories = Vec::new();
+
+        for entry in entries.flatten().filter(|entry| self.is_entry_included(entry)) {
+            if self.is_cancelled() {
+                return Err(self.cancelled_error());
+            }
+
             let entry_path = entry.path();
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                if let Ok(metadata) = entry.metadata() {
+                    total_size += metadata.len();
+                    file_count += 1;
+                }
+                continue;
+            }
 
-            if entry_path.is_dir() {
-                total_size += self.calculate_directory_size(&entry_path)?;
-            } else {
-                let metadata = entry.metadata()
-                    .map_err(|e| format!("获取文件元数据失败: {}", e))?;
-                total_size += metadata.len();
+            let PathTotals { size, file_count: count, .. } = match self.measure_path(&entry_path) {
+                Ok(totals) => totals,
+                Err(e @ MeasureError::Cancelled(_)) => return Err(e.into()),
+                Err(e) => {
+                    warn!("统计子目录大小失败 {}: {}", entry_path.display(), e);
+                    continue;
+                }
+            };
+            total_size += size;
+            file_count += count;
+
+            subdirectories.push(DirectoryInfo {
+                path: entry_path.to_string_lossy().to_string(),
+                name: entry.file_name().to_string_lossy().to_string(),
+                size,
+                file_count: count,
+                subdirectories: Vec::new(),
+                last_modified: fs::metadata(&entry_path).and_then(|m| m.modified()).ok(),
+                is_large_folder: size >= self.large_folder_threshold,
+                size_percentage: 0.0,
+                budget_exceeded: false,
+                size_on_disk: None,
+            });
+        }
+
+        subdirectories.sort_by(|a, b| b.size.cmp(&a.size));
+        if total_size > 0 {
+            for subdir in &mut subdirectories {
+                subdir.size_percentage = (subdir.size as f64 / total_size as f64) * 100.0;
             }
         }
 
-        Ok(total_size)
+        Ok(Self::with_root_percentage(DirectoryInfo {
+            path: path.to_string_lossy().to_string(),
+            name: path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+            size: total_size,
+            file_count,
+            subdirectories,
+            last_modified: fs::metadata(path).and_then(|m| m.modified()).ok(),
+            is_large_folder: total_size >= self.large_folder_threshold,
+            size_percentage: 0.0,
+            budget_exceeded: false,
+            size_on_disk: None,
+        }))
     }
+
 }
 
 
+/// 判断路径是否为整个磁盘的根目录（`C:\`、`C:` 或 `/`），Windows 盘符在任何平台上都能识别
+fn is_drive_root(path: &Path) -> bool {
+    let text = path.to_string_lossy();
+    let trimmed = text.trim_end_matches(['\\', '/']).as_bytes();
+    let is_windows_drive = trimmed.len() == 2 && trimmed[0].is_ascii_alphabetic() && trimmed[1] == b':';
+    is_windows_drive || (path.has_root() && path.parent().is_none())
+}
+
+/// 文件实际占用的磁盘空间：Unix 按已分配块数（512 字节）计算，Windows 取压缩/稀疏后的大小，
+/// 无法获取时退回逻辑大小
+pub fn allocated_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let _ = path;
+        metadata.blocks() * 512
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        use windows_sys::Win32::Foundation::{GetLastError, NO_ERROR};
+        use windows_sys::Win32::Storage::FileSystem::{GetCompressedFileSizeW, INVALID_FILE_SIZE};
+
+        let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut high: u32 = 0;
+        let low = unsafe { GetCompressedFileSizeW(wide_path.as_ptr(), &mut high) };
+        // 低 32 位恰好为 INVALID_FILE_SIZE 时需要再检查错误码才能区分失败
+        if low == INVALID_FILE_SIZE && unsafe { GetLastError() } != NO_ERROR {
+            return metadata.len();
+        }
+        ((high as u64) << 32) | low as u64
+    }
+
+    #[cfg(not(any(unix, target_os = "windows")))]
+    {
+        let _ = path;
+        metadata.len()
+    }
+}
+
 /// 工具函数：格式化文件大小
 pub fn format_file_size(size: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB"];
@@ -651,14 +1600,13 @@ pub fn format_file_size(size: u64) -> String {
         return "0 B".to_string();
     }
 
-    let size_f = size as f64;
+    let mut size_in_unit = size as f64;
     let mut unit_index = 0;
 
-    while size_f >= THRESHOLD && unit_index < UNITS.len() - 1 {
+    while size_in_unit >= THRESHOLD && unit_index < UNITS.len() - 1 {
+        size_in_unit /= THRESHOLD;
         unit_index += 1;
     }
-
-    let size_in_unit = size_f / THRESHOLD.powi(unit_index as i32);
     
     if unit_index == 0 {
         format!("{} {}", size_in_unit as u64, UNITS[unit_index])
@@ -676,6 +1624,23 @@ pub fn get_size_percentage(size: u64, total: u64) -> f64 {
     }
 }
 
+/// 工具函数：返回扫描结果中占用空间最大的 N 个目录（路径, 大小），不区分层级
+///
+/// 目录大小已包含子目录，直接按 size 比较而不重复累加；根目录本身不计入结果。
+/// 大小相同时按路径排序，保证结果稳定。
+pub fn top_n_largest(root: &DirectoryInfo, n: usize) -> Vec<(String, u64)> {
+    let mut directories = Vec::new();
+    let mut stack: Vec<&DirectoryInfo> = root.subdirectories.iter().collect();
+    while let Some(dir) = stack.pop() {
+        directories.push((dir.path.clone(), dir.size));
+        stack.extend(dir.subdirectories.iter());
+    }
+
+    directories.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    directories.truncate(n);
+    directories
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -690,6 +1655,625 @@ mod tests {
         assert_eq!(format_file_size(1024 * 1024 * 1024), "1.00 GB");
     }
 
+    #[test]
+    fn test_scan_progress_with_count_before_scan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for dir in ["a", "b", "b/c"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+            for i in 0..150 {
+                fs::write(temp_dir.path().join(dir).join(format!("{}.txt", i)), "data").unwrap();
+            }
+        }
+
+        let mut analyzer = DiskAnalyzer::new();
+        analyzer.set_count_before_scan(true);
+        analyzer.scan_directory(temp_dir.path()).unwrap();
+
+        let progress = analyzer.get_scan_progress();
+        assert_eq!(progress.total_files, 453); // 450个文件 + 3个目录
+        assert_eq!(progress.processed_files, progress.total_files);
+        assert_eq!(progress.progress, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_streaming_progress() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for dir in ["a", "a/x", "b", "c"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+            for i in 0..120 {
+                fs::write(temp_dir.path().join(dir).join(format!("{}.txt", i)), "data").unwrap();
+            }
+        }
+
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        let analyzer = DiskAnalyzer::new();
+        let info = analyzer.scan_directory_streaming(temp_dir.path(), progress_tx).await.unwrap();
+        assert_eq!(info.file_count, 484); // 480个文件 + 4个目录
+
+        let mut values = Vec::new();
+        while let Some(progress) = progress_rx.recv().await {
+            values.push(progress.progress);
+        }
+
+        // 每个目录完成时一次，外加最终的100%
+        assert_eq!(values.len(), 6);
+        assert!(values.windows(2).all(|w| w[0] <= w[1]), "进度应单调不减: {:?}", values);
+        assert_eq!(*values.last().unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_worker_threads_do_not_change_scan_result() {
+        use crate::performance_optimizer::PerformanceConfig;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for dir in ["a", "a/nested", "b", "c", "d", "e"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+            for i in 0..15 {
+                fs::write(temp_dir.path().join(dir).join(format!("{}.bin", i)), vec![0u8; i * 10]).unwrap();
+            }
+        }
+        fs::write(temp_dir.path().join("root.txt"), "root").unwrap();
+
+        let scan_with = |worker_threads: usize, batch_size: usize| {
+            let optimizer = std::sync::Arc::new(PerformanceOptimizer::new(PerformanceConfig {
+                worker_threads,
+                batch_size,
+                ..PerformanceConfig::default()
+            }));
+            let mut analyzer = DiskAnalyzer::new();
+            analyzer.set_performance_optimizer(optimizer);
+            serde_json::to_value(analyzer.scan_directory(temp_dir.path()).unwrap()).unwrap()
+        };
+
+        let single = scan_with(1, 100);
+        assert_eq!(single, scan_with(4, 100));
+        assert_eq!(single, scan_with(4, 4));
+        assert_eq!(single, scan_with(1, 1));
+    }
+
+    #[test]
+    fn test_repeated_scan_hits_cache_until_directory_changes() {
+        use crate::performance_optimizer::PerformanceConfig;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a").join("nested")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("a").join("nested").join("one.bin"), vec![0u8; 300]).unwrap();
+        fs::write(root.join("b").join("two.bin"), vec![0u8; 500]).unwrap();
+
+        let optimizer = std::sync::Arc::new(PerformanceOptimizer::new(PerformanceConfig::default()));
+        let mut analyzer = DiskAnalyzer::new();
+        analyzer.set_performance_optimizer(optimizer.clone());
+
+        let first = analyzer.scan_directory(root).unwrap();
+        let first_summary = analyzer.scan_summary();
+        let after_first = optimizer.get_performance_stats();
+        assert_eq!(after_first.cache_hit_rate, 0.0);
+        assert_eq!(after_first.cache_size, 1);
+
+        let second = analyzer.scan_directory(root).unwrap();
+        let after_second = optimizer.get_performance_stats();
+        assert!(after_second.cache_hit_rate > after_first.cache_hit_rate);
+        assert_eq!(serde_json::to_value(&second).unwrap(), serde_json::to_value(&first).unwrap());
+        assert_eq!(analyzer.scan_summary(), first_summary);
+
+        // 深层目录新增文件后缓存失效，重新扫描得到新的大小
+        fs::write(root.join("a").join("nested").join("new.bin"), vec![0u8; 200]).unwrap();
+        let third = analyzer.scan_directory(root).unwrap();
+        assert_eq!(third.size, first.size + 200);
+        assert!(optimizer.get_performance_stats().cache_hit_rate < after_second.cache_hit_rate);
+
+        // 已有文件原地增长（所在目录的修改时间不变）同样使缓存失效
+        let mut file = fs::OpenOptions::new().append(true).open(root.join("b").join("two.bin")).unwrap();
+        std::io::Write::write_all(&mut file, &[0u8; 100]).unwrap();
+        drop(file);
+        assert_eq!(analyzer.scan_directory(root).unwrap().size, third.size + 100);
+
+        // 超出扫描深度的变化也能检测到
+        analyzer.set_max_depth(1);
+        analyzer.scan_directory(root).unwrap();
+        analyzer.scan_directory(root).unwrap();
+        let after_limited_hit = optimizer.get_performance_stats();
+        fs::write(root.join("a").join("nested").join("deeper.bin"), vec![0u8; 50]).unwrap();
+        analyzer.scan_directory(root).unwrap();
+        assert!(optimizer.get_performance_stats().cache_hit_rate < after_limited_hit.cache_hit_rate);
+
+        // 扫描设置不同时不复用缓存
+        analyzer.set_max_depth(0);
+        let shallow = analyzer.scan_directory(root).unwrap();
+        assert!(shallow.subdirectories.iter().all(|dir| dir.size == 0));
+    }
+
+    #[test]
+    fn test_scan_summary_matches_fixture() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a").join("deep").join("deeper")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("root.txt"), vec![0u8; 10]).unwrap();
+        fs::write(root.join("a").join("one.bin"), vec![0u8; 200]).unwrap();
+        fs::write(root.join("a").join("deep").join("big.bin"), vec![0u8; 5000]).unwrap();
+        fs::write(root.join("a").join("deep").join("deeper").join("small.bin"), vec![0u8; 30]).unwrap();
+        fs::write(root.join("b").join("two.bin"), vec![0u8; 400]).unwrap();
+
+        let mut analyzer = DiskAnalyzer::new();
+        analyzer.set_large_folder_threshold(1000);
+        let directory = analyzer.scan_directory(root).unwrap();
+        let summary = analyzer.scan_summary();
+
+        assert_eq!(summary.total_directories, 5);
+        assert_eq!(summary.total_files, 5);
+        assert_eq!(summary.max_depth_reached, 3);
+        assert_eq!(summary.directories_by_depth, vec![1, 2, 1, 1]);
+        assert_eq!(summary.largest_file, Some(FileEntrySize {
+            path: root.join("a").join("deep").join("big.bin").to_string_lossy().to_string(),
+            size: 5000,
+        }));
+        // 根目录、a、a/deep 超过阈值
+        assert_eq!(summary.large_folders_found, 3);
+        assert_eq!(directory.size, 5640);
+
+        // 新的扫描重新开始统计
+        analyzer.scan_directory(&root.join("b")).unwrap();
+        let summary = analyzer.scan_summary();
+        assert_eq!((summary.total_directories, summary.total_files, summary.max_depth_reached), (1, 1, 0));
+        assert_eq!(summary.largest_file.map(|file| file.size), Some(400));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_scans_keep_separate_summaries() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let small = temp_dir.path().join("small");
+        let large = temp_dir.path().join("large");
+        fs::create_dir_all(&small).unwrap();
+        fs::create_dir_all(large.join("sub")).unwrap();
+        fs::write(small.join("one.bin"), vec![0u8; 10]).unwrap();
+        for i in 0..200 {
+            fs::write(large.join("sub").join(format!("file_{}.bin", i)), vec![0u8; 100]).unwrap();
+        }
+
+        // 克隆出的分析器同时扫描，各自的汇总只包含自己的目录
+        let analyzer = DiskAnalyzer::new();
+        let other = analyzer.clone();
+        let (small_scan, large_scan) = tokio::join!(
+            analyzer.scan_directory_with_summary(&small),
+            other.scan_directory_with_summary(&large),
+        );
+        let (small_scan, large_scan) = (small_scan.unwrap(), large_scan.unwrap());
+        assert_eq!((small_scan.summary.total_directories, small_scan.summary.total_files), (1, 1));
+        assert_eq!((large_scan.summary.total_directories, large_scan.summary.total_files), (2, 200));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_size_on_disk_for_sparse_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("sub")).unwrap();
+        fs::File::create(temp_dir.path().join("sub").join("sparse.bin")).unwrap().set_len(64 * 1024 * 1024).unwrap();
+        fs::write(temp_dir.path().join("dense.bin"), vec![1u8; 8192]).unwrap();
+
+        let mut analyzer = DiskAnalyzer::new();
+        let info = analyzer.scan_directory(temp_dir.path()).unwrap();
+        assert_eq!(info.size_on_disk, None);
+
+        analyzer.set_compute_size_on_disk(true);
+        let info = analyzer.scan_directory(temp_dir.path()).unwrap();
+        let size_on_disk = info.size_on_disk.unwrap();
+        assert_eq!(info.size, 64 * 1024 * 1024 + 8192);
+        assert!(size_on_disk < info.size, "占用空间 {} 应小于逻辑大小 {}", size_on_disk, info.size);
+        assert!(size_on_disk >= 8192);
+        assert!(info.subdirectories[0].size_on_disk.unwrap() < info.subdirectories[0].size);
+    }
+
+    #[test]
+    fn test_size_histogram_buckets() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("empty.txt"), b"").unwrap();
+        fs::write(root.join("tiny.txt"), vec![0u8; 1023]).unwrap();
+        fs::write(root.join("sub").join("small.bin"), vec![0u8; 1024]).unwrap();
+        fs::write(root.join("sub").join("medium.bin"), vec![0u8; 50 * 1024]).unwrap();
+        fs::write(root.join("large.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+
+        let analyzer = DiskAnalyzer::new();
+        analyzer.scan_directory(root).unwrap();
+        let histogram = analyzer.scan_summary().size_histogram;
+        assert_eq!(histogram, [2, 1, 1, 0, 1, 0, 0, 0]);
+
+        assert_eq!(size_histogram_bucket(1024 * 1024 * 1024 - 1), 6);
+        assert_eq!(size_histogram_bucket(1024 * 1024 * 1024), 7);
+        let text = format_histogram(&histogram);
+        assert_eq!(text.lines().count(), SIZE_HISTOGRAM_BUCKETS);
+        assert!(text.lines().next().unwrap().contains("0-1KB: 2 (40.0%)"), "{}", text);
+        assert!(text.lines().last().unwrap().contains(">1GB: 0"), "{}", text);
+    }
+
+    #[test]
+    fn test_root_and_children_percentages() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for (name, size) in [("big", 6000), ("medium", 3000), ("small", 900)] {
+            let dir = temp_dir.path().join(name);
+            fs::create_dir_all(dir.join("inner")).unwrap();
+            fs::write(dir.join("data.bin"), vec![0u8; size / 2]).unwrap();
+            fs::write(dir.join("inner").join("data.bin"), vec![0u8; size / 2]).unwrap();
+        }
+        fs::write(temp_dir.path().join("loose.bin"), vec![0u8; 100]).unwrap();
+
+        let root = DiskAnalyzer::new().scan_directory(temp_dir.path()).unwrap();
+        assert_eq!(root.size, 10_000);
+        assert_eq!(root.size_percentage, 100.0);
+
+        // 根目录下子目录占比之和为 100% 减去散落文件的占比
+        let children_total: f64 = root.subdirectories.iter().map(|child| child.size_percentage).sum();
+        assert!((children_total - 99.0).abs() < 0.01, "子目录占比之和: {}", children_total);
+        assert!((root.subdirectories[0].size_percentage - 60.0).abs() < 0.01);
+
+        // 更深层级同样相对父目录计算
+        for child in &root.subdirectories {
+            assert!((child.subdirectories[0].size_percentage - 50.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_path_size_matches_full_scan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("a/b/c")).unwrap();
+        std::fs::create_dir_all(root.join("d")).unwrap();
+        std::fs::write(root.join("top.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(root.join("a/one.bin"), vec![0u8; 2048]).unwrap();
+        std::fs::write(root.join("a/b/c/deep.bin"), vec![0u8; 4096]).unwrap();
+        std::fs::write(root.join("d/two.bin"), vec![0u8; 777]).unwrap();
+
+        let analyzer = DiskAnalyzer::new();
+        let full = analyzer.scan_directory(root).unwrap();
+        let totals = analyzer.measure_path(root).unwrap();
+        assert_eq!((totals.size, totals.file_count, totals.directory_count), (full.size, 4, 4));
+        assert_eq!(full.size, 100 + 2048 + 4096 + 777);
+        assert_eq!(analyzer.measure_path(&root.join("top.bin")).unwrap().size, 100);
+
+        analyzer.cancel_scan(CancellationReason::UserRequested);
+        let error = analyzer.measure_path(root).unwrap_err();
+        assert_eq!(error, MeasureError::Cancelled(CancellationReason::UserRequested));
+        assert_eq!(error.to_string(), "扫描已取消: 用户取消");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_measure_path_does_not_follow_links() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("a")).unwrap();
+        std::fs::write(root.join("a/data.bin"), vec![0u8; 500]).unwrap();
+        std::fs::write(outside.path().join("big.bin"), vec![0u8; 10_000]).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("elsewhere")).unwrap();
+        std::os::unix::fs::symlink(root, root.join("a/loop")).unwrap();
+
+        let analyzer = DiskAnalyzer::new();
+        let totals = analyzer.measure_path(root).unwrap();
+        let link_sizes: u64 = ["elsewhere", "a/loop"].iter()
+            .map(|link| std::fs::symlink_metadata(root.join(link)).unwrap().len())
+            .sum();
+        assert_eq!((totals.size, totals.file_count, totals.directory_count), (500 + link_sizes, 3, 1));
+
+        // 起始路径本身是链接时也不进入目标
+        assert_eq!(analyzer.measure_path(&root.join("elsewhere")).unwrap().file_count, 1);
+        assert!(matches!(analyzer.measure_path(&root.join("missing")), Err(MeasureError::Io(_))));
+    }
+
+    #[test]
+    fn test_scan_shallow_lists_one_level_with_full_sizes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("a/b/c")).unwrap();
+        std::fs::create_dir_all(root.join("d")).unwrap();
+        std::fs::write(root.join("top.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(root.join("a/one.bin"), vec![0u8; 2048]).unwrap();
+        std::fs::write(root.join("a/b/c/deep.bin"), vec![0u8; 4096]).unwrap();
+        std::fs::write(root.join("d/two.bin"), vec![0u8; 777]).unwrap();
+
+        let analyzer = DiskAnalyzer::new();
+        let shallow = analyzer.scan_shallow(root).unwrap();
+
+        assert_eq!(shallow.size, 100 + 2048 + 4096 + 777);
+        assert_eq!(shallow.file_count, 4);
+        let children: Vec<(&str, u64, u64)> = shallow.subdirectories.iter()
+            .map(|dir| (dir.name.as_str(), dir.size, dir.file_count))
+            .collect();
+        assert_eq!(children, vec![("a", 2048 + 4096, 2), ("d", 777, 1)]);
+        assert!(shallow.subdirectories.iter().all(|dir| dir.subdirectories.is_empty()));
+
+        analyzer.cancel_scan(CancellationReason::UserRequested);
+        assert_eq!(analyzer.scan_shallow(root).unwrap_err(), "扫描已取消: 用户取消");
+    }
+
+    #[test]
+    fn test_cancellation_reason_surfaces_in_error_and_progress() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/b/file.bin"), vec![0u8; 64]).unwrap();
+
+        let analyzer = DiskAnalyzer::new();
+        assert_eq!(analyzer.cancellation_reason(), None);
+
+        analyzer.cancel_scan(CancellationReason::Timeout);
+        let error = analyzer.scan_directory(root).unwrap_err();
+        assert!(error.starts_with(SCAN_CANCELLED));
+        assert_eq!(error, "扫描已取消: 操作超时");
+        assert_eq!(analyzer.cancellation_reason(), Some(CancellationReason::Timeout));
+        assert_eq!(analyzer.get_scan_progress().cancellation_reason, Some(CancellationReason::Timeout));
+
+        // 克隆的分析器共享取消状态，重置后原因一并清除
+        let clone = analyzer.clone();
+        assert_eq!(clone.measure_path(root).unwrap_err(), MeasureError::Cancelled(CancellationReason::Timeout));
+        analyzer.reset_cancel_flag();
+        assert_eq!(clone.cancellation_reason(), None);
+        assert_eq!(analyzer.get_scan_progress().cancellation_reason, None);
+        assert_eq!(analyzer.measure_path(root).unwrap().size, 64);
+    }
+
+    /// 按隐藏/系统文件设置扫描，断言完整扫描的大小及浅层扫描、完整统计的（大小, 文件数）符合预期
+    fn assert_totals_with_visibility(root: &Path, include_hidden: bool, include_system: bool, expected: (u64, u64)) {
+        let mut analyzer = DiskAnalyzer::new();
+        analyzer.set_include_hidden(include_hidden);
+        analyzer.set_include_system(include_system);
+        let flags = format!("隐藏: {}, 系统: {}", include_hidden, include_system);
+
+        assert_eq!(analyzer.scan_directory(root).unwrap().size, expected.0, "{}", flags);
+        let shallow = analyzer.scan_shallow(root).unwrap();
+        assert_eq!((shallow.size, shallow.file_count), expected, "{}", flags);
+        let totals = analyzer.measure_path(root).unwrap();
+        assert_eq!((totals.size, totals.file_count), expected, "{}", flags);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_hidden_dotfiles_included_or_excluded() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("visible/.cache")).unwrap();
+        std::fs::write(root.join("visible/data.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::write(root.join("visible/.hidden.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(root.join("visible/.cache/blob.bin"), vec![0u8; 30]).unwrap();
+
+        assert_totals_with_visibility(root, true, true, (1230, 3));
+        assert_totals_with_visibility(root, false, true, (1000, 1));
+        // Unix 没有系统属性，关闭系统文件不影响结果
+        assert_totals_with_visibility(root, true, false, (1230, 3));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_hidden_and_system_files_included_or_excluded() {
+        use std::os::windows::ffi::OsStrExt;
+        use windows_sys::Win32::Storage::FileSystem::{SetFileAttributesW, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM};
+
+        let set_attributes = |path: &Path, attributes: u32| {
+            let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+            assert_ne!(unsafe { SetFileAttributesW(wide.as_ptr(), attributes) }, 0);
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("visible")).unwrap();
+        std::fs::write(root.join("visible/data.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::write(root.join("visible/hidden.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(root.join("visible/system.bin"), vec![0u8; 30]).unwrap();
+        set_attributes(&root.join("visible/hidden.bin"), FILE_ATTRIBUTE_HIDDEN);
+        set_attributes(&root.join("visible/system.bin"), FILE_ATTRIBUTE_SYSTEM);
+
+        for (include_hidden, include_system, expected) in [
+            (true, true, (1230, 3)),
+            (false, true, (1030, 2)),
+            (true, false, (1200, 2)),
+            (false, false, (1000, 1)),
+        ] {
+            assert_totals_with_visibility(root, include_hidden, include_system, expected);
+        }
+    }
+
+    #[test]
+    fn test_scan_root_guard() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let subfolder = temp_dir.path().join("Games");
+        std::fs::create_dir_all(&subfolder).unwrap();
+
+        let system_root = temp_dir.path().join("System");
+        let protected = vec![system_root.clone()];
+        let mut analyzer = DiskAnalyzer::new();
+        for root in ["C:\\", "C:", "d:/"] {
+            let error = analyzer.check_scan_root(Path::new(root), &protected).unwrap_err();
+            assert!(error.contains("磁盘根目录") && error.contains("子文件夹"), "{}", error);
+        }
+        assert!(analyzer.check_scan_root(Path::new("C:\\Users\\alice"), &protected).is_ok());
+        assert!(analyzer.check_scan_root(&subfolder, &protected).is_ok());
+
+        // 传入的系统目录同样被拒绝
+        let system_dir = system_root.join("Temp");
+        assert!(analyzer.check_scan_root(&system_dir, &protected).unwrap_err().contains("系统目录"));
+        assert!(analyzer.check_scan_root(&system_dir, &[]).is_ok());
+
+        // C盘专项模式下允许扫描磁盘根目录
+        analyzer.set_c_drive_mode(true);
+        assert!(analyzer.check_scan_root(Path::new("C:\\"), &protected).is_ok());
+
+        // 设置允许的扫描根目录后，范围外的路径被拒绝
+        analyzer.set_allowed_scan_roots(Some(vec![temp_dir.path().to_path_buf()]));
+        assert!(analyzer.check_scan_root(&subfolder, &protected).is_ok());
+        assert!(analyzer.check_scan_root(Path::new("C:\\"), &protected).unwrap_err().contains("不在允许的扫描范围内"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_scan_root_guard_suggests_subfolders() {
+        let analyzer = DiskAnalyzer::new();
+        let error = analyzer.check_scan_root(Path::new("/"), &[PathBuf::from("/proc")]).unwrap_err();
+        assert!(error.contains("磁盘根目录"), "{}", error);
+        assert!(!error.contains("/proc"), "系统目录不应出现在建议中: {}", error);
+    }
+
+    #[test]
+    fn test_smoothed_scan_speed() {
+        let analyzer = DiskAnalyzer::new();
+        let start = std::time::Instant::now();
+        assert_eq!(analyzer.record_speed_sample(0, start), None);
+
+        // 每100毫秒交替处理10个和30个文件：瞬时速度在100与300之间跳动，平均约200
+        let mut processed = 0;
+        let mut speeds = Vec::new();
+        for step in 1..=20u64 {
+            processed += if step % 2 == 0 { 30 } else { 10 };
+            let at = start + std::time::Duration::from_millis(step * 100);
+            speeds.push(analyzer.record_speed_sample(processed, at).unwrap());
+        }
+        for speed in &speeds[SPEED_SAMPLE_WINDOW..] {
+            assert!((160.0..=240.0).contains(speed), "平滑速度超出预期范围: {}", speed);
+        }
+
+        // 已处理数回退时重新开始采样
+        let at = start + std::time::Duration::from_millis(2100);
+        assert_eq!(analyzer.record_speed_sample(5, at), None);
+    }
+
+    #[test]
+    fn test_scan_budget_stops_early() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for dir_index in 0..10 {
+            let dir = temp_dir.path().join(format!("dir{}", dir_index));
+            fs::create_dir_all(&dir).unwrap();
+            for file_index in 0..20 {
+                fs::write(dir.join(format!("file{}.txt", file_index)), "data").unwrap();
+            }
+        }
+
+        let mut analyzer = DiskAnalyzer::new();
+        let full = analyzer.scan_directory(temp_dir.path()).unwrap();
+        assert!(!full.budget_exceeded);
+        assert_eq!(full.file_count, 210);
+
+        analyzer.set_scan_budget(Some(50), None);
+        let limited = analyzer.scan_directory(temp_dir.path()).unwrap();
+        assert!(limited.budget_exceeded);
+        let dirs = limited.subdirectories.len() as u64;
+        let files = limited.file_count - dirs;
+        assert!((50..60).contains(&files), "预算 50 个文件，实际扫描 {} 个", files);
+        assert!(dirs < 10);
+
+        // 每次扫描重新计算预算
+        let again = analyzer.scan_directory(temp_dir.path()).unwrap();
+        assert_eq!(again.file_count, limited.file_count);
+    }
+
+    #[test]
+    fn test_prune_small_folders() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let small = temp_dir.path().join("small");
+        let big = temp_dir.path().join("big");
+        fs::create_dir_all(small.join("a").join("b")).unwrap();
+        fs::create_dir_all(big.join("inner")).unwrap();
+        fs::write(small.join("a").join("b").join("tiny.bin"), vec![0u8; 100]).unwrap();
+        fs::write(big.join("inner").join("large.bin"), vec![0u8; 20_000]).unwrap();
+
+        let scan = |prune: bool| {
+            let mut analyzer = DiskAnalyzer::new();
+            analyzer.set_large_folder_threshold(10_000);
+            analyzer.set_prune_small_folders(prune);
+            analyzer.scan_directory(temp_dir.path()).unwrap()
+        };
+        let full = scan(false);
+        let pruned = scan(true);
+
+        assert_eq!(pruned.size, full.size);
+        assert_eq!(pruned.file_count, full.file_count);
+
+        let child = |info: &DirectoryInfo, name: &str| {
+            info.subdirectories.iter().find(|d| d.name == name).cloned().unwrap()
+        };
+        // 小目录只保留汇总大小
+        assert_eq!(child(&pruned, "small").size, 100);
+        assert!(child(&pruned, "small").subdirectories.is_empty());
+        assert!(!child(&full, "small").subdirectories.is_empty());
+        // 大目录保留明细
+        assert!(child(&pruned, "big").is_large_folder);
+        assert_eq!(child(&pruned, "big").subdirectories.len(), 1);
+    }
+
+    #[test]
+    fn test_format_file_size_large_units() {
+        assert_eq!(format_file_size(1024), "1.00 KB");
+        assert_eq!(format_file_size(1024 * 1024 * 3 / 2), "1.50 MB");
+        assert_eq!(format_file_size(1024 * 1024 * 1024), "1.00 GB");
+        assert_eq!(format_file_size(5 * 1024 * 1024 * 1024), "5.00 GB");
+    }
+
+    #[test]
+    fn test_pause_and_resume_scan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for d in 0..10 {
+            let dir = temp_dir.path().join(format!("dir_{}", d));
+            fs::create_dir_all(&dir).unwrap();
+            for i in 0..500 {
+                fs::write(dir.join(format!("{}.txt", i)), "data").unwrap();
+            }
+        }
+
+        let mut analyzer = DiskAnalyzer::new();
+        analyzer.set_count_before_scan(true);
+
+        let scanner = analyzer.clone();
+        let scan_path = temp_dir.path().to_path_buf();
+        let handle = std::thread::spawn(move || scanner.scan_directory(&scan_path));
+
+        // 等到扫描开始推进后暂停
+        while analyzer.get_scan_progress().processed_files == 0 {
+            std::thread::yield_now();
+        }
+        assert!(analyzer.pause_scan());
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let paused_progress = analyzer.get_scan_progress();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let still_paused_progress = analyzer.get_scan_progress();
+
+        assert_eq!(paused_progress.processed_files, still_paused_progress.processed_files);
+        assert!(paused_progress.processed_files < paused_progress.total_files);
+
+        assert!(analyzer.resume_scan());
+        let result = handle.join().unwrap().unwrap();
+
+        let progress = analyzer.get_scan_progress();
+        assert_eq!(result.file_count, 5010);
+        assert_eq!(progress.processed_files, progress.total_files);
+        assert_eq!(progress.progress, 100.0);
+    }
+
+    #[test]
+    fn test_top_n_largest_across_depths() {
+        fn dir(path: &str, size: u64, subdirectories: Vec<DirectoryInfo>) -> DirectoryInfo {
+            DirectoryInfo { path: path.to_string(), size, subdirectories, ..Default::default() }
+        }
+
+        let root = dir("/root", 1000, vec![
+            dir("/root/a", 300, vec![
+                dir("/root/a/deep", 250, vec![dir("/root/a/deep/deeper", 240, vec![])]),
+            ]),
+            dir("/root/b", 100, vec![dir("/root/b/x", 90, vec![])]),
+            dir("/root/c", 500, vec![dir("/root/c/small", 10, vec![])]),
+        ]);
+
+        assert_eq!(top_n_largest(&root, 3), vec![
+            ("/root/c".to_string(), 500),
+            ("/root/a".to_string(), 300),
+            ("/root/a/deep".to_string(), 250),
+        ]);
+        assert_eq!(top_n_largest(&root, 100).len(), 7);
+        assert!(top_n_largest(&root, 0).is_empty());
+    }
+
     #[test]
     fn test_get_size_percentage() {
         assert_eq!(get_size_percentage(0, 100), 0.0);
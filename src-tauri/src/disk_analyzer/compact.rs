@@ -0,0 +1,154 @@
+//! 紧凑的扫描结果
+//!
+//! 大目录树经 IPC 传给前端时体积可能很大。紧凑形式省略空的子目录列表和默认值字段，
+//! 占比保留一位小数，并可把小于阈值的子目录合并为一项汇总，父目录的大小保持不变
+
+use std::time::SystemTime;
+use serde::{Serialize, Deserialize};
+
+use super::DirectoryInfo;
+
+/// 扫描命令返回紧凑结果时，小于该大小的子目录合并到父目录的汇总中
+pub const COMPACT_MIN_ENTRY_SIZE: u64 = 1024 * 1024;
+
+/// 紧凑形式的目录信息，字段含义与 `DirectoryInfo` 相同
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactDirectoryInfo {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub file_count: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subdirectories: Vec<CompactDirectoryInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<SystemTime>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_large_folder: bool,
+    /// 保留一位小数
+    pub size_percentage: f64,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub budget_exceeded: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_on_disk: Option<u64>,
+    /// 因小于阈值被省略的子目录数
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub omitted_subdirectories: u64,
+    /// 被省略子目录的总大小
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub omitted_size: u64,
+}
+
+/// 扫描命令的返回结果：完整目录树或紧凑形式（序列化时不带外层标记）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScanOutput {
+    Full(DirectoryInfo),
+    Compact(CompactDirectoryInfo),
+}
+
+impl DirectoryInfo {
+    /// 转为紧凑形式，保留全部子目录
+    pub fn to_compact(&self) -> CompactDirectoryInfo {
+        self.to_compact_with_threshold(0)
+    }
+
+    /// 转为紧凑形式，小于 `min_size` 的子目录只计入父目录的 `omitted_*` 汇总
+    pub fn to_compact_with_threshold(&self, min_size: u64) -> CompactDirectoryInfo {
+        let (kept, omitted): (Vec<_>, Vec<_>) = self.subdirectories.iter()
+            .partition(|subdir| subdir.size >= min_size);
+
+        CompactDirectoryInfo {
+            path: self.path.clone(),
+            name: self.name.clone(),
+            size: self.size,
+            file_count: self.file_count,
+            subdirectories: kept.into_iter()
+                .map(|subdir| subdir.to_compact_with_threshold(min_size))
+                .collect(),
+            last_modified: self.last_modified,
+            is_large_folder: self.is_large_folder,
+            size_percentage: (self.size_percentage * 10.0).round() / 10.0,
+            budget_exceeded: self.budget_exceeded,
+            size_on_disk: self.size_on_disk,
+            omitted_subdirectories: omitted.len() as u64,
+            omitted_size: omitted.iter().map(|subdir| subdir.size).sum(),
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 生成一棵较大的目录树：每层 8 个子目录，叶子目录大小不一
+    fn large_tree(path: &str, depth: usize) -> DirectoryInfo {
+        let subdirectories: Vec<DirectoryInfo> = if depth == 0 {
+            Vec::new()
+        } else {
+            (0..8).map(|i| large_tree(&format!("{}/dir{}", path, i), depth - 1)).collect()
+        };
+        let own_size = (path.bytes().map(u64::from).sum::<u64>() % 7) * 300 * 1024;
+        let size = own_size + subdirectories.iter().map(|subdir| subdir.size).sum::<u64>();
+        let mut info = DirectoryInfo {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            size,
+            file_count: depth as u64 * 3,
+            subdirectories,
+            ..Default::default()
+        };
+        for subdir in &mut info.subdirectories {
+            subdir.size_percentage = if size > 0 { subdir.size as f64 / size as f64 * 100.0 } else { 0.0 };
+        }
+        info
+    }
+
+    /// 校验紧凑树中每个目录的大小与原树一致，且保留与省略的子目录大小之和等于原子目录之和
+    fn assert_sizes_match(full: &DirectoryInfo, compact: &CompactDirectoryInfo) {
+        assert_eq!(full.path, compact.path);
+        assert_eq!(full.size, compact.size);
+        assert_eq!(full.file_count, compact.file_count);
+        let full_children: u64 = full.subdirectories.iter().map(|subdir| subdir.size).sum();
+        let kept_children: u64 = compact.subdirectories.iter().map(|subdir| subdir.size).sum();
+        assert_eq!(full_children, kept_children + compact.omitted_size);
+        for subdir in &compact.subdirectories {
+            let original = full.subdirectories.iter().find(|s| s.path == subdir.path).unwrap();
+            assert!((subdir.size_percentage - original.size_percentage).abs() <= 0.05 + f64::EPSILON * 100.0);
+            assert_sizes_match(original, subdir);
+        }
+    }
+
+    fn total_omitted(compact: &CompactDirectoryInfo) -> u64 {
+        compact.omitted_subdirectories + compact.subdirectories.iter().map(total_omitted).sum::<u64>()
+    }
+
+    #[test]
+    fn test_compact_is_smaller_and_keeps_sizes() {
+        let tree = large_tree("/data", 4);
+        let full_bytes = serde_json::to_vec(&tree).unwrap().len();
+
+        let compact = tree.to_compact();
+        let compact_bytes = serde_json::to_vec(&compact).unwrap().len();
+        assert!(compact_bytes < full_bytes, "紧凑 {} 字节，完整 {} 字节", compact_bytes, full_bytes);
+        assert_sizes_match(&tree, &compact);
+        assert_eq!(compact.omitted_subdirectories, 0);
+
+        let thresholded = tree.to_compact_with_threshold(COMPACT_MIN_ENTRY_SIZE);
+        let thresholded_bytes = serde_json::to_vec(&thresholded).unwrap().len();
+        assert!(thresholded_bytes < compact_bytes, "阈值 {} 字节，紧凑 {} 字节", thresholded_bytes, compact_bytes);
+        assert_sizes_match(&tree, &thresholded);
+        assert!(total_omitted(&thresholded) > 0);
+
+        // 省略的字段在反序列化时取默认值
+        let restored: CompactDirectoryInfo = serde_json::from_slice(&serde_json::to_vec(&thresholded).unwrap()).unwrap();
+        assert_eq!(restored, thresholded);
+    }
+}
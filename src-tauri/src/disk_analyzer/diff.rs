@@ -0,0 +1,185 @@
+//! 扫描结果对比
+//!
+//! 比较同一目录的两次扫描，找出新增、删除的目录以及各目录的大小变化
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+use super::DirectoryInfo;
+use super::export::{flatten, FlatDirectoryRecord};
+
+/// 单个目录在两次扫描之间的大小变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryChange {
+    pub path: String,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub delta: i64,
+}
+
+impl DirectoryChange {
+    fn new(path: &str, old_size: u64, new_size: u64) -> Self {
+        Self {
+            path: path.to_string(),
+            old_size,
+            new_size,
+            delta: new_size as i64 - old_size as i64,
+        }
+    }
+}
+
+/// 两次扫描的差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanDiff {
+    /// 新出现的目录（只列出新增子树的最上层目录），按大小降序
+    pub added: Vec<DirectoryChange>,
+    /// 已消失的目录（只列出被删除子树的最上层目录），按原大小降序
+    pub removed: Vec<DirectoryChange>,
+    /// 两次都存在且大小有变化的目录，按增长量降序
+    pub changed: Vec<DirectoryChange>,
+    /// 根目录的总大小变化
+    pub total_delta: i64,
+}
+
+/// 导出文件的内容：扁平化记录（export_scan_result 的 JSON 输出）或完整目录树
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ScanSnapshot {
+    Records(Vec<FlatDirectoryRecord>),
+    Tree(DirectoryInfo),
+}
+
+/// 比较两棵目录树
+pub fn diff_scans(old: &DirectoryInfo, new: &DirectoryInfo) -> ScanDiff {
+    diff_records(&flatten(old), &flatten(new))
+}
+
+/// 比较两份扁平化的扫描记录
+pub fn diff_records(old: &[FlatDirectoryRecord], new: &[FlatDirectoryRecord]) -> ScanDiff {
+    let old_sizes: HashMap<&str, u64> = old.iter().map(|r| (r.path.as_str(), r.size)).collect();
+    let new_sizes: HashMap<&str, u64> = new.iter().map(|r| (r.path.as_str(), r.size)).collect();
+
+    let mut diff = ScanDiff::default();
+
+    for record in new {
+        match old_sizes.get(record.path.as_str()) {
+            Some(&old_size) if old_size != record.size => {
+                diff.changed.push(DirectoryChange::new(&record.path, old_size, record.size));
+            }
+            Some(_) => {}
+            None if !parent_in(&record.path, &new_sizes, &old_sizes) => {
+                diff.added.push(DirectoryChange::new(&record.path, 0, record.size));
+            }
+            None => {}
+        }
+    }
+
+    for record in old {
+        if !new_sizes.contains_key(record.path.as_str()) && !parent_in(&record.path, &old_sizes, &new_sizes) {
+            diff.removed.push(DirectoryChange::new(&record.path, record.size, 0));
+        }
+    }
+
+    diff.added.sort_by(|a, b| b.new_size.cmp(&a.new_size));
+    diff.removed.sort_by(|a, b| b.old_size.cmp(&a.old_size));
+    diff.changed.sort_by(|a, b| b.delta.cmp(&a.delta));
+    diff.total_delta = match (old.first(), new.first()) {
+        (Some(old_root), Some(new_root)) => new_root.size as i64 - old_root.size as i64,
+        _ => 0,
+    };
+
+    diff
+}
+
+/// 父目录同样只出现在这一侧时返回 true（该目录属于新增/删除子树的内部，不单独列出）
+fn parent_in(path: &str, this_side: &HashMap<&str, u64>, other_side: &HashMap<&str, u64>) -> bool {
+    Path::new(path)
+        .parent()
+        .and_then(|parent| parent.to_str())
+        .map(|parent| this_side.contains_key(parent) && !other_side.contains_key(parent))
+        .unwrap_or(false)
+}
+
+/// 读取导出的扫描结果文件并比较
+pub fn diff_scan_files(old_path: &Path, new_path: &Path) -> Result<ScanDiff, String> {
+    let old = read_snapshot(old_path)?;
+    let new = read_snapshot(new_path)?;
+    Ok(diff_records(&old, &new))
+}
+
+fn read_snapshot(path: &Path) -> Result<Vec<FlatDirectoryRecord>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("读取扫描结果失败 {}: {}", path.display(), e))?;
+    let snapshot: ScanSnapshot = serde_json::from_str(&content)
+        .map_err(|e| format!("解析扫描结果失败 {}: {}", path.display(), e))?;
+
+    Ok(match snapshot {
+        ScanSnapshot::Records(records) => records,
+        ScanSnapshot::Tree(tree) => flatten(&tree),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk_analyzer::export::{export_scan_result, ExportFormat};
+    use tempfile::TempDir;
+
+    fn dir(path: &str, size: u64, subdirectories: Vec<DirectoryInfo>) -> DirectoryInfo {
+        DirectoryInfo {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            size,
+            subdirectories,
+            ..Default::default()
+        }
+    }
+
+    fn old_tree() -> DirectoryInfo {
+        dir("/data", 600, vec![
+            dir("/data/games", 300, vec![dir("/data/games/saves", 100, vec![])]),
+            dir("/data/old_cache", 200, vec![dir("/data/old_cache/tmp", 150, vec![])]),
+            dir("/data/docs", 100, vec![]),
+        ])
+    }
+
+    fn new_tree() -> DirectoryInfo {
+        dir("/data", 1400, vec![
+            dir("/data/games", 900, vec![dir("/data/games/saves", 700, vec![])]),
+            dir("/data/videos", 400, vec![dir("/data/videos/2024", 400, vec![])]),
+            dir("/data/docs", 100, vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_diff_reports_grown_added_and_removed() {
+        let diff = diff_scans(&old_tree(), &new_tree());
+
+        assert_eq!(diff.total_delta, 800);
+        assert_eq!(diff.added, vec![DirectoryChange::new("/data/videos", 0, 400)]);
+        assert_eq!(diff.removed, vec![DirectoryChange::new("/data/old_cache", 200, 0)]);
+
+        let changed: Vec<(&str, i64)> = diff.changed.iter().map(|c| (c.path.as_str(), c.delta)).collect();
+        assert_eq!(changed, vec![("/data", 800), ("/data/games", 600), ("/data/games/saves", 600)]);
+    }
+
+    #[test]
+    fn test_diff_scan_files_accepts_exported_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_path = temp_dir.path().join("old.json");
+        let new_path = temp_dir.path().join("new.json");
+        export_scan_result(&old_tree(), &old_path, ExportFormat::Json).unwrap();
+        fs::write(&new_path, serde_json::to_string(&new_tree()).unwrap()).unwrap();
+
+        let from_files = diff_scan_files(&old_path, &new_path).unwrap();
+        let direct = diff_scans(&old_tree(), &new_tree());
+        assert_eq!(from_files.added, direct.added);
+        assert_eq!(from_files.removed, direct.removed);
+        assert_eq!(from_files.changed, direct.changed);
+
+        fs::write(&new_path, "not json").unwrap();
+        assert!(diff_scan_files(&old_path, &new_path).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! 扫描结果导出
+//!
+//! 将 `DirectoryInfo` 树扁平化后导出为 JSON 或 CSV，便于离线分析
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+use super::DirectoryInfo;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            other => Err(format!("不支持的导出格式: {}", other)),
+        }
+    }
+}
+
+/// 扁平化后的目录记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatDirectoryRecord {
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub size: u64,
+    pub file_count: u64,
+    pub is_large_folder: bool,
+    pub size_percentage: f64,
+}
+
+/// 将目录树按先序遍历扁平化，每个目录一条记录
+pub fn flatten(root: &DirectoryInfo) -> Vec<FlatDirectoryRecord> {
+    let mut records = Vec::new();
+    flatten_into(root, 0, &mut records);
+    records
+}
+
+fn flatten_into(info: &DirectoryInfo, depth: usize, records: &mut Vec<FlatDirectoryRecord>) {
+    records.push(FlatDirectoryRecord {
+        path: info.path.clone(),
+        name: info.name.clone(),
+        depth,
+        size: info.size,
+        file_count: info.file_count,
+        is_large_folder: info.is_large_folder,
+        size_percentage: info.size_percentage,
+    });
+
+    for subdirectory in &info.subdirectories {
+        flatten_into(subdirectory, depth + 1, records);
+    }
+}
+
+/// 导出扫描结果，返回写入的目录记录数
+pub fn export_scan_result(root: &DirectoryInfo, output_path: &Path, format: ExportFormat) -> Result<usize, String> {
+    if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+
+    let records = flatten(root);
+    match format {
+        ExportFormat::Json => write_json(&records, output_path)?,
+        ExportFormat::Csv => write_csv(&records, output_path)?,
+    }
+
+    Ok(records.len())
+}
+
+fn write_json(records: &[FlatDirectoryRecord], output_path: &Path) -> Result<(), String> {
+    let file = File::create(output_path)
+        .map_err(|e| format!("创建导出文件失败: {}", e))?;
+    serde_json::to_writer_pretty(file, records)
+        .map_err(|e| format!("写入JSON失败: {}", e))
+}
+
+fn write_csv(records: &[FlatDirectoryRecord], output_path: &Path) -> Result<(), String> {
+    let mut file = File::create(output_path)
+        .map_err(|e| format!("创建导出文件失败: {}", e))?;
+
+    writeln!(file, "path,size,file_count,is_large_folder")
+        .map_err(|e| format!("写入CSV失败: {}", e))?;
+    for record in records {
+        writeln!(
+            file,
+            "{},{},{},{}",
+            csv_field(&record.path),
+            record.size,
+            record.file_count,
+            record.is_large_folder
+        ).map_err(|e| format!("写入CSV失败: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// 对包含逗号、引号或换行的字段加引号转义
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk_analyzer::DiskAnalyzer;
+    use tempfile::TempDir;
+
+    fn scan_sample_tree(temp_dir: &TempDir) -> DirectoryInfo {
+        let root = temp_dir.path().join("scan_root");
+        fs::create_dir_all(root.join("a").join("nested")).unwrap();
+        fs::create_dir_all(root.join("b, with comma")).unwrap();
+        fs::write(root.join("a").join("file.txt"), "hello").unwrap();
+        fs::write(root.join("a").join("nested").join("deep.bin"), vec![0u8; 2048]).unwrap();
+        fs::write(root.join("b, with comma").join("x.txt"), "x").unwrap();
+
+        DiskAnalyzer::new().scan_directory(&root).unwrap()
+    }
+
+    #[test]
+    fn test_export_json_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let tree = scan_sample_tree(&temp_dir);
+        let output = temp_dir.path().join("export").join("scan.json");
+
+        let count = export_scan_result(&tree, &output, ExportFormat::Json).unwrap();
+        assert_eq!(count, 4);
+
+        let parsed: Vec<FlatDirectoryRecord> =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let expected = flatten(&tree);
+        assert_eq!(parsed.len(), expected.len());
+        for (actual, expected) in parsed.iter().zip(&expected) {
+            assert_eq!(actual.path, expected.path);
+            assert_eq!(actual.depth, expected.depth);
+            assert_eq!(actual.size, expected.size);
+            assert_eq!(actual.file_count, expected.file_count);
+            assert_eq!(actual.is_large_folder, expected.is_large_folder);
+            assert!((actual.size_percentage - expected.size_percentage).abs() < 1e-9);
+        }
+        assert_eq!(parsed[0].path, tree.path);
+        assert_eq!(parsed[0].depth, 0);
+        assert_eq!(parsed[0].size, tree.size);
+    }
+
+    #[test]
+    fn test_export_csv_row_per_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let tree = scan_sample_tree(&temp_dir);
+        let output = temp_dir.path().join("scan.csv");
+
+        let count = export_scan_result(&tree, &output, ExportFormat::Csv).unwrap();
+        let content = fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        assert_eq!(lines[0], "path,size,file_count,is_large_folder");
+        assert_eq!(lines.len() - 1, count);
+        assert_eq!(count, flatten(&tree).len());
+        assert!(content.contains('"'), "含逗号的路径应加引号");
+    }
+
+    #[test]
+    fn test_export_format_parse() {
+        assert_eq!("JSON".parse::<ExportFormat>().unwrap(), ExportFormat::Json);
+        assert_eq!("csv".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+}
@@ -1,10 +1,13 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Local};
 use log::{info, error, warn};
 use uuid::Uuid;
+use crate::messages::{Locale, MessageCatalog, MessageKey};
 
 /// 错误类型枚举
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +22,8 @@ pub enum ErrorType {
     SystemProtection(String),
     NetworkError(String),
     Timeout(String),
+    /// 文件被其他程序占用（共享冲突）
+    FileLocked(String),
     Unknown(String),
 }
 
@@ -50,6 +55,15 @@ pub struct ErrorRecoveryConfig {
     pub enable_partial_rollback: bool,
     pub max_rollback_size_mb: u64,
     pub backup_retention_hours: u64,
+    // 新增：备份存放目录（未设置或空间不足时使用系统临时目录）
+    #[serde(default)]
+    pub backup_root: Option<PathBuf>,
+    // 新增：单次错误恢复中所有重试的累计时间上限（毫秒），超出后不再重试
+    #[serde(default)]
+    pub max_total_recovery_ms: Option<u64>,
+    // 新增：每次重试延迟上附加的随机抖动上限（毫秒），0 表示不加抖动
+    #[serde(default)]
+    pub retry_jitter_ms: u64,
 }
 
 impl Default for ErrorRecoveryConfig {
@@ -61,6 +75,9 @@ impl Default for ErrorRecoveryConfig {
             enable_partial_rollback: true,
             max_rollback_size_mb: 1000, // 1GB
             backup_retention_hours: 24,
+            backup_root: None,
+            max_total_recovery_ms: None,
+            retry_jitter_ms: 0,
         }
     }
 }
@@ -79,11 +96,204 @@ pub struct RecoveryState {
     pub timestamp: DateTime<Local>,
 }
 
+/// 迁移前备份使用的操作类型，撤销迁移时按此类型查找备份
+pub const MIGRATION_BACKUP_TYPE: &str = "migration";
+
+/// 备份被取消时 perform_backup 返回的错误信息
+const BACKUP_CANCELLED: &str = "备份已取消";
+
+/// 两次推送备份进度之间的最短间隔
+const BACKUP_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// 自上次推送以来复制的字节数达到该值时立即推送，不受间隔限制
+const BACKUP_PROGRESS_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 备份进度
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupProgress {
+    pub source_path: String,
+    pub current_file: String,
+    pub copied_files: u64,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub progress: f64,
+    pub in_progress: bool,
+}
+
+/// 备份的取消标志和进度，可在不持有管理器锁的情况下访问
+#[derive(Debug, Clone, Default)]
+pub struct BackupControl {
+    cancelled: Arc<AtomicBool>,
+    progress: Arc<Mutex<BackupProgress>>,
+    progress_sender: Arc<Mutex<Option<tokio::sync::mpsc::UnboundedSender<BackupProgress>>>>, // 新增：备份进度的推送通道
+    last_emitted: Arc<Mutex<Option<(std::time::Instant, u64)>>>, // 新增：上次推送进度的时间和当时已复制的字节数，用于节流
+}
+
+impl BackupControl {
+    /// 请求取消正在进行的备份
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 检查是否已请求取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 获取当前备份进度
+    pub fn progress(&self) -> BackupProgress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    /// 设置备份进度的推送通道：备份开始和结束时推送进度快照，复制过程中按时间间隔或复制量节流推送
+    pub fn set_progress_sender(&self, sender: Option<tokio::sync::mpsc::UnboundedSender<BackupProgress>>) {
+        *self.progress_sender.lock().unwrap() = sender;
+    }
+
+    /// 推送当前进度快照，接收端已关闭时移除通道
+    fn emit_progress(&self, progress: &BackupProgress) {
+        *self.last_emitted.lock().unwrap() = Some((std::time::Instant::now(), progress.copied_bytes));
+        let mut sender = self.progress_sender.lock().unwrap();
+        if sender.as_ref().is_some_and(|sender| sender.send(progress.clone()).is_err()) {
+            *sender = None;
+        }
+    }
+
+    /// 开始新的备份：清除取消标志并重置进度
+    fn start(&self, source: &Path, total_bytes: u64) {
+        self.cancelled.store(false, Ordering::Relaxed);
+        *self.progress.lock().unwrap() = BackupProgress {
+            source_path: source.to_string_lossy().to_string(),
+            total_bytes,
+            in_progress: true,
+            ..BackupProgress::default()
+        };
+        self.emit_progress(&self.progress());
+    }
+
+    fn finish(&self) {
+        let progress = {
+            let mut progress = self.progress.lock().unwrap();
+            progress.in_progress = false;
+            progress.clone()
+        };
+        self.emit_progress(&progress);
+    }
+
+    fn record_file(&self, path: &Path, size: u64) {
+        let progress = {
+            let mut progress = self.progress.lock().unwrap();
+            progress.current_file = path.to_string_lossy().to_string();
+            progress.copied_files += 1;
+            progress.copied_bytes += size;
+            if progress.total_bytes > 0 {
+                progress.progress = (progress.copied_bytes as f64 / progress.total_bytes as f64 * 100.0).min(100.0);
+            }
+            progress.clone()
+        };
+        if self.progress_due(progress.copied_bytes) {
+            self.emit_progress(&progress);
+        }
+    }
+
+    /// 距上次推送已超过最短间隔，或期间复制的字节数已达到阈值时返回真
+    fn progress_due(&self, copied_bytes: u64) -> bool {
+        match *self.last_emitted.lock().unwrap() {
+            Some((at, bytes)) => {
+                at.elapsed() >= BACKUP_PROGRESS_INTERVAL
+                    || copied_bytes.saturating_sub(bytes) >= BACKUP_PROGRESS_BYTES
+            }
+            None => true,
+        }
+    }
+}
+
 /// 错误恢复管理器
 pub struct ErrorRecoveryManager {
     config: ErrorRecoveryConfig,
     recovery_states: HashMap<String, RecoveryState>,
     backup_registry: HashMap<String, BackupInfo>,
+    backup_control: BackupControl, // 新增：备份取消标志和进度
+    #[cfg(test)]
+    retry_executor: Option<fn(&RecoveryContext) -> Result<(), String>>, // 新增：重试时执行的操作（测试中可替换）
+    #[cfg(test)]
+    backup_remover: fn(&Path) -> std::io::Result<()>, // 新增：删除备份文件或目录（测试中可替换）
+    locale: Arc<std::sync::RwLock<Locale>>, // 新增：恢复结果消息使用的语言（与应用设置共享）
+}
+
+/// 过期备份清理报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupCleanupReport {
+    /// 已删除备份文件并移除登记的备份ID
+    pub cleaned: Vec<String>,
+    /// 备份文件已被外部删除，只移除了登记的备份ID
+    pub already_gone: Vec<String>,
+    /// 删除失败、登记被保留以便下次重试的备份
+    pub failed: Vec<BackupCleanupFailure>,
+}
+
+/// 清理失败的备份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupCleanupFailure {
+    pub backup_id: String,
+    pub backup_path: PathBuf,
+    pub error: String,
+}
+
+/// 备份目录中存在、但未在当前登记表中的备份（通常是崩溃后遗留的）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedBackup {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified_at: Option<DateTime<Local>>,
+    /// 按最后修改时间计算的存在秒数
+    pub age_seconds: u64,
+}
+
+/// 查找孤立备份所需的备份目录和已登记备份路径的快照，扫描时无需持有管理器锁
+#[derive(Debug, Clone)]
+pub struct OrphanedBackupScan {
+    backup_dirs: Vec<PathBuf>,
+    registered: Vec<PathBuf>,
+}
+
+impl OrphanedBackupScan {
+    /// 列出备份目录中未登记的孤立备份（按存在时间从旧到新），会遍历每个孤立备份计算大小
+    pub fn run(&self) -> Vec<OrphanedBackup> {
+        let now = Local::now();
+        let mut orphans = Vec::new();
+
+        for dir in &self.backup_dirs {
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if self.registered.contains(&path) {
+                    continue;
+                }
+
+                let modified_at = entry.metadata().ok()
+                    .and_then(|metadata| metadata.modified().ok())
+                    .map(DateTime::<Local>::from);
+                let size = ErrorRecoveryManager::calculate_directory_size(&path).unwrap_or_else(|e| {
+                    warn!("计算孤立备份大小失败 {}: {}", path.display(), e);
+                    0
+                });
+                orphans.push(OrphanedBackup {
+                    age_seconds: modified_at.map(|modified| (now - modified).num_seconds().max(0) as u64).unwrap_or(0),
+                    path,
+                    size,
+                    modified_at,
+                });
+            }
+        }
+
+        orphans.sort_by(|a, b| b.age_seconds.cmp(&a.age_seconds));
+        info!("发现 {} 个孤立备份", orphans.len());
+        orphans
+    }
 }
 
 /// 备份信息
@@ -96,6 +306,9 @@ pub struct BackupInfo {
     pub created_at: DateTime<Local>,
     pub operation_type: String,
     pub is_active: bool,
+    // 新增：备份已存在的秒数，列出备份时计算
+    #[serde(default)]
+    pub age_seconds: u64,
 }
 
 impl ErrorRecoveryManager {
@@ -105,9 +318,37 @@ impl ErrorRecoveryManager {
             config,
             recovery_states: HashMap::new(),
             backup_registry: HashMap::new(),
+            backup_control: BackupControl::default(),
+            #[cfg(test)]
+            retry_executor: None,
+            #[cfg(test)]
+            backup_remover: remove_backup_path,
+            locale: Arc::new(std::sync::RwLock::new(Locale::default())),
         }
     }
 
+    /// 与应用共享语言设置，恢复结果消息随设置切换语言
+    pub fn with_locale(mut self, locale: Arc<std::sync::RwLock<Locale>>) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 按当前语言生成恢复结果消息
+    fn localized_message(&self, key: MessageKey) -> String {
+        let locale = self.locale.read().map(|locale| *locale).unwrap_or_default();
+        MessageCatalog::message(key, locale).to_string()
+    }
+
+    /// 获取备份控制句柄，用于在备份进行中取消或查询进度
+    pub fn backup_control(&self) -> BackupControl {
+        self.backup_control.clone()
+    }
+
+    /// 设置备份存放目录，None 表示使用系统临时目录
+    pub fn set_backup_root(&mut self, backup_root: Option<PathBuf>) {
+        self.config.backup_root = backup_root;
+    }
+
     /// 处理错误并尝试恢复
     pub async fn handle_error(
         &mut self,
@@ -167,14 +408,31 @@ impl ErrorRecoveryManager {
         max_retries: u32,
         context: &RecoveryContext,
     ) -> Result<RecoveryResult, RecoveryError> {
-        
+        let started = std::time::Instant::now();
+        let time_limit = self.config.max_total_recovery_ms.map(std::time::Duration::from_millis);
+
         while recovery_state.retry_count < max_retries {
+            // 累计时间超出上限时不再重试
+            if let Some(limit) = time_limit {
+                if started.elapsed() >= limit {
+                    warn!("重试操作 {} 超过最长恢复时间 {:?}，已重试 {} 次", operation_id, limit, recovery_state.retry_count);
+                    return self.handle_retries_exhausted(
+                        operation_id, recovery_state, context,
+                        format!("超过最长恢复时间: {} 毫秒", limit.as_millis()),
+                    ).await;
+                }
+            }
+
             recovery_state.retry_count += 1;
             
             info!("重试操作 {} - 第 {} 次", operation_id, recovery_state.retry_count);
             
-            // 等待重试延迟
-            tokio::time::sleep(tokio::time::Duration::from_millis(self.config.retry_delay_ms)).await;
+            // 等待重试延迟（附加随机抖动，且不超过剩余的恢复时间）
+            let mut delay = std::time::Duration::from_millis(self.config.retry_delay_ms + self.retry_jitter());
+            if let Some(limit) = time_limit {
+                delay = delay.min(limit.saturating_sub(started.elapsed()));
+            }
+            tokio::time::sleep(delay).await;
             
             // 尝试重新执行操作
             match self.retry_operation(context).await {
@@ -185,7 +443,8 @@ impl ErrorRecoveryManager {
                     return Ok(RecoveryResult {
                         success: true,
                         recovery_type: RecoveryType::Retry,
-                        message: "操作通过重试恢复成功".to_string(),
+                        message: self.localized_message(MessageKey::RecoveryRetrySucceeded),
+                        message_key: Some(MessageKey::RecoveryRetrySucceeded),
                         backup_path: None,
                     });
                 },
@@ -194,12 +453,10 @@ impl ErrorRecoveryManager {
                     
                     // 如果达到最大重试次数，尝试其他策略
                     if recovery_state.retry_count >= max_retries {
-                        // 尝试回滚作为备选策略
-                        if self.config.enable_partial_rollback {
-                            return self.handle_rollback(operation_id, recovery_state, context).await;
-                        } else {
-                            return Err(RecoveryError::MaxRetriesExceeded(format!("达到最大重试次数: {}", max_retries)));
-                        }
+                        return self.handle_retries_exhausted(
+                            operation_id, recovery_state, context,
+                            format!("达到最大重试次数: {}", max_retries),
+                        ).await;
                     }
                 }
             }
@@ -208,6 +465,29 @@ impl ErrorRecoveryManager {
         Err(RecoveryError::MaxRetriesExceeded("重试次数耗尽".to_string()))
     }
 
+    /// 重试次数或时间耗尽后，尝试回滚作为备选策略
+    async fn handle_retries_exhausted(
+        &mut self,
+        operation_id: &str,
+        recovery_state: &mut RecoveryState,
+        context: &RecoveryContext,
+        reason: String,
+    ) -> Result<RecoveryResult, RecoveryError> {
+        if self.config.enable_partial_rollback {
+            self.handle_rollback(operation_id, recovery_state, context).await
+        } else {
+            Err(RecoveryError::MaxRetriesExceeded(reason))
+        }
+    }
+
+    /// 本次重试延迟附加的随机抖动（毫秒），避免多个重试同时冲击繁忙的磁盘
+    fn retry_jitter(&self) -> u64 {
+        if self.config.retry_jitter_ms == 0 {
+            return 0;
+        }
+        (Uuid::new_v4().as_u128() % (self.config.retry_jitter_ms as u128 + 1)) as u64
+    }
+
     /// 处理回滚策略
     async fn handle_rollback(
         &mut self,
@@ -217,6 +497,26 @@ impl ErrorRecoveryManager {
     ) -> Result<RecoveryResult, RecoveryError> {
         
         info!("执行回滚操作: {}", operation_id);
+
+        // 有复制日志时只撤销失败操作写入的内容，不整体恢复备份
+        if let (true, Some(journal)) = (self.config.enable_partial_rollback, &context.copy_journal) {
+            let report = journal.rollback();
+            if !report.errors.is_empty() {
+                error!("部分回滚失败: {}", report.errors.join("; "));
+                return Err(RecoveryError::RollbackFailed(format!("部分回滚失败: {}", report.errors.join("; "))));
+            }
+            recovery_state.is_recovered = true;
+            recovery_state.recovery_message = Some(format!(
+                "部分回滚成功：删除 {} 项，恢复 {} 个文件", report.removed.len(), report.restored.len()
+            ));
+            return Ok(RecoveryResult {
+                success: true,
+                recovery_type: RecoveryType::Rollback,
+                message: self.localized_message(MessageKey::RecoveryPartialRollbackSucceeded),
+                message_key: Some(MessageKey::RecoveryPartialRollbackSucceeded),
+                backup_path: None,
+            });
+        }
         
         // 检查是否有可用的备份
         if let Some(backup_info) = self.find_backup_for_operation(operation_id) {
@@ -229,7 +529,8 @@ impl ErrorRecoveryManager {
                     Ok(RecoveryResult {
                         success: true,
                         recovery_type: RecoveryType::Rollback,
-                        message: "操作通过回滚恢复成功".to_string(),
+                        message: self.localized_message(MessageKey::RecoveryRollbackSucceeded),
+                        message_key: Some(MessageKey::RecoveryRollbackSucceeded),
                         backup_path: Some(backup_info.backup_path),
                     })
                 },
@@ -249,7 +550,8 @@ impl ErrorRecoveryManager {
                     Ok(RecoveryResult {
                         success: true,
                         recovery_type: RecoveryType::Rollback,
-                        message: "通过紧急备份回滚成功".to_string(),
+                        message: self.localized_message(MessageKey::RecoveryEmergencyRollbackSucceeded),
+                        message_key: Some(MessageKey::RecoveryEmergencyRollbackSucceeded),
                         backup_path: Some(backup_path),
                     })
                 },
@@ -277,7 +579,8 @@ impl ErrorRecoveryManager {
         Ok(RecoveryResult {
             success: true,
             recovery_type: RecoveryType::Skip,
-            message: "操作已跳过，继续执行后续步骤".to_string(),
+            message: self.localized_message(MessageKey::RecoverySkipped),
+            message_key: Some(MessageKey::RecoverySkipped),
             backup_path: None,
         })
     }
@@ -298,7 +601,8 @@ impl ErrorRecoveryManager {
         Ok(RecoveryResult {
             success: false,
             recovery_type: RecoveryType::Abort,
-            message: "操作已中止，需要手动干预".to_string(),
+            message: self.localized_message(MessageKey::RecoveryAborted),
+            message_key: Some(MessageKey::RecoveryAborted),
             backup_path: None,
         })
     }
@@ -319,7 +623,8 @@ impl ErrorRecoveryManager {
         Ok(RecoveryResult {
             success: false,
             recovery_type: RecoveryType::Manual,
-            message: "错误需要手动处理，请查看日志获取详细信息".to_string(),
+            message: self.localized_message(MessageKey::RecoveryManualRequired),
+            message_key: Some(MessageKey::RecoveryManualRequired),
             backup_path: None,
         })
     }
@@ -331,10 +636,25 @@ impl ErrorRecoveryManager {
         operation_type: &str,
         operation_id: &str,
     ) -> Result<BackupInfo, BackupError> {
-        
-        // 检查备份大小限制
-        let source_size = self.calculate_directory_size(source_path)
+        let source_size = Self::calculate_directory_size(source_path)
             .map_err(|e| BackupError::BackupFailed(format!("计算目录大小失败: {}", e)))?;
+
+        self.create_backup_with_known_size(source_path, source_size, operation_type, operation_id).await
+    }
+
+    /// 使用已知大小创建备份（例如扫描结果中的 DirectoryInfo.size），跳过重复的目录遍历
+    pub async fn create_backup_with_known_size(
+        &mut self,
+        source_path: &Path,
+        source_size: u64,
+        operation_type: &str,
+        operation_id: &str,
+    ) -> Result<BackupInfo, BackupError> {
+        if !source_path.exists() {
+            return Err(BackupError::BackupFailed("路径不存在".to_string()));
+        }
+
+        // 检查备份大小限制
         let max_backup_size = self.config.max_rollback_size_mb * 1024 * 1024;
         
         if source_size > max_backup_size {
@@ -346,13 +666,17 @@ impl ErrorRecoveryManager {
         }
 
         let backup_id = Uuid::new_v4().to_string();
-        let backup_path = self.generate_backup_path(source_path, &backup_id);
+        let backup_path = self.generate_backup_path(source_path, &backup_id, source_size);
         
         info!("创建备份 - 操作ID: {}, 源路径: {}, 备份路径: {}", 
               operation_id, source_path.display(), backup_path.display());
 
-        // 执行备份
-        match self.perform_backup(source_path, &backup_path).await {
+        // 执行备份（可通过 BackupControl 取消）
+        self.backup_control.start(source_path, source_size);
+        let result = self.perform_backup(source_path, &backup_path, Some(self.backup_control.clone())).await;
+        self.backup_control.finish();
+
+        match result {
             Ok(_) => {
                 let backup_info = BackupInfo {
                     backup_id: backup_id.clone(),
@@ -362,6 +686,7 @@ impl ErrorRecoveryManager {
                     created_at: Local::now(),
                     operation_type: operation_type.to_string(),
                     is_active: true,
+                    age_seconds: 0,
                 };
 
                 // 注册备份
@@ -370,6 +695,10 @@ impl ErrorRecoveryManager {
                 info!("备份创建成功: {}", backup_id);
                 Ok(backup_info)
             },
+            Err(e) if e == BACKUP_CANCELLED => {
+                warn!("备份已取消: {}", source_path.display());
+                Err(BackupError::BackupCancelled(source_path.display().to_string()))
+            },
             Err(e) => {
                 error!("备份创建失败: {}", e);
                 Err(BackupError::BackupFailed(format!("备份操作失败: {}", e)))
@@ -377,26 +706,165 @@ impl ErrorRecoveryManager {
         }
     }
 
-    /// 执行备份操作
-    async fn perform_backup(&self, source: &Path, backup_path: &Path) -> Result<(), String> {
-        // 创建备份目录
-        fs::create_dir_all(backup_path.parent().unwrap())
-            .map_err(|e| format!("创建备份目录失败: {}", e))?;
+    /// 从备份中只恢复指定的相对路径（文件或子目录），其余内容保持不变，备份仍可继续使用
+    pub async fn restore_paths(&self, backup_id: &str, relative_paths: &[String]) -> Result<Vec<PathBuf>, String> {
+        let backup_info = self.backup_registry.get(backup_id)
+            .cloned()
+            .ok_or_else(|| format!("备份不存在: {}", backup_id))?;
+
+        let cutoff_time = Local::now() - chrono::Duration::hours(self.config.backup_retention_hours as i64);
+        if backup_info.created_at < cutoff_time {
+            return Err(format!("备份已过期，无法恢复: {}", backup_id));
+        }
+        if !backup_info.backup_path.is_dir() {
+            return Err(format!("备份不是目录，无法按路径恢复: {}", backup_info.backup_path.display()));
+        }
+        if relative_paths.is_empty() {
+            return Err("未指定要恢复的路径".to_string());
+        }
+
+        // 先校验全部路径，任一无效时不做任何修改
+        let mut items = Vec::with_capacity(relative_paths.len());
+        for relative_path in relative_paths {
+            let relative = Path::new(relative_path.trim());
+            let is_plain_relative = relative.components().next().is_some()
+                && relative.components().all(|component| matches!(component, std::path::Component::Normal(_)));
+            if !is_plain_relative {
+                return Err(format!("无效的恢复路径: {}", relative_path));
+            }
+            let source = backup_info.backup_path.join(relative);
+            if !source.exists() {
+                return Err(format!("备份中不存在该路径: {}", relative_path));
+            }
+            items.push((source, backup_info.original_path.join(relative)));
+        }
+
+        let restored = tokio::task::spawn_blocking(move || -> Result<Vec<PathBuf>, String> {
+            let mut restored = Vec::with_capacity(items.len());
+            for (source, target) in items {
+                Self::restore_item(&source, &target)?;
+                restored.push(target);
+            }
+            Ok(restored)
+        })
+        .await
+        .map_err(|e| format!("恢复任务执行失败: {}", e))??;
+
+        info!("部分恢复完成 - 备份ID: {}, 恢复 {} 项", backup_id, restored.len());
+        Ok(restored)
+    }
+
+    /// 用备份中的一项替换现有数据
+    ///
+    /// 先复制到同级的 `.<名称>.dirmover-restore`，再把现有数据移到 `.<名称>.dirmover-old`
+    /// 并将副本改名到位；交换成功后才删除旧数据，复制或改名失败时现有数据保持不变。
+    fn restore_item(source: &Path, target: &Path) -> Result<(), String> {
+        let name = target.file_name()
+            .ok_or_else(|| format!("无效的恢复目标: {}", target.display()))?
+            .to_string_lossy();
+        let staging = target.with_file_name(format!(".{}.dirmover-restore", name));
+        let aside = target.with_file_name(format!(".{}.dirmover-old", name));
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建目标目录失败: {}", e))?;
+        }
+        Self::remove_path(&staging)
+            .map_err(|e| format!("清理上次恢复残留失败 {}: {}", staging.display(), e))?;
 
-        // 复制文件或目录
-        if source.is_file() {
-            fs::copy(source, backup_path)
-                .map_err(|e| format!("文件备份失败: {}", e))?;
+        let copied = if source.is_dir() {
+            Self::copy_directory_recursive(source, &staging, None)
         } else {
-            // 递归复制目录
-            self.copy_directory_recursive(source, backup_path)?;
+            fs::copy(source, &staging)
+                .map(|_| ())
+                .map_err(|e| format!("复制文件失败 {}: {}", source.display(), e))
+        };
+        if let Err(e) = copied {
+            let _ = Self::remove_path(&staging);
+            return Err(e);
         }
 
+        let had_existing = fs::symlink_metadata(target).is_ok();
+        if had_existing {
+            let moved_aside = Self::remove_path(&aside).and_then(|_| fs::rename(target, &aside));
+            if let Err(e) = moved_aside {
+                let _ = Self::remove_path(&staging);
+                return Err(format!("移开原有数据失败 {}: {}", target.display(), e));
+            }
+        }
+
+        if let Err(e) = fs::rename(&staging, target) {
+            if had_existing {
+                if let Err(restore_error) = fs::rename(&aside, target) {
+                    error!("放回原有数据失败，原有数据保留在 {}: {}", aside.display(), restore_error);
+                }
+            }
+            let _ = Self::remove_path(&staging);
+            return Err(format!("替换原有数据失败 {}: {}", target.display(), e));
+        }
+
+        if had_existing {
+            if let Err(e) = Self::remove_path(&aside) {
+                warn!("删除被替换的旧数据失败，保留在 {}: {}", aside.display(), e);
+            }
+        }
         Ok(())
     }
 
-    /// 递归复制目录
-    fn copy_directory_recursive(&self, source: &Path, target: &Path) -> Result<(), String> {
+    /// 删除文件、链接或目录；路径不存在时视为成功
+    fn remove_path(path: &Path) -> std::io::Result<()> {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+            Ok(_) => fs::remove_file(path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 执行备份操作：复制在阻塞线程池中进行，传入 control 时可取消，取消或失败后清理不完整的备份
+    async fn perform_backup(&self, source: &Path, backup_path: &Path, control: Option<BackupControl>) -> Result<(), String> {
+        let source = source.to_path_buf();
+        let backup_path = backup_path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            // 创建备份目录
+            fs::create_dir_all(backup_path.parent().unwrap())
+                .map_err(|e| format!("创建备份目录失败: {}", e))?;
+
+            // 复制文件或目录
+            let result = if source.is_file() {
+                fs::copy(&source, &backup_path)
+                    .map(|size| {
+                        if let Some(control) = &control {
+                            control.record_file(&source, size);
+                        }
+                    })
+                    .map_err(|e| format!("文件备份失败: {}", e))
+            } else {
+                // 递归复制目录
+                Self::copy_directory_recursive(&source, &backup_path, control.as_ref())
+            };
+
+            if result.is_err() && control.is_some() {
+                let cleanup = if backup_path.is_dir() {
+                    fs::remove_dir_all(&backup_path)
+                } else {
+                    fs::remove_file(&backup_path)
+                };
+                if let Err(e) = cleanup {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        warn!("清理不完整的备份失败 {}: {}", backup_path.display(), e);
+                    }
+                }
+            }
+            result
+        })
+        .await
+        .map_err(|e| format!("备份任务执行失败: {}", e))?
+    }
+
+    /// 递归复制目录，每个条目前检查取消标志
+    fn copy_directory_recursive(source: &Path, target: &Path, control: Option<&BackupControl>) -> Result<(), String> {
         fs::create_dir_all(target)
             .map_err(|e| format!("创建目标目录失败: {}", e))?;
 
@@ -404,16 +872,23 @@ impl ErrorRecoveryManager {
             .map_err(|e| format!("读取源目录失败: {}", e))?;
 
         for entry in entries {
+            if control.is_some_and(|control| control.is_cancelled()) {
+                return Err(BACKUP_CANCELLED.to_string());
+            }
+
             let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
             let entry_path = entry.path();
             let entry_name = entry.file_name();
             let target_entry_path = target.join(&entry_name);
 
             if entry_path.is_dir() {
-                self.copy_directory_recursive(&entry_path, &target_entry_path)?;
+                Self::copy_directory_recursive(&entry_path, &target_entry_path, control)?;
             } else {
-                fs::copy(&entry_path, &target_entry_path)
+                let size = fs::copy(&entry_path, &target_entry_path)
                     .map_err(|e| format!("复制文件失败 {}: {}", entry_path.display(), e))?;
+                if let Some(control) = control {
+                    control.record_file(&entry_path, size);
+                }
             }
         }
 
@@ -428,8 +903,8 @@ impl ErrorRecoveryManager {
         // 检查原始路径是否存在
         if backup_info.original_path.exists() {
             // 如果存在，先创建临时备份
-            let temp_backup_path = self.generate_temp_backup_path(&backup_info.original_path);
-            self.perform_backup(&backup_info.original_path, &temp_backup_path).await?;
+            let temp_backup_path = self.generate_temp_backup_path(&backup_info.original_path, backup_info.backup_size);
+            self.perform_backup(&backup_info.original_path, &temp_backup_path, None).await?;
         }
 
         // 删除原始路径（如果是目录）
@@ -442,7 +917,7 @@ impl ErrorRecoveryManager {
         }
 
         // 恢复备份
-        self.perform_backup(&backup_info.backup_path, &backup_info.original_path).await?;
+        self.perform_backup(&backup_info.backup_path, &backup_info.original_path, None).await?;
 
         info!("回滚完成: {}", backup_info.backup_id);
         Ok(())
@@ -456,18 +931,51 @@ impl ErrorRecoveryManager {
     }
 
     /// 重试操作
-    async fn retry_operation(&self, context: &RecoveryContext) -> Result<(), String> {
+    #[cfg(not(test))]
+    async fn retry_operation(&self, _context: &RecoveryContext) -> Result<(), String> {
         // 这个函数应该根据上下文重新执行失败的操作
         // 实现取决于具体的操作类型
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         Ok(())
     }
 
+    /// 重试操作（测试中可通过 retry_executor 指定执行的操作）
+    #[cfg(test)]
+    async fn retry_operation(&self, context: &RecoveryContext) -> Result<(), String> {
+        match self.retry_executor {
+            Some(executor) => {
+                let context = context.clone();
+                tokio::task::spawn_blocking(move || executor(&context))
+                    .await
+                    .map_err(|e| format!("重试任务执行失败: {}", e))?
+            }
+            None => {
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// 删除备份文件或目录（测试中可替换）
+    #[cfg(test)]
+    fn remove_backup_files(&self, path: &Path) -> std::io::Result<()> {
+        (self.backup_remover)(path)
+    }
+
+    #[cfg(not(test))]
+    fn remove_backup_files(&self, path: &Path) -> std::io::Result<()> {
+        remove_backup_path(path)
+    }
+
     /// 错误分类
     pub fn classify_error(&self, error: &FileOperationError) -> ErrorType {
         match error {
             FileOperationError::IoError(e) => {
-                if e.kind() == std::io::ErrorKind::PermissionDenied {
+                if is_file_locked_error(e) {
+                    ErrorType::FileLocked(e.to_string())
+                } else if is_disk_full_error(e) {
+                    ErrorType::DiskSpaceInsufficient(e.to_string())
+                } else if e.kind() == std::io::ErrorKind::PermissionDenied {
                     ErrorType::PermissionDenied(e.to_string())
                 } else if e.kind() == std::io::ErrorKind::NotFound {
                     ErrorType::PathNotFound(e.to_string())
@@ -482,6 +990,7 @@ impl ErrorRecoveryManager {
             FileOperationError::PathAlreadyExists(msg) => ErrorType::PathAlreadyExists(msg.clone()),
             FileOperationError::InvalidPath(msg) => ErrorType::InvalidPath(msg.clone()),
             FileOperationError::OperationCancelled(msg) => ErrorType::OperationCancelled(msg.clone()),
+            FileOperationError::Timeout(msg) => ErrorType::Timeout(msg.clone()),
         }
     }
 
@@ -498,6 +1007,7 @@ impl ErrorRecoveryManager {
             ErrorType::SystemProtection(_) => ErrorSeverity::Critical,
             ErrorType::NetworkError(_) => ErrorSeverity::Medium,
             ErrorType::Timeout(_) => ErrorSeverity::Medium,
+            ErrorType::FileLocked(_) => ErrorSeverity::Medium,
             ErrorType::Unknown(_) => ErrorSeverity::High,
         }
     }
@@ -514,6 +1024,8 @@ impl ErrorRecoveryManager {
             (ErrorType::PathNotFound(_), ErrorSeverity::Medium) => RecoveryStrategy::Retry(2),
             (ErrorType::PermissionDenied(_), ErrorSeverity::Medium) => RecoveryStrategy::Manual,
             (ErrorType::IoError(_), ErrorSeverity::High) => RecoveryStrategy::Retry(3),
+            // 占用文件的程序通常很快会释放句柄，等待后重试
+            (ErrorType::FileLocked(_), _) => RecoveryStrategy::Retry(3),
             (ErrorType::DiskSpaceInsufficient(_), ErrorSeverity::Critical) => RecoveryStrategy::Abort,
             (ErrorType::SystemProtection(_), ErrorSeverity::Critical) => RecoveryStrategy::Abort,
             _ => RecoveryStrategy::Manual,
@@ -528,29 +1040,52 @@ impl ErrorRecoveryManager {
     }
 
     /// 生成备份路径
-    fn generate_backup_path(&self, source_path: &Path, backup_id: &str) -> PathBuf {
+    fn generate_backup_path(&self, source_path: &Path, backup_id: &str, required_bytes: u64) -> PathBuf {
         let timestamp = Local::now().format("%Y%m%d_%H%M%S");
         let source_name = source_path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("backup");
         
-        let backup_dir = std::env::temp_dir().join("dir_mover_backups");
+        let backup_dir = self.backup_base_dir(required_bytes).join("dir_mover_backups");
         backup_dir.join(format!("{}_{}_{}", source_name, timestamp, backup_id))
     }
 
     /// 生成临时备份路径
-    fn generate_temp_backup_path(&self, source_path: &Path) -> PathBuf {
+    fn generate_temp_backup_path(&self, source_path: &Path, required_bytes: u64) -> PathBuf {
         let timestamp = Local::now().format("%Y%m%d_%H%M%S");
         let source_name = source_path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("temp_backup");
         
-        let temp_dir = std::env::temp_dir().join("dir_mover_temp");
+        let temp_dir = self.backup_base_dir(required_bytes).join("dir_mover_temp");
         temp_dir.join(format!("{}_{}_temp", source_name, timestamp))
     }
 
+    /// 选择备份存放的基础目录：配置的备份目录可用空间足够时使用它，否则回退到系统临时目录
+    fn backup_base_dir(&self, required_bytes: u64) -> PathBuf {
+        if let Some(backup_root) = &self.config.backup_root {
+            if let Err(e) = fs::create_dir_all(backup_root) {
+                warn!("无法创建备份目录 {}，改用临时目录: {}", backup_root.display(), e);
+                return std::env::temp_dir();
+            }
+
+            match available_space(backup_root) {
+                Some(available) if available < required_bytes.saturating_add(required_bytes / 5) => {
+                    warn!("备份目录 {} 可用空间不足 ({} < {})，改用临时目录",
+                          backup_root.display(), format_size(available), format_size(required_bytes));
+                }
+                _ => return backup_root.clone(),
+            }
+        }
+
+        std::env::temp_dir()
+    }
+
     /// 计算目录大小
-    fn calculate_directory_size(&self, path: &Path) -> Result<u64, String> {
+    fn calculate_directory_size(path: &Path) -> Result<u64, String> {
+        #[cfg(test)]
+        tests::SIZE_WALKS.with(|count| count.set(count.get() + 1));
+
         if !path.exists() {
             return Err("路径不存在".to_string());
         }
@@ -570,7 +1105,7 @@ impl ErrorRecoveryManager {
             let entry_path = entry.path();
 
             if entry_path.is_dir() {
-                total_size += self.calculate_directory_size(&entry_path)?;
+                total_size += Self::calculate_directory_size(&entry_path)?;
             } else {
                 total_size += entry.metadata()
                     .map_err(|e| format!("获取文件元数据失败: {}", e))?
@@ -582,49 +1117,160 @@ impl ErrorRecoveryManager {
     }
 
     /// 清理过期备份
-    pub fn cleanup_expired_backups(&mut self) -> Result<u32, String> {
+    ///
+    /// 删除前核对备份文件：已被外部删除的只移除登记；删除失败的保留登记并记入报告，下次清理时重试。
+    pub fn cleanup_expired_backups(&mut self) -> Result<BackupCleanupReport, String> {
         let cutoff_time = Local::now() - chrono::Duration::hours(self.config.backup_retention_hours as i64);
-        let mut cleaned_count = 0;
+        let mut report = BackupCleanupReport::default();
 
-        let backup_ids: Vec<String> = self.backup_registry.keys().cloned().collect();
-        
-        for backup_id in backup_ids {
-            if let Some(backup_info) = self.backup_registry.get(&backup_id) {
-                if backup_info.created_at < cutoff_time {
-                    match self.remove_backup(&backup_id) {
-                        Ok(_) => {
-                            cleaned_count += 1;
-                            info!("清理过期备份: {}", backup_id);
-                        },
-                        Err(e) => {
-                            warn!("清理备份失败 {}: {}", backup_id, e);
-                        }
-                    }
+        let mut expired: Vec<BackupInfo> = self.backup_registry.values()
+            .filter(|backup| backup.created_at < cutoff_time)
+            .cloned()
+            .collect();
+        expired.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        for backup_info in expired {
+            let backup_id = backup_info.backup_id.clone();
+            match fs::symlink_metadata(&backup_info.backup_path) {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    self.backup_registry.remove(&backup_id);
+                    info!("备份文件已不存在，移除登记: {} ({})", backup_id, backup_info.backup_path.display());
+                    report.already_gone.push(backup_id);
+                    continue;
+                }
+                // 无权限等其他错误无法确认备份已删除，保留登记
+                Err(e) => {
+                    warn!("无法访问备份，保留登记 {}: {}", backup_id, e);
+                    report.failed.push(BackupCleanupFailure {
+                        backup_id,
+                        backup_path: backup_info.backup_path,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            }
+
+            match self.remove_backup_files(&backup_info.backup_path) {
+                Ok(()) => {
+                    self.backup_registry.remove(&backup_id);
+                    info!("清理过期备份: {}", backup_id);
+                    report.cleaned.push(backup_id);
+                }
+                Err(e) => {
+                    warn!("清理备份失败，保留登记 {}: {}", backup_id, e);
+                    report.failed.push(BackupCleanupFailure {
+                        backup_id,
+                        backup_path: backup_info.backup_path,
+                        error: e.to_string(),
+                    });
                 }
             }
         }
 
-        Ok(cleaned_count)
+        Ok(report)
     }
 
-    /// 删除备份
-    fn remove_backup(&mut self, backup_id: &str) -> Result<(), String> {
-        if let Some(backup_info) = self.backup_registry.remove(backup_id) {
-            // 删除备份文件
-            if backup_info.backup_path.exists() {
-                if backup_info.backup_path.is_dir() {
-                    fs::remove_dir_all(&backup_info.backup_path)
-                        .map_err(|e| format!("删除备份目录失败: {}", e))?;
-                } else {
-                    fs::remove_file(&backup_info.backup_path)
-                        .map_err(|e| format!("删除备份文件失败: {}", e))?;
+    /// 列出已登记的备份（按创建时间从新到旧），附带备份存在的时长
+    pub fn list_backups(&self) -> Vec<BackupInfo> {
+        let now = Local::now();
+        let mut backups: Vec<BackupInfo> = self.backup_registry.values()
+            .cloned()
+            .map(|mut backup| {
+                backup.age_seconds = (now - backup.created_at).num_seconds().max(0) as u64;
+                backup
+            })
+            .collect();
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        backups
+    }
+
+    /// 可能存放备份的 `dir_mover_backups` 目录：配置的备份目录及系统临时目录（空间不足时的回退位置）
+    fn backup_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let Some(backup_root) = &self.config.backup_root {
+            dirs.push(backup_root.join("dir_mover_backups"));
+        }
+        let temp_dir = std::env::temp_dir().join("dir_mover_backups");
+        if !dirs.contains(&temp_dir) {
+            dirs.push(temp_dir);
+        }
+        dirs
+    }
+
+    /// 记录查找孤立备份所需的快照，实际扫描由 `OrphanedBackupScan::run` 在锁外完成
+    pub fn orphaned_backup_scan(&self) -> OrphanedBackupScan {
+        OrphanedBackupScan {
+            backup_dirs: self.backup_dirs(),
+            registered: self.backup_registry.values().map(|backup| backup.backup_path.clone()).collect(),
+        }
+    }
+
+    /// 删除选定的孤立备份；已登记的备份或备份目录之外的路径会被拒绝
+    pub fn purge_orphaned_backups(&self, paths: &[PathBuf]) -> BackupCleanupReport {
+        let backup_dirs = self.backup_dirs();
+        let mut report = BackupCleanupReport::default();
+
+        for path in paths {
+            let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            let in_backup_dir = path.parent().is_some_and(|parent| backup_dirs.iter().any(|dir| dir == parent));
+            if !in_backup_dir || self.is_registered_backup(path) {
+                report.failed.push(BackupCleanupFailure {
+                    backup_id: name,
+                    backup_path: path.clone(),
+                    error: "不是孤立备份，拒绝删除".to_string(),
+                });
+                continue;
+            }
+
+            if fs::symlink_metadata(path).is_err() {
+                report.already_gone.push(name);
+                continue;
+            }
+            match self.remove_backup_files(path) {
+                Ok(()) => {
+                    info!("已删除孤立备份: {}", path.display());
+                    report.cleaned.push(name);
+                }
+                Err(e) => {
+                    warn!("删除孤立备份失败 {}: {}", path.display(), e);
+                    report.failed.push(BackupCleanupFailure {
+                        backup_id: name,
+                        backup_path: path.clone(),
+                        error: e.to_string(),
+                    });
                 }
             }
-            
-            Ok(())
-        } else {
-            Err("备份不存在".to_string())
         }
+
+        report
+    }
+
+    fn is_registered_backup(&self, path: &Path) -> bool {
+        self.backup_registry.values().any(|backup| backup.backup_path == path)
+    }
+
+    /// 按 ID 删除指定备份及其文件
+    pub fn delete_backup(&mut self, backup_id: &str) -> Result<(), String> {
+        self.remove_backup(backup_id)?;
+        info!("已删除备份: {}", backup_id);
+        Ok(())
+    }
+
+    /// 删除备份，文件删除成功后才移除登记
+    fn remove_backup(&mut self, backup_id: &str) -> Result<(), String> {
+        let backup_path = match self.backup_registry.get(backup_id) {
+            Some(backup_info) => backup_info.backup_path.clone(),
+            None => return Err("备份不存在".to_string()),
+        };
+
+        if backup_path.exists() {
+            self.remove_backup_files(&backup_path)
+                .map_err(|e| format!("删除备份失败: {}", e))?;
+        }
+
+        self.backup_registry.remove(backup_id);
+        Ok(())
     }
 
     /// 获取恢复统计
@@ -670,6 +1316,8 @@ pub struct RecoveryContext {
     pub operation_phase: String,
     pub previous_operations: Vec<String>,
     pub user_preferences: HashMap<String, String>,
+    // 新增：失败操作的复制日志，启用部分回滚时只撤销其中记录的写入
+    pub copy_journal: Option<Arc<CopyJournal>>,
 }
 
 impl RecoveryContext {
@@ -686,6 +1334,7 @@ impl RecoveryContext {
             operation_phase,
             previous_operations: Vec::new(),
             user_preferences: HashMap::new(),
+            copy_journal: None,
         }
     }
 
@@ -702,6 +1351,18 @@ pub struct RecoveryResult {
     pub recovery_type: RecoveryType,
     pub message: String,
     pub backup_path: Option<PathBuf>,
+    // 新增：消息的稳定键，前端可据此识别结果或切换语言
+    #[serde(default)]
+    pub message_key: Option<MessageKey>,
+}
+
+impl RecoveryResult {
+    /// 将结果消息转换为指定语言（没有消息键的结果保持原样）
+    pub fn localize(&mut self, locale: Locale) {
+        if let Some(key) = self.message_key {
+            self.message = MessageCatalog::message(key, locale).to_string();
+        }
+    }
 }
 
 /// 恢复类型
@@ -736,6 +1397,27 @@ impl std::fmt::Display for RecoveryError {
     }
 }
 
+impl RecoveryError {
+    /// 稳定的错误码，供前端按错误类型分支处理
+    ///
+    /// | 变体 | 错误码 |
+    /// |------|--------|
+    /// | MaxRetriesExceeded | `MAX_RETRIES_EXCEEDED` |
+    /// | RollbackFailed | `ROLLBACK_FAILED` |
+    /// | BackupFailed | `RECOVERY_BACKUP_FAILED` |
+    /// | RecoveryImpossible | `RECOVERY_IMPOSSIBLE` |
+    /// | UserCancelled | `USER_CANCELLED` |
+    pub fn code(&self) -> &'static str {
+        match self {
+            RecoveryError::MaxRetriesExceeded(_) => "MAX_RETRIES_EXCEEDED",
+            RecoveryError::RollbackFailed(_) => "ROLLBACK_FAILED",
+            RecoveryError::BackupFailed(_) => "RECOVERY_BACKUP_FAILED",
+            RecoveryError::RecoveryImpossible(_) => "RECOVERY_IMPOSSIBLE",
+            RecoveryError::UserCancelled(_) => "USER_CANCELLED",
+        }
+    }
+}
+
 /// 备份错误
 #[derive(Debug)]
 pub enum BackupError {
@@ -743,6 +1425,7 @@ pub enum BackupError {
     BackupTooLarge(String),
     BackupNotFound(String),
     InvalidBackupPath(String),
+    BackupCancelled(String),
 }
 
 impl std::fmt::Display for BackupError {
@@ -752,6 +1435,28 @@ impl std::fmt::Display for BackupError {
             BackupError::BackupTooLarge(msg) => write!(f, "备份过大: {}", msg),
             BackupError::BackupNotFound(msg) => write!(f, "备份未找到: {}", msg),
             BackupError::InvalidBackupPath(msg) => write!(f, "无效备份路径: {}", msg),
+            BackupError::BackupCancelled(msg) => write!(f, "备份已取消: {}", msg),
+        }
+    }
+}
+
+impl BackupError {
+    /// 稳定的错误码，供前端按错误类型分支处理
+    ///
+    /// | 变体 | 错误码 |
+    /// |------|--------|
+    /// | BackupFailed | `BACKUP_FAILED` |
+    /// | BackupTooLarge | `BACKUP_TOO_LARGE` |
+    /// | BackupNotFound | `BACKUP_NOT_FOUND` |
+    /// | InvalidBackupPath | `INVALID_BACKUP_PATH` |
+    /// | BackupCancelled | `BACKUP_CANCELLED` |
+    pub fn code(&self) -> &'static str {
+        match self {
+            BackupError::BackupFailed(_) => "BACKUP_FAILED",
+            BackupError::BackupTooLarge(_) => "BACKUP_TOO_LARGE",
+            BackupError::BackupNotFound(_) => "BACKUP_NOT_FOUND",
+            BackupError::InvalidBackupPath(_) => "INVALID_BACKUP_PATH",
+            BackupError::BackupCancelled(_) => "BACKUP_CANCELLED",
         }
     }
 }
@@ -780,7 +1485,30 @@ impl RecoveryStatistics {
 }
 
 /// 文件操作错误（需要导入）
-use crate::file_operations::FileOperationError;
+use crate::file_operations::{detect_drive_status, is_file_locked_error, CopyJournal, FileOperationError};
+
+/// 删除备份文件或目录
+fn remove_backup_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// 判断IO错误是否为磁盘空间不足
+///
+/// Windows: ERROR_HANDLE_DISK_FULL (39) / ERROR_DISK_FULL (112)；Unix: ENOSPC (28)
+fn is_disk_full_error(error: &std::io::Error) -> bool {
+    #[cfg(target_os = "windows")]
+    const DISK_FULL_CODES: &[i32] = &[39, 112];
+    #[cfg(not(target_os = "windows"))]
+    const DISK_FULL_CODES: &[i32] = &[28];
+
+    error.raw_os_error()
+        .map(|code| DISK_FULL_CODES.contains(&code))
+        .unwrap_or(false)
+}
 
 /// 格式化文件大小
 fn format_size(bytes: u64) -> String {
@@ -796,13 +1524,36 @@ fn format_size(bytes: u64) -> String {
     format!("{:.2} {}", size, UNITS[unit_index])
 }
 
+/// 查询路径所在磁盘的可用空间（无法确定时返回 None）
+fn available_space(path: &Path) -> Option<u64> {
+    detect_drive_status(path).map(|status| status.available_space)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_operations::{ConflictPolicy, CopyOptions, FileOperator};
     use tempfile::TempDir;
     use std::fs::File;
     use std::io::Write;
 
+    #[tokio::test]
+    async fn test_backup_root_is_used() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("data.txt");
+        fs::write(&source_file, "备份内容").unwrap();
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        let backup = manager.create_backup(&source_file, "test", "op").await.unwrap();
+
+        assert!(backup.backup_path.starts_with(backup_root.path()));
+        assert_eq!(fs::read_to_string(&backup.backup_path).unwrap(), "备份内容");
+    }
+
     #[tokio::test]
     async fn test_error_recovery_manager() {
         let config = ErrorRecoveryConfig::default();
@@ -830,6 +1581,50 @@ mod tests {
         assert!(result.is_ok() || result.is_err()); // 根据策略可能成功或失败
     }
 
+    #[tokio::test]
+    async fn test_recovery_message_follows_shared_locale() {
+        let locale = Arc::new(std::sync::RwLock::new(Locale::EnUs));
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig::default()).with_locale(locale.clone());
+        let context = RecoveryContext::new("copy".to_string(), PathBuf::from("source"), None, "copy".to_string());
+        let timeout = FileOperationError::Timeout("复制超时".to_string());
+
+        let result = manager.handle_error("op_en", &timeout, &context).await.unwrap();
+        assert_eq!(result.message, MessageCatalog::message(MessageKey::RecoveryManualRequired, Locale::EnUs));
+
+        // 应用切换语言后，之后的恢复结果随之切换；已有结果可单独转换
+        *locale.write().unwrap() = Locale::ZhCn;
+        let mut converted = result.clone();
+        let result = manager.handle_error("op_zh", &timeout, &context).await.unwrap();
+        assert_eq!(result.message, MessageCatalog::message(MessageKey::RecoveryManualRequired, Locale::ZhCn));
+        converted.localize(Locale::ZhCn);
+        assert_eq!(converted.message, result.message);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let recovery_cases = [
+            (RecoveryError::MaxRetriesExceeded(String::new()), "MAX_RETRIES_EXCEEDED"),
+            (RecoveryError::RollbackFailed(String::new()), "ROLLBACK_FAILED"),
+            (RecoveryError::BackupFailed(String::new()), "RECOVERY_BACKUP_FAILED"),
+            (RecoveryError::RecoveryImpossible(String::new()), "RECOVERY_IMPOSSIBLE"),
+            (RecoveryError::UserCancelled(String::new()), "USER_CANCELLED"),
+        ];
+        for (error, code) in recovery_cases {
+            assert_eq!(error.code(), code, "{:?}", error);
+        }
+
+        let backup_cases = [
+            (BackupError::BackupFailed(String::new()), "BACKUP_FAILED"),
+            (BackupError::BackupTooLarge(String::new()), "BACKUP_TOO_LARGE"),
+            (BackupError::BackupNotFound(String::new()), "BACKUP_NOT_FOUND"),
+            (BackupError::InvalidBackupPath(String::new()), "INVALID_BACKUP_PATH"),
+            (BackupError::BackupCancelled(String::new()), "BACKUP_CANCELLED"),
+        ];
+        for (error, code) in backup_cases {
+            assert_eq!(error.code(), code, "{:?}", error);
+        }
+    }
+
     #[test]
     fn test_error_classification() {
         let config = ErrorRecoveryConfig::default();
@@ -844,6 +1639,592 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_disk_full_error_classification() {
+        let config = ErrorRecoveryConfig::default();
+        let manager = ErrorRecoveryManager::new(config);
+
+        #[cfg(target_os = "windows")]
+        let disk_full_codes = [112, 39];
+        #[cfg(not(target_os = "windows"))]
+        let disk_full_codes = [28];
+
+        for code in disk_full_codes {
+            let io_error = FileOperationError::IoError(std::io::Error::from_raw_os_error(code));
+            let classified = manager.classify_error(&io_error);
+            assert!(
+                matches!(classified, ErrorType::DiskSpaceInsufficient(_)),
+                "os error {} 应被分类为磁盘空间不足: {:?}", code, classified
+            );
+
+            let context = RecoveryContext::new(
+                "copy".to_string(),
+                PathBuf::from("/source"),
+                Some(PathBuf::from("/target")),
+                "copying".to_string(),
+            );
+            let severity = manager.determine_severity(&classified, &context);
+            assert!(matches!(
+                manager.determine_recovery_strategy(&classified, &severity, &context),
+                RecoveryStrategy::Abort
+            ));
+        }
+
+        // 其他系统错误码不应被误判
+        let other_error = FileOperationError::IoError(std::io::Error::from_raw_os_error(5));
+        assert!(!matches!(manager.classify_error(&other_error), ErrorType::DiskSpaceInsufficient(_)));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_sharing_violation_is_retried() {
+        let manager = ErrorRecoveryManager::new(ErrorRecoveryConfig::default());
+        let context = RecoveryContext::new(
+            "copy".to_string(),
+            PathBuf::from("C:\\source"),
+            Some(PathBuf::from("D:\\target")),
+            "copying".to_string(),
+        );
+
+        // ERROR_SHARING_VIOLATION
+        let locked = FileOperationError::IoError(std::io::Error::from_raw_os_error(32));
+        let classified = manager.classify_error(&locked);
+        assert!(matches!(classified, ErrorType::FileLocked(_)));
+        let severity = manager.determine_severity(&classified, &context);
+        assert_eq!(manager.determine_recovery_strategy(&classified, &severity, &context), RecoveryStrategy::Retry(3));
+    }
+
+    thread_local! {
+        /// calculate_directory_size 的调用次数，用于验证已知大小路径不会遍历目录
+        pub(super) static SIZE_WALKS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete_backups() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+
+        let mut created = Vec::new();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            let source_file = source_dir.path().join(name);
+            fs::write(&source_file, name).unwrap();
+            created.push(manager.create_backup(&source_file, "test", "op").await.unwrap());
+        }
+
+        let listed = manager.list_backups();
+        assert_eq!(listed.len(), 3);
+        assert!(listed.iter().all(|backup| backup.is_active && backup.backup_size == 5));
+
+        let removed = &created[1];
+        manager.delete_backup(&removed.backup_id).unwrap();
+        assert!(!removed.backup_path.exists());
+        assert!(created[0].backup_path.exists() && created[2].backup_path.exists());
+
+        let listed = manager.list_backups();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|backup| backup.backup_id != removed.backup_id));
+        assert!(manager.delete_backup(&removed.backup_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_backups_are_reported_and_purged() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        let source_file = source_dir.path().join("data.txt");
+        fs::write(&source_file, "data").unwrap();
+        let registered = manager.create_backup(&source_file, "test", "op").await.unwrap();
+
+        // 崩溃前留下的备份：在磁盘上但不在登记表中
+        let orphan = backup_root.path().join("dir_mover_backups").join("Old_20240101_000000_lost");
+        fs::create_dir_all(orphan.join("sub")).unwrap();
+        fs::write(orphan.join("sub").join("file.bin"), vec![0u8; 300]).unwrap();
+
+        let in_root = |manager: &ErrorRecoveryManager| -> Vec<OrphanedBackup> {
+            manager.orphaned_backup_scan().run().into_iter()
+                .filter(|backup| backup.path.starts_with(backup_root.path()))
+                .collect()
+        };
+        let orphans = in_root(&manager);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].path, orphan);
+        assert_eq!(orphans[0].size, 300);
+        assert!(orphans[0].modified_at.is_some());
+
+        // 只删除孤立备份，已登记的备份和其他路径被拒绝
+        let outside = source_dir.path().to_path_buf();
+        let report = manager.purge_orphaned_backups(&[orphan.clone(), registered.backup_path.clone(), outside.clone()]);
+        assert_eq!(report.cleaned, vec!["Old_20240101_000000_lost".to_string()]);
+        assert_eq!(report.failed.len(), 2);
+        assert!(!orphan.exists());
+        assert!(registered.backup_path.exists() && outside.exists());
+        assert!(in_root(&manager).is_empty());
+
+        let report = manager.purge_orphaned_backups(&[orphan]);
+        assert_eq!(report.already_gone.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_paths_restores_only_requested_subfolder() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let original = source_dir.path().join("app");
+        fs::create_dir_all(original.join("keep/nested")).unwrap();
+        fs::create_dir_all(original.join("other")).unwrap();
+        fs::write(original.join("keep/nested/a.txt"), "原始A").unwrap();
+        fs::write(original.join("other/b.txt"), "原始B").unwrap();
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        let backup = manager.create_backup(&original, "test", "op").await.unwrap();
+
+        // 备份之后两个子目录都被改动
+        fs::remove_dir_all(original.join("keep")).unwrap();
+        fs::write(original.join("other/b.txt"), "已修改B").unwrap();
+
+        for invalid in ["missing", "../app", ""] {
+            assert!(manager.restore_paths(&backup.backup_id, &[invalid.to_string()]).await.is_err(), "{}", invalid);
+        }
+        assert!(!original.join("keep").exists());
+
+        let restored = manager.restore_paths(&backup.backup_id, &["keep".to_string()]).await.unwrap();
+        assert_eq!(restored, vec![original.join("keep")]);
+        assert_eq!(fs::read_to_string(original.join("keep/nested/a.txt")).unwrap(), "原始A");
+        assert_eq!(fs::read_to_string(original.join("other/b.txt")).unwrap(), "已修改B");
+        assert!(manager.list_backups().iter().any(|b| b.backup_id == backup.backup_id && b.is_active));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_restore_paths_swaps_in_copy_and_keeps_data_on_failure() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let original = source_dir.path().join("app");
+        fs::create_dir_all(original.join("keep")).unwrap();
+        fs::write(original.join("keep/a.txt"), "原始A").unwrap();
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        let backup = manager.create_backup(&original, "test", "op").await.unwrap();
+        fs::write(original.join("keep/a.txt"), "已修改A").unwrap();
+        fs::write(original.join("keep/extra.txt"), "新增").unwrap();
+
+        // 复制失败（备份中有无法读取的条目）时，现有数据保持不变且不留下暂存副本
+        let dangling = backup.backup_path.join("keep/dangling");
+        std::os::unix::fs::symlink(backup_root.path().join("missing"), &dangling).unwrap();
+        let staging = original.join(".keep.dirmover-restore");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("stale.txt"), "上次残留").unwrap();
+
+        assert!(manager.restore_paths(&backup.backup_id, &["keep".to_string()]).await.is_err());
+        assert_eq!(fs::read_to_string(original.join("keep/a.txt")).unwrap(), "已修改A");
+        assert!(original.join("keep/extra.txt").exists());
+        assert!(!staging.exists());
+
+        // 成功时整体替换，旧数据和暂存副本都被清理
+        fs::remove_file(&dangling).unwrap();
+        manager.restore_paths(&backup.backup_id, &["keep".to_string()]).await.unwrap();
+        assert_eq!(fs::read_to_string(original.join("keep/a.txt")).unwrap(), "原始A");
+        assert!(!original.join("keep/extra.txt").exists());
+        assert!(!staging.exists());
+        assert!(!original.join(".keep.dirmover-old").exists());
+    }
+
+    /// 创建一个保留时间为 0 的管理器及一个已登记（即刻过期）的备份
+    async fn manager_with_expired_backup(backup_root: &Path, source_dir: &Path) -> (ErrorRecoveryManager, BackupInfo) {
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.to_path_buf()),
+            backup_retention_hours: 0,
+            ..ErrorRecoveryConfig::default()
+        });
+        let source_file = source_dir.join("data.txt");
+        fs::write(&source_file, "data").unwrap();
+        let backup = manager.create_backup(&source_file, "test", "op").await.unwrap();
+        (manager, backup)
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_removes_expired_backup() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let (mut manager, backup) = manager_with_expired_backup(backup_root.path(), source_dir.path()).await;
+
+        let report = manager.cleanup_expired_backups().unwrap();
+        assert_eq!(report.cleaned, vec![backup.backup_id.clone()]);
+        assert!(report.already_gone.is_empty() && report.failed.is_empty());
+        assert!(!backup.backup_path.exists());
+        assert!(manager.list_backups().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_drops_entry_for_externally_removed_backup() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let (mut manager, backup) = manager_with_expired_backup(backup_root.path(), source_dir.path()).await;
+        fs::remove_file(&backup.backup_path).unwrap();
+
+        let report = manager.cleanup_expired_backups().unwrap();
+        assert_eq!(report.already_gone, vec![backup.backup_id.clone()]);
+        assert!(report.cleaned.is_empty() && report.failed.is_empty());
+        assert!(manager.list_backups().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_keeps_entry_when_deletion_fails() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let (mut manager, backup) = manager_with_expired_backup(backup_root.path(), source_dir.path()).await;
+        manager.backup_remover = |_| Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "拒绝访问"));
+
+        let report = manager.cleanup_expired_backups().unwrap();
+        assert!(report.cleaned.is_empty() && report.already_gone.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].backup_id, backup.backup_id);
+        assert_eq!(report.failed[0].backup_path, backup.backup_path);
+        assert!(report.failed[0].error.contains("拒绝访问"));
+
+        // 登记和文件都保留，恢复删除能力后下次清理即可完成
+        assert!(backup.backup_path.exists());
+        assert_eq!(manager.list_backups().len(), 1);
+        manager.backup_remover = remove_backup_path;
+        let report = manager.cleanup_expired_backups().unwrap();
+        assert_eq!(report.cleaned, vec![backup.backup_id]);
+        assert!(manager.list_backups().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_at_total_time_limit() {
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            retry_delay_ms: 10,
+            retry_jitter_ms: 20,
+            enable_partial_rollback: false,
+            max_total_recovery_ms: Some(300),
+            ..ErrorRecoveryConfig::default()
+        });
+        // 每次重试都耗时 100 毫秒并失败
+        manager.retry_executor = Some(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            Err("磁盘繁忙".to_string())
+        });
+
+        let context = RecoveryContext::new("copy".to_string(), PathBuf::from("source"), None, "copy".to_string());
+        let mut state = RecoveryState {
+            operation_id: "op".to_string(),
+            error_type: ErrorType::IoError("磁盘繁忙".to_string()),
+            severity: ErrorSeverity::High,
+            recovery_strategy: RecoveryStrategy::Retry(100),
+            retry_count: 0,
+            is_recovered: false,
+            recovery_message: None,
+            backup_path: None,
+            timestamp: Local::now(),
+        };
+
+        let started = std::time::Instant::now();
+        let result = manager.handle_retry("op", &mut state, 100, &context).await;
+        let elapsed = started.elapsed();
+
+        match result {
+            Err(RecoveryError::MaxRetriesExceeded(message)) => assert!(message.contains("最长恢复时间"), "{}", message),
+            other => panic!("超时后应停止重试: {:?}", other.map(|r| r.message)),
+        }
+        assert!(state.retry_count >= 2 && state.retry_count <= 4, "重试次数: {}", state.retry_count);
+        assert!(elapsed < std::time::Duration::from_millis(1000), "耗时: {:?}", elapsed);
+    }
+
+    /// 以恢复上下文中的源/目标重新复制，模拟恢复时重新执行失败的操作
+    fn recopy_from_context(context: &RecoveryContext) -> Result<(), String> {
+        let target = context.target_path.as_deref().ok_or("缺少目标路径")?;
+        FileOperator::new().copy_path(&context.source_path, target)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn injected_io_error() -> FileOperationError {
+        FileOperationError::IoError(std::io::Error::new(std::io::ErrorKind::Other, "模拟读取错误"))
+    }
+
+    #[tokio::test]
+    async fn test_injected_io_error_recovers_by_retry() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("data.bin");
+        let target = temp_dir.path().join("copy").join("data.bin");
+        fs::write(&source, vec![7u8; 4096]).unwrap();
+
+        let mut operator = FileOperator::new();
+        operator.inject_failure_at(1, injected_io_error);
+        let error = operator.copy_path(&source, &target).unwrap_err();
+        assert!(!target.exists());
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig { retry_delay_ms: 10, ..ErrorRecoveryConfig::default() });
+        manager.retry_executor = Some(recopy_from_context);
+        let context = RecoveryContext::new("copy".to_string(), source.clone(), Some(target.clone()), "copy".to_string());
+        let result = manager.handle_error("retry_op", &error, &context).await.unwrap();
+
+        assert_eq!(result.recovery_type, RecoveryType::Retry);
+        assert!(result.success);
+        assert_eq!(fs::read(&target).unwrap(), vec![7u8; 4096]);
+    }
+
+    #[tokio::test]
+    async fn test_injected_conflict_is_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&source).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(source.join(name), name).unwrap();
+        }
+
+        // 第 2 个文件遇到冲突，按 Fail 策略终止整个复制
+        let mut operator = FileOperator::new();
+        operator.inject_failure_at(2, || FileOperationError::PathAlreadyExists("模拟已存在的文件".to_string()));
+        let error = operator.copy_path(&source, &target).unwrap_err();
+        assert_eq!(fs::read_dir(&target).unwrap().count(), 1);
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig::default());
+        let context = RecoveryContext::new("copy".to_string(), source, Some(target), "copy".to_string());
+        let result = manager.handle_error("skip_op", &error, &context).await.unwrap();
+
+        assert_eq!(result.recovery_type, RecoveryType::Skip);
+        assert_eq!(manager.get_recovery_statistics().skip_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_injected_failure_rolls_back_after_retries() {
+        let backup_root = TempDir::new().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(source.join("config.json"), "v2").unwrap();
+        fs::write(source.join("data.db"), "新数据").unwrap();
+        fs::write(target.join("config.json"), "v1").unwrap();
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            retry_delay_ms: 10,
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        manager.create_backup(&target, "update_op", "update_op").await.unwrap();
+
+        // 第 1 个文件覆盖成功，第 2 个文件复制失败，目标处于半更新状态
+        let mut operator = FileOperator::new();
+        operator.inject_failure_at(2, injected_io_error);
+        let options = CopyOptions { conflict_policy: ConflictPolicy::Overwrite, ..Default::default() };
+        operator.copy_path_with_options(&source.join("config.json"), &target.join("config.json"), &options).unwrap();
+        let error = operator.copy_path_with_options(&source.join("data.db"), &target.join("data.db"), &options).unwrap_err();
+        assert_eq!(fs::read_to_string(target.join("config.json")).unwrap(), "v2");
+
+        // 重试始终失败，耗尽后回滚到操作前的备份
+        manager.retry_executor = Some(|_| Err("仍然无法读取".to_string()));
+        let context = RecoveryContext::new("update_op".to_string(), source, Some(target.clone()), "copy".to_string());
+        let result = manager.handle_error("update_op", &error, &context).await.unwrap();
+
+        assert_eq!(result.recovery_type, RecoveryType::Rollback);
+        assert!(result.success);
+        assert_eq!(fs::read_to_string(target.join("config.json")).unwrap(), "v1");
+        assert!(!target.join("data.db").exists());
+    }
+
+    #[tokio::test]
+    async fn test_partial_rollback_keeps_existing_target_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        for (name, content) in [("config.json", "v2"), ("plugin.dll", "插件"), ("data.db", "新数据")] {
+            fs::write(source.join(name), content).unwrap();
+        }
+        fs::write(target.join("config.json"), "v1").unwrap();
+        fs::write(target.join("user.txt"), "用户数据").unwrap();
+
+        // 前两个文件写入成功（覆盖 config.json、新建 plugin.dll），第 3 个失败
+        let journal = Arc::new(CopyJournal::new(temp_dir.path().join("originals")));
+        let options = CopyOptions {
+            conflict_policy: ConflictPolicy::Overwrite,
+            journal: Some(journal.clone()),
+            ..Default::default()
+        };
+        let mut operator = FileOperator::new();
+        operator.inject_failure_at(3, injected_io_error);
+        let mut error = None;
+        for name in ["config.json", "plugin.dll", "data.db"] {
+            if let Err(e) = operator.copy_path_with_options(&source.join(name), &target.join(name), &options) {
+                error = Some(e);
+            }
+        }
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig { retry_delay_ms: 10, ..ErrorRecoveryConfig::default() });
+        manager.retry_executor = Some(|_| Err("仍然无法读取".to_string()));
+        let mut context = RecoveryContext::new("merge".to_string(), source, Some(target.clone()), "copy".to_string());
+        context.copy_journal = Some(journal);
+        let result = manager.handle_error("merge_op", &error.unwrap(), &context).await.unwrap();
+
+        assert_eq!(result.recovery_type, RecoveryType::Rollback);
+        assert_eq!(result.message_key, Some(MessageKey::RecoveryPartialRollbackSucceeded));
+        assert_eq!(fs::read_to_string(target.join("config.json")).unwrap(), "v1");
+        assert_eq!(fs::read_to_string(target.join("user.txt")).unwrap(), "用户数据");
+        assert!(!target.join("plugin.dll").exists());
+        assert!(!target.join("data.db").exists());
+        assert!(!temp_dir.path().join("originals").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_backup_removes_partial_copy() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        for dir in 0..20 {
+            let sub = source_dir.path().join(format!("dir{}", dir));
+            fs::create_dir_all(&sub).unwrap();
+            for file in 0..100 {
+                fs::write(sub.join(format!("{}.bin", file)), vec![0u8; 4096]).unwrap();
+            }
+        }
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        let control = manager.backup_control();
+
+        // 复制开始后取消，备份在阻塞线程池中进行，运行时仍可调度取消任务
+        let canceller = tokio::spawn({
+            let control = control.clone();
+            async move {
+                while control.progress().copied_files < 10 {
+                    tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                }
+                control.cancel();
+            }
+        });
+
+        let result = manager.create_backup(source_dir.path(), "test", "op").await;
+        canceller.await.unwrap();
+
+        assert!(matches!(result, Err(BackupError::BackupCancelled(_))), "{:?}", result);
+        let progress = control.progress();
+        assert!(!progress.in_progress);
+        assert!(progress.copied_files < 2000, "取消后不应继续复制");
+        assert!(manager.list_backups().is_empty());
+
+        let backup_dir = backup_root.path().join("dir_mover_backups");
+        let leftovers = fs::read_dir(&backup_dir).map(|entries| entries.count()).unwrap_or(0);
+        assert_eq!(leftovers, 0, "不完整的备份应被清理");
+    }
+
+    #[tokio::test]
+    async fn test_backup_progress_is_pushed_to_sink() {
+        let backup_root = TempDir::new().unwrap();
+        let source_dir = TempDir::new().unwrap();
+        let mut tree_size = 0u64;
+        for (index, size) in [1024usize, 2048, 4096, 8192].iter().enumerate() {
+            let sub = source_dir.path().join(format!("dir{}", index));
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(sub.join("data.bin"), vec![0u8; *size]).unwrap();
+            tree_size += *size as u64;
+        }
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig {
+            backup_root: Some(backup_root.path().to_path_buf()),
+            ..ErrorRecoveryConfig::default()
+        });
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        manager.backup_control().set_progress_sender(Some(progress_tx));
+
+        manager.create_backup(source_dir.path(), "test", "op").await.unwrap();
+
+        let mut updates = Vec::new();
+        while let Ok(progress) = progress_rx.try_recv() {
+            updates.push(progress);
+        }
+        assert!(updates.first().unwrap().in_progress);
+        assert!(updates.windows(2).all(|pair| pair[0].copied_bytes <= pair[1].copied_bytes));
+        let last = updates.last().unwrap();
+        assert!(!last.in_progress);
+        assert_eq!(last.copied_files, 4);
+        assert_eq!(last.copied_bytes, tree_size);
+        assert_eq!(last.total_bytes, tree_size);
+        assert_eq!(last.progress, 100.0);
+    }
+
+    #[test]
+    fn test_backup_progress_is_throttled() {
+        let control = BackupControl::default();
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        control.set_progress_sender(Some(progress_tx));
+
+        // 大量小文件在间隔内只推送开始和结束两次
+        control.start(Path::new("source"), 1000);
+        for i in 0..1000 {
+            control.record_file(&PathBuf::from(format!("file_{}", i)), 1);
+        }
+        control.finish();
+
+        let mut updates = Vec::new();
+        while let Ok(progress) = progress_rx.try_recv() {
+            updates.push(progress);
+        }
+        assert!(updates.len() < 10, "推送了 {} 次", updates.len());
+        assert_eq!(updates.last().unwrap().copied_files, 1000);
+        assert!(!updates.last().unwrap().in_progress);
+
+        // 单个文件达到字节阈值时立即推送
+        control.start(Path::new("source"), 2 * BACKUP_PROGRESS_BYTES);
+        control.record_file(Path::new("large.bin"), BACKUP_PROGRESS_BYTES);
+        let mut updates = Vec::new();
+        while let Ok(progress) = progress_rx.try_recv() {
+            updates.push(progress);
+        }
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[1].copied_bytes, BACKUP_PROGRESS_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_create_backup_with_known_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        fs::create_dir_all(source_dir.join("sub")).unwrap();
+        fs::write(source_dir.join("a.txt"), vec![0u8; 1024]).unwrap();
+        fs::write(source_dir.join("sub").join("b.txt"), vec![0u8; 2048]).unwrap();
+
+        let mut manager = ErrorRecoveryManager::new(ErrorRecoveryConfig::default());
+
+        SIZE_WALKS.with(|count| count.set(0));
+        let scanned = manager.create_backup(&source_dir, "test", "op_scan").await.unwrap();
+        assert!(SIZE_WALKS.with(|count| count.get()) > 0);
+
+        SIZE_WALKS.with(|count| count.set(0));
+        let known = manager
+            .create_backup_with_known_size(&source_dir, 3072, "test", "op_known")
+            .await
+            .unwrap();
+        assert_eq!(SIZE_WALKS.with(|count| count.get()), 0, "已知大小时不应遍历目录");
+
+        assert_eq!(scanned.backup_size, 3072);
+        assert_eq!(known.backup_size, scanned.backup_size);
+        assert!(known.backup_path.join("sub").join("b.txt").exists());
+
+        // 已知大小同样受备份大小上限约束
+        let too_large = manager
+            .create_backup_with_known_size(&source_dir, u64::MAX, "test", "op_large")
+            .await;
+        assert!(matches!(too_large, Err(BackupError::BackupTooLarge(_))));
+    }
+
     #[test]
     fn test_recovery_statistics() {
         let config = ErrorRecoveryConfig::default();
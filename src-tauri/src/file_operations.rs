@@ -1,9 +1,11 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::io;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
 use serde::{Serialize, Deserialize};
-use std::time::SystemTime;
-use log::{info, error};
+use std::time::{Duration, SystemTime};
+use log::{info, error, warn};
 
 /// 文件操作结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +14,456 @@ pub struct FileOperationResult {
     pub message: String,
     pub source_path: String,
     pub target_path: Option<String>,
+    // 新增：逐文件的处理动作记录
+    #[serde(default)]
+    pub file_actions: Vec<FileActionRecord>,
+    // 新增：逐文件的处理结果（含失败原因）
+    #[serde(default)]
+    pub file_results: Vec<FileResult>,
+    // 新增：本次实际写入目标的字节数（跳过和续传的文件不计入）
+    #[serde(default)]
+    pub total_size: u64,
+}
+
+/// 目标文件已存在时的冲突处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// 遇到第一个已存在的文件即报错
+    #[default]
+    Fail,
+    /// 覆盖已存在的文件
+    Overwrite,
+    /// 保留已存在的文件，跳过复制
+    Skip,
+    /// 以 `name (1).ext` 形式另存为新文件
+    RenameNew,
+}
+
+/// 单个文件的处理动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileAction {
+    Copied,
+    Overwritten,
+    Skipped,
+    Renamed,
+    /// 上次中断的迁移中已复制完成，续传时跳过
+    Resumed,
+}
+
+/// 单个文件的处理记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileActionRecord {
+    pub source_path: String,
+    /// 实际写入（或被跳过）的目标路径
+    pub target_path: String,
+    pub action: FileAction,
+}
+
+/// 单个文件的处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Succeeded,
+    Skipped,
+    Failed,
+    /// 文件被其他程序占用，重试后仍无法复制
+    Locked,
+}
+
+/// 单个文件的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileResult {
+    pub path: String,
+    pub status: FileStatus,
+    pub error: Option<String>,
+}
+
+impl FileOperationResult {
+    /// 去掉结果中路径与消息的扩展长度前缀
+    fn for_display(mut self) -> Self {
+        self.message = strip_extended_length_prefix(&self.message);
+        self.source_path = strip_extended_length_prefix(&self.source_path);
+        self.target_path = self.target_path.map(|path| strip_extended_length_prefix(&path));
+        for record in &mut self.file_actions {
+            record.source_path = strip_extended_length_prefix(&record.source_path);
+            record.target_path = strip_extended_length_prefix(&record.target_path);
+        }
+        for result in &mut self.file_results {
+            result.path = strip_extended_length_prefix(&result.path);
+            result.error = result.error.take().map(|error| strip_extended_length_prefix(&error));
+        }
+        self
+    }
+}
+
+impl FileResult {
+    fn from_action(path: &Path, action: FileAction) -> Self {
+        Self {
+            path: path.display().to_string(),
+            status: if action == FileAction::Skipped { FileStatus::Skipped } else { FileStatus::Succeeded },
+            error: None,
+        }
+    }
+
+    fn failed(path: &Path, error: &FileOperationError) -> Self {
+        Self {
+            path: path.display().to_string(),
+            status: FileStatus::Failed,
+            error: Some(error.to_string()),
+        }
+    }
+
+    fn locked(path: &Path, error: &io::Error) -> Self {
+        Self {
+            path: path.display().to_string(),
+            status: FileStatus::Locked,
+            error: Some(format!("文件被其他程序占用: {}", error)),
+        }
+    }
+}
+
+/// 复制选项
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    pub conflict_policy: ConflictPolicy,
+    /// 复制后还原修改/访问时间与权限位（Windows 下包括隐藏、只读属性；Unix 下包括完整的模式位，
+    /// 有权限时还包括属主和属组）
+    pub preserve_metadata: bool,
+    /// 断点续传检查点，跳过已完成的文件并记录新完成的文件
+    pub checkpoint: Option<Arc<CopyCheckpoint>>,
+    /// 分块复制的缓冲区大小；设置后以固定大小的缓冲区循环读写并在块之间响应取消，
+    /// 未设置时使用 `fs::copy`
+    pub buffer_size: Option<usize>,
+    /// 允许目标位于源目录内部（原地整理），复制时跳过目标路径及其下的所有条目
+    pub allow_nested_target: bool,
+    /// 不复制的子路径（相对于源目录），等于或位于其下的条目都会跳过，其上层目录结构照常创建
+    pub exclude_subpaths: Vec<PathBuf>,
+    /// 单个文件复制的时限；设置后文件在独立线程上复制，超时即以 `Timeout` 终止整个复制
+    pub file_timeout: Option<Duration>,
+    /// 复制日志，记录新建的条目并在覆盖前保存原文件，用于失败时只撤销本次写入的内容
+    pub journal: Option<Arc<CopyJournal>>,
+}
+
+/// 断点续传检查点：逐行记录已复制并校验过的文件（`大小\t源路径`）
+#[derive(Debug)]
+pub struct CopyCheckpoint {
+    path: PathBuf,
+    completed: HashMap<PathBuf, u64>,
+    writer: Mutex<fs::File>,
+}
+
+impl CopyCheckpoint {
+    /// 打开检查点文件，加载此前已完成的记录并以追加方式继续写入
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut completed = HashMap::new();
+        if let Ok(content) = fs::read_to_string(path) {
+            for line in content.lines() {
+                if let Some((size, source)) = line.split_once('\t') {
+                    if let Ok(size) = size.parse::<u64>() {
+                        completed.insert(PathBuf::from(source), size);
+                    }
+                }
+            }
+        }
+
+        let writer = fs::OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            completed,
+            writer: Mutex::new(writer),
+        })
+    }
+
+    /// 检查点文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 打开时已记录的完成文件数
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// 文件是否已在之前的运行中复制完成，且源和目标大小均与记录一致
+    ///
+    /// 只比较大小、不重新读取内容：记录在文件完整写入后才追加，中断时写了一半的文件不会有记录；
+    /// 但两次运行之间源文件被改写且大小不变时无法发现，这类文件沿用上次复制的内容。
+    fn is_completed(&self, source: &Path, target: &Path) -> bool {
+        let Some(&size) = self.completed.get(source) else {
+            return false;
+        };
+        let source_len = fs::metadata(source).map(|m| m.len()).ok();
+        let target_len = fs::metadata(target).map(|m| m.len()).ok();
+        source_len == Some(size) && target_len == Some(size)
+    }
+
+    /// 追加一条完成记录
+    fn record(&self, source: &Path, size: u64) -> io::Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| io::Error::other("检查点文件锁已损坏"))?;
+        writeln!(writer, "{}\t{}", size, source.display())?;
+        writer.flush()
+    }
+
+    /// 删除检查点文件
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 复制日志：记录一次复制新建的文件和目录，覆盖已有文件前先把原文件保存到 `backup_dir`，
+/// 复制失败时据此只撤销本次写入的内容，目标中原有的其他内容保持不变
+#[derive(Debug)]
+pub struct CopyJournal {
+    backup_dir: PathBuf,
+    entries: Mutex<Vec<JournalEntry>>,
+}
+
+#[derive(Debug)]
+enum JournalEntry {
+    CreatedDir(PathBuf),
+    CreatedFile(PathBuf),
+    Overwritten { target: PathBuf, original: PathBuf },
+}
+
+/// 部分回滚的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartialRollbackReport {
+    /// 删除的新建文件和目录
+    pub removed: Vec<String>,
+    /// 从备份恢复的被覆盖文件
+    pub restored: Vec<String>,
+    /// 无法撤销的条目及原因
+    pub errors: Vec<String>,
+}
+
+impl CopyJournal {
+    /// 创建复制日志，被覆盖的原文件保存在 `backup_dir` 中
+    pub fn new(backup_dir: PathBuf) -> Self {
+        Self { backup_dir, entries: Mutex::new(Vec::new()) }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Vec<JournalEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record_created_dir(&self, path: &Path) {
+        self.entries().push(JournalEntry::CreatedDir(path.to_path_buf()));
+    }
+
+    fn record_created_file(&self, path: &Path) {
+        self.entries().push(JournalEntry::CreatedFile(path.to_path_buf()));
+    }
+
+    /// 覆盖前保存原文件，同一文件只保存第一次覆盖前的内容
+    fn preserve_original(&self, target: &Path) -> io::Result<()> {
+        let mut entries = self.entries();
+        let preserved = entries.iter()
+            .any(|entry| matches!(entry, JournalEntry::Overwritten { target: existing, .. } if existing == target));
+        if preserved {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.backup_dir)?;
+        let original = self.backup_dir.join(entries.len().to_string());
+        fs::copy(target, &original)?;
+        entries.push(JournalEntry::Overwritten { target: target.to_path_buf(), original });
+        Ok(())
+    }
+
+    /// 按与写入相反的顺序撤销：删除新建的文件和目录，用保存的原文件恢复被覆盖的文件
+    ///
+    /// 全部撤销成功后删除保存的原文件；有条目无法撤销时保留它们以便手动恢复。
+    pub fn rollback(&self) -> PartialRollbackReport {
+        let entries = std::mem::take(&mut *self.entries());
+        let mut report = PartialRollbackReport::default();
+
+        for entry in entries.iter().rev() {
+            match entry {
+                JournalEntry::CreatedFile(path) | JournalEntry::CreatedDir(path) => {
+                    let removed = match entry {
+                        JournalEntry::CreatedDir(_) => fs::remove_dir(path),
+                        _ => fs::remove_file(path),
+                    };
+                    match removed {
+                        Ok(()) => report.removed.push(path.display().to_string()),
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                        Err(e) => report.errors.push(format!("{}: {}", path.display(), e)),
+                    }
+                }
+                JournalEntry::Overwritten { target, original } => match fs::copy(original, target) {
+                    Ok(_) => report.restored.push(target.display().to_string()),
+                    Err(e) => report.errors.push(format!("{}: {}", target.display(), e)),
+                },
+            }
+        }
+
+        if report.errors.is_empty() {
+            let _ = self.discard();
+        } else {
+            warn!("部分回滚未完成，被覆盖的原文件保留在 {}", self.backup_dir.display());
+        }
+        info!("部分回滚完成: 删除 {} 项，恢复 {} 个文件，失败 {} 项",
+              report.removed.len(), report.restored.len(), report.errors.len());
+        report
+    }
+
+    /// 删除保存的原文件（复制成功、不再需要回滚时调用）
+    pub fn discard(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.backup_dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 迁移后在源位置创建的链接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkType {
+    /// 符号链接（Windows 上创建目录符号链接需要管理员权限或开发者模式）
+    Symlink,
+    /// 目录联接（仅 Windows，无需提权，只能指向本地目录）
+    Junction,
+}
+
+impl Default for LinkType {
+    fn default() -> Self {
+        if cfg!(target_os = "windows") {
+            LinkType::Junction
+        } else {
+            LinkType::Symlink
+        }
+    }
+}
+
+impl LinkType {
+    /// 另一种链接类型（创建失败时的回退）
+    pub fn fallback(&self) -> LinkType {
+        match self {
+            LinkType::Symlink => LinkType::Junction,
+            LinkType::Junction => LinkType::Symlink,
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            LinkType::Symlink => "符号链接",
+            LinkType::Junction => "目录联接",
+        }
+    }
+}
+
+/// 文件系统类型（用于判断能否创建链接）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilesystemKind {
+    Ntfs,
+    Refs,
+    Fat32,
+    ExFat,
+    Apfs,
+    HfsPlus,
+    Ext,
+    Network,
+    Other(String),
+    Unknown,
+}
+
+impl FilesystemKind {
+    /// 根据系统报告的文件系统名称识别类型
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ntfs" | "ntfs3" => FilesystemKind::Ntfs,
+            "refs" => FilesystemKind::Refs,
+            "fat" | "fat16" | "fat32" | "vfat" | "msdos" => FilesystemKind::Fat32,
+            "exfat" => FilesystemKind::ExFat,
+            "apfs" => FilesystemKind::Apfs,
+            "hfs" | "hfsplus" => FilesystemKind::HfsPlus,
+            "ext2" | "ext3" | "ext4" => FilesystemKind::Ext,
+            "nfs" | "nfs4" | "cifs" | "smbfs" | "smb2" | "smb3" | "afpfs" | "webdav" => FilesystemKind::Network,
+            "" => FilesystemKind::Unknown,
+            other => FilesystemKind::Other(other.to_string()),
+        }
+    }
+
+    /// 该文件系统上能否创建指定类型的链接，`None` 表示无法确定
+    pub fn supports_link(&self, link_type: LinkType) -> Option<bool> {
+        match (self, link_type) {
+            (FilesystemKind::Fat32 | FilesystemKind::ExFat, _) => Some(false),
+            (FilesystemKind::Ntfs | FilesystemKind::Refs, _) => Some(true),
+            (FilesystemKind::Apfs | FilesystemKind::HfsPlus | FilesystemKind::Ext, LinkType::Symlink) => Some(true),
+            (FilesystemKind::Apfs | FilesystemKind::HfsPlus | FilesystemKind::Ext, LinkType::Junction) => Some(false),
+            (FilesystemKind::Network, LinkType::Junction) => Some(false),
+            (FilesystemKind::Network, LinkType::Symlink)
+            | (FilesystemKind::Other(_) | FilesystemKind::Unknown, _) => None,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            FilesystemKind::Ntfs => "NTFS".to_string(),
+            FilesystemKind::Refs => "ReFS".to_string(),
+            FilesystemKind::Fat32 => "FAT32".to_string(),
+            FilesystemKind::ExFat => "exFAT".to_string(),
+            FilesystemKind::Apfs => "APFS".to_string(),
+            FilesystemKind::HfsPlus => "HFS+".to_string(),
+            FilesystemKind::Ext => "ext".to_string(),
+            FilesystemKind::Network => "网络文件系统".to_string(),
+            FilesystemKind::Other(name) => name.clone(),
+            FilesystemKind::Unknown => "未知文件系统".to_string(),
+        }
+    }
+}
+
+/// 查找路径所在的磁盘（挂载点与路径匹配最长者），并用 `read` 读取所需信息
+///
+/// 路径可能尚未创建，取最近的已存在上级目录；找不到对应磁盘时返回 None。
+fn with_containing_disk<T>(path: &Path, read: impl FnOnce(&sysinfo::Disk) -> T) -> Option<T> {
+    use sysinfo::{DiskExt, System, SystemExt};
+
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let resolved = fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+
+    let mut system = System::new();
+    system.refresh_disks_list();
+    system.disks()
+        .iter()
+        .filter(|disk| resolved.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(read)
+}
+
+/// 检测路径所在卷的文件系统类型
+pub fn detect_filesystem(path: &Path) -> FilesystemKind {
+    use sysinfo::DiskExt;
+
+    with_containing_disk(path, |disk| FilesystemKind::from_name(&String::from_utf8_lossy(disk.file_system())))
+        .unwrap_or(FilesystemKind::Unknown)
+}
+
+/// 路径所在磁盘的容量和类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriveStatus {
+    pub total_space: u64,
+    pub available_space: u64,
+    pub is_removable: bool,
+}
+
+/// 查询路径所在磁盘的容量和是否为可移动磁盘（无法确定时返回 None）
+pub fn detect_drive_status(path: &Path) -> Option<DriveStatus> {
+    use sysinfo::DiskExt;
+
+    with_containing_disk(path, |disk| DriveStatus {
+        total_space: disk.total_space(),
+        available_space: disk.available_space(),
+        is_removable: disk.is_removable(),
+    })
 }
 
 /// 文件操作错误
@@ -23,6 +475,8 @@ pub enum FileOperationError {
     PathAlreadyExists(String),
     InvalidPath(String),
     OperationCancelled(String),
+    /// 单个文件的复制超过了设定的时限（如网络驱动器无响应）
+    Timeout(String),
 }
 
 impl From<io::Error> for FileOperationError {
@@ -40,23 +494,126 @@ impl std::fmt::Display for FileOperationError {
             FileOperationError::PathAlreadyExists(path) => write!(f, "路径已存在: {}", path),
             FileOperationError::InvalidPath(path) => write!(f, "无效路径: {}", path),
             FileOperationError::OperationCancelled(msg) => write!(f, "操作已取消: {}", msg),
+            FileOperationError::Timeout(msg) => write!(f, "操作超时: {}", msg),
+        }
+    }
+}
+
+impl FileOperationError {
+    /// 去掉错误信息中路径的扩展长度前缀
+    fn for_display(self) -> Self {
+        match self {
+            FileOperationError::IoError(e) => FileOperationError::IoError(e),
+            FileOperationError::PermissionDenied(path) => FileOperationError::PermissionDenied(strip_extended_length_prefix(&path)),
+            FileOperationError::PathNotFound(path) => FileOperationError::PathNotFound(strip_extended_length_prefix(&path)),
+            FileOperationError::PathAlreadyExists(path) => FileOperationError::PathAlreadyExists(strip_extended_length_prefix(&path)),
+            FileOperationError::InvalidPath(path) => FileOperationError::InvalidPath(strip_extended_length_prefix(&path)),
+            FileOperationError::OperationCancelled(msg) => FileOperationError::OperationCancelled(strip_extended_length_prefix(&msg)),
+            FileOperationError::Timeout(msg) => FileOperationError::Timeout(strip_extended_length_prefix(&msg)),
+        }
+    }
+
+    /// 稳定的错误码，供前端按错误类型分支处理
+    ///
+    /// | 变体 | 错误码 |
+    /// |------|--------|
+    /// | IoError | `IO_ERROR` |
+    /// | PermissionDenied | `PERMISSION_DENIED` |
+    /// | PathNotFound | `PATH_NOT_FOUND` |
+    /// | PathAlreadyExists | `PATH_ALREADY_EXISTS` |
+    /// | InvalidPath | `INVALID_PATH` |
+    /// | OperationCancelled | `OPERATION_CANCELLED` |
+    /// | Timeout | `TIMEOUT` |
+    pub fn code(&self) -> &'static str {
+        match self {
+            FileOperationError::IoError(_) => "IO_ERROR",
+            FileOperationError::PermissionDenied(_) => "PERMISSION_DENIED",
+            FileOperationError::PathNotFound(_) => "PATH_NOT_FOUND",
+            FileOperationError::PathAlreadyExists(_) => "PATH_ALREADY_EXISTS",
+            FileOperationError::InvalidPath(_) => "INVALID_PATH",
+            FileOperationError::OperationCancelled(_) => "OPERATION_CANCELLED",
+            FileOperationError::Timeout(_) => "TIMEOUT",
         }
     }
 }
 
+/// 测试用的失败注入：第 `nth` 次（从 1 开始计数）文件复制返回 `error` 生成的错误，
+/// 用于不依赖系统权限、稳定地触发各种错误恢复路径
+#[cfg(test)]
+pub(crate) struct FailureInjection {
+    nth: usize,
+    count: std::sync::atomic::AtomicUsize,
+    error: fn() -> FileOperationError,
+}
+
+/// 带时限的复制未设置缓冲区大小时使用的分块大小（分块之间才能响应中止）
+const TIMED_COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// 复制超时后等待工作线程中止并删除半成品文件的最长时间
+const TIMED_COPY_ABORT_GRACE: Duration = Duration::from_secs(5);
+
+/// 等待带时限复制结果时检查取消标志的间隔
+const TIMED_COPY_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 交给复制工作线程的单个文件
+struct TimedCopyJob {
+    source: PathBuf,
+    target: PathBuf,
+    buffer_size: usize,
+    reply: std::sync::mpsc::Sender<Result<u64, FileOperationError>>,
+}
+
+/// 带时限复制使用的常驻工作线程：依次复制文件，调用方在每个文件的时限到达时置位 `abort`，
+/// 工作线程在分块之间看到后删除写了一半的文件并返回
+struct TimedCopyWorker {
+    jobs: std::sync::mpsc::Sender<TimedCopyJob>,
+    abort: Arc<std::sync::atomic::AtomicBool>,
+}
+
 /// 文件操作器
 pub struct FileOperator {
     cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    timed_copy_worker: Mutex<Option<TimedCopyWorker>>, // 新增：带时限复制的工作线程（首次使用时创建）
+    #[cfg(test)]
+    copy_delay: Option<Duration>, // 新增：每个文件复制前的人为延迟
+    #[cfg(test)]
+    failure_injection: Option<Arc<FailureInjection>>, // 新增：在第 N 次文件复制时强制失败
 }
 
 impl FileOperator {
     /// 创建新的文件操作器
     pub fn new() -> Self {
+        Self::with_cancel_flag(std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)))
+    }
+
+    /// 使用外部共享的取消标志创建文件操作器
+    pub fn with_cancel_flag(cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>) -> Self {
         Self {
-            cancel_flag: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            cancel_flag,
+            timed_copy_worker: Mutex::new(None),
+            #[cfg(test)]
+            copy_delay: None,
+            #[cfg(test)]
+            failure_injection: None,
         }
     }
 
+    /// 模拟缓慢的磁盘：每个文件复制前等待指定时间
+    #[cfg(test)]
+    pub(crate) fn set_copy_delay(&mut self, delay: Option<Duration>) {
+        self.copy_delay = delay;
+    }
+
+    /// 让第 `nth` 次（从 1 开始计数）文件复制返回 `error` 生成的错误，其余复制照常进行
+    #[cfg(test)]
+    pub(crate) fn inject_failure_at(&mut self, nth: usize, error: fn() -> FileOperationError) {
+        self.failure_injection = Some(Arc::new(FailureInjection {
+            nth,
+            count: std::sync::atomic::AtomicUsize::new(0),
+            error,
+        }));
+    }
+
     /// 取消当前操作
     pub fn cancel_operation(&self) {
         self.cancel_flag.store(true, std::sync::atomic::Ordering::Relaxed);
@@ -79,32 +636,70 @@ impl FileOperator {
             return Err(FileOperationError::InvalidPath("路径包含非法字符".to_string()));
         }
 
-        Ok(())
+        check_path_length(path).map_err(FileOperationError::InvalidPath)
     }
 
     /// 复制文件或目录
     pub fn copy_path(&self, source: &Path, target: &Path) -> Result<FileOperationResult, FileOperationError> {
+        self.copy_path_with_options(source, target, &CopyOptions::default())
+    }
+
+    /// 按指定选项复制文件或目录
+    ///
+    /// 目录会与已存在的目标目录合并，文件冲突按 `options.conflict_policy` 处理。
+    pub fn copy_path_with_options(
+        &self,
+        source: &Path,
+        target: &Path,
+        options: &CopyOptions,
+    ) -> Result<FileOperationResult, FileOperationError> {
         self.validate_path(source)?;
         self.validate_path(target)?;
 
+        // 根路径使用扩展长度前缀，拼接出的子路径即使超过 MAX_PATH 也有效；返回前再去掉前缀
+        let source = &extended_length_path(source);
+        let target = &extended_length_path(target);
+
         if !source.exists() {
             return Err(FileOperationError::PathNotFound(source.display().to_string()));
         }
 
-        if target.exists() {
-            return Err(FileOperationError::PathAlreadyExists(target.display().to_string()));
-        }
-
         if self.is_cancelled() {
             return Err(FileOperationError::OperationCancelled("复制操作已取消".to_string()));
         }
 
+        // 目标位于源目录内部时，只有显式允许才复制，并排除目标子树以免复制自身
+        let nested_target = if source.is_dir() { nested_target_under(source, target) } else { None };
+        if nested_target.is_some() && !options.allow_nested_target {
+            return Err(FileOperationError::InvalidPath(format!(
+                "不能将目录复制到其子目录中: {}", target.display()
+            )));
+        }
+        let excluded: Vec<PathBuf> = nested_target.into_iter()
+            .chain(options.exclude_subpaths.iter().map(|subpath| source.join(subpath)))
+            .collect();
+
         let start_time = SystemTime::now();
         
         let result = if source.is_dir() {
-            self.copy_directory(source, target)
+            self.copy_directory(source, target, options, &excluded)
+                .map(|mut result| {
+                    self.retry_locked_files(source, target, options, &mut result);
+                    result
+                })
         } else {
-            self.copy_file(source, target)
+            let mut result = self.copy_file(source, target, options);
+            for _ in 0..LOCKED_FILE_RETRY_ATTEMPTS {
+                match &result {
+                    Err(FileOperationError::IoError(e)) if is_file_locked_error(e) && !self.is_cancelled() => {
+                        warn!("文件被占用，稍后重试: {}", source.display());
+                        std::thread::sleep(LOCKED_FILE_RETRY_DELAY);
+                        result = self.copy_file(source, target, options);
+                    }
+                    _ => break,
+                }
+            }
+            result
         };
 
         match &result {
@@ -117,31 +712,305 @@ impl FileOperator {
             }
         }
 
-        result
+        result.map(FileOperationResult::for_display).map_err(FileOperationError::for_display)
     }
 
     /// 复制文件
-    fn copy_file(&self, source: &Path, target: &Path) -> Result<FileOperationResult, FileOperationError> {
+    fn copy_file(&self, source: &Path, target: &Path, options: &CopyOptions) -> Result<FileOperationResult, FileOperationError> {
         // 确保目标目录存在
         if let Some(parent) = target.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        // 已在上次中断的迁移中完成的文件直接跳过
+        if let Some(checkpoint) = &options.checkpoint {
+            if checkpoint.is_completed(source, target) {
+                return Ok(FileOperationResult {
+                    success: true,
+                    message: "文件已在上次迁移中完成，已跳过".to_string(),
+                    source_path: source.display().to_string(),
+                    target_path: Some(target.display().to_string()),
+                    file_actions: vec![FileActionRecord {
+                        source_path: source.display().to_string(),
+                        target_path: target.display().to_string(),
+                        action: FileAction::Resumed,
+                    }],
+                    file_results: vec![FileResult::from_action(source, FileAction::Resumed)],
+                    total_size: 0,
+                });
+            }
+        }
+
+        // 处理目标文件冲突：续传（非合并）时检查点之外的已有目标文件是上次中断时写了一半的副本，
+        // 直接覆盖，不按冲突策略处理
+        let resuming_partial = options.checkpoint.is_some() && options.journal.is_none();
+        let (actual_target, action) = if target.exists() {
+            match options.conflict_policy {
+                _ if resuming_partial => (target.to_path_buf(), FileAction::Overwritten),
+                ConflictPolicy::Fail => {
+                    return Err(FileOperationError::PathAlreadyExists(target.display().to_string()));
+                }
+                ConflictPolicy::Skip => (target.to_path_buf(), FileAction::Skipped),
+                ConflictPolicy::Overwrite => (target.to_path_buf(), FileAction::Overwritten),
+                ConflictPolicy::RenameNew => (next_available_path(target), FileAction::Renamed),
+            }
+        } else {
+            (target.to_path_buf(), FileAction::Copied)
+        };
+
         // 复制文件
-        fs::copy(source, target)?;
+        let (message, total_size) = if action == FileAction::Skipped {
+            ("目标文件已存在，已跳过".to_string(), 0)
+        } else {
+            if let Some(journal) = &options.journal {
+                if action == FileAction::Overwritten {
+                    journal.preserve_original(&actual_target)?;
+                } else {
+                    journal.record_created_file(&actual_target);
+                }
+            }
+            let copied_size = match options.file_timeout {
+                Some(timeout) => self.copy_file_contents_with_timeout(source, &actual_target, options.buffer_size, timeout)?,
+                None => self.copy_file_contents(source, &actual_target, options.buffer_size)?,
+            };
+            if options.preserve_metadata {
+                self.copy_metadata(source, &actual_target)?;
+            }
+            if let Some(checkpoint) = &options.checkpoint {
+                if actual_target == target {
+                    checkpoint.record(source, copied_size)?;
+                }
+            }
+            ("文件复制成功".to_string(), copied_size)
+        };
 
         Ok(FileOperationResult {
             success: true,
-            message: "文件复制成功".to_string(),
+            message,
             source_path: source.display().to_string(),
-            target_path: Some(target.display().to_string()),
+            target_path: Some(actual_target.display().to_string()),
+            file_actions: vec![FileActionRecord {
+                source_path: source.display().to_string(),
+                target_path: actual_target.display().to_string(),
+                action,
+            }],
+            file_results: vec![FileResult::from_action(source, action)],
+            total_size,
         })
     }
 
-    /// 复制目录
-    fn copy_directory(&self, source: &Path, target: &Path) -> Result<FileOperationResult, FileOperationError> {
+    /// 重试目录复制中因被占用而跳过的文件（等待片刻后按原相对路径重新复制），
+    /// 重试后仍被占用的文件保留 Locked 状态，整体复制视为未完成
+    fn retry_locked_files(&self, source: &Path, target: &Path, options: &CopyOptions, result: &mut FileOperationResult) {
+        let locked_count = |result: &FileOperationResult| {
+            result.file_results.iter().filter(|r| r.status == FileStatus::Locked).count()
+        };
+        if locked_count(result) == 0 {
+            return;
+        }
+
+        for attempt in 1..=LOCKED_FILE_RETRY_ATTEMPTS {
+            if locked_count(result) == 0 || self.is_cancelled() {
+                break;
+            }
+            std::thread::sleep(LOCKED_FILE_RETRY_DELAY);
+            info!("重试被占用的文件 (第 {} 次): {} 个", attempt, locked_count(result));
+
+            for index in 0..result.file_results.len() {
+                if result.file_results[index].status != FileStatus::Locked {
+                    continue;
+                }
+                let entry_path = PathBuf::from(&result.file_results[index].path);
+                let target_entry_path = match entry_path.strip_prefix(source) {
+                    Ok(relative) => target.join(relative),
+                    Err(_) => continue,
+                };
+
+                match self.copy_file(&entry_path, &target_entry_path, options) {
+                    Ok(retried) => {
+                        result.total_size += retried.total_size;
+                        result.file_actions.extend(retried.file_actions);
+                        if let Some(file_result) = retried.file_results.into_iter().next() {
+                            result.file_results[index] = file_result;
+                        }
+                    }
+                    Err(FileOperationError::IoError(e)) if is_file_locked_error(&e) => {
+                        result.file_results[index] = FileResult::locked(&entry_path, &e);
+                    }
+                    Err(e) => {
+                        result.file_results[index] = FileResult::failed(&entry_path, &e);
+                    }
+                }
+            }
+        }
+
+        let failed_count = result.file_results.iter().filter(|r| r.status == FileStatus::Failed).count();
+        let still_locked = locked_count(result);
+        result.success = failed_count == 0 && still_locked == 0;
+        result.message = if result.success {
+            "目录复制成功 (被占用的文件已在重试后完成)".to_string()
+        } else {
+            format!("目录复制部分失败 (失败: {}, 仍被占用: {})", failed_count, still_locked)
+        };
+        if still_locked > 0 {
+            warn!("{} 个文件在重试后仍被其他程序占用", still_locked);
+        }
+    }
+
+    /// 复制文件内容，设置了缓冲区大小时分块复制，否则使用 `fs::copy`
+    fn copy_file_contents(&self, source: &Path, target: &Path, buffer_size: Option<usize>) -> Result<u64, FileOperationError> {
+        #[cfg(test)]
+        if let Some(injection) = &self.failure_injection {
+            if injection.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1 == injection.nth {
+                return Err((injection.error)());
+            }
+        }
+        #[cfg(test)]
+        if let Some(delay) = self.copy_delay {
+            // 模拟的慢速磁盘同样可以被取消，便于测试超时后的清理
+            let deadline = std::time::Instant::now() + delay;
+            while std::time::Instant::now() < deadline && !self.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+        match buffer_size {
+            Some(buffer_size) => self.copy_file_chunked(source, target, buffer_size),
+            None => Ok(fs::copy(source, target)?),
+        }
+    }
+
+    /// 启动带时限复制的工作线程；线程使用独立的中止标志，任务队列关闭后退出
+    fn spawn_timed_copy_worker(&self) -> TimedCopyWorker {
+        let abort = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let operator = FileOperator {
+            cancel_flag: abort.clone(),
+            timed_copy_worker: Mutex::new(None),
+            #[cfg(test)]
+            copy_delay: self.copy_delay,
+            #[cfg(test)]
+            failure_injection: self.failure_injection.clone(),
+        };
+
+        let (jobs, receiver) = std::sync::mpsc::channel::<TimedCopyJob>();
+        std::thread::spawn(move || {
+            for job in receiver {
+                let result = operator.copy_file_contents(&job.source, &job.target, Some(job.buffer_size));
+                let _ = job.reply.send(result);
+            }
+        });
+        TimedCopyWorker { jobs, abort }
+    }
+
+    /// 在工作线程上复制文件内容，超过 `timeout` 仍未完成时返回 `Timeout`
+    ///
+    /// 所有文件共用一个工作线程，每个文件单独计时。超时或取消时通知工作线程中止，并等待它删除
+    /// 写了一半的文件后再返回；阻塞在无响应设备上的系统调用无法中断，等待超过
+    /// `TIMED_COPY_ABORT_GRACE` 时放弃该线程（之后的复制使用新线程），半成品文件可能残留。
+    fn copy_file_contents_with_timeout(
+        &self,
+        source: &Path,
+        target: &Path,
+        buffer_size: Option<usize>,
+        timeout: Duration,
+    ) -> Result<u64, FileOperationError> {
+        let mut worker = self.timed_copy_worker.lock().unwrap_or_else(|e| e.into_inner());
+        let active = worker.get_or_insert_with(|| self.spawn_timed_copy_worker());
+        active.abort.store(false, std::sync::atomic::Ordering::SeqCst);
+
+        let (reply, receiver) = std::sync::mpsc::channel();
+        let job = TimedCopyJob {
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+            buffer_size: buffer_size.unwrap_or(TIMED_COPY_BUFFER_SIZE),
+            reply,
+        };
+        if active.jobs.send(job).is_err() {
+            *worker = None;
+            return Err(FileOperationError::IoError(io::Error::other(format!("复制线程异常退出: {}", source.display()))));
+        }
+
+        let deadline = std::time::Instant::now() + timeout;
+        let outcome = loop {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() || self.is_cancelled() {
+                break None;
+            }
+            match receiver.recv_timeout(remaining.min(TIMED_COPY_POLL_INTERVAL)) {
+                Ok(result) => break Some(result),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    *worker = None;
+                    return Err(FileOperationError::IoError(io::Error::other(format!("复制线程异常退出: {}", source.display()))));
+                }
+            }
+        };
+        if let Some(result) = outcome {
+            return result;
+        }
+
+        // 超时或取消：让工作线程在下一个分块处中止并删除半成品文件
+        active.abort.store(true, std::sync::atomic::Ordering::SeqCst);
+        let stopped = receiver.recv_timeout(TIMED_COPY_ABORT_GRACE).is_ok();
+        if !stopped {
+            error!("复制线程未能及时中止，半成品文件可能残留: {}", target.display());
+            *worker = None;
+        }
+
+        if self.is_cancelled() {
+            return Err(FileOperationError::OperationCancelled(format!("复制已取消: {}", source.display())));
+        }
+        warn!("复制文件超时 ({:?}): {}", timeout, source.display());
+        let detail = if stopped { String::new() } else { "，复制线程仍未退出".to_string() };
+        Err(FileOperationError::Timeout(format!("复制 {} 超过 {} 秒{}", source.display(), timeout.as_secs_f64(), detail)))
+    }
+
+    /// 以固定大小的缓冲区分块复制文件，内存占用与文件大小无关
+    ///
+    /// 每个分块之间检查取消标志，取消时删除写了一半的目标文件。
+    fn copy_file_chunked(&self, source: &Path, target: &Path, buffer_size: usize) -> Result<u64, FileOperationError> {
+        let mut reader = fs::File::open(source)?;
+        let mut writer = fs::File::create(target)?;
+        let mut buffer = vec![0u8; buffer_size.max(1)];
+        let mut copied: u64 = 0;
+
+        loop {
+            if self.is_cancelled() {
+                drop(writer);
+                let _ = fs::remove_file(target);
+                return Err(FileOperationError::OperationCancelled(format!("复制已取消: {}", source.display())));
+            }
+
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            writer.write_all(&buffer[..read])?;
+            copied += read as u64;
+        }
+
+        writer.flush()?;
+        // 与 fs::copy 一致，复制权限位
+        fs::set_permissions(target, reader.metadata()?.permissions())?;
+
+        Ok(copied)
+    }
+
+    /// 复制目录，跳过等于或位于 `excluded` 中任一路径下的条目
+    fn copy_directory(
+        &self,
+        source: &Path,
+        target: &Path,
+        options: &CopyOptions,
+        excluded: &[PathBuf],
+    ) -> Result<FileOperationResult, FileOperationError> {
         // 创建目标目录
+        let created = !target.exists();
         fs::create_dir_all(target)?;
+        if let (true, Some(journal)) = (created, &options.journal) {
+            journal.record_created_dir(target);
+        }
 
         // 读取源目录
         let entries = fs::read_dir(source)
@@ -149,6 +1018,9 @@ impl FileOperator {
 
         let mut copied_files = 0;
         let mut copied_dirs = 0;
+        let mut file_actions = Vec::new();
+        let mut file_results = Vec::new();
+        let mut total_size = 0;
 
         for entry in entries {
             if self.is_cancelled() {
@@ -160,28 +1032,179 @@ impl FileOperator {
             let entry_name = entry.file_name();
             let target_entry_path = target.join(&entry_name);
 
-            if entry_path.is_dir() {
+            if excluded.iter().any(|excluded| entry_path.starts_with(excluded)) {
+                info!("跳过排除的路径: {}", entry_path.display());
+                continue;
+            }
+
+            let entry_result = if entry_path.is_dir() {
                 // 递归复制子目录
-                self.copy_directory(&entry_path, &target_entry_path)?;
-                copied_dirs += 1;
+                self.copy_directory(&entry_path, &target_entry_path, options, excluded)
             } else {
                 // 复制文件
-                self.copy_file(&entry_path, &target_entry_path)?;
-                copied_files += 1;
+                self.copy_file(&entry_path, &target_entry_path, options)
+            };
+
+            match entry_result {
+                Ok(result) => {
+                    total_size += result.total_size;
+                    file_actions.extend(result.file_actions);
+                    file_results.extend(result.file_results);
+                    if entry_path.is_dir() {
+                        copied_dirs += 1;
+                    } else {
+                        copied_files += 1;
+                    }
+                }
+                // 取消、冲突（Fail 策略）和超时终止整个复制
+                Err(e @ FileOperationError::OperationCancelled(_))
+                | Err(e @ FileOperationError::PathAlreadyExists(_))
+                | Err(e @ FileOperationError::Timeout(_)) => return Err(e),
+                // 被其他程序占用的文件先记录下来，整个目录复制完成后再重试
+                Err(FileOperationError::IoError(e)) if is_file_locked_error(&e) => {
+                    warn!("文件被占用，稍后重试: {} ({})", entry_path.display(), e);
+                    file_results.push(FileResult::locked(&entry_path, &e));
+                }
+                Err(e) => {
+                    warn!("复制失败，继续处理其余条目: {} ({})", entry_path.display(), e);
+                    file_results.push(FileResult::failed(&entry_path, &e));
+                }
             }
         }
 
+        // 目录内容写入会改变目录时间，因此在子项复制完成后再还原
+        if options.preserve_metadata {
+            self.copy_metadata(source, target)?;
+        }
+
+        let failed_count = file_results.iter()
+            .filter(|r| matches!(r.status, FileStatus::Failed | FileStatus::Locked))
+            .count();
+        let message = if failed_count == 0 {
+            format!("目录复制成功 (文件: {}, 目录: {})", copied_files, copied_dirs)
+        } else {
+            format!("目录复制部分失败 (文件: {}, 目录: {}, 失败: {})", copied_files, copied_dirs, failed_count)
+        };
+
         Ok(FileOperationResult {
-            success: true,
-            message: format!("目录复制成功 (文件: {}, 目录: {})", copied_files, copied_dirs),
+            success: failed_count == 0,
+            message,
             source_path: source.display().to_string(),
             target_path: Some(target.display().to_string()),
+            file_actions,
+            file_results,
+            total_size,
+        })
+    }
+
+    /// 将源路径的时间戳与权限复制到目标路径
+    fn copy_metadata(&self, source: &Path, target: &Path) -> Result<(), FileOperationError> {
+        let metadata = fs::metadata(source)?;
+
+        let accessed = filetime::FileTime::from_last_access_time(&metadata);
+        let modified = filetime::FileTime::from_last_modification_time(&metadata);
+        filetime::set_file_times(target, accessed, modified)?;
+
+        // 属主在权限之前设置：修改属主会清除 setuid/setgid 位
+        #[cfg(unix)]
+        Self::copy_ownership(&metadata, target)?;
+
+        // 权限放在最后设置，避免只读属性影响前面的写入
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{MetadataExt, PermissionsExt};
+            fs::set_permissions(target, fs::Permissions::from_mode(metadata.mode() & 0o7777))?;
+        }
+        #[cfg(not(unix))]
+        fs::set_permissions(target, metadata.permissions())?;
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::ffi::OsStrExt;
+            use std::os::windows::fs::MetadataExt;
+            use windows_sys::Win32::Storage::FileSystem::{
+                SetFileAttributesW, FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_HIDDEN,
+                FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_SYSTEM,
+            };
+
+            let preserved = FILE_ATTRIBUTE_READONLY | FILE_ATTRIBUTE_HIDDEN
+                | FILE_ATTRIBUTE_SYSTEM | FILE_ATTRIBUTE_ARCHIVE;
+            let target_metadata = fs::metadata(target)?;
+            let attributes = (target_metadata.file_attributes() & !preserved)
+                | (metadata.file_attributes() & preserved);
+
+            let wide_path: Vec<u16> = target.as_os_str().encode_wide().chain(Some(0)).collect();
+            if unsafe { SetFileAttributesW(wide_path.as_ptr(), attributes) } == 0 {
+                return Err(FileOperationError::IoError(io::Error::last_os_error()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 将源的属主和属组复制到目标，仅 root 等有权限的身份能成功；无权限时跳过并记录警告
+    #[cfg(unix)]
+    fn copy_ownership(metadata: &fs::Metadata, target: &Path) -> Result<(), FileOperationError> {
+        use std::os::unix::fs::MetadataExt;
+
+        let target_metadata = fs::metadata(target)?;
+        if target_metadata.uid() == metadata.uid() && target_metadata.gid() == metadata.gid() {
+            return Ok(());
+        }
+
+        match std::os::unix::fs::chown(target, Some(metadata.uid()), Some(metadata.gid())) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                warn!("无权限保留属主，已跳过 {} (uid: {}, gid: {}): {}",
+                      target.display(), metadata.uid(), metadata.gid(), e);
+                Ok(())
+            }
+            Err(e) => Err(FileOperationError::IoError(e)),
+        }
+    }
+
+    /// 删除文件或目录，`use_trash` 为 true 时移到系统回收站而不是永久删除
+    pub fn delete_path_with_options(&self, path: &Path, use_trash: bool) -> Result<FileOperationResult, FileOperationError> {
+        if use_trash {
+            self.move_to_trash(path)
+        } else {
+            self.delete_path(path)
+        }
+    }
+
+    /// 将文件或目录移到系统回收站（可从回收站还原）
+    pub fn move_to_trash(&self, path: &Path) -> Result<FileOperationResult, FileOperationError> {
+        self.validate_path(path)?;
+
+        if fs::symlink_metadata(path).is_err() {
+            return Err(FileOperationError::PathNotFound(path.display().to_string()));
+        }
+
+        if self.is_cancelled() {
+            return Err(FileOperationError::OperationCancelled("删除操作已取消".to_string()));
+        }
+
+        trash::delete(path).map_err(|e| {
+            error!("移到回收站失败: {} (错误: {})", path.display(), e);
+            FileOperationError::IoError(io::Error::new(io::ErrorKind::Other, format!("移到回收站失败: {}", e)))
+        })?;
+        info!("已移到回收站: {}", path.display());
+
+        Ok(FileOperationResult {
+            success: true,
+            message: "已移到回收站".to_string(),
+            source_path: path.display().to_string(),
+            target_path: None,
+            file_actions: Vec::new(),
+            file_results: Vec::new(),
+            total_size: 0,
         })
     }
 
     /// 删除文件或目录
     pub fn delete_path(&self, path: &Path) -> Result<FileOperationResult, FileOperationError> {
         self.validate_path(path)?;
+        let path = &extended_length_path(path);
 
         if !path.exists() {
             return Err(FileOperationError::PathNotFound(path.display().to_string()));
@@ -209,21 +1232,39 @@ impl FileOperator {
             }
         }
 
-        result
+        result.map(FileOperationResult::for_display).map_err(FileOperationError::for_display)
     }
 
     /// 删除文件
     fn delete_file(&self, path: &Path) -> Result<FileOperationResult, FileOperationError> {
-        fs::remove_file(path)?;
+        self.remove_with_retry(path, |path| fs::remove_file(path))?;
         
         Ok(FileOperationResult {
             success: true,
             message: "文件删除成功".to_string(),
             source_path: path.display().to_string(),
             target_path: None,
+            file_actions: Vec::new(),
+            file_results: Vec::new(),
+            total_size: 0,
         })
     }
 
+    /// 删除单个文件或空目录，被其他程序占用时等待片刻后重试（与复制时的重试次数和间隔相同）
+    fn remove_with_retry(&self, path: &Path, remove: impl Fn(&Path) -> io::Result<()>) -> io::Result<()> {
+        let mut attempts = 0;
+        loop {
+            match remove(path) {
+                Err(e) if is_file_locked_error(&e) && attempts < LOCKED_FILE_RETRY_ATTEMPTS && !self.is_cancelled() => {
+                    attempts += 1;
+                    warn!("文件被占用，稍后重试删除 (第 {} 次): {}", attempts, path.display());
+                    std::thread::sleep(LOCKED_FILE_RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// 删除目录
     fn delete_directory(&self, path: &Path) -> Result<FileOperationResult, FileOperationError> {
         let mut deleted_files = 0;
@@ -233,7 +1274,7 @@ impl FileOperator {
         self.delete_directory_contents(path, &mut deleted_files, &mut deleted_dirs)?;
 
         // 删除目录本身
-        fs::remove_dir(path)?;
+        self.remove_with_retry(path, |path| fs::remove_dir(path))?;
 
         deleted_dirs += 1;
 
@@ -242,6 +1283,9 @@ impl FileOperator {
             message: format!("目录删除成功 (文件: {}, 目录: {})", deleted_files, deleted_dirs),
             source_path: path.display().to_string(),
             target_path: None,
+            file_actions: Vec::new(),
+            file_results: Vec::new(),
+            total_size: 0,
         })
     }
 
@@ -266,10 +1310,10 @@ impl FileOperator {
             if entry_path.is_dir() {
                 // 递归删除子目录
                 self.delete_directory_contents(&entry_path, deleted_files, deleted_dirs)?;
-                fs::remove_dir(&entry_path)?;
+                self.remove_with_retry(&entry_path, |path| fs::remove_dir(path))?;
                 *deleted_dirs += 1;
             } else {
-                fs::remove_file(&entry_path)?;
+                self.remove_with_retry(&entry_path, |path| fs::remove_file(path))?;
                 *deleted_files += 1;
             }
         }
@@ -277,9 +1321,19 @@ impl FileOperator {
         Ok(())
     }
 
-    /// 创建符号链接（Windows）
-    #[cfg(target_os = "windows")]
+    /// 创建符号链接
     pub fn create_symlink(&self, target: &Path, link_path: &Path) -> Result<FileOperationResult, FileOperationError> {
+        self.create_link(target, link_path, LinkType::Symlink)
+            .map(|(result, _)| result)
+    }
+
+    /// 按指定类型创建链接，失败时回退到另一种链接类型，同时返回实际创建的链接类型
+    pub fn create_link(
+        &self,
+        target: &Path,
+        link_path: &Path,
+        link_type: LinkType,
+    ) -> Result<(FileOperationResult, LinkType), FileOperationError> {
         self.validate_path(target)?;
         self.validate_path(link_path)?;
 
@@ -300,80 +1354,94 @@ impl FileOperator {
             fs::create_dir_all(parent)?;
         }
 
-        // 使用 Windows API 创建符号链接
-        use std::os::windows::fs::symlink_dir;
-        
-        let result = if target.is_dir() {
-            symlink_dir(target, link_path)
-        } else {
-            std::os::windows::fs::symlink_file(target, link_path)
+        // 目录联接只能指向目录，文件始终使用符号链接
+        let preferred = if target.is_dir() { link_type } else { LinkType::Symlink };
+        let fallback = match preferred {
+            LinkType::Symlink if !target.is_dir() => None,
+            _ => Some(preferred.fallback()),
         };
 
-        result.map_err(|e| FileOperationError::IoError(e))?;
+        let created = match create_link_of_type(target, link_path, preferred) {
+            Ok(()) => preferred,
+            Err(e) => match fallback {
+                Some(fallback) => {
+                    info!("创建{}失败: {}，回退为{}", preferred.description(), e, fallback.description());
+                    create_link_of_type(target, link_path, fallback)?;
+                    fallback
+                }
+                None => return Err(FileOperationError::IoError(e)),
+            },
+        };
 
-        Ok(FileOperationResult {
-            success: true,
-            message: "符号链接创建成功".to_string(),
-            source_path: target.display().to_string(),
-            target_path: Some(link_path.display().to_string()),
-        })
+        Ok((
+            FileOperationResult {
+                success: true,
+                message: format!("{}创建成功", created.description()),
+                source_path: target.display().to_string(),
+                target_path: Some(link_path.display().to_string()),
+                file_actions: Vec::new(),
+                file_results: Vec::new(),
+                total_size: 0,
+            },
+            created,
+        ))
     }
 
-    /// 创建符号链接（Unix/Linux/macOS）
-    #[cfg(not(target_os = "windows"))]
-    pub fn create_symlink(&self, target: &Path, link_path: &Path) -> Result<FileOperationResult, FileOperationError> {
-        self.validate_path(target)?;
-        self.validate_path(link_path)?;
-
-        if !target.exists() {
-            return Err(FileOperationError::PathNotFound(target.display().to_string()));
-        }
-
-        if link_path.exists() {
-            return Err(FileOperationError::PathAlreadyExists(link_path.display().to_string()));
-        }
-
-        if self.is_cancelled() {
-            return Err(FileOperationError::OperationCancelled("创建链接操作已取消".to_string()));
-        }
-
-        // 确保链接路径的父目录存在
-        if let Some(parent) = link_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        // 使用标准库创建符号链接
-        std::os::unix::fs::symlink(target, link_path)?;
-
-        Ok(FileOperationResult {
-            success: true,
-            message: "符号链接创建成功".to_string(),
-            source_path: target.display().to_string(),
-            target_path: Some(link_path.display().to_string()),
-        })
+    /// 验证路径是否可以进行迁移，返回（是否通过, 说明, 修改建议）
+    pub fn validate_migration_path(&self, source: &Path, target: &Path) -> Result<(bool, String, Vec<String>), FileOperationError> {
+        self.validate_migration_path_with(source, target, false, false)
     }
 
-    /// 验证路径是否可以进行迁移
-    pub fn validate_migration_path(&self, source: &Path, target: &Path) -> Result<(bool, String), FileOperationError> {
+    /// 验证路径是否可以进行迁移，`allow_existing_target` 为 true 时允许目标已存在（如续传中断的迁移），
+    /// `allow_nested_target` 为 true 时允许目标位于源目录内部（原地整理，复制时排除目标子树）
+    pub fn validate_migration_path_with(
+        &self,
+        source: &Path,
+        target: &Path,
+        allow_existing_target: bool,
+        allow_nested_target: bool,
+    ) -> Result<(bool, String, Vec<String>), FileOperationError> {
         self.validate_path(source)?;
         self.validate_path(target)?;
 
         // 检查源路径是否存在
         if !source.exists() {
-            return Ok((false, "源路径不存在".to_string()));
+            let suggestions = nearest_existing_ancestor(source)
+                .map(|ancestor| vec![format!("使用最近的现有上级目录: {}", ancestor.display())])
+                .unwrap_or_default();
+            return Ok((false, "源路径不存在".to_string(), suggestions));
+        }
+
+        // 检查目标名称是否为系统保留名称（保留名称在 Windows 上总是“存在”，需先于存在性检查）
+        if let Some(name) = target.file_name().map(|n| n.to_string_lossy().to_string()) {
+            if is_reserved_name(&name) {
+                let alternative = next_available_path(&target.with_file_name(format!("{}_data", name)));
+                return Ok((
+                    false,
+                    format!("目标路径使用了系统保留名称: {}", name),
+                    vec![format!("改用非保留名称: {}", alternative.display())],
+                ));
+            }
         }
 
         // 检查目标路径是否已存在
-        if target.exists() {
-            return Ok((false, "目标路径已存在".to_string()));
+        if target.exists() && !allow_existing_target {
+            return Ok((
+                false,
+                "目标路径已存在".to_string(),
+                vec![format!("改用未被占用的目标路径: {}", next_available_path(target).display())],
+            ));
         }
 
-        // 检查源路径是否是目标路径的子路径（避免循环复制）
+        // 检查目标路径是否位于源路径内部（避免循环复制）
         if let Ok(source_canonical) = fs::canonicalize(source) {
-            if let Ok(target_canonical) = fs::canonicalize(target.parent().unwrap_or(target)) {
-                if source_canonical.starts_with(&target_canonical) {
-                    return Ok((false, "不能将目录迁移到其子目录中".to_string()));
+            if !allow_nested_target && resolve_with_existing_ancestor(target).starts_with(&source_canonical) {
+                let mut suggestions = Vec::new();
+                if let (Some(parent), Some(name)) = (source.parent(), source.file_name()) {
+                    let sibling = next_available_path(&parent.join(format!("{}_migrated", name.to_string_lossy())));
+                    suggestions.push(format!("将目标放在源目录之外，例如: {}", sibling.display()));
                 }
+                return Ok((false, "不能将目录迁移到其子目录中".to_string(), suggestions));
             }
         }
 
@@ -384,13 +1452,20 @@ impl FileOperator {
                     // 这里应该检查可用空间，但fs::metadata不提供这些信息
                     // 在实际应用中，可能需要使用系统特定的API
                     if source_size > 1024 * 1024 * 1024 * 100 { // 100GB
-                        return Ok((false, "源目录过大，可能需要更多磁盘空间".to_string()));
+                        return Ok((
+                            false,
+                            "源目录过大，可能需要更多磁盘空间".to_string(),
+                            vec![
+                                "选择剩余空间更大的目标磁盘".to_string(),
+                                "分批迁移源目录下的子目录".to_string(),
+                            ],
+                        ));
                     }
                 }
             }
         }
 
-        Ok((true, "路径验证通过".to_string()))
+        Ok((true, "路径验证通过".to_string(), Vec::new()))
     }
     /// 估计目录大小
     fn estimate_directory_size(&self, path: &Path) -> Result<u64, FileOperationError> {
@@ -420,6 +1495,285 @@ impl FileOperator {
     }
 }
 
+/// Windows 系统保留的设备名称
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 判断名称是否为系统保留名称（忽略大小写和扩展名，如 `nul.txt`）
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// 查找路径最近的已存在上级目录
+fn nearest_existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+        .map(Path::to_path_buf)
+}
+
+/// 被占用文件的重试次数与每次重试前的等待时间
+const LOCKED_FILE_RETRY_ATTEMPTS: u32 = 3;
+const LOCKED_FILE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// 判断IO错误是否为文件被其他程序占用
+///
+/// Windows: ERROR_SHARING_VIOLATION (32) / ERROR_LOCK_VIOLATION (33)；其他平台不存在强制共享锁
+pub(crate) fn is_file_locked_error(error: &io::Error) -> bool {
+    #[cfg(target_os = "windows")]
+    const LOCKED_CODES: &[i32] = &[32, 33];
+    #[cfg(not(target_os = "windows"))]
+    const LOCKED_CODES: &[i32] = &[];
+
+    error.raw_os_error()
+        .map(|code| LOCKED_CODES.contains(&code))
+        .unwrap_or(false)
+}
+
+/// 传统 Win32 API 的路径长度上限（MAX_PATH）
+pub(crate) const MAX_PATH: usize = 260;
+/// 单个路径组件（文件或目录名）的长度上限
+const MAX_COMPONENT_LEN: usize = 255;
+/// 使用 `\\?\` 扩展长度前缀后的路径长度上限
+const MAX_EXTENDED_PATH: usize = 32_767;
+
+/// 检查路径长度是否真正无效：单个名称超过255字符或整体超过扩展长度上限。
+/// 仅超过 MAX_PATH 的路径可以通过扩展长度前缀处理，不视为错误。
+pub(crate) fn check_path_length(path: &Path) -> Result<(), String> {
+    if path.to_string_lossy().chars().count() > MAX_EXTENDED_PATH {
+        return Err(format!("路径过长（超过{}字符）", MAX_EXTENDED_PATH));
+    }
+    for component in path.components() {
+        if let std::path::Component::Normal(name) = component {
+            if name.to_string_lossy().chars().count() > MAX_COMPONENT_LEN {
+                return Err(format!("路径中的名称过长（超过{}字符）: {}", MAX_COMPONENT_LEN, name.to_string_lossy()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 为 Windows 路径加上 `\\?\` 扩展长度前缀
+///
+/// 复制和删除的根路径总是加前缀：根路径本身不长，拼接出的子路径也可能超过 MAX_PATH。
+#[cfg(target_os = "windows")]
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    if path.as_os_str().is_empty() || path.to_string_lossy().starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+
+    // 扩展长度路径不会再做规范化，必须是使用反斜杠的绝对路径
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let absolute = absolute.to_string_lossy().replace('/', "\\");
+    match absolute.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{}", unc)),
+        None => PathBuf::from(format!(r"\\?\{}", absolute)),
+    }
+}
+
+/// 非 Windows 平台没有 MAX_PATH 限制，路径原样返回
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// 去掉文本中路径的扩展长度前缀（`\\?\UNC\` 还原为 `\\`），用于返回给用户的路径和消息
+#[cfg(target_os = "windows")]
+pub(crate) fn strip_extended_length_prefix(text: &str) -> String {
+    text.replace(r"\\?\UNC\", r"\\").replace(r"\\?\", "")
+}
+
+/// 非 Windows 平台不使用扩展长度前缀，文本原样返回
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn strip_extended_length_prefix(text: &str) -> String {
+    text.to_string()
+}
+
+/// 用于显示的路径（不含扩展长度前缀）
+pub(crate) fn display_path(path: &Path) -> String {
+    strip_extended_length_prefix(&path.display().to_string())
+}
+
+/// 目标位于源目录内部时，返回以源路径表示的目标路径（与遍历源目录得到的条目路径可直接比较）
+pub(crate) fn nested_target_under(source: &Path, target: &Path) -> Option<PathBuf> {
+    let source_canonical = fs::canonicalize(source).ok()?;
+    let target_resolved = resolve_with_existing_ancestor(target);
+    let relative = target_resolved.strip_prefix(&source_canonical).ok()?;
+    Some(source.join(relative))
+}
+
+/// 规范化路径：已存在的部分取真实路径，其余部分原样拼接
+pub(crate) fn resolve_with_existing_ancestor(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = fs::canonicalize(ancestor) {
+            return match path.strip_prefix(ancestor) {
+                Ok(rest) => canonical.join(rest),
+                Err(_) => canonical,
+            };
+        }
+    }
+    path.to_path_buf()
+}
+
+/// 生成不冲突的新路径：`name (1).ext`、`name (2).ext`……
+fn next_available_path(path: &Path) -> PathBuf {
+    let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+
+    let mut index = 1;
+    loop {
+        let file_name = match &extension {
+            Some(ext) => format!("{} ({}).{}", stem, index, ext),
+            None => format!("{} ({})", stem, index),
+        };
+        let candidate = parent.join(file_name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// 创建指定类型的链接
+fn create_link_of_type(target: &Path, link_path: &Path, link_type: LinkType) -> io::Result<()> {
+    match link_type {
+        LinkType::Symlink => create_symlink_raw(target, link_path),
+        LinkType::Junction => create_junction(target, link_path),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn create_symlink_raw(target: &Path, link_path: &Path) -> io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link_path)
+    } else {
+        std::os::windows::fs::symlink_file(target, link_path)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn create_symlink_raw(target: &Path, link_path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link_path)
+}
+
+/// 通过 FSCTL_SET_REPARSE_POINT 创建目录联接（挂载点重解析点）
+#[cfg(target_os = "windows")]
+fn create_junction(target: &Path, link_path: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{CloseHandle, GENERIC_WRITE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+    use windows_sys::Win32::System::Ioctl::FSCTL_SET_REPARSE_POINT;
+    use windows_sys::Win32::System::SystemServices::IO_REPARSE_TAG_MOUNT_POINT;
+
+    // 联接目标必须是不带 \\?\ 前缀的绝对路径
+    let target = std::path::absolute(target)?;
+    let print_name: Vec<u16> = target.as_os_str().encode_wide().collect();
+    let substitute_name: Vec<u16> = "\\??\\".encode_utf16().chain(print_name.iter().copied()).collect();
+
+    // REPARSE_DATA_BUFFER.MountPointReparseBuffer：两个名称均以 NUL 结尾
+    let substitute_bytes = (substitute_name.len() * 2) as u16;
+    let print_bytes = (print_name.len() * 2) as u16;
+    let path_buffer_bytes = substitute_bytes + 2 + print_bytes + 2;
+    let reparse_data_length = 8 + path_buffer_bytes;
+
+    let mut buffer: Vec<u8> = Vec::with_capacity(8 + reparse_data_length as usize);
+    buffer.extend_from_slice(&IO_REPARSE_TAG_MOUNT_POINT.to_le_bytes());
+    buffer.extend_from_slice(&reparse_data_length.to_le_bytes());
+    buffer.extend_from_slice(&0u16.to_le_bytes()); // Reserved
+    buffer.extend_from_slice(&0u16.to_le_bytes()); // SubstituteNameOffset
+    buffer.extend_from_slice(&substitute_bytes.to_le_bytes());
+    buffer.extend_from_slice(&(substitute_bytes + 2).to_le_bytes()); // PrintNameOffset
+    buffer.extend_from_slice(&print_bytes.to_le_bytes());
+    for unit in substitute_name.iter().chain(&[0]).chain(&print_name).chain(&[0]) {
+        buffer.extend_from_slice(&unit.to_le_bytes());
+    }
+
+    fs::create_dir(link_path)?;
+
+    let wide_link: Vec<u16> = link_path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let handle = unsafe {
+        CreateFileW(
+            wide_link.as_ptr(),
+            GENERIC_WRITE,
+            0,
+            std::ptr::null(),
+            OPEN_EXISTING,
+            FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS,
+            std::ptr::null_mut(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        let error = io::Error::last_os_error();
+        let _ = fs::remove_dir(link_path);
+        return Err(error);
+    }
+
+    let mut bytes_returned = 0u32;
+    let ok = unsafe {
+        DeviceIoControl(
+            handle,
+            FSCTL_SET_REPARSE_POINT,
+            buffer.as_ptr() as *const _,
+            buffer.len() as u32,
+            std::ptr::null_mut(),
+            0,
+            &mut bytes_returned,
+            std::ptr::null_mut(),
+        )
+    };
+    let error = io::Error::last_os_error();
+    unsafe { CloseHandle(handle) };
+
+    if ok == 0 {
+        let _ = fs::remove_dir(link_path);
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn create_junction(_target: &Path, _link_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "目录联接仅在 Windows 上可用"))
+}
+
+/// 判断路径本身是否为链接及其类型（不跟随链接），不是链接时返回 None
+pub fn detect_link_type(path: &Path) -> Option<LinkType> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    if !metadata.file_type().is_symlink() {
+        return None;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+        use windows_sys::Win32::Storage::FileSystem::{FindClose, FindFirstFileW, WIN32_FIND_DATAW};
+        use windows_sys::Win32::System::SystemServices::IO_REPARSE_TAG_MOUNT_POINT;
+
+        // 重解析点的标记保存在 WIN32_FIND_DATAW.dwReserved0 中
+        let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut find_data: WIN32_FIND_DATAW = unsafe { std::mem::zeroed() };
+        let handle = unsafe { FindFirstFileW(wide_path.as_ptr(), &mut find_data) };
+        if handle != INVALID_HANDLE_VALUE {
+            unsafe { FindClose(handle) };
+            if find_data.dwReserved0 == IO_REPARSE_TAG_MOUNT_POINT {
+                return Some(LinkType::Junction);
+            }
+        }
+    }
+
+    Some(LinkType::Symlink)
+}
+
 impl Default for FileOperator {
     fn default() -> Self {
         Self::new()
@@ -444,7 +1798,7 @@ mod tests {
         writeln!(file, "测试内容").unwrap();
 
         let operator = FileOperator::new();
-        let result = operator.copy_file(&source_file, &target_file).unwrap();
+        let result = operator.copy_file(&source_file, &target_file, &CopyOptions::default()).unwrap();
 
         assert!(result.success);
         assert!(target_file.exists());
@@ -469,7 +1823,7 @@ mod tests {
         writeln!(file2, "内容2").unwrap();
 
         let operator = FileOperator::new();
-        let result = operator.copy_directory(&source_dir, &target_dir).unwrap();
+        let result = operator.copy_directory(&source_dir, &target_dir, &CopyOptions::default(), &[]).unwrap();
 
         assert!(result.success);
         assert!(target_dir.exists());
@@ -487,9 +1841,404 @@ mod tests {
         fs::create_dir_all(&source).unwrap();
 
         let operator = FileOperator::new();
-        let (valid, message) = operator.validate_migration_path(&source, &target).unwrap();
+        let (valid, message, _) = operator.validate_migration_path(&source, &target).unwrap();
 
         assert!(valid);
         assert_eq!(message, "路径验证通过");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validation_suggestions() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(&source).unwrap();
+        let operator = FileOperator::new();
+
+        // 目标在源目录内部：建议同级目录
+        let (valid, message, suggestions) = operator
+            .validate_migration_path(&source, &source.join("nested").join("target"))
+            .unwrap();
+        assert!(!valid);
+        assert_eq!(message, "不能将目录迁移到其子目录中");
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions[0].contains(&temp_dir.path().join("source_migrated").display().to_string()));
+
+        // 源路径不存在：建议最近的现有上级目录
+        let missing = source.join("gone").join("deeper");
+        let (valid, message, suggestions) = operator
+            .validate_migration_path(&missing, &temp_dir.path().join("target"))
+            .unwrap();
+        assert!(!valid);
+        assert_eq!(message, "源路径不存在");
+        assert_eq!(suggestions, vec![format!("使用最近的现有上级目录: {}", source.display())]);
+
+        // 保留名称：建议替代名称
+        let (valid, message, suggestions) = operator
+            .validate_migration_path(&source, &temp_dir.path().join("con"))
+            .unwrap();
+        assert!(!valid);
+        assert!(message.contains("系统保留名称"));
+        assert!(suggestions[0].contains(&temp_dir.path().join("con_data").display().to_string()));
+
+        // 目标已存在：建议未被占用的路径
+        let existing = temp_dir.path().join("existing");
+        fs::create_dir_all(&existing).unwrap();
+        let (valid, message, suggestions) = operator.validate_migration_path(&source, &existing).unwrap();
+        assert!(!valid);
+        assert_eq!(message, "目标路径已存在");
+        assert!(suggestions[0].contains(&temp_dir.path().join("existing (1)").display().to_string()));
+    }
+
+    #[test]
+    fn test_copy_into_nested_target_excludes_target_subtree() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("Big");
+        fs::create_dir_all(source.join("docs")).unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        fs::write(source.join("docs").join("b.txt"), "b").unwrap();
+        // 目标目录已有内容，同样不能被复制进自身
+        let target = source.join("Archive");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("old.txt"), "old").unwrap();
+
+        let operator = FileOperator::new();
+
+        // 未显式允许时拒绝嵌套目标
+        assert!(matches!(
+            operator.copy_path(&source, &target),
+            Err(FileOperationError::InvalidPath(_))
+        ));
+        let (valid, _, _) = operator.validate_migration_path_with(&source, &target, true, false).unwrap();
+        assert!(!valid);
+        let (valid, _, _) = operator.validate_migration_path_with(&source, &target, true, true).unwrap();
+        assert!(valid);
+
+        let options = CopyOptions { allow_nested_target: true, ..Default::default() };
+        let result = operator.copy_path_with_options(&source, &target, &options).unwrap();
+        assert!(result.success);
+
+        assert_eq!(fs::read_to_string(target.join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(target.join("docs").join("b.txt")).unwrap(), "b");
+        assert!(!target.join("Archive").exists(), "目标子树不应被复制进自身");
+        assert!(result.file_actions.iter().all(|record| !record.source_path.contains("Archive")));
+        // 源目录保持不变
+        assert!(source.join("a.txt").exists() && target.join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_copy_skips_excluded_subpaths() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        fs::create_dir_all(source.path().join("Cache").join("deep")).unwrap();
+        fs::create_dir_all(source.path().join("Data").join("Logs")).unwrap();
+        fs::write(source.path().join("Cache").join("deep").join("c.bin"), "cache").unwrap();
+        fs::write(source.path().join("Data").join("Logs").join("today.log"), "log").unwrap();
+        fs::write(source.path().join("Data").join("keep.txt"), "keep").unwrap();
+
+        let options = CopyOptions {
+            exclude_subpaths: vec![PathBuf::from("Cache"), PathBuf::from("Data").join("Logs")],
+            ..Default::default()
+        };
+        let result = FileOperator::new()
+            .copy_path_with_options(source.path(), &target.path().join("App"), &options)
+            .unwrap();
+
+        assert!(result.success);
+        let copied = target.path().join("App");
+        assert!(!copied.join("Cache").exists());
+        assert!(!copied.join("Data").join("Logs").exists());
+        assert_eq!(fs::read_to_string(copied.join("Data").join("keep.txt")).unwrap(), "keep");
+    }
+
+    /// 创建源目录与预先存在同名文件的目标目录
+    fn setup_conflict_dirs() -> (TempDir, TempDir) {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        fs::write(source_dir.path().join("a.txt"), "新内容A").unwrap();
+        fs::write(source_dir.path().join("b.txt"), "新内容B").unwrap();
+        fs::write(target_dir.path().join("a.txt"), "旧内容A").unwrap();
+
+        (source_dir, target_dir)
+    }
+
+    fn copy_with_policy(source: &Path, target: &Path, policy: ConflictPolicy) -> Result<FileOperationResult, FileOperationError> {
+        let options = CopyOptions { conflict_policy: policy, ..Default::default() };
+        FileOperator::new().copy_path_with_options(source, target, &options)
+    }
+
+    fn action_for<'a>(result: &'a FileOperationResult, source: &Path) -> &'a FileActionRecord {
+        let source = source.display().to_string();
+        result.file_actions.iter().find(|r| r.source_path == source).unwrap()
+    }
+
+    #[test]
+    fn test_conflict_policy_fail() {
+        let (source_dir, target_dir) = setup_conflict_dirs();
+
+        let result = copy_with_policy(source_dir.path(), target_dir.path(), ConflictPolicy::Fail);
+
+        assert!(matches!(result, Err(FileOperationError::PathAlreadyExists(_))));
+        assert_eq!(fs::read_to_string(target_dir.path().join("a.txt")).unwrap(), "旧内容A");
+    }
+
+    #[test]
+    fn test_chunked_copy_keeps_memory_bounded() {
+        use crate::performance_optimizer::ProcessMemorySampler;
+
+        const FILE_SIZE: u64 = 200 * 1024 * 1024;
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let source = source_dir.path().join("large.dat");
+        let target = target_dir.path().join("large.dat");
+        File::create(&source).unwrap().set_len(FILE_SIZE).unwrap();
+
+        let sampler = ProcessMemorySampler::new();
+        let Some(baseline) = sampler.sample() else { return };
+
+        let copy_thread = {
+            let (source, target) = (source.clone(), target.clone());
+            std::thread::spawn(move || {
+                let options = CopyOptions { buffer_size: Some(1024 * 1024), ..Default::default() };
+                FileOperator::new().copy_path_with_options(&source, &target, &options)
+            })
+        };
+        while !copy_thread.is_finished() {
+            sampler.sample();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let result = copy_thread.join().unwrap().unwrap();
+
+        assert!(result.success);
+        assert_eq!(fs::metadata(&target).unwrap().len(), FILE_SIZE);
+        let growth = sampler.peak().saturating_sub(baseline);
+        assert!(growth < FILE_SIZE / 4, "复制期间内存增长过大: {} 字节", growth);
+    }
+
+    #[test]
+    fn test_delete_with_and_without_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let operator = FileOperator::new();
+
+        // 不使用回收站：永久删除
+        let permanent = temp_dir.path().join("permanent.txt");
+        fs::write(&permanent, "内容").unwrap();
+        operator.delete_path_with_options(&permanent, false).unwrap();
+        assert!(!permanent.exists());
+
+        // 使用回收站：原位置消失，但可在回收站中找到
+        let name = format!("dir_mover_trash_{}.txt", uuid::Uuid::new_v4());
+        let trashed = temp_dir.path().join(&name);
+        fs::write(&trashed, "内容").unwrap();
+        if operator.delete_path_with_options(&trashed, true).is_err() {
+            // 当前环境没有可用的回收站
+            return;
+        }
+        assert!(!trashed.exists());
+
+        #[cfg(any(windows, all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+        {
+            let items: Vec<_> = trash::os_limited::list().unwrap()
+                .into_iter()
+                .filter(|item| item.name.to_string_lossy() == name)
+                .collect();
+            assert_eq!(items.len(), 1, "回收站中应能找到被删除的文件");
+            trash::os_limited::purge_all(items).unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_records_per_file_results() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        fs::write(source_dir.path().join("ok.txt"), "内容").unwrap();
+        fs::create_dir_all(source_dir.path().join("sub")).unwrap();
+        fs::write(source_dir.path().join("sub").join("nested.txt"), "内容").unwrap();
+        // 悬空链接无法复制，模拟单个文件失败
+        std::os::unix::fs::symlink(source_dir.path().join("missing"), source_dir.path().join("broken.txt")).unwrap();
+
+        let target = target_dir.path().join("copy");
+        let result = FileOperator::new().copy_path(source_dir.path(), &target).unwrap();
+
+        assert!(!result.success);
+        assert!(result.message.contains("失败: 1"));
+        assert_eq!(result.file_results.len(), 3);
+
+        let status_of = |path: &Path| {
+            result.file_results.iter().find(|r| r.path == path.display().to_string()).unwrap()
+        };
+        assert_eq!(status_of(&source_dir.path().join("ok.txt")).status, FileStatus::Succeeded);
+        assert_eq!(status_of(&source_dir.path().join("sub").join("nested.txt")).status, FileStatus::Succeeded);
+        let broken = status_of(&source_dir.path().join("broken.txt"));
+        assert_eq!(broken.status, FileStatus::Failed);
+        assert!(broken.error.is_some());
+        assert!(target.join("ok.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preserve_metadata_keeps_unix_mode_and_owner() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let source_file = source_dir.path().join("sub").join("secret.conf");
+        fs::create_dir_all(source_file.parent().unwrap()).unwrap();
+        fs::write(&source_file, "token=1").unwrap();
+        fs::set_permissions(&source_file, fs::Permissions::from_mode(0o640)).unwrap();
+        fs::set_permissions(source_dir.path().join("sub"), fs::Permissions::from_mode(0o750)).unwrap();
+
+        // 以 root 运行时把源文件改为其他属主，验证属主也被保留；无权限时只验证模式位
+        let privileged = std::os::unix::fs::chown(&source_file, Some(1234), Some(1234)).is_ok();
+
+        let target = target_dir.path().join("copy");
+        let options = CopyOptions { preserve_metadata: true, ..Default::default() };
+        let result = FileOperator::new().copy_path_with_options(source_dir.path(), &target, &options).unwrap();
+        assert!(result.success, "{}", result.message);
+
+        let target_file = fs::metadata(target.join("sub").join("secret.conf")).unwrap();
+        assert_eq!(target_file.mode() & 0o7777, 0o640);
+        assert_eq!(fs::metadata(target.join("sub")).unwrap().mode() & 0o7777, 0o750);
+        if privileged {
+            assert_eq!((target_file.uid(), target_file.gid()), (1234, 1234));
+        }
+    }
+
+    #[test]
+    fn test_conflict_policy_skip() {
+        let (source_dir, target_dir) = setup_conflict_dirs();
+
+        let result = copy_with_policy(source_dir.path(), target_dir.path(), ConflictPolicy::Skip).unwrap();
+
+        assert_eq!(fs::read_to_string(target_dir.path().join("a.txt")).unwrap(), "旧内容A");
+        assert_eq!(fs::read_to_string(target_dir.path().join("b.txt")).unwrap(), "新内容B");
+        assert_eq!(action_for(&result, &source_dir.path().join("a.txt")).action, FileAction::Skipped);
+        assert_eq!(action_for(&result, &source_dir.path().join("b.txt")).action, FileAction::Copied);
+    }
+
+    #[test]
+    fn test_conflict_policy_overwrite() {
+        let (source_dir, target_dir) = setup_conflict_dirs();
+
+        let result = copy_with_policy(source_dir.path(), target_dir.path(), ConflictPolicy::Overwrite).unwrap();
+
+        assert_eq!(fs::read_to_string(target_dir.path().join("a.txt")).unwrap(), "新内容A");
+        assert_eq!(action_for(&result, &source_dir.path().join("a.txt")).action, FileAction::Overwritten);
+        assert_eq!(action_for(&result, &source_dir.path().join("b.txt")).action, FileAction::Copied);
+    }
+
+    #[test]
+    fn test_conflict_policy_rename_new() {
+        let (source_dir, target_dir) = setup_conflict_dirs();
+
+        let result = copy_with_policy(source_dir.path(), target_dir.path(), ConflictPolicy::RenameNew).unwrap();
+
+        let renamed = target_dir.path().join("a (1).txt");
+        assert_eq!(fs::read_to_string(target_dir.path().join("a.txt")).unwrap(), "旧内容A");
+        assert_eq!(fs::read_to_string(&renamed).unwrap(), "新内容A");
+
+        let record = action_for(&result, &source_dir.path().join("a.txt"));
+        assert_eq!(record.action, FileAction::Renamed);
+        assert_eq!(record.target_path, renamed.display().to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_create_link_each_type_resolves_to_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("data.txt"), "内容").unwrap();
+
+        let operator = FileOperator::new();
+        for (name, link_type) in [("junction_link", LinkType::Junction), ("symlink_link", LinkType::Symlink)] {
+            let link_path = temp_dir.path().join(name);
+            let (result, created) = operator.create_link(&target, &link_path, link_type).unwrap();
+            assert!(result.success);
+
+            // 未启用开发者模式时符号链接会回退为目录联接
+            if link_type == LinkType::Junction {
+                assert_eq!(created, LinkType::Junction);
+            }
+            assert_eq!(fs::canonicalize(&link_path).unwrap(), fs::canonicalize(&target).unwrap());
+            assert_eq!(fs::read_to_string(link_path.join("data.txt")).unwrap(), "内容");
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_create_link_junction_falls_back_to_symlink() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        let link_path = temp_dir.path().join("link");
+
+        let (result, created) = FileOperator::new()
+            .create_link(&target, &link_path, LinkType::Junction)
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(created, LinkType::Symlink);
+        assert_eq!(fs::read_link(&link_path).unwrap(), target);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let cases = [
+            (FileOperationError::IoError(io::Error::new(io::ErrorKind::Other, "io")), "IO_ERROR"),
+            (FileOperationError::PermissionDenied(String::new()), "PERMISSION_DENIED"),
+            (FileOperationError::PathNotFound(String::new()), "PATH_NOT_FOUND"),
+            (FileOperationError::PathAlreadyExists(String::new()), "PATH_ALREADY_EXISTS"),
+            (FileOperationError::InvalidPath(String::new()), "INVALID_PATH"),
+            (FileOperationError::OperationCancelled(String::new()), "OPERATION_CANCELLED"),
+            (FileOperationError::Timeout(String::new()), "TIMEOUT"),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "{:?}", error);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_extended_length_path_prefixes_short_roots() {
+        let extended = extended_length_path(Path::new(r"C:\Data"));
+        assert_eq!(extended, PathBuf::from(r"\\?\C:\Data"));
+        assert_eq!(display_path(&extended.join("sub")), r"C:\Data\sub");
+        assert_eq!(strip_extended_length_prefix(r"\\?\UNC\server\share"), r"\\server\share");
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_delete_retries_briefly_locked_file() {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("data");
+        fs::create_dir_all(&dir).unwrap();
+        let locked_path = dir.join("locked.txt");
+        fs::write(&locked_path, "内容").unwrap();
+
+        // 不允许共享访问地打开文件，片刻后释放，模拟被其他程序短暂占用
+        let holder = fs::OpenOptions::new().read(true).share_mode(0).open(&locked_path).unwrap();
+        let releaser = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            drop(holder);
+        });
+
+        let result = FileOperator::new().delete_path(&dir);
+        releaser.join().unwrap();
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_check_path_length_only_rejects_invalid_lengths() {
+        let long_path: PathBuf = std::iter::repeat("segment_of_forty_characters_long_000000").take(10).collect();
+        assert!(long_path.to_string_lossy().len() > MAX_PATH);
+        assert!(check_path_length(&long_path).is_ok());
+
+        let long_name = Path::new("root").join("n".repeat(256));
+        assert!(check_path_length(&long_name).unwrap_err().contains("名称过长"));
+    }
+}
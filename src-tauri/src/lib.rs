@@ -8,20 +8,27 @@ mod types;
 mod logger;
 mod tests;
 mod appdata_analyzer;
+mod messages;
+mod util;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tauri::{State, Emitter};
 use log::{info, error, warn};
 
-use disk_analyzer::{DiskAnalyzer, DirectoryInfo, format_file_size};
-use error_recovery::{ErrorRecoveryManager, ErrorRecoveryConfig, RecoveryContext, RecoveryStatistics};
-use migration_service::{MigrationService, MigrationOptions, MigrationResult, validate_migration_options};
-use operation_logger::{OperationLogger, OperationStatistics};
-use performance_optimizer::{PerformanceOptimizer, PerformanceConfig};
-use types::PathValidationResult;
-use appdata_analyzer::{AppDataAnalyzer, AppDataInfo, AppDataConfig, AppDataMigrationOptions, ScanEvent, StreamingAppDataScanner};
+use disk_analyzer::{DiskAnalyzer, DirectoryInfo, DirectoryScanWithSummary, PathTotals, format_file_size, top_n_largest};
+use disk_analyzer::export::ExportFormat;
+use disk_analyzer::compact::{ScanOutput, COMPACT_MIN_ENTRY_SIZE};
+use error_recovery::{ErrorRecoveryManager, ErrorRecoveryConfig, RecoveryContext, RecoveryStatistics, BackupInfo, BackupControl, BackupProgress, BackupCleanupReport, OrphanedBackup};
+use migration_service::{MigrationService, PENDING_DELETIONS_FILE, INTEGRITY_BASELINES_FILE, MigrationOptions, MigrationResult, BatchMigrationResult, MigrationIntegrityReport, MigrationLockRegistry, PathInspection, CancelRollbackResult, MigrationUndoResult, LinkResolution, RelocationResult, SpaceFreedEstimate, default_max_parallel_migrations, validate_migration_options};
+use operation_logger::{OperationLogger, OperationStatistics, OperationTypeStatistics, OperationType, LogQueryFilter};
+use performance_optimizer::{PerformanceOptimizer, PerformanceConfig, DriveBenchmark, DRIVE_BENCHMARK_SIZE};
+use types::{CancellationReason, ErrorInfo, PathValidationResult, MigrationEstimate};
+use file_operations::{FileOperationError, detect_drive_status};
+use appdata_analyzer::{AppDataAnalyzer, AppDataInfo, AppDataItemPager, AppDataItemsPage, DEFAULT_APPDATA_PAGE_SIZE, AppDataConfig, AppDataFirstLevelItem, AppDataCategorySummary, AppDataMigrationOptions, AppDataPlanValidation, ScanEvent, StreamingAppDataScanner, StreamingScanOptions, APPDATA_BLOCKLIST, running_process_names};
+use migration_service::symlink_watch::{SymlinkWatch, SymlinkWatchList, SymlinkCheckReport, SYMLINK_WATCH_FILE};
+use messages::{KeyedError, Locale, MessageCatalog, MessageKey};
 
 /// 应用状态
 struct AppState {
@@ -29,16 +36,92 @@ struct AppState {
     migration_service: Arc<MigrationService>,
     operation_logger: Arc<Mutex<OperationLogger>>,
     error_recovery_manager: Arc<Mutex<ErrorRecoveryManager>>,
-    performance_optimizer: Arc<Mutex<PerformanceOptimizer>>,
+    backup_control: BackupControl, // 新增：备份进行中时无需获取管理器锁即可取消或查询进度
+    performance_optimizer: Arc<PerformanceOptimizer>,
+    migration_locks: Arc<MigrationLockRegistry>,
+    locale: Arc<std::sync::RwLock<Locale>>,
+    symlink_watches: Arc<SymlinkWatchList>, // 新增：迁移后创建的链接，供健康检查
+    appdata_pager: Arc<AppDataItemPager>, // 新增：最近一次 AppData 扫描的项目，供分页读取
 }
 
 /// 扫描目录（异步版本）
 #[tauri::command]
-async fn scan_directory(path: String, c_drive_mode: bool, state: State<'_, AppState>) -> Result<DirectoryInfo, String> {
+async fn scan_directory(
+    path: String,
+    c_drive_mode: bool,
+    count_before_scan: Option<bool>,
+    prune_small_folders: Option<bool>,
+    compute_size_on_disk: Option<bool>,
+    compact: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<ScanOutput, String> {
     info!("收到扫描目录请求: {}, C盘模式: {}", path, c_drive_mode);
     
     let path = Path::new(&path);
+    let analyzer = prepare_directory_scan(path, c_drive_mode, count_before_scan, prune_small_folders, compute_size_on_disk, &state).await?;
+    
+    info!("开始扫描目录: {}", path.display());
+    
+    // 使用异步扫描（分析器已克隆并释放锁，以便扫描期间可以暂停、恢复或取消）
+    let result = analyzer.scan_directory_async(path).await;
+    
+    match &result {
+        Ok(info) => {
+            info!("扫描完成: {} (文件数: {}, 大小: {}, 大文件夹: {})",
+                  path.display(), info.file_count, format_file_size(info.size), info.is_large_folder);
+        }
+        Err(e) => {
+            error!("扫描失败 {}: {}", path.display(), e);
+        }
+    }
     
+    // 紧凑模式省略空字段以减小传给前端的数据量，只关注大文件夹时再合并小目录
+    result.map(|info| match (compact.unwrap_or(false), prune_small_folders.unwrap_or(false)) {
+        (true, true) => ScanOutput::Compact(info.to_compact_with_threshold(COMPACT_MIN_ENTRY_SIZE)),
+        (true, false) => ScanOutput::Compact(info.to_compact()),
+        (false, _) => ScanOutput::Full(info),
+    })
+}
+
+/// 扫描目录并返回汇总统计（目录数、文件数、最大深度、最大文件、大文件夹数）
+#[tauri::command]
+async fn scan_directory_with_summary(
+    path: String,
+    c_drive_mode: bool,
+    count_before_scan: Option<bool>,
+    prune_small_folders: Option<bool>,
+    compute_size_on_disk: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<DirectoryScanWithSummary, String> {
+    info!("收到带汇总统计的扫描请求: {}, C盘模式: {}", path, c_drive_mode);
+
+    let path = Path::new(&path);
+    let analyzer = prepare_directory_scan(path, c_drive_mode, count_before_scan, prune_small_folders, compute_size_on_disk, &state).await?;
+
+    let result = analyzer.scan_directory_with_summary(path).await;
+    match &result {
+        Ok(scan) => {
+            info!("扫描完成: {} (目录数: {}, 文件数: {}, 最大深度: {}, 大文件夹: {})",
+                  path.display(), scan.summary.total_directories, scan.summary.total_files,
+                  scan.summary.max_depth_reached, scan.summary.large_folders_found);
+        }
+        Err(e) => {
+            error!("扫描失败 {}: {}", path.display(), e);
+        }
+    }
+
+    result
+}
+
+/// 校验扫描路径并按请求配置共享的分析器，返回配置好的分析器副本（不持有锁）
+async fn prepare_directory_scan(
+    path: &Path,
+    c_drive_mode: bool,
+    count_before_scan: Option<bool>,
+    prune_small_folders: Option<bool>,
+    compute_size_on_disk: Option<bool>,
+    state: &State<'_, AppState>,
+) -> Result<DiskAnalyzer, String> {
     if !path.exists() {
         error!("路径不存在: {}", path.display());
         return Err("路径不存在".to_string());
@@ -49,43 +132,224 @@ async fn scan_directory(path: String, c_drive_mode: bool, state: State<'_, AppSt
         return Err("路径不是目录".to_string());
     }
 
-    let analyzer = state.disk_analyzer.clone();
-    
-    // 在异步任务中配置分析器
-    {
-        let mut analyzer = analyzer.lock().await;
+    let mut analyzer = state.disk_analyzer.lock().await;
+    analyzer.reset_cancel_flag();
+    analyzer.set_max_depth(5); // 增加扫描深度到5层
+    analyzer.set_c_drive_mode(c_drive_mode); // 设置C盘专项扫描模式
+    analyzer.set_count_before_scan(count_before_scan.unwrap_or(false)); // 预统计条目总数以获得准确进度
+    analyzer.set_prune_small_folders(prune_small_folders.unwrap_or(false)); // 只关注大文件夹时裁剪小目录明细
+    analyzer.set_compute_size_on_disk(compute_size_on_disk.unwrap_or(false)); // 额外统计实际占用空间
+    if c_drive_mode {
+        analyzer.set_large_folder_threshold(1024 * 1024 * 1024); // C盘模式使用1GB作为大文件夹阈值
+    } else {
+        analyzer.set_large_folder_threshold(100 * 1024 * 1024); // 普通模式使用100MB作为大文件夹阈值
+    }
+    // 拒绝扫描整个磁盘根目录、系统目录或允许范围以外的路径
+    if let Err(e) = analyzer.check_scan_root(path, &state.migration_service.protected_paths()) {
+        warn!("拒绝扫描请求: {}", e);
+        return Err(e);
+    }
+    info!("分析器配置完成，最大深度: 5, C盘模式: {}", c_drive_mode);
+
+    // 克隆后释放锁，以便扫描期间可以暂停、恢复或取消
+    Ok(analyzer.clone())
+}
+
+/// 快速计算单个路径的总字节数（不构建目录树，可通过 cancel_scan 取消）
+#[tauri::command]
+async fn get_path_size(path: String, state: State<'_, AppState>) -> Result<u64, String> {
+    info!("收到计算路径大小请求: {}", path);
+
+    let analyzer = {
+        let analyzer = state.disk_analyzer.lock().await;
+        if let Err(e) = analyzer.check_scan_root(Path::new(&path), &state.migration_service.protected_paths()) {
+            warn!("拒绝计算路径大小: {}", e);
+            return Err(e);
+        }
+        analyzer.reset_cancel_flag();
+        analyzer.clone()
+    };
+
+    let size = tokio::task::spawn_blocking(move || analyzer.measure_path(Path::new(&path)))
+        .await
+        .map_err(|e| format!("计算路径大小失败: {}", e))??
+        .size;
+
+    info!("路径大小计算完成: {}", format_file_size(size));
+    Ok(size)
+}
+
+/// 浅层扫描：只返回直接子目录及其完整大小，供首次渲染快速展示
+#[tauri::command]
+async fn scan_shallow(path: String, state: State<'_, AppState>) -> Result<DirectoryInfo, String> {
+    info!("收到浅层扫描请求: {}", path);
+
+    let analyzer = {
+        let analyzer = state.disk_analyzer.lock().await;
+        if let Err(e) = analyzer.check_scan_root(Path::new(&path), &state.migration_service.protected_paths()) {
+            warn!("拒绝浅层扫描请求: {}", e);
+            return Err(e);
+        }
         analyzer.reset_cancel_flag();
-        analyzer.set_max_depth(5); // 增加扫描深度到5层
-        analyzer.set_c_drive_mode(c_drive_mode); // 设置C盘专项扫描模式
+        analyzer.clone()
+    };
+
+    let result = tokio::task::spawn_blocking(move || analyzer.scan_shallow(Path::new(&path)))
+        .await
+        .map_err(|e| format!("浅层扫描失败: {}", e))??;
+
+    info!("浅层扫描完成: {} 个子目录, 总大小: {}", result.subdirectories.len(), format_file_size(result.size));
+    Ok(result)
+}
+
+/// 设置扫描预算（文件数/总字节数），用于在超大磁盘上快速预览；传入 None 取消限制
+#[tauri::command]
+async fn set_scan_budget(max_files: Option<u64>, max_bytes: Option<u64>, state: State<'_, AppState>) -> Result<(), String> {
+    state.disk_analyzer.lock().await.set_scan_budget(max_files, max_bytes);
+    info!("扫描预算已设置: 文件数 {:?}, 字节数 {:?}", max_files, max_bytes);
+    Ok(())
+}
+
+/// 设置允许扫描的根目录，传入 None 取消限制
+#[tauri::command]
+async fn set_allowed_scan_roots(roots: Option<Vec<String>>, state: State<'_, AppState>) -> Result<(), String> {
+    info!("允许扫描的根目录已设置: {:?}", roots);
+    let roots = roots.map(|roots| roots.iter().map(|root| PathBuf::from(root.trim())).collect());
+    state.disk_analyzer.lock().await.set_allowed_scan_roots(roots);
+    Ok(())
+}
+
+/// 设置扫描是否统计隐藏文件和系统文件，关闭后的大小与资源管理器默认显示一致
+#[tauri::command]
+async fn set_scan_file_visibility(include_hidden: bool, include_system: bool, state: State<'_, AppState>) -> Result<(), String> {
+    let mut analyzer = state.disk_analyzer.lock().await;
+    analyzer.set_include_hidden(include_hidden);
+    analyzer.set_include_system(include_system);
+    info!("扫描文件可见性已设置: 隐藏文件 {}, 系统文件 {}", include_hidden, include_system);
+    Ok(())
+}
+
+/// 流式扫描目录：通过 `scan-progress` 事件推送进度，无需轮询 get_scan_progress
+#[tauri::command]
+async fn scan_directory_streaming(
+    path: String,
+    c_drive_mode: bool,
+    window: tauri::Window,
+    state: State<'_, AppState>
+) -> Result<DirectoryInfo, String> {
+    info!("收到流式扫描目录请求: {}, C盘模式: {}", path, c_drive_mode);
+
+    let path = Path::new(&path);
+    if !path.is_dir() {
+        error!("路径不存在或不是目录: {}", path.display());
+        return Err("路径不存在或不是目录".to_string());
+    }
+
+    let analyzer = {
+        let mut analyzer = state.disk_analyzer.lock().await;
+        analyzer.reset_cancel_flag();
+        analyzer.set_max_depth(5);
+        analyzer.set_c_drive_mode(c_drive_mode);
         if c_drive_mode {
-            analyzer.set_large_folder_threshold(1024 * 1024 * 1024); // C盘模式使用1GB作为大文件夹阈值
+            analyzer.set_large_folder_threshold(1024 * 1024 * 1024);
         } else {
-            analyzer.set_large_folder_threshold(100 * 1024 * 1024); // 普通模式使用100MB作为大文件夹阈值
+            analyzer.set_large_folder_threshold(100 * 1024 * 1024);
         }
-        info!("分析器配置完成，最大深度: 5, C盘模式: {}", c_drive_mode);
-    }
-    
-    info!("开始扫描目录: {}", path.display());
-    
-    // 使用异步扫描
-    let result = {
-        let analyzer = analyzer.lock().await;
-        analyzer.scan_directory_async(path).await
+        if let Err(e) = analyzer.check_scan_root(path, &state.migration_service.protected_paths()) {
+            warn!("拒绝扫描请求: {}", e);
+            return Err(e);
+        }
+        analyzer.clone()
     };
-    
+
+    // 将进度转发到前端
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel::<disk_analyzer::ScanProgress>();
+    let window_clone = window.clone();
+    let progress_forward_task = tokio::spawn(async move {
+        while let Some(progress) = progress_rx.recv().await {
+            if let Err(e) = window_clone.emit("scan-progress", &progress) {
+                error!("发送扫描进度到前端失败: {}", e);
+                break;
+            }
+        }
+    });
+
+    let result = analyzer.scan_directory_streaming(path, progress_tx).await;
+
+    if let Err(e) = progress_forward_task.await {
+        error!("进度转发任务失败: {}", e);
+    }
+
     match &result {
         Ok(info) => {
-            info!("扫描完成: {} (文件数: {}, 大小: {}, 大文件夹: {})",
-                  path.display(), info.file_count, format_file_size(info.size), info.is_large_folder);
+            info!("流式扫描完成: {} (文件数: {}, 大小: {})",
+                  path.display(), info.file_count, format_file_size(info.size));
+            let _ = window.emit("scan-complete", info);
         }
         Err(e) => {
-            error!("扫描失败 {}: {}", path.display(), e);
+            error!("流式扫描失败 {}: {}", path.display(), e);
+            let _ = window.emit("scan-error", &serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }));
         }
     }
-    
+
     result
 }
 
+/// 导出扫描结果（JSON/CSV），未提供目录树时重新扫描 path
+#[tauri::command]
+async fn export_scan_result(
+    output_path: String,
+    format: String,
+    tree: Option<DirectoryInfo>,
+    path: Option<String>,
+    state: State<'_, AppState>
+) -> Result<usize, String> {
+    let format: ExportFormat = format.parse()?;
+
+    let tree = match (tree, path) {
+        (Some(tree), _) => tree,
+        (None, Some(path)) => {
+            info!("导出前重新扫描目录: {}", path);
+            let analyzer = state.disk_analyzer.lock().await.clone();
+            analyzer.scan_directory_async(Path::new(&path)).await?
+        }
+        (None, None) => return Err("需要提供扫描结果或扫描路径".to_string()),
+    };
+
+    match disk_analyzer::export::export_scan_result(&tree, Path::new(&output_path), format) {
+        Ok(count) => {
+            info!("扫描结果已导出: {} ({} 个目录)", output_path, count);
+            Ok(count)
+        }
+        Err(e) => Err(format!("导出扫描结果失败: {}", e))
+    }
+}
+
+/// 比较两份导出的扫描结果，报告新增、删除的目录和大小变化
+#[tauri::command]
+fn diff_scan_files(old_path: String, new_path: String) -> Result<disk_analyzer::diff::ScanDiff, String> {
+    let diff = disk_analyzer::diff::diff_scan_files(Path::new(&old_path), Path::new(&new_path))
+        .map_err(|e| format!("比较扫描结果失败: {}", e))?;
+    info!("扫描结果对比完成: 新增 {} 个, 删除 {} 个, 变化 {} 个目录, 总变化 {} 字节",
+          diff.added.len(), diff.removed.len(), diff.changed.len(), diff.total_delta);
+    Ok(diff)
+}
+
+/// 比较前端保留的两棵扫描结果目录树
+#[tauri::command]
+fn diff_scans(old: DirectoryInfo, new: DirectoryInfo) -> Result<disk_analyzer::diff::ScanDiff, String> {
+    Ok(disk_analyzer::diff::diff_scans(&old, &new))
+}
+
+/// 列出扫描结果中占用空间最大的 N 个目录
+#[tauri::command]
+fn get_top_largest_directories(root: DirectoryInfo, n: usize) -> Result<Vec<(String, u64)>, String> {
+    Ok(top_n_largest(&root, n))
+}
+
 /// 获取扫描进度
 #[tauri::command]
 fn get_scan_progress(state: State<'_, AppState>) -> Result<types::ScanProgress, String> {
@@ -108,44 +372,499 @@ fn get_scan_progress(state: State<'_, AppState>) -> Result<types::ScanProgress,
         estimated_time_remaining: progress.estimated_time_remaining,
         scan_speed: progress.scan_speed,
         large_folders_found: progress.large_folders_found,
+        start_time: progress.start_time,
+        cancellation_reason: progress.cancellation_reason,
     })
 }
 
-/// 停止扫描
+/// 停止扫描（未指定原因时视为用户取消）
 #[tauri::command]
-fn stop_scan(state: State<'_, AppState>) -> Result<(), String> {
+fn stop_scan(state: State<'_, AppState>, reason: Option<CancellationReason>) -> Result<(), String> {
     let analyzer = state.disk_analyzer.clone();
     let analyzer = analyzer.blocking_lock();
     
-    analyzer.cancel_scan();
+    analyzer.cancel_scan(reason.unwrap_or_default());
     info!("扫描取消请求已发送");
     
     Ok(())
 }
 
-/// 迁移目录
+/// 暂停扫描
+#[tauri::command]
+fn pause_scan(state: State<'_, AppState>) -> Result<(), String> {
+    let analyzer = state.disk_analyzer.clone();
+    let analyzer = analyzer.blocking_lock();
+    
+    if !analyzer.pause_scan() {
+        return Err("当前没有可暂停的扫描".to_string());
+    }
+    info!("扫描已暂停");
+    
+    Ok(())
+}
+
+/// 恢复扫描
+#[tauri::command]
+fn resume_scan(state: State<'_, AppState>) -> Result<(), String> {
+    let analyzer = state.disk_analyzer.clone();
+    let analyzer = analyzer.blocking_lock();
+    
+    if !analyzer.resume_scan() {
+        return Err("扫描未处于暂停状态".to_string());
+    }
+    info!("扫描已恢复");
+    
+    Ok(())
+}
+
+/// 停止迁移（未指定原因时视为用户取消）
+#[tauri::command]
+async fn stop_migration(state: State<'_, AppState>, reason: Option<CancellationReason>) -> Result<(), String> {
+    state.migration_service.cancel_migration(reason.unwrap_or_default());
+    info!("迁移取消请求已发送");
+
+    Ok(())
+}
+
+/// 取消迁移时等待其停止的最长时间
+const CANCEL_ROLLBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// 取消进行中的迁移并撤销：等待迁移停止后清理目标端数据，必要时恢复源目录
 #[tauri::command]
-fn migrate_directory(
+async fn cancel_and_rollback_migration(
     source_path: String,
     target_path: String,
-    create_symlink: bool,
-    delete_source: bool,
+    state: State<'_, AppState>,
+) -> Result<CancelRollbackResult, String> {
+    state.migration_service
+        .cancel_and_rollback(
+            Path::new(&source_path),
+            Path::new(&target_path),
+            &state.migration_locks,
+            CANCEL_ROLLBACK_TIMEOUT,
+        )
+        .await
+        .map_err(|e| format!("取消并回滚迁移失败: {}", e))
+}
+
+/// 迁移目录，未指定的选项取默认值；未设置复制缓冲区大小时使用性能优化器推荐的值
+///
+/// 迁移在阻塞线程池中进行，期间仍可通过 stop_migration 取消。
+#[tauri::command]
+async fn migrate_directory(
+    mut options: MigrationOptions,
     state: State<'_, AppState>
 ) -> Result<MigrationResult, String> {
-    let options = MigrationOptions {
-        source_path: source_path.clone(),
-        target_path: target_path.clone(),
-        create_symlink,
-        delete_source,
-    };
+    options.copy_buffer_size.get_or_insert(state.performance_optimizer.copy_buffer_size());
 
-    // 验证迁移选项
-    validate_migration_options(&options)?;
+    state.migration_service.reset_cancel_flag();
+    let locale = current_locale(&state);
+    let runner = MigrationRunner::new(&state);
+    tokio::task::spawn_blocking(move || runner.run(options))
+        .await
+        .map_err(|e| format!("迁移任务执行失败: {}", e))?
+        .map(|mut migration_result| {
+            migration_result.localize(locale);
+            migration_result
+        })
+        .map_err(|e| e.message(locale))
+}
+
+/// 单项迁移与批量迁移共用的迁移流程：校验选项、登记路径锁、迁移、处理复制超时、
+/// 记录吞吐量并把新建的链接加入健康监测。包含阻塞的文件操作，须在阻塞线程中调用。
+#[derive(Clone)]
+struct MigrationRunner {
+    service: Arc<MigrationService>,
+    locks: Arc<MigrationLockRegistry>,
+    error_recovery_manager: Arc<Mutex<ErrorRecoveryManager>>,
+    performance_optimizer: Arc<PerformanceOptimizer>,
+    symlink_watches: Arc<SymlinkWatchList>,
+}
+
+impl MigrationRunner {
+    fn new(state: &AppState) -> Self {
+        Self {
+            service: state.migration_service.clone(),
+            locks: state.migration_locks.clone(),
+            error_recovery_manager: state.error_recovery_manager.clone(),
+            performance_optimizer: state.performance_optimizer.clone(),
+            symlink_watches: state.symlink_watches.clone(),
+        }
+    }
+
+    /// 执行一次迁移；选项无效或路径与进行中的迁移重叠时返回带消息键的错误
+    fn run(&self, options: MigrationOptions) -> Result<MigrationResult, KeyedError> {
+        validate_migration_options(&options)?;
+
+        // 拒绝与进行中的迁移重叠的路径，锁在本次迁移结束时释放
+        let source_path = PathBuf::from(&options.source_path);
+        let _migration_lock = self.locks
+            .try_acquire_migration_lock(&source_path, Path::new(&options.target_path))
+            .map_err(|e| KeyedError::with_detail(MessageKey::PreCheckFailed, e))?;
+
+        // 使用 block_on 来执行异步操作
+        let runtime = tokio::runtime::Handle::current();
+        let result = runtime.block_on(async {
+            let mut result = self.service.migrate_folder(options).await
+                .map_err(|e| KeyedError::with_detail(MessageKey::MigrationFailed, e))?;
+            self.service.recover_timed_out_migration(&mut result, &self.error_recovery_manager).await;
+            Ok::<_, KeyedError>(result)
+        })?;
+
+        // 吞吐量只按复制阶段实际写入的字节数和耗时计算
+        if result.success {
+            self.performance_optimizer.record_migration_throughput(
+                result.copied_bytes,
+                std::time::Duration::from_millis(result.copy_duration_ms),
+            );
+        }
+        // 新建的链接加入健康监测
+        if let MigrationResult { success: true, already_migrated: false, symlink_path: Some(target), .. } = &result {
+            if let Err(e) = self.symlink_watches.register(&source_path, Path::new(target)) {
+                warn!("登记链接监测失败: {}", e);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// 确认删除两阶段迁移保留的源数据
+#[tauri::command]
+fn confirm_source_deletion(token: String, state: State<'_, AppState>) -> Result<String, String> {
+    let source = state.migration_service.confirm_source_deletion(&token)
+        .map_err(|e| format!("删除源数据失败: {}", e))?;
+    info!("已确认删除源数据: {}", source.display());
+    Ok(source.display().to_string())
+}
+
+/// 批量迁移：以有限并发处理每个源/目标对，单项失败不会中止整批；每项与单项迁移走同一流程
+#[tauri::command]
+async fn migrate_batch(
+    items: Vec<MigrationOptions>,
+    max_parallel: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<BatchMigrationResult, String> {
+    info!("收到批量迁移请求，共 {} 个项目", items.len());
+    let max_parallel = max_parallel.unwrap_or_else(default_max_parallel_migrations);
+    if max_parallel == 0 {
+        return Err("最大并行数必须大于 0".to_string());
+    }
+
+    let copy_buffer_size = state.performance_optimizer.copy_buffer_size();
+    let items = items
+        .into_iter()
+        .map(|mut options| {
+            options.copy_buffer_size.get_or_insert(copy_buffer_size);
+            options
+        })
+        .collect();
 
     let service = &state.migration_service;
-    // 使用 block_on 来执行异步操作
+    service.reset_cancel_flag();
+    let runner = MigrationRunner::new(&state);
+    let mut batch = service.migrate_batch(items, max_parallel, move |options| runner.run(options)).await;
+
+    let locale = current_locale(&state);
+    for result in &mut batch.results {
+        result.localize(locale);
+    }
+    Ok(batch)
+}
+
+/// 估算迁移计划能为 C 盘释放多少空间，并给出迁移后 C 盘的预计剩余空间
+#[tauri::command]
+async fn estimate_space_freed(items: Vec<MigrationOptions>, state: State<'_, AppState>) -> Result<SpaceFreedEstimate, String> {
+    let service = state.migration_service.clone();
+    let estimate = tokio::task::spawn_blocking(move || {
+        service.estimate_space_freed(&items, &DiskAnalyzer::get_c_drive_path())
+    })
+    .await
+    .map_err(|e| format!("估算释放空间失败: {}", e))?;
+
+    info!("迁移计划预计释放 C 盘空间 {} ({} 个项目)", format_file_size(estimate.freed_bytes), estimate.counted_items);
+    Ok(estimate)
+}
+
+/// 迁移前检查源目录中无法读取的条目，供界面提前警告
+#[tauri::command]
+async fn scan_inaccessible_paths(source_path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let source = Path::new(&source_path);
+    if !source.exists() {
+        return Err("源路径不存在".to_string());
+    }
+
+    let service = state.migration_service.clone();
+    let source_buf = source.to_path_buf();
+    let inaccessible = tokio::task::spawn_blocking(move || service.scan_for_inaccessible_paths(&source_buf))
+        .await
+        .map_err(|e| format!("检查源目录权限失败: {}", e))?;
+    if !inaccessible.is_empty() {
+        warn!("源目录 {} 中有 {} 个无法读取的条目", source.display(), inaccessible.len());
+    }
+
+    Ok(inaccessible.iter().map(|path| path.display().to_string()).collect())
+}
+
+/// 检查已完成迁移的链接与目标是否仍然一致（可选重新读取目标内容）
+#[tauri::command]
+async fn verify_migration_integrity(
+    source_paths: Vec<String>,
+    recheck_contents: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<MigrationIntegrityReport>, String> {
+    let service = state.migration_service.clone();
+    let recheck_contents = recheck_contents.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        source_paths
+            .iter()
+            .map(|source| service.verify_migration_integrity(Path::new(source), recheck_contents))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("检查迁移完整性失败: {}", e))
+}
+
+/// 在系统文件管理器中显示路径（尽量选中该项）
+#[tauri::command]
+fn reveal_path(path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    // 链接本身也可显示，因此不跟随链接判断存在性
+    if std::fs::symlink_metadata(path).is_err() {
+        return Err(format!("路径不存在: {}", path.display()));
+    }
+
+    tauri_plugin_opener::reveal_item_in_dir(path)
+        .map_err(|e| format!("打开文件管理器失败: {}", e))?;
+    info!("已在文件管理器中显示: {}", path.display());
+
+    Ok(())
+}
+
+/// 预估迁移耗时：统计源目录大小和文件数，按实测（或配置的）吞吐量计算；
+/// 提供目标路径且该驱动器测过速时，按其写入速度修正
+#[tauri::command]
+async fn estimate_migration(
+    source_path: String,
+    target_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<MigrationEstimate, String> {
+    info!("收到迁移耗时预估请求: {}", source_path);
+
+    let source = Path::new(&source_path).to_path_buf();
+    if !source.is_dir() {
+        return Err("路径不存在或不是目录".to_string());
+    }
+
+    let PathTotals { size: total_bytes, file_count, .. } = tokio::task::spawn_blocking(move || {
+        DiskAnalyzer::new().measure_path(&source)
+    })
+    .await
+    .map_err(|e| format!("统计源目录失败: {}", e))??;
+
+    let estimated_seconds = match target_path.filter(|p| !p.trim().is_empty()) {
+        Some(target) => state.performance_optimizer.estimate_migration_seconds_to(total_bytes, Path::new(&target)),
+        None => state.performance_optimizer.estimate_migration_seconds(total_bytes),
+    };
+    info!("迁移预估: {} ({} 个文件)，约 {} 秒", format_file_size(total_bytes), file_count, estimated_seconds);
+
+    Ok(MigrationEstimate {
+        total_bytes,
+        file_count,
+        estimated_seconds,
+    })
+}
+
+/// 测试候选目标驱动器的读写速度，结果用于之后迁移到该驱动器的耗时预估
+#[tauri::command]
+async fn benchmark_drive(path: String, state: State<'_, AppState>) -> Result<DriveBenchmark, String> {
+    info!("收到驱动器测速请求: {}", path);
+
+    let target = PathBuf::from(&path);
+    let benchmark = tokio::task::spawn_blocking(move || {
+        performance_optimizer::benchmark_drive(&target, DRIVE_BENCHMARK_SIZE)
+    })
+    .await
+    .map_err(|e| format!("驱动器测速失败: {}", e))??;
+
+    state.performance_optimizer.record_drive_benchmark(benchmark.clone());
+    Ok(benchmark)
+}
+
+/// 设置备份存放目录，传空则恢复为系统临时目录
+#[tauri::command]
+async fn set_backup_root(path: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let backup_root = path.filter(|p| !p.trim().is_empty()).map(std::path::PathBuf::from);
+    if let Some(root) = &backup_root {
+        std::fs::create_dir_all(root).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    }
+
+    info!("备份目录设置为: {}", backup_root.as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "系统临时目录".to_string()));
+    state.error_recovery_manager.lock().await.set_backup_root(backup_root);
+    Ok(())
+}
+
+/// 获取禁止迁移进出的系统保护目录列表
+#[tauri::command]
+fn get_protected_paths(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.migration_service.protected_paths()
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// 添加系统保护目录，已存在时返回 false
+#[tauri::command]
+fn add_protected_path(path: String, state: State<'_, AppState>) -> Result<bool, String> {
+    if path.trim().is_empty() {
+        return Err("保护目录不能为空".to_string());
+    }
+    Ok(state.migration_service.add_protected_path(PathBuf::from(path.trim())))
+}
+
+/// 移除系统保护目录，不存在时返回 false
+#[tauri::command]
+fn remove_protected_path(path: String, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.migration_service.remove_protected_path(Path::new(path.trim())))
+}
+
+/// 撤销本次会话最近一次迁移：移除源位置的链接、恢复源目录并删除迁移目标
+#[tauri::command]
+async fn undo_last_migration(state: State<'_, AppState>) -> Result<MigrationUndoResult, String> {
+    let undone = match state.migration_service.last_completed_migration() {
+        Some((source, target)) => match state.migration_locks.try_acquire_migration_lock(&source, &target) {
+            Ok(lock) => {
+                let service = state.migration_service.clone();
+                let undone = tokio::task::spawn_blocking(move || service.undo_migration(&source, &target))
+                    .await
+                    .map_err(|e| format!("撤销任务执行失败: {}", e))
+                    .and_then(|result| result);
+                drop(lock);
+                undone
+            }
+            Err(e) => Err(e),
+        },
+        None => Err("没有可撤销的迁移".to_string()),
+    };
+
+    let logger = state.operation_logger.lock().await;
+    match undone {
+        Ok(result) => {
+            if let Err(e) = state.symlink_watches.unregister(Path::new(&result.source_path)) {
+                warn!("取消链接监测失败: {}", e);
+            }
+
+            let details = format!("从 {} 恢复", result.target_path);
+            match logger.log_operation_start(
+                OperationType::Undo,
+                result.source_path.clone(),
+                Some(result.target_path.clone()),
+                details,
+            ) {
+                Ok(mut log) => {
+                    if let Err(e) = logger.complete_operation(&mut log, 0, 0, 0, None) {
+                        warn!("记录撤销日志失败: {}", e);
+                    }
+                }
+                Err(e) => warn!("记录撤销日志失败: {}", e),
+            }
+
+            info!("迁移已撤销: {}", result.source_path);
+            Ok(result)
+        }
+        Err(e) => {
+            if let Ok(mut log) = logger.log_operation_start(OperationType::Undo, String::new(), None, "撤销迁移".to_string()) {
+                let _ = logger.fail_operation(&mut log, e.clone(), None);
+            }
+            Err(format!("撤销迁移失败: {}", e))
+        }
+    }
+}
+
+/// 从备份中只恢复指定的相对路径，用于撤销迁移的一部分
+#[tauri::command]
+async fn restore_backup_paths(backup_id: String, relative_paths: Vec<String>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let recovery_manager = state.error_recovery_manager.lock().await;
+    let restored = recovery_manager.restore_paths(&backup_id, &relative_paths).await
+        .map_err(|e| format!("部分恢复失败: {}", e))?;
+    Ok(restored.into_iter().map(|path| path.to_string_lossy().to_string()).collect())
+}
+
+/// 登记需要监测的迁移链接
+#[tauri::command]
+fn register_symlink_watch(link_path: String, target_path: String, state: State<'_, AppState>) -> Result<SymlinkWatch, String> {
+    state.symlink_watches.register(Path::new(&link_path), Path::new(&target_path))
+}
+
+/// 取消监测迁移链接，未登记时返回 false
+#[tauri::command]
+fn unregister_symlink_watch(link_path: String, state: State<'_, AppState>) -> Result<bool, String> {
+    state.symlink_watches.unregister(Path::new(&link_path))
+}
+
+/// 获取所有被监测的迁移链接
+#[tauri::command]
+fn list_symlink_watches(state: State<'_, AppState>) -> Result<Vec<SymlinkWatch>, String> {
+    Ok(state.symlink_watches.list())
+}
+
+/// 检查所有被监测的链接是否仍解析到迁移目标，返回失效的链接
+#[tauri::command]
+async fn check_all_symlinks(state: State<'_, AppState>) -> Result<SymlinkCheckReport, String> {
+    let watches = state.symlink_watches.clone();
+    let report = tokio::task::spawn_blocking(move || watches.check_all())
+        .await
+        .map_err(|e| format!("检查迁移链接失败: {}", e))?;
+    info!("迁移链接检查完成: 共 {} 个，失效 {} 个", report.checked, report.broken.len());
+    Ok(report)
+}
+
+/// 解析迁移后源位置的链接，返回链接类型及其真实指向
+#[tauri::command]
+fn resolve_migration_target(link_path: String) -> Result<LinkResolution, String> {
+    MigrationService::resolve_migration_target(Path::new(&link_path))
+}
+
+/// 把已迁移的目标移到另一个磁盘，并更新源位置的链接（失败时恢复原目标和原链接）
+#[tauri::command]
+async fn relocate_migrated_target(
+    link_path: String,
+    new_target_drive: String,
+    state: State<'_, AppState>,
+) -> Result<RelocationResult, String> {
+    let link_path = PathBuf::from(link_path);
+    let new_target_drive = PathBuf::from(new_target_drive);
+    // 只锁定链接和新目标，不影响同一磁盘上其他路径的迁移
+    let new_target = MigrationService::relocation_target(&link_path, &new_target_drive)?;
+    let _migration_lock = state.migration_locks
+        .try_acquire_migration_lock(&link_path, &new_target)?;
+
+    // 移动数据和校验内容都是阻塞的文件操作，放到阻塞线程池中执行
+    let service = state.migration_service.clone();
     let runtime = tokio::runtime::Handle::current();
-    runtime.block_on(service.migrate_folder(options))
+    let relocate_link = link_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        runtime.block_on(service.relocate_migrated_target(&relocate_link, &new_target_drive))
+    })
+    .await
+    .map_err(|e| format!("改换迁移目标失败: {}", e))??;
+
+    // 链接监测改为检查新目标
+    if let Err(e) = state.symlink_watches.register(&link_path, Path::new(&result.new_target)) {
+        warn!("登记链接监测失败: {}", e);
+    }
+    Ok(result)
+}
+
+/// 检查单个路径的迁移就绪情况（用于浏览目录时即时提示）；`measure_size` 为 true 时才统计大小
+#[tauri::command]
+async fn inspect_path(path: String, measure_size: Option<bool>, state: State<'_, AppState>) -> Result<PathInspection, String> {
+    let service = state.migration_service.clone();
+    let measure_size = measure_size.unwrap_or(false);
+    tokio::task::spawn_blocking(move || service.inspect_path(Path::new(&path), measure_size))
+        .await
+        .map_err(|e| format!("检查路径失败: {}", e))
 }
 
 /// 验证迁移路径
@@ -153,21 +872,50 @@ fn migrate_directory(
 fn validate_migration_path(
     source_path: String,
     target_path: String,
+    allow_nested_target: Option<bool>,
     state: State<'_, AppState>
-) -> Result<PathValidationResult, String> {
+) -> Result<PathValidationResult, ErrorInfo> {
     let source = Path::new(&source_path);
     let target = Path::new(&target_path);
 
-    let result = state.migration_service.file_operator().validate_migration_path(source, target);
-    let result = result.map_err(|e| e.to_string())?;
+    let result = state.migration_service.file_operator()
+        .validate_migration_path_with(source, target, false, allow_nested_target.unwrap_or(false))?;
+    let (key, message_detail) = if result.0 {
+        (MessageKey::ValidationPassed, None)
+    } else {
+        (MessageKey::ValidationFailed, Some(result.1))
+    };
+    let message = MessageCatalog::format(key, current_locale(&state), message_detail.as_deref());
     
     Ok(PathValidationResult {
         valid: result.0,
-        message: result.1,
-        suggestions: Vec::new(),
+        message,
+        suggestions: result.2,
+        message_key: Some(key),
+        message_detail,
     })
 }
 
+/// 当前界面语言
+fn current_locale(state: &AppState) -> Locale {
+    state.locale.read().map(|locale| *locale).unwrap_or_default()
+}
+
+/// 设置结果消息使用的语言
+#[tauri::command]
+fn set_locale(locale: Locale, state: State<'_, AppState>) -> Result<(), String> {
+    let mut current = state.locale.write().map_err(|e| format!("设置语言失败: {}", e))?;
+    *current = locale;
+    info!("结果消息语言已设置为 {:?}", locale);
+    Ok(())
+}
+
+/// 获取结果消息使用的语言
+#[tauri::command]
+fn get_locale(state: State<'_, AppState>) -> Result<Locale, String> {
+    Ok(current_locale(&state))
+}
+
 /// 获取磁盘信息
 #[tauri::command]
 fn get_disk_info() -> Result<Vec<types::DiskInfo>, String> {
@@ -208,6 +956,18 @@ async fn get_operation_logs(limit: u32, state: State<'_, AppState>) -> Result<Ve
     }
 }
 
+/// 按条件查询操作日志
+#[tauri::command]
+async fn query_operation_logs(filter: LogQueryFilter, state: State<'_, AppState>) -> Result<Vec<operation_logger::OperationLog>, String> {
+    let logger = state.operation_logger.clone();
+    let logger = logger.lock().await;
+    
+    match logger.query_logs(&filter) {
+        Ok(logs) => Ok(logs),
+        Err(e) => Err(format!("查询操作日志失败: {}", e))
+    }
+}
+
 /// 获取操作统计信息
 #[tauri::command]
 async fn get_operation_statistics(state: State<'_, AppState>) -> Result<OperationStatistics, String> {
@@ -220,6 +980,14 @@ async fn get_operation_statistics(state: State<'_, AppState>) -> Result<Operatio
     }
 }
 
+/// 按操作类型获取统计（次数、总字节数、平均耗时）
+#[tauri::command]
+async fn get_operation_statistics_by_type(state: State<'_, AppState>) -> Result<Vec<OperationTypeStatistics>, String> {
+    let logger = state.operation_logger.lock().await;
+    logger.get_statistics_by_type()
+        .map_err(|e| format!("获取分类操作统计失败: {}", e))
+}
+
 /// 导出操作日志
 #[tauri::command]
 async fn export_operation_logs(output_path: String, state: State<'_, AppState>) -> Result<bool, String> {
@@ -329,16 +1097,63 @@ async fn get_recovery_statistics(state: State<'_, AppState>) -> Result<RecoveryS
 
 /// 清理过期备份
 #[tauri::command]
-async fn cleanup_expired_backups(state: State<'_, AppState>) -> Result<u32, String> {
+async fn cleanup_expired_backups(state: State<'_, AppState>) -> Result<BackupCleanupReport, String> {
     let recovery_manager = state.error_recovery_manager.clone();
     let mut recovery_manager = recovery_manager.lock().await;
     
     match recovery_manager.cleanup_expired_backups() {
-        Ok(count) => Ok(count),
+        Ok(report) => Ok(report),
         Err(e) => Err(format!("清理过期备份失败: {}", e))
     }
 }
 
+/// 列出现有备份
+#[tauri::command]
+async fn list_backups(state: State<'_, AppState>) -> Result<Vec<BackupInfo>, String> {
+    let recovery_manager = state.error_recovery_manager.lock().await;
+    Ok(recovery_manager.list_backups())
+}
+
+/// 删除指定备份及其文件
+#[tauri::command]
+async fn delete_backup(backup_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let mut recovery_manager = state.error_recovery_manager.lock().await;
+    recovery_manager.delete_backup(&backup_id)
+        .map_err(|e| format!("删除备份失败: {}", e))
+}
+
+/// 列出备份目录中未登记的孤立备份
+#[tauri::command]
+async fn scan_orphaned_backups(state: State<'_, AppState>) -> Result<Vec<OrphanedBackup>, String> {
+    // 只在记录快照时持有锁，遍历和计算大小在阻塞线程池中进行
+    let scan = state.error_recovery_manager.lock().await.orphaned_backup_scan();
+    tokio::task::spawn_blocking(move || scan.run())
+        .await
+        .map_err(|e| format!("扫描孤立备份失败: {}", e))
+}
+
+/// 删除选定的孤立备份
+#[tauri::command]
+async fn purge_orphaned_backups(paths: Vec<String>, state: State<'_, AppState>) -> Result<BackupCleanupReport, String> {
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    let recovery_manager = state.error_recovery_manager.lock().await;
+    Ok(recovery_manager.purge_orphaned_backups(&paths))
+}
+
+/// 取消正在进行的备份，不完整的备份会被清理
+#[tauri::command]
+fn cancel_backup(state: State<'_, AppState>) -> Result<(), String> {
+    state.backup_control.cancel();
+    info!("备份取消请求已发送");
+    Ok(())
+}
+
+/// 获取当前备份进度
+#[tauri::command]
+fn get_backup_progress(state: State<'_, AppState>) -> Result<BackupProgress, String> {
+    Ok(state.backup_control.progress())
+}
+
 /// 测试错误恢复
 #[tauri::command]
 async fn test_error_recovery(state: State<'_, AppState>) -> Result<bool, String> {
@@ -367,7 +1182,6 @@ async fn test_error_recovery(state: State<'_, AppState>) -> Result<bool, String>
 #[tauri::command]
 async fn get_performance_stats(state: State<'_, AppState>) -> Result<performance_optimizer::PerformanceStats, String> {
     let optimizer = state.performance_optimizer.clone();
-    let optimizer = optimizer.lock().await;
     
     Ok(optimizer.get_performance_stats())
 }
@@ -376,7 +1190,6 @@ async fn get_performance_stats(state: State<'_, AppState>) -> Result<performance
 #[tauri::command]
 async fn optimize_disk_scan(path: String, state: State<'_, AppState>) -> Result<bool, String> {
     let optimizer = state.performance_optimizer.clone();
-    let optimizer = optimizer.lock().await;
     
     let path = std::path::Path::new(&path);
     optimizer.optimize_scan_operation(path, || {
@@ -387,11 +1200,21 @@ async fn optimize_disk_scan(path: String, state: State<'_, AppState>) -> Result<
     Ok(true)
 }
 
+/// 更新扫描的工作线程数和批大小，返回更新后的设置
+#[tauri::command]
+async fn update_scan_settings(
+    worker_threads: Option<usize>,
+    batch_size: Option<usize>,
+    state: State<'_, AppState>
+) -> Result<performance_optimizer::ScanSettings, String> {
+    info!("收到更新扫描设置请求: 工作线程 {:?}, 批大小 {:?}", worker_threads, batch_size);
+    state.performance_optimizer.update_scan_settings(worker_threads, batch_size)
+}
+
 /// 运行内存清理
 #[tauri::command]
 async fn run_memory_cleanup(state: State<'_, AppState>) -> Result<bool, String> {
     let optimizer = state.performance_optimizer.clone();
-    let optimizer = optimizer.lock().await;
     
     if optimizer.should_periodic_cleanup() {
         optimizer.perform_cleanup().await;
@@ -405,9 +1228,9 @@ async fn run_memory_cleanup(state: State<'_, AppState>) -> Result<bool, String>
 #[tauri::command]
 async fn get_performance_benchmark(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let optimizer = state.performance_optimizer.clone();
-    let optimizer = optimizer.lock().await;
     
     let stats = optimizer.get_performance_stats();
+    let throughput = optimizer.throughput_stats();
     
     // 创建基准测试报告
     let benchmark = serde_json::json!({
@@ -417,6 +1240,8 @@ async fn get_performance_benchmark(state: State<'_, AppState>) -> Result<serde_j
         "cache_size": stats.cache_size,
         "batch_queue_size": stats.batch_queue_size,
         "last_cleanup_seconds_ago": stats.last_cleanup_seconds_ago,
+        "migration_throughput_bytes_per_sec": throughput.average_bytes_per_sec,
+        "migration_throughput_samples": throughput.sample_count,
         "timestamp": chrono::Utc::now().to_rfc3339()
     });
     
@@ -469,20 +1294,39 @@ pub fn run() {
     
     // 初始化错误恢复管理器
     let recovery_config = ErrorRecoveryConfig::default();
-    let error_recovery_manager = ErrorRecoveryManager::new(recovery_config);
+    // 结果消息的语言由应用与错误恢复管理器共享
+    let locale = Arc::new(std::sync::RwLock::new(Locale::default()));
+    let error_recovery_manager = ErrorRecoveryManager::new(recovery_config).with_locale(locale.clone());
+    let backup_control = error_recovery_manager.backup_control();
+    // 备份进度经通道转发到前端（backup-progress 事件）
+    let (backup_progress_tx, mut backup_progress_rx) = tokio::sync::mpsc::unbounded_channel::<BackupProgress>();
+    backup_control.set_progress_sender(Some(backup_progress_tx));
     info!("错误恢复管理器初始化成功");
     
     // 初始化性能优化器
     let perf_config = PerformanceConfig::default();
-    let performance_optimizer = PerformanceOptimizer::new(perf_config);
+    let performance_optimizer = Arc::new(
+        PerformanceOptimizer::new(perf_config)
+            .with_throughput_stats_file(log_dir.join(performance_optimizer::THROUGHPUT_STATS_FILE))
+    );
     info!("性能优化器初始化成功");
+
+    let mut disk_analyzer = DiskAnalyzer::new();
+    disk_analyzer.set_performance_optimizer(performance_optimizer.clone());
     
     let app_state = AppState {
-        disk_analyzer: Arc::new(Mutex::new(DiskAnalyzer::new())),
-        migration_service: Arc::new(MigrationService::new()),
+        disk_analyzer: Arc::new(Mutex::new(disk_analyzer)),
+        migration_service: Arc::new(MigrationService::new()
+            .with_pending_deletions_file(log_dir.join(PENDING_DELETIONS_FILE))
+            .with_integrity_baselines_file(log_dir.join(INTEGRITY_BASELINES_FILE))),
         operation_logger: Arc::new(Mutex::new(operation_logger)),
         error_recovery_manager: Arc::new(Mutex::new(error_recovery_manager)),
-        performance_optimizer: Arc::new(Mutex::new(performance_optimizer)),
+        backup_control,
+        performance_optimizer,
+        migration_locks: Arc::new(MigrationLockRegistry::new()),
+        locale,
+        symlink_watches: Arc::new(SymlinkWatchList::open(log_dir.join(SYMLINK_WATCH_FILE))),
+        appdata_pager: Arc::new(AppDataItemPager::new()),
     };
 
     info!("应用程序状态初始化完成");
@@ -490,47 +1334,121 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(app_state)
+        .setup(move |app| {
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(progress) = backup_progress_rx.recv().await {
+                    if let Err(e) = handle.emit("backup-progress", &progress) {
+                        error!("发送备份进度到前端失败: {}", e);
+                    }
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             scan_directory,
+            scan_directory_with_summary,
+            scan_directory_streaming,
+            set_scan_budget,
+            set_scan_file_visibility,
+            set_allowed_scan_roots,
+            get_path_size,
+            scan_shallow,
+            export_scan_result,
+            diff_scan_files,
+            diff_scans,
+            get_top_largest_directories,
             get_scan_progress,
             stop_scan,
+            pause_scan,
+            resume_scan,
+            stop_migration,
+            cancel_and_rollback_migration,
             migrate_directory,
+            confirm_source_deletion,
+            estimate_migration,
+            benchmark_drive,
+            migrate_batch,
+            scan_inaccessible_paths,
+            verify_migration_integrity,
+            reveal_path,
+            undo_last_migration,
+            restore_backup_paths,
+            register_symlink_watch,
+            unregister_symlink_watch,
+            list_symlink_watches,
+            check_all_symlinks,
+            estimate_space_freed,
+            set_backup_root,
             validate_migration_path,
+            inspect_path,
+            resolve_migration_target,
+            relocate_migrated_target,
+            set_locale,
+            get_locale,
             get_disk_info,
             path_exists,
             format_size,
             get_operation_logs,
+            query_operation_logs,
             get_operation_statistics,
+            get_operation_statistics_by_type,
             export_operation_logs,
             cleanup_old_operation_logs,
             get_recovery_statistics,
             cleanup_expired_backups,
+            list_backups,
+            get_protected_paths,
+            add_protected_path,
+            remove_protected_path,
+            delete_backup,
+            scan_orphaned_backups,
+            purge_orphaned_backups,
+            cancel_backup,
+            get_backup_progress,
             test_error_recovery,
             run_comprehensive_tests,
             run_test_suite,
             generate_test_report,
             get_performance_stats,
             optimize_disk_scan,
+            update_scan_settings,
             run_memory_cleanup,
             get_performance_benchmark,
             scan_appdata,
+            list_migrated_appdata_items,
+            get_appdata_items_page,
             scan_appdata_streaming,
             get_appdata_path,
+            get_appdata_path_for_user,
             migrate_appdata_items,
             get_available_drives,
+            get_drive_suggestions,
             get_migration_progress,
-            validate_appdata_migration_options
+            validate_appdata_migration_options,
+            get_appdata_blocklist,
+            get_appdata_categories
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 /// 扫描AppData目录（新版本，支持配置）
+///
+/// 全部一级项目按排序缓存，供 `get_appdata_items_page` 分页读取；指定 `page_size` 时结果中的
+/// `first_level_items` 只包含第一页。
 #[tauri::command]
-async fn scan_appdata(config: Option<AppDataConfig>, state: State<'_, AppState>) -> Result<AppDataInfo, String> {
+async fn scan_appdata(
+    config: Option<AppDataConfig>,
+    base_path: Option<String>,
+    page_size: Option<usize>,
+    state: State<'_, AppState>
+) -> Result<AppDataInfo, String> {
     info!("收到扫描AppData目录请求");
     
     let mut analyzer = AppDataAnalyzer::new();
+    let sort_order = config.as_ref()
+        .map_or_else(|| AppDataConfig::default().sort_order, |config| config.sort_order.clone());
     
     // 应用配置（如果有）
     if let Some(config) = config {
@@ -545,13 +1463,23 @@ async fn scan_appdata(config: Option<AppDataConfig>, state: State<'_, AppState>)
     
     info!("开始扫描AppData目录");
     
-    match analyzer.scan_appdata().await {
+    let scan_result = match base_path {
+        Some(base_path) => analyzer.scan_appdata_at(Path::new(&base_path)).await,
+        None => analyzer.scan_appdata().await,
+    };
+    
+    match scan_result {
         Ok(result) => {
             info!("AppData扫描完成 - 总大小: {}, 一级项目数量: {}, 大项目数量: {}, 耗时: {}ms",
                   format_file_size(result.total_size),
                   result.first_level_items.len(),
                   result.large_items.len(),
                   result.scan_time_ms);
+            state.appdata_pager.store(&result.first_level_items, &sort_order);
+            let mut result = result;
+            if let Some(page_size) = page_size {
+                result.first_level_items = state.appdata_pager.page(0, page_size)?.items;
+            }
             Ok(result)
         }
         Err(e) => {
@@ -561,38 +1489,86 @@ async fn scan_appdata(config: Option<AppDataConfig>, state: State<'_, AppState>)
     }
 }
 
+/// 分页读取最近一次 AppData 扫描的一级项目（按扫描时的排序），`limit` 未指定时取默认每页数量
+#[tauri::command]
+fn get_appdata_items_page(offset: usize, limit: Option<usize>, state: State<'_, AppState>) -> Result<AppDataItemsPage, String> {
+    state.appdata_pager.page(offset, limit.unwrap_or(DEFAULT_APPDATA_PAGE_SIZE))
+}
+
+/// 列出已迁移（原位置是指向其他磁盘的链接）的 AppData 项目
+#[tauri::command]
+async fn list_migrated_appdata_items(base_path: Option<String>) -> Result<Vec<AppDataFirstLevelItem>, String> {
+    let analyzer = AppDataAnalyzer::new();
+    let info = match base_path {
+        Some(base_path) => analyzer.scan_appdata_at(Path::new(&base_path)).await,
+        None => analyzer.scan_appdata().await,
+    }.map_err(|e| format!("扫描已迁移的AppData项目失败: {}", e))?;
+
+    let migrated: Vec<AppDataFirstLevelItem> = info.first_level_items.into_iter()
+        .filter(|item| item.is_symlink)
+        .collect();
+    info!("已迁移的AppData项目: {} 个, 已释放空间: {}", migrated.len(), format_file_size(info.already_freed_bytes));
+    Ok(migrated)
+}
+
 /// 迁移AppData项目
 #[tauri::command]
-async fn migrate_appdata_items(options: AppDataMigrationOptions, state: State<'_, AppState>) -> Result<MigrationResult, String> {
+async fn migrate_appdata_items(options: AppDataMigrationOptions, state: State<'_, AppState>) -> Result<MigrationResult, ErrorInfo> {
     info!("收到AppData迁移请求 - 目标盘符: {}, 项目数量: {}",
           options.target_drive, options.source_items.len());
     
     // 验证目标盘符
     let target_drive = Path::new(&options.target_drive);
     if !target_drive.exists() {
-        return Err(format!("目标盘符不存在: {}", options.target_drive));
+        return Err(FileOperationError::PathNotFound(format!("目标盘符 {}", options.target_drive)).into());
     }
     
     let mut all_results = Vec::new();
     let mut total_migrated_size = 0u64;
     let mut success_count = 0;
     let mut failure_count = 0;
+
+    state.migration_service.reset_cancel_flag();
+    let runner = MigrationRunner::new(&state);
     
     // 逐个迁移项目
     for source_item in &options.source_items {
+        // 每个项目开始前检查是否已取消，已完成的项目保持原样
+        if state.migration_service.is_cancelled() {
+            let reason = state.migration_service.cancellation_reason().unwrap_or_default();
+            let cancelled = FileOperationError::OperationCancelled(format!(
+                "AppData迁移已取消（{}） - 成功: {}, 失败: {}, 未处理: {}",
+                reason.description(), success_count, failure_count,
+                options.source_items.len() - success_count - failure_count
+            ));
+            warn!("{}", cancelled);
+            return Err(cancelled.into());
+        }
+
         let source_path = Path::new(source_item);
         if !source_path.exists() {
             warn!("源项目不存在，跳过: {}", source_item);
             failure_count += 1;
             continue;
         }
+
+        if let (false, message) = AppDataAnalyzer::validate_migration_item(source_path, options.allow_risky) {
+            warn!("跳过不可迁移的项目: {}, 原因: {}", source_item, message);
+            failure_count += 1;
+            continue;
+        }
         
-        // 构建目标路径（保持相对路径结构）
-        let item_name = source_path.file_name()
-            .ok_or_else(|| format!("无法获取项目名称: {}", source_item))?
-            .to_string_lossy();
+        // 按选择的布局构建目标路径（默认保留 Local/LocalLow/Roaming 子目录结构，避免同名项目冲突）
+        let target_path = AppDataAnalyzer::build_migration_target(target_drive, source_path, &options.target_layout)
+            .ok_or_else(|| FileOperationError::InvalidPath(format!("无法构建目标路径: {} (布局: {:?})", source_item, options.target_layout)))?;
         
-        let target_path = target_drive.join(item_name.to_string());
+        if let Some(target_parent) = target_path.parent() {
+            if let Err(e) = std::fs::create_dir_all(target_parent) {
+                error!("创建目标目录失败: {}, 错误: {}", target_parent.display(), e);
+                failure_count += 1;
+                continue;
+            }
+        }
         
         info!("迁移项目: {} -> {}", source_item, target_path.display());
         
@@ -601,18 +1577,19 @@ async fn migrate_appdata_items(options: AppDataMigrationOptions, state: State<'_
             target_path: target_path.to_string_lossy().to_string(),
             create_symlink: options.create_symlink,
             delete_source: options.delete_source,
+            copy_buffer_size: Some(state.performance_optimizer.copy_buffer_size()),
+            ..Default::default()
         };
         
-        // 验证迁移选项
-        if let Err(e) = validate_migration_options(&migration_options) {
-            error!("迁移选项验证失败: {}", e);
-            failure_count += 1;
-            continue;
-        }
-        
-        // 执行迁移
-        match state.migration_service.migrate_folder(migration_options).await {
-            Ok(result) => {
+        // 与单项迁移走同一流程（校验选项、登记路径锁、处理复制超时、记录吞吐量和链接监测），
+        // 在阻塞线程池中执行；选项无效或路径与进行中的迁移重叠时跳过该项目
+        let runner = runner.clone();
+        match tokio::task::spawn_blocking(move || runner.run(migration_options)).await {
+            Ok(Err(e)) => {
+                warn!("跳过项目: {}, 原因: {}", source_item, e);
+                failure_count += 1;
+            }
+            Ok(Ok(result)) => {
                 if result.success {
                     success_count += 1;
                     // 估算迁移大小（简化处理）
@@ -645,6 +1622,7 @@ async fn migrate_appdata_items(options: AppDataMigrationOptions, state: State<'_
         source_path: format!("{}个项目", options.source_items.len()),
         target_path: options.target_drive.clone(),
         symlink_path: if options.create_symlink { Some(format!("创建了{}个符号链接", success_count)) } else { None },
+        ..Default::default()
     })
 }
 
@@ -671,6 +1649,31 @@ fn get_available_drives() -> Result<Vec<String>, String> {
     Ok(drives)
 }
 
+/// 获取目标盘建议（按可用空间降序，并标记是否足以容纳 required_bytes）
+#[tauri::command]
+fn get_drive_suggestions(required_bytes: u64) -> Result<Vec<types::DriveSuggestion>, String> {
+    use sysinfo::{DiskExt, System, SystemExt};
+
+    let mut system = System::new();
+    system.refresh_disks_list();
+
+    let drives = system.disks().iter()
+        .map(|disk| types::DriveSuggestion {
+            name: disk.name().to_string_lossy().to_string(),
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            total_space: disk.total_space(),
+            free_space: disk.available_space(),
+            recommended: false,
+        })
+        .collect();
+
+    let suggestions = types::rank_drive_suggestions(drives, required_bytes);
+    info!("目标盘建议: 需要 {}, 推荐 {} 个",
+          format_file_size(required_bytes),
+          suggestions.iter().filter(|d| d.recommended).count());
+    Ok(suggestions)
+}
+
 /// 获取迁移进度（用于实时进度报告）
 #[tauri::command]
 fn get_migration_progress(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
@@ -680,61 +1683,52 @@ fn get_migration_progress(state: State<'_, AppState>) -> Result<serde_json::Valu
         "current_item": "暂无迁移任务",
         "progress": 0,
         "total_items": 0,
-        "estimated_time_remaining": 0
+        "estimated_time_remaining": 0,
+        "active_migrations": state.migration_locks.active_count()
     }))
 }
 
 /// 验证AppData迁移选项
 #[tauri::command]
-fn validate_appdata_migration_options(options: AppDataMigrationOptions) -> Result<serde_json::Value, String> {
+async fn validate_appdata_migration_options(options: AppDataMigrationOptions, state: State<'_, AppState>) -> Result<AppDataPlanValidation, String> {
     info!("验证AppData迁移选项 - 目标盘符: {}, 项目数量: {}", options.target_drive, options.source_items.len());
     
-    let mut validation_results = Vec::new();
-    let mut valid_count = 0;
-    
     // 验证目标盘符
     let target_drive_path = Path::new(&options.target_drive);
     if !target_drive_path.exists() {
         return Err(format!("目标盘符不存在: {}", options.target_drive));
     }
     
-    // 验证每个源项目
-    for source_item in &options.source_items {
-        let source_path = Path::new(source_item);
-        let item_validation = if !source_path.exists() {
-            serde_json::json!({
-                "path": source_item,
-                "valid": false,
-                "message": "源路径不存在"
-            })
-        } else if !source_path.is_dir() && !source_path.is_file() {
-            serde_json::json!({
-                "path": source_item,
-                "valid": false,
-                "message": "路径既不是文件也不是目录"
-            })
-        } else {
-            valid_count += 1;
-            serde_json::json!({
-                "path": source_item,
-                "valid": true,
-                "message": "路径有效"
-            })
-        };
-        
-        validation_results.push(item_validation);
+    // 逐项验证并统计大小需要遍历目录，放到阻塞线程池中执行
+    let service = state.migration_service.clone();
+    let plan = tokio::task::spawn_blocking(move || {
+        let available_bytes = detect_drive_status(Path::new(&options.target_drive))
+            .map(|status| status.available_space);
+        AppDataAnalyzer::validate_migration_plan(&options, &service, available_bytes, &running_process_names())
+    })
+    .await
+    .map_err(|e| format!("验证迁移选项失败: {}", e))?;
+
+    info!("{}", plan.summary);
+    if !plan.reasons.is_empty() {
+        warn!("AppData迁移计划未通过验证: {}", plan.reasons.join("; "));
     }
-    
-    let summary = format!("验证完成：{}/{} 个项目有效", valid_count, options.source_items.len());
-    info!("{}", summary);
-    
-    Ok(serde_json::json!({
-        "valid": valid_count == options.source_items.len(),
-        "items": validation_results,
-        "summary": summary,
-        "target_drive_valid": true,
-        "target_drive": options.target_drive
-    }))
+    for warning in &plan.warnings {
+        warn!("{}", warning);
+    }
+    Ok(plan)
+}
+
+/// 获取AppData迁移黑名单（相对于AppData目录）
+#[tauri::command]
+fn get_appdata_blocklist() -> Result<Vec<String>, String> {
+    Ok(APPDATA_BLOCKLIST.iter().map(|entry| entry.to_string()).collect())
+}
+
+/// 按分类汇总AppData项目大小（用于分类占比图表）
+#[tauri::command]
+fn get_appdata_categories(items: Vec<AppDataFirstLevelItem>) -> Result<Vec<AppDataCategorySummary>, String> {
+    Ok(AppDataAnalyzer::category_sizes(&items))
 }
 
 /// 获取AppData路径信息
@@ -752,16 +1746,35 @@ fn get_appdata_path() -> Result<String, String> {
     }
 }
 
+/// 获取指定用户（用户名或配置文件目录）的AppData路径，供管理员扫描和迁移其他账户
+#[tauri::command]
+fn get_appdata_path_for_user(username: String) -> Result<String, String> {
+    match AppDataAnalyzer::get_appdata_path_for_user(&username) {
+        Ok(path) => {
+            info!("获取用户 {} 的AppData路径: {}", username, path.display());
+            Ok(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            error!("获取用户 {} 的AppData路径失败: {}", username, e);
+            Err(format!("获取用户AppData路径失败: {}", e))
+        }
+    }
+}
+
 /// 流式扫描AppData目录 - 使用Tauri事件系统实现实时推送
 #[tauri::command]
 async fn scan_appdata_streaming(
     config: Option<AppDataConfig>,
+    concurrency: Option<usize>,
+    event_throttle_ms: Option<u64>,
     window: tauri::Window,
-    _state: State<'_, AppState>
+    state: State<'_, AppState>
 ) -> Result<AppDataInfo, String> {
     info!("收到流式扫描AppData目录请求");
     
     let mut analyzer = AppDataAnalyzer::new();
+    let sort_order = config.as_ref()
+        .map_or_else(|| AppDataConfig::default().sort_order, |config| config.sort_order.clone());
     
     // 应用配置（如果有）
     if let Some(config) = config.clone() {
@@ -776,7 +1789,13 @@ async fn scan_appdata_streaming(
     
     // 创建事件通道
     let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel::<ScanEvent>();
-    let streaming_scanner = StreamingAppDataScanner::new(analyzer, event_tx);
+    let mut options = StreamingScanOptions::default();
+    if let Some(concurrency) = concurrency {
+        options.concurrency = concurrency.max(1);
+    }
+    options.event_throttle = event_throttle_ms.map(tokio::time::Duration::from_millis);
+    info!("流式扫描选项: 并发上限={}, 事件间隔={:?}", options.concurrency, options.event_throttle);
+    let streaming_scanner = StreamingAppDataScanner::new(analyzer, event_tx).with_options(options);
     
     // 启动事件转发任务 - 将扫描事件转发到Tauri前端
     let window_clone = window.clone();
@@ -787,9 +1806,6 @@ async fn scan_appdata_streaming(
                 error!("发送扫描事件到前端失败: {}", e);
                 break;
             }
-            
-            // 添加小延迟避免事件发送过快
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         }
     });
     
@@ -797,6 +1813,8 @@ async fn scan_appdata_streaming(
     
     // 执行流式扫描
     let scan_result = streaming_scanner.scan_appdata_streaming().await;
+    // 释放扫描器持有的发送端，使转发任务在事件发送完毕后结束
+    drop(streaming_scanner);
     
     // 等待事件转发任务完成
     if let Err(e) = event_forward_task.await {
@@ -810,6 +1828,7 @@ async fn scan_appdata_streaming(
                   result.first_level_items.len(),
                   result.large_items.len(),
                   result.scan_time_ms);
+            state.appdata_pager.store(&result.first_level_items, &sort_order);
             
             // 发送最终的扫描完成事件
             let _ = window.emit("appdata-scan-complete", &serde_json::json!({
@@ -832,3 +1851,56 @@ async fn scan_appdata_streaming(
     
     scan_result
 }
+
+#[cfg(test)]
+mod command_tests {
+    use super::*;
+
+    #[test]
+    fn test_reveal_path_rejects_missing_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let error = reveal_path(missing.display().to_string()).unwrap_err();
+        assert!(error.contains("路径不存在"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_migration_on_blocking_pool_can_be_cancelled() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        std::fs::create_dir_all(&source).unwrap();
+        for i in 0..2000 {
+            std::fs::write(source.join(format!("file_{:04}.bin", i)), vec![0u8; 16 * 1024]).unwrap();
+        }
+
+        let runner = MigrationRunner {
+            service: Arc::new(MigrationService::new()),
+            locks: Arc::new(MigrationLockRegistry::new()),
+            error_recovery_manager: Arc::new(Mutex::new(ErrorRecoveryManager::new(ErrorRecoveryConfig::default()))),
+            performance_optimizer: Arc::new(PerformanceOptimizer::new(PerformanceConfig::default())),
+            symlink_watches: Arc::new(SymlinkWatchList::open(temp_dir.path().join(SYMLINK_WATCH_FILE))),
+        };
+        let service = runner.service.clone();
+        let options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            ..Default::default()
+        };
+
+        // 与 migrate_directory 相同，迁移在阻塞线程池中进行，运行时仍可处理取消请求
+        let migration = tokio::task::spawn_blocking(move || runner.run(options));
+        let staging = MigrationService::staging_path(&target);
+        while std::fs::read_dir(&staging).map(|entries| entries.count()).unwrap_or(0) == 0 {
+            assert!(!migration.is_finished(), "迁移在取消前已结束");
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+        service.cancel_migration(CancellationReason::UserRequested);
+
+        let result = migration.await.unwrap().unwrap();
+        assert!(!result.success);
+        assert_eq!(result.cancellation_reason, Some(CancellationReason::UserRequested));
+        assert!(source.join("file_0000.bin").exists());
+    }
+}
@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+/// 结果消息的稳定键，序列化为 `模块.事件` 形式，供前端和脚本识别结果而不依赖文案
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MessageKey {
+    #[serde(rename = "migration.succeeded")]
+    MigrationSucceeded,
+    #[serde(rename = "migration.already_completed")]
+    MigrationAlreadyCompleted,
+    #[serde(rename = "migration.source_deletion_failed")]
+    SourceDeletionFailed,
+    #[serde(rename = "migration.pre_check_failed")]
+    PreCheckFailed,
+    #[serde(rename = "migration.cancelled")]
+    MigrationCancelled,
+    #[serde(rename = "migration.failed")]
+    MigrationFailed,
+    #[serde(rename = "migration.copy_failed")]
+    CopyFailed,
+    #[serde(rename = "migration.copy_timed_out")]
+    CopyTimedOut,
+    #[serde(rename = "migration.copy_verification_failed")]
+    CopyVerificationFailed,
+    #[serde(rename = "migration.commit_failed")]
+    CommitFailed,
+    #[serde(rename = "migration.link_failed")]
+    LinkFailed,
+    #[serde(rename = "validation.passed")]
+    ValidationPassed,
+    #[serde(rename = "validation.failed")]
+    ValidationFailed,
+    #[serde(rename = "validation.source_empty")]
+    ValidationSourceEmpty,
+    #[serde(rename = "validation.target_empty")]
+    ValidationTargetEmpty,
+    #[serde(rename = "validation.same_path")]
+    ValidationSamePath,
+    #[serde(rename = "validation.invalid_exclude_path")]
+    ValidationInvalidExcludePath,
+    #[serde(rename = "validation.exclude_conflict")]
+    ValidationExcludeConflict,
+    #[serde(rename = "validation.nested_conflict")]
+    ValidationNestedConflict,
+    #[serde(rename = "validation.merge_skip_conflict")]
+    ValidationMergeSkipConflict,
+    #[serde(rename = "recovery.retry_succeeded")]
+    RecoveryRetrySucceeded,
+    #[serde(rename = "recovery.rollback_succeeded")]
+    RecoveryRollbackSucceeded,
+    #[serde(rename = "recovery.partial_rollback_succeeded")]
+    RecoveryPartialRollbackSucceeded,
+    #[serde(rename = "recovery.emergency_rollback_succeeded")]
+    RecoveryEmergencyRollbackSucceeded,
+    #[serde(rename = "recovery.skipped")]
+    RecoverySkipped,
+    #[serde(rename = "recovery.aborted")]
+    RecoveryAborted,
+    #[serde(rename = "recovery.manual_required")]
+    RecoveryManualRequired,
+}
+
+/// 按语言查找结果消息的文案表
+pub struct MessageCatalog;
+
+impl MessageCatalog {
+    /// 获取指定语言下的消息文案
+    pub fn message(key: MessageKey, locale: Locale) -> &'static str {
+        match locale {
+            Locale::ZhCn => Self::zh_cn(key),
+            Locale::EnUs => Self::en_us(key),
+        }
+    }
+
+    /// 生成完整消息，附带详情时以 `文案: 详情` 的形式拼接
+    ///
+    /// 详情来自底层错误，只有中文；其他语言的消息不拼接详情，详情仍可单独展示。
+    pub fn format(key: MessageKey, locale: Locale, detail: Option<&str>) -> String {
+        let message = Self::message(key, locale);
+        match (detail, locale) {
+            (Some(detail), Locale::ZhCn) => format!("{}: {}", message, detail),
+            _ => message.to_string(),
+        }
+    }
+
+    fn zh_cn(key: MessageKey) -> &'static str {
+        match key {
+            MessageKey::MigrationSucceeded => "迁移成功完成",
+            MessageKey::MigrationAlreadyCompleted => "迁移已完成，无需重复执行",
+            MessageKey::SourceDeletionFailed => "迁移成功，但源目录删除失败，原数据仍保留",
+            MessageKey::PreCheckFailed => "预检查失败",
+            MessageKey::MigrationCancelled => "迁移已取消",
+            MessageKey::MigrationFailed => "迁移失败",
+            MessageKey::CopyFailed => "复制失败",
+            MessageKey::CopyTimedOut => "复制超时，已中止迁移",
+            MessageKey::CopyVerificationFailed => "复制验证失败",
+            MessageKey::CommitFailed => "提交迁移目标失败",
+            MessageKey::LinkFailed => "创建链接失败，已恢复源目录",
+            MessageKey::ValidationPassed => "路径验证通过",
+            MessageKey::ValidationFailed => "路径验证未通过",
+            MessageKey::ValidationSourceEmpty => "源路径不能为空",
+            MessageKey::ValidationTargetEmpty => "目标路径不能为空",
+            MessageKey::ValidationSamePath => "源路径和目标路径不能相同",
+            MessageKey::ValidationInvalidExcludePath => "排除路径必须是源目录内的相对路径",
+            MessageKey::ValidationExcludeConflict => "排除子路径时不能创建链接或删除源目录，否则排除的内容会随源目录一并丢失",
+            MessageKey::ValidationNestedConflict => "目标位于源目录内部时不能创建链接或删除源目录，否则目标会随源目录一并丢失",
+            MessageKey::ValidationMergeSkipConflict => "合并时跳过已存在的文件不能与创建链接或删除源目录同时使用，否则被跳过的源文件会丢失",
+            MessageKey::RecoveryRetrySucceeded => "操作通过重试恢复成功",
+            MessageKey::RecoveryRollbackSucceeded => "操作通过回滚恢复成功",
+            MessageKey::RecoveryPartialRollbackSucceeded => "已撤销失败操作写入的内容，原有数据保持不变",
+            MessageKey::RecoveryEmergencyRollbackSucceeded => "通过紧急备份回滚成功",
+            MessageKey::RecoverySkipped => "操作已跳过，继续执行后续步骤",
+            MessageKey::RecoveryAborted => "操作已中止，需要手动干预",
+            MessageKey::RecoveryManualRequired => "错误需要手动处理，请查看日志获取详细信息",
+        }
+    }
+
+    fn en_us(key: MessageKey) -> &'static str {
+        match key {
+            MessageKey::MigrationSucceeded => "Migration completed successfully",
+            MessageKey::MigrationAlreadyCompleted => "Migration already completed, nothing to do",
+            MessageKey::SourceDeletionFailed => "Migration succeeded, but the source could not be deleted and was kept",
+            MessageKey::PreCheckFailed => "Pre-migration check failed",
+            MessageKey::MigrationCancelled => "Migration was cancelled",
+            MessageKey::MigrationFailed => "Migration failed",
+            MessageKey::CopyFailed => "Copy failed",
+            MessageKey::CopyTimedOut => "Copy timed out, the migration was aborted",
+            MessageKey::CopyVerificationFailed => "Copy verification failed",
+            MessageKey::CommitFailed => "Failed to commit the migration target",
+            MessageKey::LinkFailed => "Failed to create the link, the source was restored",
+            MessageKey::ValidationPassed => "Path validation passed",
+            MessageKey::ValidationFailed => "Path validation failed",
+            MessageKey::ValidationSourceEmpty => "The source path must not be empty",
+            MessageKey::ValidationTargetEmpty => "The target path must not be empty",
+            MessageKey::ValidationSamePath => "The source and target must be different locations",
+            MessageKey::ValidationInvalidExcludePath => "Excluded paths must be relative paths inside the source",
+            MessageKey::ValidationExcludeConflict => "Excluding subpaths cannot be combined with linking or deleting the source, the excluded content would be lost",
+            MessageKey::ValidationNestedConflict => "A target inside the source cannot be combined with linking or deleting the source, the target would be lost",
+            MessageKey::ValidationMergeSkipConflict => "Skipping existing files while merging cannot be combined with linking or deleting the source, the skipped source files would be lost",
+            MessageKey::RecoveryRetrySucceeded => "Operation recovered after retrying",
+            MessageKey::RecoveryRollbackSucceeded => "Operation recovered by rolling back",
+            MessageKey::RecoveryPartialRollbackSucceeded => "Undid the content written by the failed operation, existing data was kept",
+            MessageKey::RecoveryEmergencyRollbackSucceeded => "Rolled back from an emergency backup",
+            MessageKey::RecoverySkipped => "Operation skipped, continuing with the remaining steps",
+            MessageKey::RecoveryAborted => "Operation aborted, manual intervention required",
+            MessageKey::RecoveryManualRequired => "The error must be handled manually, see the log for details",
+        }
+    }
+}
+
+/// 带消息键的错误，可按语言生成消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyedError {
+    pub key: MessageKey,
+    pub detail: Option<String>,
+}
+
+impl KeyedError {
+    pub fn new(key: MessageKey) -> Self {
+        Self { key, detail: None }
+    }
+
+    pub fn with_detail(key: MessageKey, detail: impl Into<String>) -> Self {
+        Self { key, detail: Some(detail.into()) }
+    }
+
+    /// 指定语言下的错误消息
+    pub fn message(&self, locale: Locale) -> String {
+        MessageCatalog::format(self.key, locale, self.detail.as_deref())
+    }
+}
+
+impl std::fmt::Display for KeyedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(Locale::default()))
+    }
+}
+
+impl From<KeyedError> for String {
+    fn from(error: KeyedError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_key_and_locale_serialization() {
+        assert_eq!(serde_json::to_string(&MessageKey::MigrationSucceeded).unwrap(), "\"migration.succeeded\"");
+        assert_eq!(serde_json::to_string(&Locale::EnUs).unwrap(), "\"en-US\"");
+        assert_eq!(serde_json::from_str::<Locale>("\"zh-CN\"").unwrap(), Locale::ZhCn);
+    }
+
+    #[test]
+    fn test_format_with_detail() {
+        assert_eq!(MessageCatalog::format(MessageKey::CopyFailed, Locale::ZhCn, Some("磁盘已满")), "复制失败: 磁盘已满");
+        assert_eq!(MessageCatalog::format(MessageKey::ValidationPassed, Locale::EnUs, None), "Path validation passed");
+        // 详情只有中文，英文消息不拼接
+        assert_eq!(MessageCatalog::format(MessageKey::CopyFailed, Locale::EnUs, Some("磁盘已满")), "Copy failed");
+    }
+}
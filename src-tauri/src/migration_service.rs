@@ -1,40 +1,577 @@
 use std::path::{Path, PathBuf};
 use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
 use log::{info, error, warn};
-use crate::file_operations::{FileOperator, FileOperationResult, FileOperationError};
-use crate::disk_analyzer::DirectoryInfo;
+use crate::file_operations::{FileOperator, FileOperationResult, FileOperationError, ConflictPolicy, CopyOptions, CopyJournal, FileAction, FileActionRecord, FileResult, LinkType, CopyCheckpoint, FilesystemKind, DriveStatus, detect_filesystem, detect_drive_status, detect_link_type, nested_target_under, resolve_with_existing_ancestor, check_path_length, display_path, extended_length_path, MAX_PATH};
+use crate::disk_analyzer::{DirectoryInfo, DiskAnalyzer, format_file_size};
+use crate::messages::{KeyedError, Locale, MessageCatalog, MessageKey};
+use crate::types::CancellationReason;
+use crate::error_recovery::{ErrorRecoveryManager, RecoveryContext, RecoveryResult, MIGRATION_BACKUP_TYPE};
+use crate::util::{is_within, same_path};
+
+pub mod symlink_watch;
 
 /// 迁移选项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MigrationOptions {
     pub source_path: String,
     pub target_path: String,
     pub create_symlink: bool,
     pub delete_source: bool,
+    // 新增：目标文件已存在时的冲突处理策略
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    // 新增：是否保留时间戳与文件属性
+    #[serde(default)]
+    pub preserve_metadata: bool,
+    // 新增：源位置创建的链接类型（Windows 默认目录联接）
+    #[serde(default)]
+    pub link_type: LinkType,
+    // 新增：断点续传，中断后以相同源/目标重新运行时跳过已完成的文件
+    #[serde(default)]
+    pub resume: bool,
+    // 新增：预检查时递归检查源目录中所有条目的可读性
+    #[serde(default)]
+    pub deep_permission_check: bool,
+    // 新增：分块复制的缓冲区大小，未设置时使用系统默认的整文件复制
+    #[serde(default)]
+    pub copy_buffer_size: Option<usize>,
+    // 新增：删除源数据时移到系统回收站而不是永久删除
+    #[serde(default)]
+    pub use_trash: bool,
+    // 新增：两阶段迁移，本次迁移不删除源数据（忽略 delete_source），
+    // 返回待确认删除令牌，用户确认应用正常后再通过 confirm_source_deletion 删除
+    #[serde(default)]
+    pub defer_source_deletion: bool,
+    // 新增：复制校验允许的总大小差异百分比（None 时为 10%，0 表示要求完全一致）
+    #[serde(default)]
+    pub size_tolerance_percent: Option<f64>,
+    // 新增：复制校验允许的文件数量差异（None 时不比较文件数量）
+    #[serde(default)]
+    pub file_count_tolerance: Option<u64>,
+    // 新增：迁移完成后目标磁盘至少保留的剩余空间（字节），不足时拒绝迁移，0 表示不限制
+    #[serde(default)]
+    pub min_target_free_bytes_after: u64,
+    // 新增：不迁移的子路径（相对于源目录，如缓存或日志目录），不会出现在目标中；
+    // 排除的内容只保留在源目录，因此不能与创建链接或删除源目录同时使用
+    #[serde(default)]
+    pub exclude_subpaths: Vec<String>,
+    // 新增：合并模式，目标已存在时按冲突策略把源文件合并进去，校验时只要求源文件均已出现在目标中
+    #[serde(default)]
+    pub merge: bool,
+    // 新增：单个文件复制的时限（毫秒），超时（如网络驱动器无响应）即中止迁移，None 表示不限制
+    #[serde(default)]
+    pub operation_timeout_ms: Option<u64>,
+    // 新增：允许目标位于源目录内部（原地整理），复制与校验时排除目标子树；
+    // 目标随源目录保留，因此不能与创建链接或删除源目录同时使用
+    #[serde(default)]
+    pub allow_nested_target: bool,
 }
 
 /// 迁移结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MigrationResult {
     pub success: bool,
     pub message: String,
     pub source_path: String,
     pub target_path: String,
     pub symlink_path: Option<String>,
+    // 新增：复制阶段逐文件的处理动作
+    #[serde(default)]
+    pub file_actions: Vec<FileActionRecord>,
+    // 新增：实际创建的链接类型（未创建链接时为 None）
+    #[serde(default)]
+    pub link_type: Option<LinkType>,
+    // 新增：复制阶段逐文件的处理结果，整体成功与否由其汇总
+    #[serde(default)]
+    pub file_results: Vec<FileResult>,
+    // 新增：预检查产生的警告（如文件系统对链接的限制）
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    // 新增：消息的稳定键与附加详情，前端可据此识别结果或切换语言
+    #[serde(default)]
+    pub message_key: Option<MessageKey>,
+    #[serde(default)]
+    pub message_detail: Option<String>,
+    // 新增：两阶段迁移中用于确认删除源数据的令牌
+    #[serde(default)]
+    pub pending_deletion_token: Option<String>,
+    // 新增：目标已与源一致（重复执行同一迁移），本次未做任何改动
+    #[serde(default)]
+    pub already_migrated: bool,
+    // 新增：迁移成功但删除源数据失败，源数据仍保留
+    #[serde(default)]
+    pub source_deletion_failed: bool,
+    // 新增：因 exclude_subpaths 未迁移的字节数
+    #[serde(default)]
+    pub excluded_bytes: u64,
+    // 新增：复制阶段实际写入的字节数与耗时（毫秒），用于统计迁移吞吐量
+    #[serde(default)]
+    pub copied_bytes: u64,
+    #[serde(default)]
+    pub copy_duration_ms: u64,
+    // 新增：迁移被取消时的取消原因
+    #[serde(default)]
+    pub cancellation_reason: Option<CancellationReason>,
+    // 新增：复制超时后错误恢复管理器给出的处理结果
+    #[serde(default)]
+    pub recovery: Option<RecoveryResult>,
+}
+
+/// 迁移中排除的子路径（相对于源目录）及其在源目录中的总量，迁移开始时统计一次
+#[derive(Debug, Clone, Default)]
+struct ExcludedSubpaths {
+    paths: Vec<PathBuf>,
+    bytes: u64,
+    files: u64,
+}
+
+impl ExcludedSubpaths {
+    /// 统计排除的子路径在源目录中的总字节数和文件数，不存在的子路径不计
+    fn measure(source: &Path, paths: Vec<PathBuf>) -> Self {
+        let analyzer = DiskAnalyzer::new();
+        let (bytes, files) = paths.iter()
+            .filter_map(|subpath| analyzer.measure_path(&source.join(subpath)).ok())
+            .fold((0, 0), |(bytes, files), totals| (bytes + totals.size, files + totals.file_count));
+        Self { paths, bytes, files }
+    }
+
+    /// 目标位于源目录内部时，把目标子树（复制后的内容）也计入排除项，校验时不与自身比较
+    fn with_nested_target(mut self, source: &Path, target: &Path) -> Self {
+        let Some(relative) = nested_target_under(source, target)
+            .and_then(|nested| nested.strip_prefix(source).ok().map(Path::to_path_buf))
+        else {
+            return self;
+        };
+        if let Ok(totals) = DiskAnalyzer::new().measure_path(&source.join(&relative)) {
+            self.bytes += totals.size;
+            self.files += totals.file_count;
+        }
+        self.paths.push(relative);
+        self
+    }
+}
+
+/// 已通过复制校验并提交的迁移目标，只能在校验通过后构造，删除源数据前必须持有
+struct VerifiedTarget {
+    target: PathBuf,
+}
+
+impl MigrationResult {
+    /// 按消息键设置结果消息（默认语言），并保留键与详情以便按其他语言重新生成
+    fn with_message(mut self, key: MessageKey, detail: Option<String>) -> Self {
+        self.message = MessageCatalog::format(key, Locale::default(), detail.as_deref());
+        self.message_key = Some(key);
+        self.message_detail = detail;
+        self
+    }
+
+    /// 将结果消息转换为指定语言（没有消息键的结果保持原样）
+    pub fn localize(&mut self, locale: Locale) {
+        if let Some(key) = self.message_key {
+            self.message = MessageCatalog::format(key, locale, self.message_detail.as_deref());
+        }
+        if let Some(recovery) = &mut self.recovery {
+            recovery.localize(locale);
+        }
+    }
+}
+
+/// 批量迁移结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchMigrationResult {
+    /// 与输入顺序一致的逐项结果
+    pub results: Vec<MigrationResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub summary: String,
+}
+
+/// 迁移后源位置的链接本身占用的空间估计（一个簇）
+pub const SYMLINK_OVERHEAD_BYTES: u64 = 4096;
+
+/// 迁移计划预计在某个驱动器上释放的空间
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpaceFreedEstimate {
+    pub drive: String,
+    /// 计入的项目数：源在该驱动器上、目标不在，且迁移后源数据会被移除
+    pub counted_items: usize,
+    /// 计入项目的总大小
+    pub moved_bytes: u64,
+    /// 源位置保留的链接占用的空间
+    pub symlink_overhead_bytes: u64,
+    /// 预计释放的空间（总大小减去链接占用）
+    pub freed_bytes: u64,
+    /// 当前剩余空间（无法获取时为 None）
+    pub current_free_bytes: Option<u64>,
+    /// 迁移完成后预计的剩余空间
+    pub projected_free_bytes: Option<u64>,
+    /// 未计入的项目及原因
+    pub skipped: Vec<String>,
+}
+
+/// 已完成迁移的完整性检查报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationIntegrityReport {
+    /// 原始源路径（迁移后应为指向目标的链接）
+    pub source_path: String,
+    /// 链接记录的目标路径（源路径不是链接时为 None）
+    pub target_path: Option<String>,
+    pub link_exists: bool,
+    pub link_resolves: bool,
+    pub target_exists: bool,
+    /// 重新读取目标中全部文件得到的 SHA-256 内容校验值（未要求复查时为 None）
+    pub content_checksum: Option<String>,
+    /// 创建链接时记录的内容校验值（没有记录时为 None），复查结果与之不同说明目标内容已改变
+    #[serde(default)]
+    pub baseline_checksum: Option<String>,
+    /// 复查时无法读取的文件
+    pub unreadable_files: Vec<String>,
+    /// 发现的问题，为空表示迁移状态正常
+    pub issues: Vec<String>,
+    pub healthy: bool,
+}
+
+/// 单个路径的迁移就绪情况，供浏览目录时即时提示
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathInspection {
+    pub path: String,
+    pub exists: bool,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub is_system_protected: bool,
+    /// 路径的总字节数（未要求统计、不存在或无法统计时为 None）
+    pub estimated_size: Option<u64>,
+    pub readable: bool,
+    pub writable: bool,
+    pub on_removable_drive: bool,
+    /// 系统保护等不可迁移的原因
+    pub reasons: Vec<String>,
+}
+
+/// 迁移后源位置链接的解析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkResolution {
+    pub link_path: String,
+    pub link_type: LinkType,
+    /// 链接中记录的原始指向（可能是相对路径）
+    pub link_target: String,
+    /// 解析后的真实路径（链接悬空时为 None）
+    pub resolved_path: Option<String>,
+}
+
+/// 已迁移目标改换磁盘的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelocationResult {
+    pub link_path: String,
+    pub link_type: LinkType,
+    pub previous_target: String,
+    pub new_target: String,
+    /// 跨磁盘时通过复制移动（同一磁盘内直接重命名）
+    pub copied: bool,
+    /// 不影响结果的问题，例如复制后旧目标未能删除
+    pub warnings: Vec<String>,
+}
+
+/// 取消并回滚迁移的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CancelRollbackResult {
+    pub success: bool,
+    pub message: String,
+    pub source_path: String,
+    pub target_path: String,
+    /// 等待期间迁移是否已停止（没有进行中的迁移时也为 true）
+    pub stopped: bool,
+    /// 已清理的目标端数据（未完成的暂存目录、已提交的迁移目标）
+    pub removed_paths: Vec<String>,
+}
+
+/// 撤销已完成迁移的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationUndoResult {
+    pub message: String,
+    pub source_path: String,
+    pub target_path: String,
+    /// 已删除的迁移目标（源目录未被改动时只删除复制出的目标）
+    pub removed_paths: Vec<String>,
+}
+
+/// 正在进行的迁移路径登记表，防止并发迁移重叠的路径
+#[derive(Default)]
+pub struct MigrationLockRegistry {
+    active: std::sync::Mutex<Vec<(u64, PathBuf, PathBuf)>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+/// 迁移路径锁，释放（drop）时自动从登记表移除
+pub struct MigrationLockGuard {
+    registry: Arc<MigrationLockRegistry>,
+    id: u64,
+}
+
+impl Drop for MigrationLockGuard {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.registry.active.lock() {
+            active.retain(|(id, _, _)| *id != self.id);
+        }
+    }
+}
+
+impl MigrationLockRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一次迁移；源或目标与进行中的迁移重叠（相同或互为上下级）时拒绝
+    pub fn try_acquire_migration_lock(self: &Arc<Self>, source: &Path, target: &Path) -> Result<MigrationLockGuard, String> {
+        let source = resolve_with_existing_ancestor(source);
+        let target = resolve_with_existing_ancestor(target);
+        let overlaps = |a: &Path, b: &Path| a.starts_with(b) || b.starts_with(a);
+
+        let mut active = self.active.lock().map_err(|_| "无法获取迁移锁".to_string())?;
+        if let Some((_, active_source, active_target)) = active.iter().find(|(_, active_source, active_target)| {
+            [&source, &target].iter().any(|path| overlaps(path, active_source) || overlaps(path, active_target))
+        }) {
+            return Err(format!(
+                "已有涉及相同路径的迁移正在进行: {} -> {}",
+                active_source.display(),
+                active_target.display()
+            ));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        active.push((id, source, target));
+        Ok(MigrationLockGuard { registry: self.clone(), id })
+    }
+
+    /// 进行中的迁移数量
+    pub fn active_count(&self) -> usize {
+        self.active.lock().map(|active| active.len()).unwrap_or(0)
+    }
+
+    /// 是否有进行中的迁移涉及该路径（相同或互为上下级）
+    pub fn is_locked(&self, path: &Path) -> bool {
+        let path = resolve_with_existing_ancestor(path);
+        self.active.lock()
+            .map(|active| active.iter().any(|(_, source, target)| {
+                [source, target].iter().any(|locked| path.starts_with(locked) || locked.starts_with(&path))
+            }))
+            .unwrap_or(false)
+    }
+
+    /// 等待涉及该路径的迁移结束，超时返回 false
+    pub async fn wait_until_released(&self, path: &Path, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.is_locked(path) {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        true
+    }
+}
+
+/// 迁移流程的阶段（用于在测试中模拟各阶段失败）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationPhase {
+    /// 复制到暂存目标
+    Copy,
+    /// 校验暂存目标
+    Verify,
+    /// 暂存目标重命名为最终目标
+    Commit,
+    /// 在源位置创建链接
+    Link,
+    /// 删除源数据
+    SourceCleanup,
+}
+
+/// 复制校验默认允许的总大小差异百分比（考虑到系统文件、临时文件等）
+const DEFAULT_SIZE_TOLERANCE_PERCENT: f64 = 10.0;
+
+/// 目标磁盘剩余空间比例低于该值时给出警告
+const LOW_FREE_SPACE_RATIO: f64 = 0.05;
+
+/// 待确认删除令牌的默认有效期
+const PENDING_DELETION_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 日志目录中保存待确认删除登记的文件名
+pub const PENDING_DELETIONS_FILE: &str = "pending_deletions.json";
+
+/// 日志目录中保存迁移内容校验基线的文件名
+pub const INTEGRITY_BASELINES_FILE: &str = "integrity_baselines.json";
+
+/// 两阶段迁移中等待用户确认删除的源数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingDeletion {
+    source: PathBuf,
+    target: PathBuf,
+    linked: bool,
+    use_trash: bool,
+    expires_at: DateTime<Local>,
 }
 
 /// 迁移服务
 pub struct MigrationService {
     file_operator: FileOperator,
+    cancel_flag: Arc<AtomicBool>,
+    cancel_reason: AtomicU8, // 新增：取消原因（CancellationReason 编码，0 表示未取消）
+    #[cfg(test)]
+    filesystem_detector: fn(&Path) -> FilesystemKind, // 新增：文件系统检测（测试中可替换）
+    #[cfg(test)]
+    drive_status_detector: fn(&Path) -> Option<DriveStatus>, // 新增：目标磁盘状况检测（测试中可替换）
+    protected_paths: std::sync::RwLock<Vec<PathBuf>>, // 新增：禁止迁移进出的系统保护目录
+    #[cfg(test)]
+    injected_failure: Option<MigrationPhase>, // 新增：在指定阶段模拟失败（仅测试使用）
+    pending_deletions: std::sync::Mutex<HashMap<String, PendingDeletion>>, // 新增：两阶段迁移的待确认删除
+    pending_deletion_ttl: Duration, // 新增：待确认删除令牌的有效期
+    pending_deletions_file: Option<PathBuf>, // 新增：待确认删除登记的保存文件（None 时只保存在内存中）
+    completed_migrations: std::sync::Mutex<Vec<(PathBuf, PathBuf)>>, // 新增：本次会话完成的迁移（源, 目标），供撤销使用
+    integrity_baselines: std::sync::Mutex<HashMap<String, String>>, // 新增：创建链接时记录的目标内容校验值（按源路径）
+    integrity_baselines_file: Option<PathBuf>, // 新增：内容校验基线的保存文件（None 时只保存在内存中）
 }
 
 impl MigrationService {
     /// 创建新的迁移服务
     pub fn new() -> Self {
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+
         Self {
-            file_operator: FileOperator::new(),
+            file_operator: FileOperator::with_cancel_flag(cancel_flag.clone()),
+            cancel_flag,
+            cancel_reason: AtomicU8::new(0),
+            #[cfg(test)]
+            filesystem_detector: detect_filesystem,
+            #[cfg(test)]
+            drive_status_detector: detect_drive_status,
+            protected_paths: std::sync::RwLock::new(Self::default_protected_paths()),
+            #[cfg(test)]
+            injected_failure: None,
+            pending_deletions: std::sync::Mutex::new(HashMap::new()),
+            pending_deletion_ttl: PENDING_DELETION_TTL,
+            pending_deletions_file: None,
+            completed_migrations: std::sync::Mutex::new(Vec::new()),
+            integrity_baselines: std::sync::Mutex::new(HashMap::new()),
+            integrity_baselines_file: None,
+        }
+    }
+
+    /// 当前平台默认的系统保护目录
+    pub fn default_protected_paths() -> Vec<PathBuf> {
+        #[cfg(target_os = "windows")]
+        let paths = [
+            "C:\\Windows",
+            "C:\\Program Files",
+            "C:\\Program Files (x86)",
+            "C:\\Users\\Default",
+            "C:\\Recovery",
+            "C:\\System Volume Information",
+            "C:\\$Recycle.Bin",
+        ];
+        #[cfg(not(target_os = "windows"))]
+        let paths = [
+            "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/sbin", "/sys", "/usr",
+            "/System", "/Library",
+        ];
+
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    /// 获取当前的系统保护目录列表
+    pub fn protected_paths(&self) -> Vec<PathBuf> {
+        self.protected_paths.read().map(|paths| paths.clone()).unwrap_or_default()
+    }
+
+    /// 添加系统保护目录，已存在时返回 false
+    pub fn add_protected_path(&self, path: PathBuf) -> bool {
+        let mut paths = self.protected_paths.write().unwrap_or_else(|e| e.into_inner());
+        if paths.iter().any(|existing| same_path(existing, &path)) {
+            return false;
+        }
+        info!("添加系统保护目录: {}", path.display());
+        paths.push(path);
+        true
+    }
+
+    /// 移除系统保护目录，不存在时返回 false
+    pub fn remove_protected_path(&self, path: &Path) -> bool {
+        let mut paths = self.protected_paths.write().unwrap_or_else(|e| e.into_inner());
+        let before = paths.len();
+        paths.retain(|existing| !same_path(existing, path));
+        let removed = paths.len() != before;
+        if removed {
+            info!("移除系统保护目录: {}", path.display());
+        }
+        removed
+    }
+
+    /// 到达被注入失败的阶段时返回错误
+    #[cfg(test)]
+    fn inject_failure(&self, phase: MigrationPhase) -> Result<(), FileOperationError> {
+        if self.injected_failure == Some(phase) {
+            return Err(FileOperationError::IoError(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("模拟 {:?} 阶段失败", phase),
+            )));
         }
+        Ok(())
+    }
+
+    /// 正式构建中不注入失败
+    #[cfg(not(test))]
+    fn inject_failure(&self, _phase: MigrationPhase) -> Result<(), FileOperationError> {
+        Ok(())
+    }
+
+    /// 检测路径所在的文件系统（测试中可替换）
+    #[cfg(test)]
+    fn filesystem_kind(&self, path: &Path) -> FilesystemKind {
+        (self.filesystem_detector)(path)
+    }
+
+    #[cfg(not(test))]
+    fn filesystem_kind(&self, path: &Path) -> FilesystemKind {
+        detect_filesystem(path)
+    }
+
+    /// 检测路径所在磁盘的状况（测试中可替换）
+    #[cfg(test)]
+    fn drive_status(&self, path: &Path) -> Option<DriveStatus> {
+        (self.drive_status_detector)(path)
+    }
+
+    #[cfg(not(test))]
+    fn drive_status(&self, path: &Path) -> Option<DriveStatus> {
+        detect_drive_status(path)
+    }
+
+    /// 取消正在进行的迁移，并记录取消原因
+    pub fn cancel_migration(&self, reason: CancellationReason) {
+        self.cancel_reason.store(reason.to_u8(), Ordering::Relaxed);
+        self.cancel_flag.store(true, Ordering::Release);
+        info!("迁移已取消，原因: {}", reason.description());
+    }
+
+    /// 重置取消标志
+    pub fn reset_cancel_flag(&self) {
+        self.cancel_flag.store(false, Ordering::Relaxed);
+        self.cancel_reason.store(0, Ordering::Relaxed);
+    }
+
+    /// 获取迁移的取消原因，未取消时返回 None
+    pub fn cancellation_reason(&self) -> Option<CancellationReason> {
+        CancellationReason::from_u8(self.cancel_reason.load(Ordering::Relaxed))
+    }
+
+    /// 检查迁移是否已被取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::Relaxed)
     }
 
     /// 获取文件操作器
@@ -49,174 +586,1336 @@ impl MigrationService {
 
         info!("开始迁移: {} -> {}", source.display(), target.display());
 
-        // 1. 预检查
-        match self.pre_migration_check(source, target).await {
-            Ok(_) => {},
+        // 1. 预检查（续传已有检查点的迁移时允许目标已存在）
+        let resuming = options.resume && Self::checkpoint_path(source, target).exists();
+        if !resuming {
+            if let Some(detail) = Self::detect_completed_migration(source, target, &options) {
+                info!("迁移已完成，跳过: {} -> {} ({})", source.display(), target.display(), detail);
+                let linked = fs::symlink_metadata(source).map(|m| m.file_type().is_symlink()).unwrap_or(false);
+                return Ok(MigrationResult {
+                    success: true,
+                    symlink_path: linked.then(|| target.display().to_string()),
+                    source_path: options.source_path,
+                    target_path: options.target_path,
+                    already_migrated: true,
+                    ..Default::default()
+                }.with_message(MessageKey::MigrationAlreadyCompleted, Some(detail)));
+            }
+        }
+        let mut warnings = match self.pre_migration_check(source, target, &options, resuming || options.merge).await {
+            Ok(warnings) => warnings,
             Err(e) => {
                 return Ok(MigrationResult {
                     success: false,
-                    message: format!("预检查失败: {}", e),
                     source_path: options.source_path,
                     target_path: options.target_path,
                     symlink_path: None,
-                });
+                    ..Default::default()
+                }.with_message(MessageKey::PreCheckFailed, Some(e.to_string())));
+            }
+        };
+
+        // 2. 复制文件夹（跳过排除的子路径）
+        let excluded = ExcludedSubpaths::measure(
+            source,
+            options.exclude_subpaths.iter().map(|subpath| PathBuf::from(subpath.trim())).collect(),
+        );
+        let excluded_bytes = excluded.bytes;
+        if excluded_bytes > 0 {
+            info!("排除 {} 个子路径，共 {} 不迁移", excluded.paths.len(), format_file_size(excluded_bytes));
+        }
+        let checkpoint = if options.resume {
+            let checkpoint_path = Self::checkpoint_path(source, target);
+            match CopyCheckpoint::open(&checkpoint_path) {
+                Ok(checkpoint) => {
+                    info!("断点续传检查点: {} (已完成 {} 个文件)",
+                          checkpoint_path.display(), checkpoint.completed_count());
+                    Some(Arc::new(checkpoint))
+                }
+                Err(e) => {
+                    warn!("无法打开检查点文件，将完整复制: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        // 目标不存在时先复制到同目录下的暂存路径，验证通过后再整体重命名为最终目标，
+        // 这样任何阶段失败都不会留下半成品目标；续传或合并到已存在的目标时直接在原处复制，
+        // 合并时记录本次写入的内容，失败时只撤销这些内容，目标中原有的数据保持不变
+        let target_existed = target.exists();
+        let journal = (options.merge && target_existed)
+            .then(|| Arc::new(CopyJournal::new(Self::merge_originals_path(target))));
+        let copy_options = CopyOptions {
+            conflict_policy: options.conflict_policy,
+            preserve_metadata: options.preserve_metadata,
+            checkpoint: checkpoint.clone(),
+            buffer_size: options.copy_buffer_size,
+            allow_nested_target: options.allow_nested_target,
+            exclude_subpaths: excluded.paths.clone(),
+            file_timeout: options.operation_timeout_ms.map(Duration::from_millis),
+            journal: journal.clone(),
+        };
+        let staging = if target_existed { target.to_path_buf() } else { Self::staging_path(target) };
+        let rollback_merge = || {
+            if let Some(journal) = &journal {
+                let report = journal.rollback();
+                for e in &report.errors {
+                    warn!("合并回滚失败: {}", e);
+                }
             }
+        };
+        let remove_staging = || {
+            if !target_existed {
+                let staging = extended_length_path(&staging);
+                let _ = fs::remove_dir_all(&staging).or_else(|_| fs::remove_file(&staging));
+            } else {
+                rollback_merge();
+            }
+        };
+
+        // 不续传时上次中断遗留的暂存目录已无用处，先清理，避免旧文件混入本次复制结果
+        if checkpoint.is_none() && !target_existed && staging.exists() {
+            info!("清理上次遗留的暂存目录: {}", staging.display());
+            remove_staging();
         }
 
-        // 2. 复制文件夹
-        let copy_result = match self.file_operator.copy_path(source, target) {
+        let copy_started = std::time::Instant::now();
+        let copied = match self.inject_failure(MigrationPhase::Copy) {
+            Ok(()) => self.file_operator.copy_path_with_options(source, &staging, &copy_options),
+            Err(e) => Err(e),
+        };
+        let copy_duration = copy_started.elapsed();
+        let copy_result = match copied {
             Ok(result) => result,
+            Err(e @ FileOperationError::OperationCancelled(_)) => {
+                // 迁移被取消：源目录未改动；续传模式保留已复制的内容，否则清理
+                if checkpoint.is_none() {
+                    remove_staging();
+                }
+                let reason = self.cancellation_reason().unwrap_or_default();
+                warn!("迁移已取消: {} -> {} ({})", source.display(), target.display(), reason.description());
+
+                return Ok(MigrationResult {
+                    success: false,
+                    source_path: options.source_path,
+                    target_path: options.target_path,
+                    symlink_path: None,
+                    cancellation_reason: Some(reason),
+                    ..Default::default()
+                }.with_message(MessageKey::CopyFailed, Some(format!("{} ({})", e, reason.description()))));
+            }
+            Err(e @ FileOperationError::Timeout(_)) => {
+                // 复制超时：设备可能仍无响应，不保留检查点之外的半成品
+                if checkpoint.is_none() {
+                    remove_staging();
+                }
+                error!("迁移复制超时: {} -> {}: {}", source.display(), target.display(), e);
+
+                return Ok(MigrationResult {
+                    success: false,
+                    source_path: options.source_path,
+                    target_path: options.target_path,
+                    symlink_path: None,
+                    ..Default::default()
+                }.with_message(MessageKey::CopyTimedOut, Some(e.to_string())));
+            }
             Err(e) => {
+                if checkpoint.is_none() {
+                    remove_staging();
+                }
                 return Ok(MigrationResult {
                     success: false,
-                    message: format!("复制失败: {}", e),
                     source_path: options.source_path,
                     target_path: options.target_path,
                     symlink_path: None,
-                });
+                    ..Default::default()
+                }.with_message(MessageKey::CopyFailed, Some(e.to_string())));
             }
         };
 
         if !copy_result.success {
+            if checkpoint.is_none() {
+                remove_staging();
+            }
             return Ok(MigrationResult {
                 success: false,
-                message: format!("复制失败: {}", copy_result.message),
                 source_path: options.source_path,
                 target_path: options.target_path,
                 symlink_path: None,
-            });
+                file_actions: copy_result.file_actions,
+                file_results: copy_result.file_results,
+                ..Default::default()
+            }.with_message(MessageKey::CopyFailed, Some(copy_result.message)));
         }
 
         info!("复制完成: {}", copy_result.message);
 
         // 3. 验证复制结果
-        match self.verify_copy_result(source, target).await {
+        let verified = match self.inject_failure(MigrationPhase::Verify) {
+            Ok(()) => {
+                let size_tolerance = options.size_tolerance_percent.unwrap_or(DEFAULT_SIZE_TOLERANCE_PERCENT);
+                let merged = (options.merge && target_existed).then_some(copy_result.file_actions.as_slice());
+                let excluded = if options.allow_nested_target {
+                    excluded.with_nested_target(source, &staging)
+                } else {
+                    excluded
+                };
+                self.verify_copy_result(source, &staging, size_tolerance, options.file_count_tolerance, &excluded, merged).await
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        match verified {
             Ok(_) => {
                 info!("复制验证通过");
             },
             Err(e) => {
-                // 复制验证失败，尝试清理目标目录
-                let _ = self.file_operator.delete_path(target);
+                // 复制验证失败，清理暂存目标（源目录未改动）
+                remove_staging();
+                if let Some(checkpoint) = &checkpoint {
+                    let _ = checkpoint.remove();
+                }
                 
                 return Ok(MigrationResult {
                     success: false,
-                    message: format!("复制验证失败: {}", e),
                     source_path: options.source_path,
                     target_path: options.target_path,
                     symlink_path: None,
-                });
+                    ..Default::default()
+                }.with_message(MessageKey::CopyVerificationFailed, Some(e)));
+            }
+        }
+
+        // 4. 提交：将暂存目录原子重命名为最终目标
+        if !target_existed {
+            let committed = self.inject_failure(MigrationPhase::Commit)
+                .and_then(|_| fs::rename(extended_length_path(&staging), extended_length_path(target)).map_err(FileOperationError::from));
+            if let Err(e) = committed {
+                error!("提交迁移目标失败 {} -> {}: {}", staging.display(), target.display(), e);
+                remove_staging();
+                if let Some(checkpoint) = &checkpoint {
+                    let _ = checkpoint.remove();
+                }
+
+                return Ok(MigrationResult {
+                    success: false,
+                    source_path: options.source_path,
+                    target_path: options.target_path,
+                    symlink_path: None,
+                    ..Default::default()
+                }.with_message(MessageKey::CommitFailed, Some(e.to_string())));
+            }
+        }
+
+        let verified_target = VerifiedTarget { target: target.to_path_buf() };
+
+        // 复制完成并通过验证后不再需要检查点
+        if let Some(checkpoint) = &checkpoint {
+            if let Err(e) = checkpoint.remove() {
+                warn!("删除检查点文件失败 {}: {}", checkpoint.path().display(), e);
             }
         }
 
         let mut symlink_path = None;
+        let mut link_type = None;
 
-        // 4. 创建符号链接（如果启用）
+        // 5. 创建符号链接（如果启用）：源目录先重命名为 `.backup`，失败时恢复源目录并撤销目标，
+        //    保证只留下源目录这一份完整数据
         if options.create_symlink {
-            match self.create_symlink_after_migration(source, target).await {
-                Ok(symlink_result) => {
-                    info!("符号链接创建成功: {}", symlink_result.message);
-                    symlink_path = Some(source.display().to_string());
+            let linked = match self.inject_failure(MigrationPhase::Link) {
+                Ok(()) => self.create_symlink_after_migration(source, target, options.link_type).await,
+                Err(e) => Err(e),
+            };
+            match linked {
+                Ok((symlink_result, created_type)) => {
+                    info!("链接创建成功: {}", symlink_result.message);
+                    // 记录已验证的链接指向
+                    symlink_path = Some(target.display().to_string());
+                    link_type = Some(created_type);
+                    self.record_integrity_baseline(source, target);
                 },
                 Err(e) => {
-                    error!("符号链接创建失败: {}", e);
-                    // 符号链接创建失败，但不影响整体迁移结果
+                    error!("符号链接创建失败，源目录已恢复: {}", e);
+                    if !target_existed {
+                        let target = extended_length_path(target);
+                        let _ = fs::remove_dir_all(&target).or_else(|_| fs::remove_file(&target));
+                    } else {
+                        rollback_merge();
+                    }
+
+                    return Ok(MigrationResult {
+                        success: false,
+                        source_path: options.source_path,
+                        target_path: options.target_path,
+                        symlink_path: None,
+                        ..Default::default()
+                    }.with_message(MessageKey::LinkFailed, Some(e.to_string())));
                 }
             }
         }
 
-        // 5. 删除源目录（如果启用）
-        if options.delete_source {
-            match self.file_operator.delete_path(source) {
-                Ok(delete_result) => {
-                    info!("源目录删除成功: {}", delete_result.message);
-                },
-                Err(e) => {
-                    error!("源目录删除失败: {}", e);
-                    // 源目录删除失败，但不影响整体迁移结果
-                }
+        // 合并已完成，不再需要保存的原文件
+        if let Some(journal) = &journal {
+            if let Err(e) = journal.discard() {
+                warn!("删除合并前保存的原文件失败: {}", e);
             }
         }
 
+        // 6. 处理源目录：两阶段迁移只登记待确认删除；否则按需删除，
+        //    目标已提交，删除失败时保留可恢复的源数据
+        let mut pending_deletion_token = None;
+        let mut source_deletion_error = None;
+        if options.defer_source_deletion {
+            pending_deletion_token = Some(self.register_pending_deletion(
+                source, target, options.create_symlink, options.use_trash,
+            ));
+        } else if options.delete_source {
+            if let Err(e) = self.delete_source_after_verification(source, &verified_target, options.create_symlink, options.use_trash) {
+                error!("源目录删除失败: {}", e);
+                warnings.push(format!("源目录删除失败，原数据仍保留: {}", e));
+                source_deletion_error = Some(e);
+            }
+        }
+
+        // 合并到已有目标的迁移无法整体撤销（目标中还有原有数据），不登记
+        if !target_existed {
+            self.completed_migrations.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((source.to_path_buf(), target.to_path_buf()));
+        }
+
+        let source_deletion_failed = source_deletion_error.is_some();
+        let (message_key, message_detail) = match source_deletion_error {
+            Some(e) => (MessageKey::SourceDeletionFailed, Some(e)),
+            None => (MessageKey::MigrationSucceeded, None),
+        };
         Ok(MigrationResult {
             success: true,
-            message: "迁移成功完成".to_string(),
             source_path: options.source_path,
             target_path: options.target_path,
             symlink_path,
-        })
+            file_actions: copy_result.file_actions,
+            link_type,
+            file_results: copy_result.file_results,
+            warnings,
+            pending_deletion_token,
+            source_deletion_failed,
+            excluded_bytes,
+            copied_bytes: copy_result.total_size,
+            copy_duration_ms: copy_duration.as_millis() as u64,
+            ..Default::default()
+        }
+        .with_message(message_key, message_detail))
     }
 
-    /// 预迁移检查（增强版）
-    async fn pre_migration_check(&self, source: &Path, target: &Path) -> Result<(), String> {
-        // 1. 路径安全性检查
-        match self.validate_path_security(source, target) {
-            Ok(_) => {},
-            Err(e) => return Err(format!("路径安全检查失败: {}", e)),
+    /// 在目标已通过校验的前提下删除源数据；删除前再次确认目标仍然存在
+    fn delete_source_after_verification(
+        &self,
+        source: &Path,
+        verified: &VerifiedTarget,
+        linked: bool,
+        use_trash: bool,
+    ) -> Result<(), String> {
+        if fs::symlink_metadata(&verified.target).is_err() {
+            return Err(format!("已校验的目标不存在，拒绝删除源目录: {}", verified.target.display()));
         }
+        self.remove_migrated_source(source, linked, use_trash)
+    }
 
-        // 2. 验证路径
-        match self.file_operator.validate_migration_path(source, target) {
-            Ok((valid, message)) => {
-                if !valid {
-                    return Err(message);
-                }
-            },
-            Err(e) => {
-                return Err(format!("路径验证失败: {}", e));
-            }
+    /// 检测同一迁移是否已完成（例如重复点击迁移），返回判断依据
+    ///
+    /// 源位置已是解析到目标的链接时视为已完成；不创建链接且不合并的迁移在目标内容与源目录
+    /// 完全一致时视为已完成。先比较文件数和总字节数，一致时才逐文件计算校验值。
+    fn detect_completed_migration(source: &Path, target: &Path, options: &MigrationOptions) -> Option<String> {
+        if !fs::symlink_metadata(target).map(|m| m.is_dir()).unwrap_or(false) {
+            return None;
         }
 
-        // 3. 权限检查
-        match self.check_permissions(source, target).await {
-            Ok(_) => {},
-            Err(e) => return Err(format!("权限检查失败: {}", e)),
+        let source_metadata = fs::symlink_metadata(source).ok()?;
+        if source_metadata.file_type().is_symlink() {
+            let resolved = fs::canonicalize(source).ok()?;
+            let canonical_target = fs::canonicalize(target).ok()?;
+            return (resolved == canonical_target)
+                .then(|| format!("源位置已是指向目标的链接: {}", target.display()));
         }
 
-        // 4. 磁盘空间检查
-        match self.check_disk_space(source, target).await {
-            Ok(_) => {},
-            Err(e) => return Err(format!("磁盘空间检查失败: {}", e)),
+        if options.create_symlink || options.merge || !source_metadata.is_dir() {
+            return None;
+        }
+        let analyzer = DiskAnalyzer::new();
+        let source_totals = analyzer.measure_path(source).ok()?;
+        let target_totals = analyzer.measure_path(target).ok()?;
+        if (source_totals.file_count, source_totals.size) != (target_totals.file_count, target_totals.size) {
+            return None;
         }
+        let (source_checksum, source_unreadable) = Self::checksum_tree(source);
+        let (target_checksum, target_unreadable) = Self::checksum_tree(target);
+        (source_unreadable.is_empty() && target_unreadable.is_empty() && source_checksum == target_checksum)
+            .then(|| "目标内容与源目录一致".to_string())
+    }
 
-        // 5. 系统保护检查
-        match self.check_system_protection(source, target) {
-            Ok(_) => {},
-            Err(e) => return Err(format!("系统保护检查失败: {}", e)),
+    /// 把待确认删除的登记保存到文件，重启后仍可凭令牌删除保留的源数据
+    ///
+    /// 打开时加载文件中的登记并清理已过期的项。
+    pub fn with_pending_deletions_file(mut self, path: PathBuf) -> Self {
+        let loaded: HashMap<String, PendingDeletion> = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("解析待确认删除登记失败，将重新记录 {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        self.pending_deletions_file = Some(path);
+
+        let mut pending = self.pending_deletions.lock().unwrap_or_else(|e| e.into_inner());
+        pending.extend(loaded);
+        Self::sweep_expired_deletions(&mut pending);
+        self.save_pending_deletions(&pending);
+        drop(pending);
+        self
+    }
+
+    /// 移除已过期的登记；对应的源数据不会自动删除，记录其位置供手动处理
+    fn sweep_expired_deletions(pending: &mut HashMap<String, PendingDeletion>) {
+        let now = Local::now();
+        pending.retain(|token, deletion| {
+            if deletion.expires_at > now {
+                return true;
+            }
+            let retained = if deletion.linked {
+                Self::source_backup_path(&deletion.source).unwrap_or_else(|| deletion.source.clone())
+            } else {
+                deletion.source.clone()
+            };
+            warn!("删除令牌已过期 ({})，源数据保留在 {}，可手动删除", token, retained.display());
+            false
+        });
+    }
+
+    /// 写回待确认删除登记文件（未配置文件时不保存）
+    fn save_pending_deletions(&self, pending: &HashMap<String, PendingDeletion>) {
+        let Some(path) = &self.pending_deletions_file else {
+            return;
+        };
+        if let Err(e) = save_json_file(path, pending) {
+            warn!("保存待确认删除登记失败 {}: {}", path.display(), e);
         }
+    }
 
-        info!("预迁移检查通过: {} -> {}", source.display(), target.display());
-        Ok(())
+    /// 把迁移时的内容校验基线保存到文件，重启后完整性检查仍可比对
+    pub fn with_integrity_baselines_file(mut self, path: PathBuf) -> Self {
+        let loaded: HashMap<String, String> = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("解析内容校验基线失败，将重新记录 {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        self.integrity_baselines_file = Some(path);
+        self.integrity_baselines.lock().unwrap_or_else(|e| e.into_inner()).extend(loaded);
+        self
     }
 
-    /// 验证复制结果
-    async fn verify_copy_result(&self, source: &Path, target: &Path) -> Result<(), String> {
-        // 检查目标是否存在
-        if !target.exists() {
-            return Err("目标目录不存在".to_string());
+    /// 创建链接后读取目标中的全部文件，记录内容校验基线；有文件无法读取时不记录
+    fn record_integrity_baseline(&self, source: &Path, target: &Path) {
+        let (checksum, unreadable) = Self::checksum_tree(target);
+        if !unreadable.is_empty() {
+            warn!("目标中有 {} 个文件无法读取，不记录内容校验基线: {}", unreadable.len(), target.display());
+            return;
         }
+        let mut baselines = self.integrity_baselines.lock().unwrap_or_else(|e| e.into_inner());
+        baselines.insert(source.display().to_string(), checksum);
+        self.save_integrity_baselines(&baselines);
+    }
 
-        // 比较文件数量（粗略验证）
+    /// 迁移撤销后移除对应的内容校验基线
+    fn forget_integrity_baseline(&self, source: &Path) {
+        let mut baselines = self.integrity_baselines.lock().unwrap_or_else(|e| e.into_inner());
+        if baselines.remove(&source.display().to_string()).is_some() {
+            self.save_integrity_baselines(&baselines);
+        }
+    }
+
+    /// 写回内容校验基线文件（未配置文件时不保存）
+    fn save_integrity_baselines(&self, baselines: &HashMap<String, String>) {
+        let Some(path) = &self.integrity_baselines_file else {
+            return;
+        };
+        if let Err(e) = save_json_file(path, baselines) {
+            warn!("保存内容校验基线失败 {}: {}", path.display(), e);
+        }
+    }
+
+    /// 登记待确认删除的源数据并返回令牌（同时清理已过期的登记）
+    fn register_pending_deletion(&self, source: &Path, target: &Path, linked: bool, use_trash: bool) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        let ttl = chrono::Duration::from_std(self.pending_deletion_ttl)
+            .unwrap_or_else(|_| chrono::Duration::days(7));
+        let mut pending = self.pending_deletions.lock().unwrap_or_else(|e| e.into_inner());
+        Self::sweep_expired_deletions(&mut pending);
+        pending.insert(token.clone(), PendingDeletion {
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+            linked,
+            use_trash,
+            expires_at: Local::now() + ttl,
+        });
+        self.save_pending_deletions(&pending);
+
+        info!("源数据保留待确认删除: {} (令牌: {})", source.display(), token);
+        token
+    }
+
+    /// 两阶段迁移的第二阶段：凭令牌删除已迁移的源数据，返回被删除的源路径
+    ///
+    /// 令牌只能使用一次，过期或目标已不存在时拒绝删除。
+    pub fn confirm_source_deletion(&self, token: &str) -> Result<PathBuf, String> {
+        let deletion = {
+            let mut pending = self.pending_deletions.lock().unwrap_or_else(|e| e.into_inner());
+            let deletion = pending.remove(token);
+            if deletion.is_some() {
+                self.save_pending_deletions(&pending);
+            }
+            deletion.ok_or_else(|| "删除令牌无效或已使用".to_string())?
+        };
+
+        if Local::now() >= deletion.expires_at {
+            warn!("删除令牌已过期，源数据保留: {}", deletion.source.display());
+            return Err("删除令牌已过期，请重新迁移或手动删除源数据".to_string());
+        }
+        if !deletion.target.exists() {
+            return Err(format!("迁移目标已不存在，源数据保留: {}", deletion.target.display()));
+        }
+
+        self.remove_migrated_source(&deletion.source, deletion.linked, deletion.use_trash)?;
+        Ok(deletion.source)
+    }
+
+    /// 本次会话最近一次可撤销的迁移（源, 目标）
+    pub fn last_completed_migration(&self) -> Option<(PathBuf, PathBuf)> {
+        self.completed_migrations.lock().unwrap_or_else(|e| e.into_inner()).last().cloned()
+    }
+
+    /// 撤销本次会话完成的一次迁移
+    ///
+    /// 不依赖迁移前的完整备份：源目录已被链接替换或删除时，从 `.backup` 或迁移目标本身恢复源目录，
+    /// 然后删除迁移目标；源目录未被改动时只删除复制出的目标。待确认删除的登记一并作废。
+    /// 撤销成功后该迁移不再参与后续撤销。
+    pub fn undo_migration(&self, source: &Path, target: &Path) -> Result<MigrationUndoResult, String> {
+        let recorded = self.completed_migrations.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|(recorded_source, recorded_target)| recorded_source == source && recorded_target == target);
+        if !recorded {
+            return Err(format!("本次会话没有可撤销的迁移: {}", source.display()));
+        }
+        info!("撤销迁移: {} -> {}", source.display(), target.display());
+
+        let removed_paths = if Self::is_real_directory(source) {
+            if !target.exists() {
+                return Err(format!("迁移目标已不存在: {}", target.display()));
+            }
+            self.file_operator.delete_path(target)
+                .map_err(|e| format!("删除迁移目标失败: {}", e))?;
+            vec![target.display().to_string()]
+        } else {
+            self.restore_migrated_source(source, target)?
+        };
+
+        self.completed_migrations.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(recorded_source, recorded_target)| recorded_source != source || recorded_target != target);
+        let mut pending = self.pending_deletions.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, deletion| deletion.source != source);
+        self.save_pending_deletions(&pending);
+        drop(pending);
+        self.forget_integrity_baseline(source);
+
+        let result = MigrationUndoResult {
+            message: "迁移已撤销，源目录已恢复".to_string(),
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            removed_paths,
+        };
+        info!("{}: {}", result.message, source.display());
+        Ok(result)
+    }
+
+    /// 取消进行中的迁移并撤销其影响
+    ///
+    /// 发出取消请求并等待迁移释放路径锁；随后清理未完成的暂存目录。若源目录已被链接替换或删除，
+    /// 按 `restore_migrated_source` 恢复源目录并删除已提交的迁移目标。
+    pub async fn cancel_and_rollback(
+        &self,
+        source: &Path,
+        target: &Path,
+        locks: &MigrationLockRegistry,
+        timeout: Duration,
+    ) -> Result<CancelRollbackResult, String> {
+        self.cancel_migration(CancellationReason::UserRequested);
+        info!("取消并回滚迁移: {} -> {}", source.display(), target.display());
+
+        let mut result = CancelRollbackResult {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            ..Default::default()
+        };
+
+        result.stopped = locks.wait_until_released(source, timeout).await;
+        if !result.stopped {
+            return Err(format!("等待迁移停止超时 ({} 秒)，未执行回滚", timeout.as_secs()));
+        }
+
+        let staging = Self::staging_path(target);
+        if staging.exists() {
+            let staging = extended_length_path(&staging);
+            fs::remove_dir_all(&staging).or_else(|_| fs::remove_file(&staging))
+                .map_err(|e| format!("删除未完成的迁移数据失败: {}", e))?;
+            result.removed_paths.push(Self::staging_path(target).display().to_string());
+        }
+
+        let source_intact = Self::is_real_directory(source);
+        if !source_intact {
+            result.removed_paths.extend(self.restore_migrated_source(source, target)?);
+        }
+
+        result.success = true;
+        result.message = if source_intact {
+            "迁移已取消，源目录未受影响".to_string()
+        } else {
+            "迁移已取消，源目录已恢复".to_string()
+        };
+        info!("{}: {}", result.message, source.display());
+        Ok(result)
+    }
+
+    /// 复制超时的迁移交给错误恢复管理器处理（按 `ErrorType::Timeout` 分类），处理结果写入 `result.recovery`；
+    /// 恢复处理本身失败时追加一条警告。其他结果保持不变
+    pub async fn recover_timed_out_migration(
+        &self,
+        result: &mut MigrationResult,
+        recovery_manager: &tokio::sync::Mutex<ErrorRecoveryManager>,
+    ) {
+        if result.message_key != Some(MessageKey::CopyTimedOut) {
+            return;
+        }
+
+        let error = FileOperationError::Timeout(result.message.clone());
+        let context = RecoveryContext::new(
+            MIGRATION_BACKUP_TYPE.to_string(),
+            PathBuf::from(&result.source_path),
+            Some(PathBuf::from(&result.target_path)),
+            "copy".to_string(),
+        );
+        let operation_id = uuid::Uuid::new_v4().to_string();
+        match recovery_manager.lock().await.handle_error(&operation_id, &error, &context).await {
+            Ok(recovery) => result.recovery = Some(recovery),
+            Err(e) => {
+                warn!("处理复制超时失败: {}", e);
+                result.warnings.push(format!("处理复制超时失败: {}", e));
+            }
+        }
+    }
+
+    /// 估算迁移计划能在 `drive_root` 所在驱动器上释放多少空间
+    ///
+    /// 只计入源位于该驱动器、目标位于其他驱动器，且迁移后源数据会被链接替换或删除的项目；
+    /// 源位置已是链接（已迁移）的项目不再占用空间，不计入。
+    pub fn estimate_space_freed(&self, items: &[MigrationOptions], drive_root: &Path) -> SpaceFreedEstimate {
+        let analyzer = DiskAnalyzer::new();
+        let mut estimate = SpaceFreedEstimate {
+            drive: drive_root.display().to_string(),
+            current_free_bytes: self.get_available_space(drive_root),
+            ..Default::default()
+        };
+
+        for item in items {
+            let source = Path::new(&item.source_path);
+            let skip_reason = if !is_within(source, drive_root) {
+                Some("源不在该驱动器上")
+            } else if is_within(Path::new(&item.target_path), drive_root) {
+                Some("目标位于同一驱动器")
+            } else if fs::symlink_metadata(source).map(|m| m.file_type().is_symlink()).unwrap_or(false) {
+                Some("源位置已是链接")
+            } else if !(item.create_symlink || item.delete_source || item.defer_source_deletion) {
+                Some("迁移后源数据仍保留")
+            } else {
+                None
+            };
+            if let Some(reason) = skip_reason {
+                estimate.skipped.push(format!("{}: {}", item.source_path, reason));
+                continue;
+            }
+
+            match analyzer.measure_path(source) {
+                Ok(totals) => {
+                    estimate.counted_items += 1;
+                    estimate.moved_bytes += totals.size;
+                    if item.create_symlink {
+                        estimate.symlink_overhead_bytes += SYMLINK_OVERHEAD_BYTES;
+                    }
+                }
+                Err(e) => estimate.skipped.push(format!("{}: {}", item.source_path, e)),
+            }
+        }
+
+        estimate.freed_bytes = estimate.moved_bytes.saturating_sub(estimate.symlink_overhead_bytes);
+        estimate.projected_free_bytes = estimate.current_free_bytes.map(|free| free + estimate.freed_bytes);
+        estimate
+    }
+
+    /// 以有限并发执行一组迁移，单项失败不影响其他项目
+    ///
+    /// 每项交给 `migrate_item` 在阻塞线程池中完成，不占用异步工作线程；`migrate_item` 负责校验选项、
+    /// 登记路径锁和执行迁移（与单项迁移走同一流程），返回的错误按其消息键记入该项结果。
+    /// 服务只有一个取消标志，取消作用于整批：进行中的项目全部中止，尚未开始的项目直接标记为已取消。
+    pub async fn migrate_batch<F>(
+        &self,
+        items: Vec<MigrationOptions>,
+        max_parallel: usize,
+        migrate_item: F,
+    ) -> BatchMigrationResult
+    where
+        F: Fn(MigrationOptions) -> Result<MigrationResult, KeyedError> + Send + Sync + 'static,
+    {
+        let total = items.len();
+        let mut batch = BatchMigrationResult::default();
+        info!("批量迁移共 {} 个项目，最多同时进行 {} 个", total, max_parallel.max(1));
+
+        let paths: Vec<(String, String)> = items.iter()
+            .map(|options| (options.source_path.clone(), options.target_path.clone()))
+            .collect();
+        let failed = |(source_path, target_path): &(String, String), key: MessageKey, detail: Option<String>| MigrationResult {
+            success: false,
+            source_path: source_path.clone(),
+            target_path: target_path.clone(),
+            ..Default::default()
+        }.with_message(key, detail);
+
+        let migrate_item = Arc::new(migrate_item);
+        let outputs = run_bounded(items, max_parallel, |index, options| {
+            let migrate_item = Arc::clone(&migrate_item);
+            let cancel_flag = Arc::clone(&self.cancel_flag);
+            async move {
+                info!("批量迁移 [{}/{}]: {} -> {}", index + 1, total, options.source_path, options.target_path);
+                if cancel_flag.load(Ordering::Relaxed) {
+                    return None;
+                }
+                Some(tokio::task::spawn_blocking(move || migrate_item(options)).await)
+            }
+        }).await;
+
+        for (item_paths, output) in paths.iter().zip(outputs) {
+            let result = match output {
+                Ok(Some(Ok(Ok(result)))) => result,
+                Ok(Some(Ok(Err(e)))) => failed(item_paths, e.key, e.detail),
+                Ok(None) => MigrationResult {
+                    cancellation_reason: Some(self.cancellation_reason().unwrap_or_default()),
+                    ..failed(item_paths, MessageKey::MigrationCancelled, None)
+                },
+                Ok(Some(Err(e))) | Err(e) => failed(item_paths, MessageKey::MigrationFailed, Some(format!("迁移任务异常退出: {}", e))),
+            };
+
+            if result.success {
+                batch.succeeded += 1;
+            } else {
+                warn!("批量迁移项目失败: {} ({})", result.source_path, result.message);
+                batch.failed += 1;
+            }
+            batch.results.push(result);
+        }
+
+        batch.summary = format!("批量迁移完成: 成功 {} 个，失败 {} 个，共 {} 个", batch.succeeded, batch.failed, total);
+        info!("{}", batch.summary);
+        batch
+    }
+
+    /// 复制阶段使用的暂存路径：与最终目标同目录，保证提交时的重命名是原子操作
+    pub(crate) fn staging_path(target: &Path) -> PathBuf {
+        let name = target.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        target.with_file_name(format!(".{}.dirmover-partial", name))
+    }
+
+    /// 合并迁移时被覆盖文件的原内容保存路径（目标同级的隐藏目录）
+    pub(crate) fn merge_originals_path(target: &Path) -> PathBuf {
+        let name = target.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        target.with_file_name(format!(".{}.dirmover-merge-originals", name))
+    }
+
+    /// 源目录在创建链接或删除前被移到的备份路径（`<名称>.backup`）
+    fn source_backup_path(source: &Path) -> Option<PathBuf> {
+        let name = source.file_name()?;
+        Some(source.with_file_name(format!("{}.backup", name.to_string_lossy())))
+    }
+
+    /// 路径本身（不跟随链接）是否为真实目录
+    fn is_real_directory(path: &Path) -> bool {
+        fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_dir())
+    }
+
+    /// 源目录已被链接替换或删除时恢复源目录，并删除多余的迁移目标，返回已清理的目标端路径
+    ///
+    /// 源数据仍保留在 `.backup` 时直接重命名回来；否则迁移目标就是唯一的数据，先把它移到源位置旁
+    /// （同一磁盘内重命名，否则复制并校验），再移除源位置的链接并提交。链接移除失败时数据移回原处，
+    /// 迁移目标保持不变。
+    fn restore_migrated_source(&self, source: &Path, target: &Path) -> Result<Vec<String>, String> {
+        let remove_link = || -> Result<(), String> {
+            if fs::symlink_metadata(source).is_ok() {
+                fs::remove_file(source).or_else(|_| fs::remove_dir(source))
+                    .map_err(|e| format!("删除源位置的链接失败: {}", e))?;
+            }
+            Ok(())
+        };
+
+        if let Some(backup) = Self::source_backup_path(source).filter(|backup| Self::is_real_directory(backup)) {
+            remove_link()?;
+            fs::rename(extended_length_path(&backup), extended_length_path(source))
+                .map_err(|e| format!("从 {} 恢复源目录失败: {}", backup.display(), e))?;
+        } else {
+            if !Self::is_real_directory(target) {
+                return Err(format!("源目录与迁移目标都不存在，无法恢复: {}", target.display()));
+            }
+            let name = source.file_name()
+                .ok_or_else(|| "无法获取源目录名称".to_string())?;
+            let restoring = source.with_file_name(format!(".{}.dirmover-restore", name.to_string_lossy()));
+            let copied = self.move_migration_target(target, &restoring)
+                .map_err(|e| format!("恢复源目录失败，迁移目标保留在 {}: {}", target.display(), e))?;
+
+            let committed = remove_link().and_then(|_| {
+                fs::rename(extended_length_path(&restoring), extended_length_path(source))
+                    .map_err(|e| format!("提交恢复的源目录失败: {}", e))
+            });
+            if let Err(e) = committed {
+                let moved_back = if copied {
+                    fs::remove_dir_all(&restoring)
+                } else {
+                    fs::rename(&restoring, target)
+                };
+                if let Err(move_error) = moved_back {
+                    error!("撤销源目录恢复失败 {}: {}", restoring.display(), move_error);
+                }
+                return Err(e);
+            }
+            if !copied {
+                return Ok(vec![target.display().to_string()]);
+            }
+        }
+
+        // 源目录已恢复，迁移目标即为多余的副本
+        if target.exists() {
+            let target_path = extended_length_path(target);
+            fs::remove_dir_all(&target_path).or_else(|_| fs::remove_file(&target_path))
+                .map_err(|e| format!("源目录已恢复，但删除迁移目标失败: {}", e))?;
+            return Ok(vec![target.display().to_string()]);
+        }
+        Ok(Vec::new())
+    }
+
+    /// 目标提交后删除源数据
+    ///
+    /// 已创建链接时源数据位于 `.backup`；否则先把源目录整体移到 `.backup` 再删除，
+    /// 删除中途失败时原位置不会留下残缺的源目录，剩余数据保留在 `.backup` 中可供恢复。
+    fn remove_migrated_source(&self, source: &Path, linked: bool, use_trash: bool) -> Result<(), String> {
+        let backup = Self::source_backup_path(source)
+            .ok_or_else(|| "无法确定源目录备份路径".to_string())?;
+
+        if !linked {
+            fs::rename(extended_length_path(source), extended_length_path(&backup))
+                .map_err(|e| format!("移动源目录失败，源目录保持不变: {}", e))?;
+        }
+
+        self.inject_failure(MigrationPhase::SourceCleanup)
+            .and_then(|_| self.file_operator.delete_path_with_options(&backup, use_trash))
+            .map_err(|e| format!("剩余数据保留在 {}: {}", backup.display(), e))?;
+
+        info!("源目录删除成功: {}", source.display());
+        Ok(())
+    }
+
+    /// 断点续传检查点文件路径（由源路径和目标路径唯一确定）
+    ///
+    /// 文件名取两条路径的 SHA-256（十六进制），跨 Rust 版本保持稳定，升级后仍能找到中断前的检查点。
+    pub fn checkpoint_path(source: &Path, target: &Path) -> PathBuf {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for path in [source, target] {
+            let bytes = path.as_os_str().as_encoded_bytes();
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        }
+        let digest: String = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        std::env::temp_dir()
+            .join("dir_mover_checkpoints")
+            .join(format!("{}.checkpoint", digest))
+    }
+
+    /// 预迁移检查（增强版）
+    ///
+    /// 通过时返回需要提示用户的警告。
+    async fn pre_migration_check(
+        &self,
+        source: &Path,
+        target: &Path,
+        options: &MigrationOptions,
+        allow_existing_target: bool,
+    ) -> Result<Vec<String>, String> {
+        // 1. 路径安全性检查
+        match self.validate_path_security(source, target) {
+            Ok(_) => {},
+            Err(e) => return Err(format!("路径安全检查失败: {}", e)),
+        }
+
+        // 2. 验证路径
+        match self.file_operator.validate_migration_path_with(source, target, allow_existing_target, options.allow_nested_target) {
+            Ok((valid, message, _)) => {
+                if !valid {
+                    return Err(message);
+                }
+            },
+            Err(e) => {
+                return Err(format!("路径验证失败: {}", e));
+            }
+        }
+
+        // 3. 权限检查
+        match self.check_permissions(source, target).await {
+            Ok(_) => {},
+            Err(e) => return Err(format!("权限检查失败: {}", e)),
+        }
+        if options.deep_permission_check {
+            let inaccessible = self.scan_for_inaccessible_paths(source);
+            if let Some(first) = inaccessible.first() {
+                return Err(format!(
+                    "权限检查失败: 源目录中有 {} 个无法读取的条目，首个: {}",
+                    inaccessible.len(),
+                    first.display()
+                ));
+            }
+        }
+
+        // 4. 磁盘空间检查
+        match self.check_disk_space(source, target, options.min_target_free_bytes_after).await {
+            Ok(_) => {},
+            Err(e) => return Err(format!("磁盘空间检查失败: {}", e)),
+        }
+
+        // 5. 系统保护检查
+        match self.check_system_protection(source, target) {
+            Ok(_) => {},
+            Err(e) => return Err(format!("系统保护检查失败: {}", e)),
+        }
+
+        // 6. 文件系统对链接的支持
+        let mut warnings = if options.create_symlink {
+            self.check_link_support(source, options.link_type)?
+        } else {
+            Vec::new()
+        };
+
+        // 7. 目标磁盘健康状况（仅警告，不阻止迁移）
+        let link_type = options.create_symlink.then_some(options.link_type);
+        warnings.extend(self.check_target_health(target, link_type));
+
+        info!("预迁移检查通过: {} -> {}", source.display(), target.display());
+        Ok(warnings)
+    }
+
+    /// 检查目标磁盘的健康状况，返回不阻止迁移的警告
+    ///
+    /// 包括剩余空间低于5%、可移动磁盘和网络驱动器；SMART 状态没有跨平台的查询方式，暂不检查。
+    /// `link_type` 为迁移后要创建的链接类型，目标位于网络驱动器时一并提示链接可能失效。
+    pub fn check_target_health(&self, target: &Path, link_type: Option<LinkType>) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let Some(status) = self.drive_status(target) {
+            if status.total_space > 0
+                && (status.available_space as f64 / status.total_space as f64) < LOW_FREE_SPACE_RATIO
+            {
+                warnings.push(format!(
+                    "目标磁盘剩余空间不足5%（剩余 {} / 共 {}），迁移后磁盘可能被写满",
+                    format_file_size(status.available_space),
+                    format_file_size(status.total_space)
+                ));
+            }
+            if status.is_removable {
+                warnings.push("目标位于可移动磁盘，断开后迁移的数据将无法访问".to_string());
+            }
+        }
+
+        if self.filesystem_kind(target) == FilesystemKind::Network {
+            warnings.push(match link_type {
+                Some(link_type) => format!(
+                    "目标位于网络驱动器，网络断开时迁移的数据将无法访问，且{}可能无法指向网络位置或被系统策略禁用",
+                    link_type.description()
+                ),
+                None => "目标位于网络驱动器，网络断开时迁移的数据将无法访问".to_string(),
+            });
+        }
+
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        warnings
+    }
+
+    /// 路径属于系统保护目录、程序安装目录或系统文件时返回原因
+    pub fn protection_reason(&self, path: &Path) -> Option<String> {
+        self.check_system_protection(path, path).err()
+    }
+
+    /// 解析迁移后源位置的链接，返回链接类型、记录的指向和真实路径
+    pub fn resolve_migration_target(link_path: &Path) -> Result<LinkResolution, String> {
+        let link_type = detect_link_type(link_path)
+            .ok_or_else(|| format!("路径不是符号链接或目录联接: {}", link_path.display()))?;
+        let link_target = fs::read_link(link_path)
+            .map_err(|e| format!("读取链接失败: {}", e))?;
+        let resolved_path = fs::canonicalize(link_path).ok()
+            .map(|resolved| strip_verbatim_prefix(&resolved).display().to_string());
+
+        Ok(LinkResolution {
+            link_path: link_path.display().to_string(),
+            link_type,
+            link_target: strip_verbatim_prefix(&link_target).display().to_string(),
+            resolved_path,
+        })
+    }
+
+    /// 把已迁移的目标移到另一个磁盘，并让源位置的链接改为指向新位置
+    ///
+    /// 新目标保持旧目标在其磁盘上的布局。数据先移动（同一磁盘内重命名，否则复制到暂存目录并
+    /// 校验内容后提交），再在链接旁创建指向新目标的临时链接并替换原链接；任一步失败都会把数据
+    /// 移回原处并保留原链接。复制方式下旧目标在链接替换成功后才删除。
+    ///
+    /// 移动和校验都是同步的文件操作，调用方应在阻塞线程池中执行。
+    pub async fn relocate_migrated_target(&self, link_path: &Path, new_target_drive: &Path) -> Result<RelocationResult, String> {
+        let (link_type, previous_target) = Self::read_migration_link(link_path)?;
+
+        if !previous_target.is_dir() {
+            return Err(format!("迁移目标不存在: {}", previous_target.display()));
+        }
+        if !new_target_drive.is_dir() {
+            return Err(format!("目标磁盘不存在: {}", new_target_drive.display()));
+        }
+        let new_target = Self::relocated_target_path(&previous_target, new_target_drive)
+            .ok_or_else(|| format!("无法确定新的迁移目标: {}", previous_target.display()))?;
+        if same_path(&new_target, &previous_target) {
+            return Err(format!("新目标与当前目标相同: {}", new_target.display()));
+        }
+        if fs::symlink_metadata(&new_target).is_ok() {
+            return Err(format!("新目标已存在: {}", new_target.display()));
+        }
+        self.check_system_protection(&previous_target, &new_target)?;
+        self.check_disk_space(&previous_target, &new_target, 0).await?;
+
+        info!("开始改换迁移目标: {} ({} -> {})", link_path.display(), previous_target.display(), new_target.display());
+        let copied = self.move_migration_target(&previous_target, &new_target)?;
+        let move_back = || {
+            let restored = if copied {
+                fs::remove_dir_all(&new_target)
+            } else {
+                fs::rename(&new_target, &previous_target)
+            };
+            match restored {
+                Ok(_) => warn!("已撤销迁移目标的移动: {}", previous_target.display()),
+                Err(e) => error!("撤销迁移目标的移动失败 {}: {}", new_target.display(), e),
+            }
+        };
+
+        if let Err(e) = self.replace_link(link_path, &new_target, link_type) {
+            move_back();
+            return Err(format!("更新链接失败，已恢复原目标: {}", e));
+        }
+
+        let mut warnings = Vec::new();
+        if copied {
+            if let Err(e) = self.file_operator.delete_path(&previous_target) {
+                let warning = format!("链接已更新，但旧目标删除失败 {}: {}", previous_target.display(), e);
+                warn!("{}", warning);
+                warnings.push(warning);
+            }
+        }
+
+        info!("迁移目标已改换: {} -> {}", link_path.display(), new_target.display());
+        Ok(RelocationResult {
+            link_path: link_path.display().to_string(),
+            link_type,
+            previous_target: previous_target.display().to_string(),
+            new_target: new_target.display().to_string(),
+            copied,
+            warnings,
+        })
+    }
+
+    /// 改换磁盘后迁移目标的新路径，供调用方在移动前锁定
+    pub fn relocation_target(link_path: &Path, new_target_drive: &Path) -> Result<PathBuf, String> {
+        let (_, previous_target) = Self::read_migration_link(link_path)?;
+        Self::relocated_target_path(&previous_target, new_target_drive)
+            .ok_or_else(|| format!("无法确定新的迁移目标: {}", previous_target.display()))
+    }
+
+    /// 读取源位置链接的类型及其指向的迁移目标（相对指向按链接所在目录解析）
+    fn read_migration_link(link_path: &Path) -> Result<(LinkType, PathBuf), String> {
+        let link_type = detect_link_type(link_path)
+            .ok_or_else(|| format!("路径不是符号链接或目录联接: {}", link_path.display()))?;
+        let recorded = fs::read_link(link_path)
+            .map_err(|e| format!("读取链接失败: {}", e))?;
+        let target = match link_path.parent() {
+            Some(parent) if recorded.is_relative() => parent.join(&recorded),
+            _ => recorded,
+        };
+        Ok((link_type, strip_verbatim_prefix(&target)))
+    }
+
+    /// 迁移目标在新磁盘上的路径：Windows 下保留去掉盘符后的路径；没有盘符的平台上保留
+    /// 从 `AppData` 起的部分，不在 `AppData` 布局下时只保留目录名
+    fn relocated_target_path(previous_target: &Path, new_target_drive: &Path) -> Option<PathBuf> {
+        use std::path::Component;
+
+        let components: Vec<Component> = previous_target.components().collect();
+        let relative: PathBuf = if matches!(components.first(), Some(Component::Prefix(_))) {
+            components.iter()
+                .filter(|component| matches!(component, Component::Normal(_)))
+                .collect()
+        } else {
+            match components.iter().rposition(|c| c.as_os_str().to_string_lossy().eq_ignore_ascii_case("AppData")) {
+                Some(index) => components[index..].iter().collect(),
+                None => PathBuf::from(previous_target.file_name()?),
+            }
+        };
+
+        (!relative.as_os_str().is_empty()).then(|| new_target_drive.join(relative))
+    }
+
+    /// 把迁移目标移到新位置，返回是否经由复制完成
+    ///
+    /// 同一磁盘内直接重命名；否则复制到新位置旁的暂存目录，内容校验一致后再重命名为新目标，
+    /// 旧目标保持不变，由调用方在链接更新后删除。
+    fn move_migration_target(&self, from: &Path, to: &Path) -> Result<bool, String> {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        if fs::rename(extended_length_path(from), extended_length_path(to)).is_ok() {
+            return Ok(false);
+        }
+
+        let staging = Self::staging_path(to);
+        let discard_staging = || {
+            if let Err(e) = fs::remove_dir_all(&staging) {
+                error!("删除暂存目录失败 {}: {}", staging.display(), e);
+            }
+        };
+        let copy_options = CopyOptions {
+            preserve_metadata: true,
+            ..Default::default()
+        };
+        if let Err(e) = self.file_operator.copy_path_with_options(from, &staging, &copy_options) {
+            discard_staging();
+            return Err(format!("复制迁移目标失败: {}", e));
+        }
+
+        let (expected, unreadable) = Self::checksum_tree(from);
+        let (actual, _) = Self::checksum_tree(&staging);
+        if !unreadable.is_empty() || expected != actual {
+            discard_staging();
+            return Err(format!("复制后的内容与原目标不一致: {}", from.display()));
+        }
+
+        fs::rename(extended_length_path(&staging), extended_length_path(to)).map_err(|e| {
+            discard_staging();
+            format!("提交新目标失败: {}", e)
+        })?;
+        Ok(true)
+    }
+
+    /// 让链接改为指向新目标：先在链接旁创建并验证临时链接，再替换原链接
+    fn replace_link(&self, link_path: &Path, new_target: &Path, link_type: LinkType) -> Result<(), FileOperationError> {
+        let name = link_path.file_name()
+            .ok_or_else(|| FileOperationError::InvalidPath("无法获取链接名称".to_string()))?;
+        let temp_link = link_path.with_file_name(format!(".{}.dirmover-relink", name.to_string_lossy()));
+        let remove_link = |path: &Path| fs::remove_file(path).or_else(|_| fs::remove_dir(path));
+        if fs::symlink_metadata(&temp_link).is_ok() {
+            remove_link(&temp_link)?;
+        }
+
+        let prepared = self.inject_failure(MigrationPhase::Link)
+            .and_then(|_| self.file_operator.create_link(new_target, &temp_link, link_type))
+            .and_then(|_| Self::verify_link_target(&temp_link, new_target));
+        if let Err(e) = prepared {
+            if fs::symlink_metadata(&temp_link).is_ok() {
+                if let Err(remove_error) = remove_link(&temp_link) {
+                    error!("删除临时链接失败 {}: {}", temp_link.display(), remove_error);
+                }
+            }
+            return Err(e);
+        }
+
+        // Unix 下重命名会原子地替换原链接；Windows 不能覆盖已存在的目录联接，先把原链接移开
+        #[cfg(windows)]
+        {
+            let old_link = link_path.with_file_name(format!(".{}.dirmover-oldlink", name.to_string_lossy()));
+            fs::rename(link_path, &old_link)?;
+            if let Err(e) = fs::rename(&temp_link, link_path) {
+                if let Err(restore_error) = fs::rename(&old_link, link_path) {
+                    error!("恢复原链接失败 {}: {}", link_path.display(), restore_error);
+                }
+                let _ = remove_link(&temp_link);
+                return Err(e.into());
+            }
+            if let Err(e) = remove_link(&old_link) {
+                warn!("删除旧链接失败 {}: {}", old_link.display(), e);
+            }
+        }
+        #[cfg(not(windows))]
+        {
+            if let Err(e) = fs::rename(&temp_link, link_path) {
+                let _ = remove_link(&temp_link);
+                return Err(e.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 检查单个路径的迁移就绪情况（存在性、链接、系统保护、大小、读写权限、所在磁盘）
+    ///
+    /// 统计大小需要完整遍历目录，仅在 `measure_size` 为 true 时进行。
+    pub fn inspect_path(&self, path: &Path, measure_size: bool) -> PathInspection {
+        let mut inspection = PathInspection {
+            path: path.display().to_string(),
+            ..Default::default()
+        };
+
+        if let Some(reason) = self.protection_reason(path) {
+            inspection.is_system_protected = true;
+            inspection.reasons.push(reason);
+        }
+
+        let link_metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                inspection.reasons.push("路径不存在".to_string());
+                return inspection;
+            }
+        };
+        inspection.is_symlink = link_metadata.file_type().is_symlink();
+        inspection.exists = path.exists();
+        if !inspection.exists {
+            inspection.reasons.push("链接指向的目标不存在".to_string());
+            return inspection;
+        }
+
+        inspection.is_dir = path.is_dir();
+        if inspection.is_dir {
+            inspection.readable = self.has_read_permission(path);
+            inspection.writable = self.has_write_permission(path);
+        } else {
+            inspection.readable = fs::File::open(path).is_ok();
+            inspection.writable = fs::OpenOptions::new().write(true).open(path).is_ok();
+        }
+        if measure_size {
+            inspection.estimated_size = DiskAnalyzer::new().measure_path(path).ok().map(|totals| totals.size);
+        }
+        inspection.on_removable_drive = self.drive_status(path)
+            .is_some_and(|status| status.is_removable);
+
+        inspection
+    }
+
+    /// 检查源位置（链接所在）的文件系统能否支持所需链接
+    fn check_link_support(&self, source: &Path, link_type: LinkType) -> Result<Vec<String>, String> {
+        let mut warnings = Vec::new();
+
+        let link_fs = self.filesystem_kind(source);
+        let fallback = link_type.fallback();
+        match (link_fs.supports_link(link_type), link_fs.supports_link(fallback)) {
+            (Some(false), Some(false)) => {
+                return Err(format!(
+                    "源位置的文件系统 {} 不支持{}或{}，无法在迁移后创建链接",
+                    link_fs.description(), link_type.description(), fallback.description()
+                ));
+            }
+            (Some(false), _) => warnings.push(format!(
+                "源位置的文件系统 {} 不支持{}，将尝试改用{}",
+                link_fs.description(), link_type.description(), fallback.description()
+            )),
+            _ => {}
+        }
+
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        Ok(warnings)
+    }
+
+    /// 验证复制结果
+    ///
+    /// 总大小差异超过 `size_tolerance_percent`，或文件数量差异超过 `file_count_tolerance`（给定时）即视为失败；
+    /// `excluded` 中的内容不计入源目录的预期大小和文件数。
+    /// `merged` 为合并时复制阶段的逐文件处理记录：目标含有合并前已有的内容，不比较大小和数量，
+    /// 改为要求每个源文件都已出现在目标中，且实际写入的文件与源文件内容一致
+    async fn verify_copy_result(
+        &self,
+        source: &Path,
+        target: &Path,
+        size_tolerance_percent: f64,
+        file_count_tolerance: Option<u64>,
+        excluded: &ExcludedSubpaths,
+        merged: Option<&[FileActionRecord]>,
+    ) -> Result<(), String> {
+        let source = &extended_length_path(source);
+        let target = &extended_length_path(target);
+        let excluded_paths: Vec<PathBuf> = excluded.paths.iter().map(|subpath| source.join(subpath)).collect();
+
+        // 检查目标是否存在
+        if !target.exists() {
+            return Err("目标目录不存在".to_string());
+        }
+
+        if let Some(file_actions) = merged {
+            Self::verify_merged_files(source, target, &excluded_paths)?;
+            return Self::verify_merged_contents(file_actions);
+        }
+
+        // 比较文件数量（粗略验证）
         let source_info = self.get_directory_info(source).await
             .map_err(|e| format!("无法获取源目录信息: {}", e))?;
         
         let target_info = self.get_directory_info(target).await
             .map_err(|e| format!("无法获取目标目录信息: {}", e))?;
 
-        // 允许10%的差异（考虑到系统文件、临时文件等）
-        let size_diff = if source_info.size > target_info.size {
-            source_info.size - target_info.size
-        } else {
-            target_info.size - source_info.size
-        };
+        // 简要目录信息不含文件计数，需要比较数量时单独统计
+        if let Some(tolerance) = file_count_tolerance {
+            let analyzer = DiskAnalyzer::new();
+            let source_files = analyzer.measure_path(source)
+                .map_err(|e| format!("无法统计源目录文件数: {}", e))?.file_count;
+            let target_files = analyzer.measure_path(target)
+                .map_err(|e| format!("无法统计目标目录文件数: {}", e))?.file_count;
+            let source_files = source_files.saturating_sub(excluded.files);
+            if source_files.abs_diff(target_files) > tolerance {
+                return Err(format!(
+                    "文件数量差异过大: 源 {} 个, 目标 {} 个 (允许差异 {} 个)",
+                    source_files, target_files, tolerance
+                ));
+            }
+        }
+
+        let source_size = source_info.size.saturating_sub(excluded.bytes);
+        let size_diff = source_size.abs_diff(target_info.size);
 
-        let size_diff_percentage = (size_diff as f64 / source_info.size as f64) * 100.0;
+        let size_diff_percentage = (size_diff as f64 / source_size as f64) * 100.0;
         
-        if size_diff_percentage > 10.0 {
-            return Err(format!("大小差异过大: {:.1}%", size_diff_percentage));
+        if size_diff_percentage > size_tolerance_percent {
+            return Err(format!("大小差异过大: {:.1}% (允许 {:.1}%)", size_diff_percentage, size_tolerance_percent));
         }
 
         // 检查关键文件是否存在
-        self.verify_key_files(source, target)?;
+        self.verify_key_files(source, target, &excluded_paths)?;
 
         Ok(())
     }
@@ -230,12 +1929,13 @@ impl MigrationService {
             .map_err(|e| format!("获取目录信息失败: {}", e))
     }
 
-    /// 验证关键文件
-    fn verify_key_files(&self, source: &Path, target: &Path) -> Result<(), String> {
+    /// 验证关键文件（跳过 `excluded` 中的路径）
+    fn verify_key_files(&self, source: &Path, target: &Path, excluded: &[PathBuf]) -> Result<(), String> {
         // 获取源目录中的前10个文件/目录
         let source_entries = std::fs::read_dir(source)
             .map_err(|e| format!("读取源目录失败: {}", e))?
             .filter_map(|entry| entry.ok())
+            .filter(|entry| !excluded.iter().any(|excluded| entry.path().starts_with(excluded)))
             .take(10)
             .collect::<Vec<_>>();
 
@@ -249,15 +1949,59 @@ impl MigrationService {
 
             // 如果是目录，递归验证
             if source_entry.path().is_dir() {
-                self.verify_key_files(&source_entry.path(), &target_entry_path)?;
+                self.verify_key_files(&source_entry.path(), &target_entry_path, excluded)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 验证合并结果：源目录中的每个文件和子目录（跳过 `excluded` 中的路径）都已出现在目标中
+    fn verify_merged_files(source: &Path, target: &Path, excluded: &[PathBuf]) -> Result<(), String> {
+        let entries = fs::read_dir(source)
+            .map_err(|e| format!("读取源目录失败: {}", e))?;
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let entry_path = entry.path();
+            if excluded.iter().any(|excluded| entry_path.starts_with(excluded)) {
+                continue;
+            }
+
+            let target_entry_path = target.join(entry.file_name());
+            if !target_entry_path.exists() {
+                return Err(format!("合并后目标中缺少: {}", display_path(&target_entry_path)));
+            }
+            if entry_path.is_dir() {
+                Self::verify_merged_files(&entry_path, &target_entry_path, excluded)?;
             }
         }
 
         Ok(())
     }
 
+    /// 验证合并时实际写入的文件（跳过的文件除外，目标中保留的是原有版本）与源文件内容一致
+    fn verify_merged_contents(file_actions: &[FileActionRecord]) -> Result<(), String> {
+        for record in file_actions.iter().filter(|record| record.action != FileAction::Skipped) {
+            let source = extended_length_path(Path::new(&record.source_path));
+            let target = extended_length_path(Path::new(&record.target_path));
+            match files_identical(&source, &target) {
+                Ok(true) => {}
+                Ok(false) => return Err(format!("合并后文件内容与源文件不一致: {}", record.target_path)),
+                Err(e) => return Err(format!("比较合并后的文件失败 {}: {}", record.target_path, e)),
+            }
+        }
+        Ok(())
+    }
+
     /// 创建符号链接
-    async fn create_symlink_after_migration(&self, source: &Path, target: &Path) -> Result<FileOperationResult, FileOperationError> {
+    ///
+    /// 源目录先重命名为 `.backup`；链接创建失败或未指向迁移目标时删除链接并恢复源目录。
+    async fn create_symlink_after_migration(
+        &self,
+        source: &Path,
+        target: &Path,
+        link_type: LinkType,
+    ) -> Result<(FileOperationResult, LinkType), FileOperationError> {
         // 在源目录的父目录中创建符号链接
         if let Some(parent) = source.parent() {
             let link_name = source.file_name()
@@ -266,21 +2010,195 @@ impl MigrationService {
             let link_path = parent.join(link_name);
             
             // 如果源目录还存在，先重命名它
+            let mut backup_path = None;
             if source.exists() {
-                let backup_name = format!("{}.backup", link_name.to_string_lossy());
-                let backup_path = parent.join(backup_name);
-                std::fs::rename(source, &backup_path)
+                let path = Self::source_backup_path(source)
+                    .ok_or_else(|| FileOperationError::InvalidPath("无法确定源目录备份路径".to_string()))?;
+                std::fs::rename(extended_length_path(source), extended_length_path(&path))
                     .map_err(|e| FileOperationError::IoError(e))?;
+                backup_path = Some(path);
             }
 
-            // 创建符号链接
-            self.file_operator.create_symlink(target, &link_path)
+            // 创建链接（失败时自动回退到另一种链接类型）
+            let created = match self.file_operator.create_link(target, &link_path, link_type) {
+                Ok(created) => created,
+                Err(e) => {
+                    Self::restore_source_backup(backup_path.as_deref(), &link_path);
+                    return Err(e);
+                }
+            };
+
+            // 确认链接确实解析到迁移目标
+            if let Err(e) = Self::verify_link_target(&link_path, target) {
+                if let Err(remove_error) = fs::remove_file(&link_path).or_else(|_| fs::remove_dir(&link_path)) {
+                    error!("删除无效链接失败 {}: {}", link_path.display(), remove_error);
+                }
+                Self::restore_source_backup(backup_path.as_deref(), &link_path);
+                return Err(e);
+            }
+
+            Ok(created)
         } else {
             Err(FileOperationError::InvalidPath("无法确定源目录父路径".to_string()))
         }
     }
 
 
+    /// 验证链接解析后的真实路径与迁移目标一致
+    fn verify_link_target(link_path: &Path, target: &Path) -> Result<(), FileOperationError> {
+        let resolved = fs::canonicalize(link_path).map_err(|e| {
+            FileOperationError::InvalidPath(format!("链接无法解析 {}: {}", link_path.display(), e))
+        })?;
+        let expected = fs::canonicalize(target)?;
+
+        if resolved != expected {
+            return Err(FileOperationError::InvalidPath(format!(
+                "链接 {} 指向 {}，而非迁移目标 {}",
+                link_path.display(),
+                resolved.display(),
+                expected.display()
+            )));
+        }
+
+        info!("链接验证通过: {} -> {}", link_path.display(), expected.display());
+        Ok(())
+    }
+
+    /// 检查一次已完成迁移的链接与目标是否仍然一致
+    ///
+    /// 确认源位置的链接存在、能够解析且目标仍然存在；`recheck_contents` 为 true 时
+    /// 重新读取目标中的全部文件计算内容校验值，并与迁移时记录的基线比对。
+    pub fn verify_migration_integrity(&self, source: &Path, recheck_contents: bool) -> MigrationIntegrityReport {
+        let mut report = MigrationIntegrityReport {
+            source_path: source.display().to_string(),
+            baseline_checksum: self.integrity_baselines.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(&source.display().to_string())
+                .cloned(),
+            ..Default::default()
+        };
+
+        match fs::symlink_metadata(source) {
+            Ok(metadata) if metadata.file_type().is_symlink() => report.link_exists = true,
+            Ok(_) => report.issues.push("源路径不是链接，可能已被还原或重新创建".to_string()),
+            Err(_) => report.issues.push("源位置的链接不存在".to_string()),
+        }
+
+        if report.link_exists {
+            match fs::read_link(source) {
+                Ok(target) => {
+                    let target = if target.is_relative() {
+                        source.parent().map(|parent| parent.join(&target)).unwrap_or(target)
+                    } else {
+                        target
+                    };
+                    report.target_exists = target.exists();
+                    if !report.target_exists {
+                        report.issues.push(format!("迁移目标不存在: {}", target.display()));
+                    }
+                    report.target_path = Some(target.display().to_string());
+                }
+                Err(e) => report.issues.push(format!("读取链接失败: {}", e)),
+            }
+
+            report.link_resolves = fs::canonicalize(source).is_ok();
+            if !report.link_resolves {
+                report.issues.push("链接无法解析（悬空链接）".to_string());
+            }
+        }
+
+        if recheck_contents && report.link_resolves {
+            let (checksum, unreadable) = Self::checksum_tree(source);
+            if !unreadable.is_empty() {
+                report.issues.push(format!("目标中有 {} 个文件无法读取", unreadable.len()));
+            } else if report.baseline_checksum.as_ref().is_some_and(|baseline| *baseline != checksum) {
+                report.issues.push("目标内容与迁移时记录的校验值不一致".to_string());
+            }
+            report.content_checksum = Some(checksum);
+            report.unreadable_files = unreadable.iter().map(|path| path.display().to_string()).collect();
+        }
+
+        report.healthy = report.issues.is_empty();
+        if !report.healthy {
+            warn!("迁移完整性检查发现问题 {}: {:?}", source.display(), report.issues);
+        }
+        report
+    }
+
+    /// 按相对路径顺序读取目录中的全部文件，返回 SHA-256 内容校验值（十六进制）与无法读取的文件
+    ///
+    /// 每个文件依次计入带长度前缀的相对路径（以 `/` 分隔）和内容，不同的文件划分不会得到相同的输入。
+    fn checksum_tree(root: &Path) -> (String, Vec<PathBuf>) {
+        use sha2::{Digest, Sha256};
+        use std::io::Read;
+
+        let mut hasher = Sha256::new();
+        let mut unreadable = Vec::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut stack = vec![root.to_path_buf()];
+
+        while let Some(dir) = stack.pop() {
+            let mut entries: Vec<PathBuf> = match fs::read_dir(&dir) {
+                Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+                Err(_) => {
+                    unreadable.push(dir);
+                    continue;
+                }
+            };
+            entries.sort();
+
+            for path in entries {
+                let metadata = match fs::symlink_metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(_) => {
+                        unreadable.push(path);
+                        continue;
+                    }
+                };
+                if metadata.is_dir() {
+                    stack.push(path);
+                    continue;
+                }
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                let relative = path.strip_prefix(root).unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                hasher.update((relative.len() as u64).to_le_bytes());
+                hasher.update(relative.as_bytes());
+                hasher.update(metadata.len().to_le_bytes());
+                let read_result = fs::File::open(&path).and_then(|mut file| loop {
+                    let read = file.read(&mut buffer)?;
+                    if read == 0 {
+                        break Ok(());
+                    }
+                    hasher.update(&buffer[..read]);
+                });
+                if read_result.is_err() {
+                    unreadable.push(path);
+                }
+            }
+        }
+
+        unreadable.sort();
+        let digest = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+        (digest, unreadable)
+    }
+
+    /// 将 `.backup` 目录恢复为原始源目录
+    fn restore_source_backup(backup_path: Option<&Path>, source: &Path) {
+        if let Some(backup_path) = backup_path {
+            match fs::rename(backup_path, source) {
+                Ok(_) => warn!("已从备份恢复源目录: {}", source.display()),
+                Err(e) => error!("恢复源目录失败 {} -> {}: {}", backup_path.display(), source.display(), e),
+            }
+        }
+    }
+
     /// 估计所需空间
     async fn estimate_required_space(&self, path: &Path) -> Result<u64, String> {
         use crate::disk_analyzer::DiskAnalyzer;
@@ -294,18 +2212,7 @@ impl MigrationService {
 
     /// 获取可用空间（简化版）
     fn get_available_space(&self, path: &Path) -> Option<u64> {
-        // 简化实现：使用粗略估计
-        // 在实际应用中，这里应该使用系统API获取真实的磁盘空间
-        let path_str = path.to_string_lossy();
-        
-        // 根据路径所在磁盘返回估计值
-        if path_str.starts_with("C:\\") {
-            Some(50 * 1024 * 1024 * 1024) // 假设C盘有50GB可用
-        } else if path_str.starts_with("D:\\") {
-            Some(100 * 1024 * 1024 * 1024) // 假设D盘有100GB可用
-        } else {
-            Some(20 * 1024 * 1024 * 1024) // 默认20GB
-        }
+        self.drive_status(path).map(|status| status.available_space)
     }
 
     /// 路径安全性检查
@@ -319,11 +2226,16 @@ impl MigrationService {
             return Err("路径包含非法的父目录引用".to_string());
         }
         
-        // 检查是否包含特殊字符
+        // 检查文件/目录名是否包含特殊字符（盘符等路径前缀中的冒号不在检查范围内）
         let invalid_chars = ['<', '>', ':', '*', '?', '|'];
-        for char in &invalid_chars {
-            if source_str.contains(*char) || target_str.contains(*char) {
-                return Err(format!("路径包含非法字符: {}", char));
+        for path in [source, target] {
+            for component in path.components() {
+                if let std::path::Component::Normal(name) = component {
+                    let name = name.to_string_lossy();
+                    if let Some(char) = invalid_chars.iter().find(|c| name.contains(**c)) {
+                        return Err(format!("路径包含非法字符: {}", char));
+                    }
+                }
             }
         }
         
@@ -351,9 +2263,11 @@ impl MigrationService {
             }
         }
         
-        // 检查路径长度
-        if source_str.len() > 260 || target_str.len() > 260 {
-            return Err("路径过长（超过260字符）".to_string());
+        // 检查路径长度：超过 MAX_PATH 的路径在文件操作时使用扩展长度前缀，只拒绝真正无效的长度
+        check_path_length(source)?;
+        check_path_length(target)?;
+        if source_str.chars().count() >= MAX_PATH || target_str.chars().count() >= MAX_PATH {
+            info!("路径超过 MAX_PATH（{}字符），将使用扩展长度路径进行文件操作", MAX_PATH);
         }
         
         info!("路径安全检查通过");
@@ -384,7 +2298,9 @@ impl MigrationService {
     }
 
     /// 磁盘空间检查
-    async fn check_disk_space(&self, source: &Path, target: &Path) -> Result<(), String> {
+    ///
+    /// `min_free_after` 大于 0 时，要求迁移完成后目标磁盘的实际剩余空间不低于该值。
+    async fn check_disk_space(&self, source: &Path, target: &Path, min_free_after: u64) -> Result<(), String> {
         // 获取源目录大小
         let required_space = match self.estimate_required_space(source).await {
             Ok(size) => size,
@@ -396,7 +2312,8 @@ impl MigrationService {
         };
         
         // 获取目标磁盘可用空间
-        let available_space = match self.get_available_space(target) {
+        let real_available_space = self.get_available_space(target);
+        let available_space = match real_available_space {
             Some(space) => space,
             None => {
                 // 如果无法获取可用空间，检查目标父目录是否存在
@@ -420,39 +2337,50 @@ impl MigrationService {
             ));
         }
         
+        // 迁移后目标磁盘需保留的剩余空间（只按真实剩余空间判断）
+        if min_free_after > 0 {
+            match real_available_space {
+                Some(space) if space.saturating_sub(required_space) < min_free_after => {
+                    return Err(format!(
+                        "迁移后目标磁盘剩余空间将低于保留值。迁移后剩余: {}，要求保留: {}",
+                        crate::disk_analyzer::format_file_size(space.saturating_sub(required_space)),
+                        crate::disk_analyzer::format_file_size(min_free_after)
+                    ));
+                }
+                Some(_) => {}
+                None => warn!("无法获取目标磁盘的实际剩余空间，跳过保留空间检查: {}", target.display()),
+            }
+        }
+
         // 检查目标磁盘是否即将满
         let usage_percentage = (required_with_buffer as f64 / (available_space + required_with_buffer) as f64) * 100.0;
         if usage_percentage > 90.0 {
             warn!("迁移后磁盘使用率将超过90%: {:.1}%", usage_percentage);
-        }
-        
-        info!("磁盘空间检查通过: 需要 {}, 可用 {}", 
-              crate::disk_analyzer::format_file_size(required_with_buffer), 
-              crate::disk_analyzer::format_file_size(available_space));
-        Ok(())
-    }
-
-    /// 系统保护检查
-    fn check_system_protection(&self, source: &Path, target: &Path) -> Result<(), String> {
-        // 检查是否是系统关键目录
-        let system_paths = [
-            "C:\\Windows",
-            "C:\\Program Files",
-            "C:\\Program Files (x86)",
-            "C:\\Users\\Default",
-            "C:\\Recovery",
-            "C:\\System Volume Information",
-            "C:\\$Recycle.Bin",
-        ];
-        
-        let source_str = source.to_string_lossy().to_uppercase();
-        let target_str = target.to_string_lossy().to_uppercase();
+        }
         
-        for system_path in &system_paths {
-            if source_str.starts_with(system_path) || target_str.starts_with(system_path) {
-                return Err(format!("不能操作系统保护目录: {}", system_path));
+        info!("磁盘空间检查通过: 需要 {}, 可用 {}", 
+              crate::disk_analyzer::format_file_size(required_with_buffer), 
+              crate::disk_analyzer::format_file_size(available_space));
+        Ok(())
+    }
+
+    /// 系统保护检查
+    fn check_system_protection(&self, source: &Path, target: &Path) -> Result<(), String> {
+        // 检查是否是系统关键目录（同时检查解析符号链接后的真实路径）
+        let candidates = [
+            source.to_path_buf(),
+            target.to_path_buf(),
+            resolve_with_existing_ancestor(source),
+            resolve_with_existing_ancestor(target),
+        ];
+        for protected in self.protected_paths() {
+            if candidates.iter().any(|path| is_within(path, &protected)) {
+                return Err(format!("不能操作系统保护目录: {}", protected.display()));
             }
         }
+
+        let source_str = source.to_string_lossy().to_lowercase();
+        let target_str = target.to_string_lossy().to_lowercase();
         
         // 检查是否是程序安装目录
         if self.is_program_installation_directory(source) || self.is_program_installation_directory(target) {
@@ -487,6 +2415,54 @@ impl MigrationService {
         }
     }
 
+    /// 递归检查源目录，返回所有无法读取的条目（不可读的目录不再深入）
+    pub fn scan_for_inaccessible_paths(&self, source: &Path) -> Vec<PathBuf> {
+        let mut inaccessible = Vec::new();
+        let mut pending = vec![source.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("无法读取目录 {}: {}", dir.display(), e);
+                    inaccessible.push(dir);
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        warn!("读取条目失败 {}: {}", dir.display(), e);
+                        inaccessible.push(dir.clone());
+                        continue;
+                    }
+                };
+
+                let path = entry.path();
+                match entry.file_type() {
+                    // 链接不跟随，由复制逻辑单独处理
+                    Ok(file_type) if file_type.is_symlink() => {}
+                    Ok(file_type) if file_type.is_dir() => pending.push(path),
+                    Ok(_) => {
+                        if let Err(e) = fs::File::open(&path) {
+                            warn!("无法读取文件 {}: {}", path.display(), e);
+                            inaccessible.push(path);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("获取条目类型失败 {}: {}", path.display(), e);
+                        inaccessible.push(path);
+                    }
+                }
+            }
+        }
+
+        inaccessible.sort();
+        inaccessible
+    }
+
     /// 检查是否需要管理员权限
     fn requires_admin_permission(&self, source: &Path, target: &Path) -> bool {
         // 检查是否涉及系统目录
@@ -575,88 +2551,1214 @@ impl MigrationService {
                 }
             }
         }
-        
-        Err("无法估计可用磁盘空间".to_string())
+        
+        Err("无法估计可用磁盘空间".to_string())
+    }
+
+    /// 测试写入空间
+    fn test_write_space(&self, temp_file: &Path, size: u64) -> Result<(), String> {
+        // 创建指定大小的临时文件
+        let data = vec![0u8; size as usize];
+        
+        match fs::write(temp_file, &data) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("写入测试失败: {}", e)),
+        }
+    }
+
+    /// 检查是否有写入权限（增强版）
+    fn has_write_permission(&self, path: &Path) -> bool {
+        if !path.exists() {
+            // 如果路径不存在，检查父目录
+            if let Some(parent) = path.parent() {
+                return self.has_write_permission(parent);
+            }
+            return false;
+        }
+        
+        // 只根据元数据判断，不在用户目录中创建测试文件
+        match fs::metadata(path) {
+            Ok(metadata) => metadata_allows_write(&metadata),
+            Err(e) => {
+                warn!("写入权限检查失败 {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+}
+
+/// 根据权限位判断当前进程能否写入该路径
+///
+/// 通过 `/proc/self` 的属主得到当前用户；无法确定附加组，组或其他用户可写即视为可写。
+#[cfg(unix)]
+fn metadata_allows_write(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let mode = metadata.mode();
+    match fs::metadata("/proc/self") {
+        Ok(process) if process.uid() == 0 => true,
+        Ok(process) if process.uid() == metadata.uid() => mode & 0o200 != 0,
+        Ok(_) => mode & 0o022 != 0,
+        Err(_) => mode & 0o222 != 0,
+    }
+}
+
+/// 根据只读属性判断能否写入该路径（目录的只读属性不限制在其中创建文件）
+#[cfg(not(unix))]
+fn metadata_allows_write(metadata: &fs::Metadata) -> bool {
+    metadata.is_dir() || !metadata.permissions().readonly()
+}
+
+impl Default for MigrationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 以 JSON 格式写入文件，必要时创建所在目录
+fn save_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// 批量迁移默认的最大并行数：CPU 核数与 4 中的较小值，避免同时迁移大量目录时磁盘来回寻道
+pub fn default_max_parallel_migrations() -> usize {
+    std::thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+        .min(4)
+}
+
+/// 以有限并发执行一组任务：同时运行的任务不超过 `max_parallel` 个，结果按输入顺序返回
+pub async fn run_bounded<T, F, Fut>(
+    items: Vec<T>,
+    max_parallel: usize,
+    task: F,
+) -> Vec<Result<Fut::Output, tokio::task::JoinError>>
+where
+    F: Fn(usize, T) -> Fut,
+    Fut: std::future::Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(max_parallel.max(1)));
+    let handles: Vec<_> = items.into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let semaphore = Arc::clone(&semaphore);
+            let future = task(index, item);
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("信号量不会被关闭");
+                future.await
+            })
+        })
+        .collect();
+
+    let mut outputs = Vec::with_capacity(handles.len());
+    for handle in handles {
+        outputs.push(handle.await);
+    }
+    outputs
+}
+
+/// 去掉 Windows 规范化路径和联接指向中的 `\\?\` / `\??\` 前缀，便于显示
+fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    let raw = path.to_string_lossy();
+    if let Some(unc) = raw.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", unc));
+    }
+    match raw.strip_prefix(r"\\?\").or_else(|| raw.strip_prefix(r"\??\")) {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path.to_path_buf(),
+    }
+}
+
+/// 逐字节比较两个文件的内容
+fn files_identical(a: &Path, b: &Path) -> std::io::Result<bool> {
+    use std::io::Read;
+
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+
+    let mut a = std::io::BufReader::new(fs::File::open(a)?);
+    let mut b = std::io::BufReader::new(fs::File::open(b)?);
+    let mut buffer_a = vec![0u8; 64 * 1024];
+    let mut buffer_b = vec![0u8; 64 * 1024];
+    loop {
+        let read = a.read(&mut buffer_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buffer_b[..read])?;
+        if buffer_a[..read] != buffer_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// 验证迁移选项，失败时返回带消息键的错误
+pub fn validate_migration_options(options: &MigrationOptions) -> Result<(), KeyedError> {
+    let source = Path::new(&options.source_path);
+    let target = Path::new(&options.target_path);
+
+    // 检查源路径
+    if options.source_path.is_empty() {
+        return Err(KeyedError::new(MessageKey::ValidationSourceEmpty));
+    }
+
+    // 检查目标路径
+    if options.target_path.is_empty() {
+        return Err(KeyedError::new(MessageKey::ValidationTargetEmpty));
+    }
+
+    // 检查路径是否相同
+    if source == target {
+        return Err(KeyedError::new(MessageKey::ValidationSamePath));
+    }
+
+    // 排除的内容只留在源目录，源目录被链接替换或删除后会随之丢失
+    if !options.exclude_subpaths.is_empty()
+        && (options.create_symlink || options.delete_source || options.defer_source_deletion)
+    {
+        return Err(KeyedError::new(MessageKey::ValidationExcludeConflict));
+    }
+
+    // 目标位于源目录内部时，源目录被链接替换或删除会连同目标一起丢失
+    if options.allow_nested_target
+        && (options.create_symlink || options.delete_source || options.defer_source_deletion)
+    {
+        return Err(KeyedError::new(MessageKey::ValidationNestedConflict));
+    }
+
+    // 合并时被跳过的源文件没有复制到目标，源目录被链接替换或删除后这些版本会丢失
+    if options.merge
+        && options.conflict_policy == ConflictPolicy::Skip
+        && (options.create_symlink || options.delete_source || options.defer_source_deletion)
+    {
+        return Err(KeyedError::new(MessageKey::ValidationMergeSkipConflict));
+    }
+
+    // 排除的子路径必须是源目录内的普通相对路径
+    for subpath in &options.exclude_subpaths {
+        let relative = Path::new(subpath.trim());
+        let is_plain_relative = relative.components().next().is_some()
+            && relative.components().all(|component| matches!(component, std::path::Component::Normal(_)));
+        if !is_plain_relative {
+            return Err(KeyedError::with_detail(MessageKey::ValidationInvalidExcludePath, subpath.clone()));
+        }
+    }
+
+    // 检查路径是否有效
+    if let Ok(source_canonical) = std::fs::canonicalize(source) {
+        if let Ok(target_canonical) = std::fs::canonicalize(target) {
+            if source_canonical == target_canonical {
+                return Err(KeyedError::with_detail(MessageKey::ValidationSamePath, "源路径和目标路径指向同一位置"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use std::fs::{self, File};
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn test_symlink_after_migration_is_verified() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        let target = target_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("file.txt"), "内容").unwrap();
+
+        let service = MigrationService::new();
+        service.create_symlink_after_migration(&source, &target, LinkType::default()).await.unwrap();
+
+        assert_eq!(fs::canonicalize(&source).unwrap(), fs::canonicalize(&target).unwrap());
+        assert!(source.join("file.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_failed_symlink_restores_source() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("file.txt"), "原始内容").unwrap();
+
+        // 目标不存在：链接无法解析到迁移目标
+        let missing_target = target_root.path().join("missing");
+        let service = MigrationService::new();
+        let result = service.create_symlink_after_migration(&source, &missing_target, LinkType::default()).await;
+
+        assert!(result.is_err());
+        assert!(!fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(source.join("file.txt")).unwrap(), "原始内容");
+        assert!(!source_root.path().join("data.backup").exists());
+    }
+
+    /// 创建带两个文件的源目录，返回（源根目录, 目标根目录, 源路径, 目标路径, 迁移选项）
+    fn setup_swap_migration() -> (TempDir, TempDir, PathBuf, PathBuf, MigrationOptions) {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        let target = target_root.path().join("data");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("a.txt"), "alpha").unwrap();
+        fs::write(source.join("nested").join("b.txt"), "beta").unwrap();
+
+        let options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            create_symlink: true,
+            delete_source: true,
+            ..Default::default()
+        };
+        (source_root, target_root, source, target, options)
+    }
+
+    fn assert_tree_intact(root: &Path) {
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "alpha");
+        assert_eq!(fs::read_to_string(root.join("nested").join("b.txt")).unwrap(), "beta");
+    }
+
+    #[tokio::test]
+    async fn test_swap_migration_with_link_and_delete_source() {
+        let (_source_root, _target_root, source, target, options) = setup_swap_migration();
+
+        let result = MigrationService::new().migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+
+        // 删除源数据时不能经由链接删除目标
+        assert_tree_intact(&target);
+        assert!(fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+        assert_tree_intact(&source);
+        assert!(!MigrationService::source_backup_path(&source).unwrap().exists());
+        assert!(!MigrationService::staging_path(&target).exists());
+    }
+
+    #[tokio::test]
+    async fn test_relocate_migrated_target_to_another_drive() {
+        let (_source_root, _target_root, source, target, options) = setup_swap_migration();
+        let new_drive = TempDir::new().unwrap();
+        let service = MigrationService::new();
+
+        let migrated = service.migrate_folder(options).await.unwrap();
+        assert!(migrated.success, "{}", migrated.message);
+
+        let new_target = new_drive.path().join("data");
+        assert_eq!(MigrationService::relocation_target(&source, new_drive.path()).unwrap(), new_target);
+        let result = service.relocate_migrated_target(&source, new_drive.path()).await.unwrap();
+        assert_eq!(result.new_target, new_target.display().to_string());
+        assert_eq!(result.previous_target, target.display().to_string());
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        // 链接解析到新目标，旧目标已移走，数据完整
+        assert!(fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+        assert_eq!(fs::canonicalize(&source).unwrap(), fs::canonicalize(&new_target).unwrap());
+        assert!(!target.exists());
+        assert_tree_intact(&new_target);
+        assert_tree_intact(&source);
+        assert!(!MigrationService::staging_path(&new_target).exists());
+    }
+
+    #[tokio::test]
+    async fn test_relocate_rolls_back_when_link_update_fails() {
+        let (_source_root, _target_root, source, target, options) = setup_swap_migration();
+        let new_drive = TempDir::new().unwrap();
+
+        let migrated = MigrationService::new().migrate_folder(options).await.unwrap();
+        assert!(migrated.success, "{}", migrated.message);
+
+        let mut service = MigrationService::new();
+        service.injected_failure = Some(MigrationPhase::Link);
+        let error = service.relocate_migrated_target(&source, new_drive.path()).await.unwrap_err();
+        assert!(error.contains("已恢复原目标"), "{}", error);
+
+        // 原链接仍指向原目标，新磁盘上没有留下任何内容
+        assert_eq!(fs::canonicalize(&source).unwrap(), fs::canonicalize(&target).unwrap());
+        assert_tree_intact(&target);
+        assert_eq!(fs::read_dir(new_drive.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_migration_is_idempotent_noop() {
+        let (_source_root, target_root, source, target, options) = setup_swap_migration();
+        let service = MigrationService::new();
+
+        let first = service.migrate_folder(options.clone()).await.unwrap();
+        assert!(first.success && !first.already_migrated, "{}", first.message);
+
+        let second = service.migrate_folder(options).await.unwrap();
+        assert!(second.success, "{}", second.message);
+        assert!(second.already_migrated);
+        assert_eq!(second.message_key, Some(MessageKey::MigrationAlreadyCompleted));
+        assert_eq!(second.symlink_path, Some(target.display().to_string()));
+        assert!(second.file_results.is_empty());
+
+        // 未重新复制：目标下只有原来的数据，没有暂存或备份残留
+        assert!(fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+        assert_tree_intact(&target);
+        let totals = DiskAnalyzer::new().measure_path(&target).unwrap();
+        assert_eq!((totals.size, totals.file_count), (9, 2));
+        assert_eq!(fs::read_dir(target_root.path()).unwrap().count(), 1);
+        assert!(!MigrationService::source_backup_path(&source).unwrap().exists());
+
+        // 不创建链接的迁移：目标与源内容一致时同样跳过，内容不一致时照常拒绝已存在的目标
+        let (_plain_source_root, _plain_target_root, plain_source, plain_target, mut plain_options) = setup_swap_migration();
+        plain_options.create_symlink = false;
+        plain_options.delete_source = false;
+        assert!(service.migrate_folder(plain_options.clone()).await.unwrap().success);
+        let repeated = service.migrate_folder(plain_options.clone()).await.unwrap();
+        assert!(repeated.success && repeated.already_migrated, "{}", repeated.message);
+        assert_eq!(repeated.symlink_path, None);
+
+        // 合并迁移需要照常执行，即使目标内容已与源目录一致
+        let mut merge_options = plain_options.clone();
+        merge_options.merge = true;
+        merge_options.conflict_policy = ConflictPolicy::Overwrite;
+        let merged = service.migrate_folder(merge_options).await.unwrap();
+        assert!(merged.success && !merged.already_migrated, "{}", merged.message);
+        assert_tree_intact(&plain_target);
+
+        fs::write(plain_source.join("a.txt"), "changed").unwrap();
+        let changed = service.migrate_folder(plain_options).await.unwrap();
+        assert!(!changed.success && !changed.already_migrated);
+        assert_tree_intact(&plain_target);
+    }
+
+    #[tokio::test]
+    async fn test_deferred_source_deletion_with_token() {
+        let (_source_root, _target_root, source, target, mut options) = setup_swap_migration();
+        options.defer_source_deletion = true;
+        let service = MigrationService::new();
+
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        let token = result.pending_deletion_token.expect("两阶段迁移应返回删除令牌");
+
+        // 第一阶段：链接已创建，源数据保留在备份中
+        let backup = MigrationService::source_backup_path(&source).unwrap();
+        assert!(fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+        assert_tree_intact(&backup);
+        assert_tree_intact(&target);
+
+        // 第二阶段：确认后删除源数据，令牌只能使用一次
+        assert_eq!(service.confirm_source_deletion(&token).unwrap(), source);
+        assert!(!backup.exists());
+        assert_tree_intact(&source);
+        assert!(service.confirm_source_deletion(&token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pending_deletion_survives_restart() {
+        let (_source_root, _target_root, source, _target, mut options) = setup_swap_migration();
+        options.defer_source_deletion = true;
+        let log_dir = TempDir::new().unwrap();
+        let file = log_dir.path().join(PENDING_DELETIONS_FILE);
+
+        let service = MigrationService::new().with_pending_deletions_file(file.clone());
+        let result = service.migrate_folder(options).await.unwrap();
+        let token = result.pending_deletion_token.unwrap();
+        drop(service);
+
+        // 重启后凭原令牌仍可删除保留的源数据，使用后登记从文件中移除
+        let restarted = MigrationService::new().with_pending_deletions_file(file.clone());
+        assert_eq!(restarted.confirm_source_deletion(&token).unwrap(), source);
+        assert!(!MigrationService::source_backup_path(&source).unwrap().exists());
+        let reopened = MigrationService::new().with_pending_deletions_file(file);
+        assert!(reopened.confirm_source_deletion(&token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_deletion_token_is_rejected() {
+        let (_source_root, _target_root, source, _target, mut options) = setup_swap_migration();
+        options.create_symlink = false;
+        options.defer_source_deletion = true;
+        let mut service = MigrationService::new();
+        service.pending_deletion_ttl = Duration::ZERO;
+
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        let token = result.pending_deletion_token.unwrap();
+
+        let error = service.confirm_source_deletion(&token).unwrap_err();
+        assert!(error.contains("过期"), "{}", error);
+        assert_tree_intact(&source);
+    }
+
+    #[tokio::test]
+    async fn test_swap_migration_failure_at_each_phase_keeps_source() {
+        for phase in [MigrationPhase::Copy, MigrationPhase::Verify, MigrationPhase::Commit, MigrationPhase::Link] {
+            let (_source_root, _target_root, source, target, options) = setup_swap_migration();
+            let mut service = MigrationService::new();
+            service.injected_failure = Some(phase);
+
+            let result = service.migrate_folder(options).await.unwrap();
+            assert!(!result.success, "{:?} 阶段失败时迁移不应成功", phase);
+
+            // 源目录原样保留，且不残留目标、暂存目录或备份
+            assert!(!fs::symlink_metadata(&source).unwrap().file_type().is_symlink(), "{:?}", phase);
+            assert_tree_intact(&source);
+            assert!(!target.exists(), "{:?} 阶段失败后不应留下目标", phase);
+            assert!(!MigrationService::staging_path(&target).exists(), "{:?}", phase);
+            assert!(!MigrationService::source_backup_path(&source).unwrap().exists(), "{:?}", phase);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swap_migration_source_cleanup_failure_keeps_backup() {
+        for create_symlink in [true, false] {
+            let (_source_root, _target_root, source, target, mut options) = setup_swap_migration();
+            options.create_symlink = create_symlink;
+            let mut service = MigrationService::new();
+            service.injected_failure = Some(MigrationPhase::SourceCleanup);
+
+            let result = service.migrate_folder(options).await.unwrap();
+            assert!(result.success, "{}", result.message);
+            assert!(result.warnings.iter().any(|w| w.contains("源目录删除失败")));
+
+            // 目标完整，源数据保留在备份中可供恢复
+            assert_tree_intact(&target);
+            assert_tree_intact(&MigrationService::source_backup_path(&source).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_deletion_failure_is_flagged() {
+        let (_source_root, _target_root, source, target, mut options) = setup_swap_migration();
+        options.create_symlink = false;
+        let mut service = MigrationService::new();
+        service.injected_failure = Some(MigrationPhase::SourceCleanup);
+
+        let result = service.migrate_folder(options.clone()).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        assert!(result.source_deletion_failed);
+        assert_eq!(result.message_key, Some(MessageKey::SourceDeletionFailed));
+        assert!(result.message.contains("源目录删除失败"), "{}", result.message);
+        assert_tree_intact(&target);
+        assert_tree_intact(&MigrationService::source_backup_path(&source).unwrap());
+
+        // 删除成功时不设置该标记
+        let (_source_root, _target_root, source, _target, options) = setup_swap_migration();
+        let result = MigrationService::new().migrate_folder(options).await.unwrap();
+        assert!(result.success && !result.source_deletion_failed);
+        assert_eq!(result.message_key, Some(MessageKey::MigrationSucceeded));
+        assert!(fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+    }
+
+    #[test]
+    fn test_resolve_migration_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("real").join("data");
+        fs::create_dir_all(&target).unwrap();
+        let link = temp_dir.path().join("link");
+        let (_, created) = FileOperator::new().create_link(&target, &link, LinkType::default()).unwrap();
+
+        let resolution = MigrationService::resolve_migration_target(&link).unwrap();
+        assert_eq!(resolution.link_type, created);
+        assert_eq!(resolution.link_path, link.display().to_string());
+        let canonical_target = strip_verbatim_prefix(&fs::canonicalize(&target).unwrap());
+        assert_eq!(resolution.resolved_path, Some(canonical_target.display().to_string()));
+        assert_eq!(fs::canonicalize(&resolution.link_target).unwrap(), fs::canonicalize(&target).unwrap());
+
+        // 普通目录不是链接；悬空链接仍可读取指向但没有真实路径
+        assert!(MigrationService::resolve_migration_target(&target).is_err());
+        fs::remove_dir_all(temp_dir.path().join("real")).unwrap();
+        let dangling = MigrationService::resolve_migration_target(&link).unwrap();
+        assert_eq!(dangling.resolved_path, None);
+    }
+
+    #[tokio::test]
+    async fn test_migration_excludes_subpaths() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("App");
+        fs::create_dir_all(source.join("Cache")).unwrap();
+        fs::create_dir_all(source.join("Config")).unwrap();
+        fs::write(source.join("Cache").join("blob.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(source.join("Config").join("settings.json"), "{}").unwrap();
+        fs::write(source.join("data.db"), vec![1u8; 100]).unwrap();
+        let target = target_root.path().join("App");
+
+        let mut options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            exclude_subpaths: vec!["Cache".to_string()],
+            size_tolerance_percent: Some(0.0),
+            file_count_tolerance: Some(0),
+            ..Default::default()
+        };
+        assert!(validate_migration_options(&options).is_ok());
+
+        let result = MigrationService::new().migrate_folder(options.clone()).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        assert_eq!(result.excluded_bytes, 4096);
+        assert!(!target.join("Cache").exists());
+        assert_eq!(fs::read_to_string(target.join("Config").join("settings.json")).unwrap(), "{}");
+        assert_eq!(fs::read(target.join("data.db")).unwrap(), vec![1u8; 100]);
+        // 未删除源时排除的内容仍留在源目录
+        assert!(source.join("Cache").join("blob.bin").exists());
+
+        // 排除的内容只留在源目录，不能与链接或删除源目录同时使用
+        let mut linked = options.clone();
+        linked.create_symlink = true;
+        assert!(validate_migration_options(&linked).is_err());
+        let mut deleting = options.clone();
+        deleting.delete_source = true;
+        assert!(validate_migration_options(&deleting).is_err());
+        let mut deferred = options.clone();
+        deferred.defer_source_deletion = true;
+        assert!(validate_migration_options(&deferred).is_err());
+
+        options.exclude_subpaths = vec!["../Other".to_string()];
+        assert!(validate_migration_options(&options).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_into_nested_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("Big");
+        fs::create_dir_all(source.join("Docs")).unwrap();
+        fs::write(source.join("a.bin"), vec![1u8; 1000]).unwrap();
+        fs::write(source.join("Docs").join("b.txt"), "docs").unwrap();
+        let target = source.join("Archive");
+
+        let mut options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            size_tolerance_percent: Some(0.0),
+            file_count_tolerance: Some(0),
+            ..Default::default()
+        };
+        // 未显式允许时拒绝嵌套目标
+        let result = MigrationService::new().migrate_folder(options.clone()).await.unwrap();
+        assert!(!result.success);
+        assert!(!target.exists());
+
+        options.allow_nested_target = true;
+        let result = MigrationService::new().migrate_folder(options.clone()).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        assert_eq!(fs::read(target.join("a.bin")).unwrap(), vec![1u8; 1000]);
+        assert_eq!(fs::read_to_string(target.join("Docs").join("b.txt")).unwrap(), "docs");
+        assert!(!target.join("Archive").exists());
+        assert!(source.join("a.bin").exists());
+
+        // 源目录会保留，不能与链接或删除源目录同时使用
+        options.create_symlink = true;
+        assert!(validate_migration_options(&options).is_err());
+        options.create_symlink = false;
+        options.delete_source = true;
+        assert!(validate_migration_options(&options).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_merge_into_existing_target() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("App");
+        fs::create_dir_all(source.join("Plugins")).unwrap();
+        fs::write(source.join("new.txt"), "new").unwrap();
+        fs::write(source.join("shared.txt"), "from source").unwrap();
+        fs::write(source.join("Plugins").join("plugin.dll"), vec![2u8; 64]).unwrap();
+        let target = target_root.path().join("App");
+        fs::create_dir_all(target.join("Plugins")).unwrap();
+        fs::write(target.join("existing.txt"), vec![3u8; 8192]).unwrap();
+        fs::write(target.join("shared.txt"), "from target").unwrap();
+        fs::write(target.join("Plugins").join("old.dll"), "old").unwrap();
+
+        let mut options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            conflict_policy: ConflictPolicy::Overwrite,
+            size_tolerance_percent: Some(0.0),
+            ..Default::default()
+        };
+        // 非合并模式拒绝已存在的目标
+        let result = MigrationService::new().migrate_folder(options.clone()).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.message_key, Some(MessageKey::PreCheckFailed));
+
+        options.merge = true;
+        let result = MigrationService::new().migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        assert_eq!(fs::read_to_string(target.join("new.txt")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(target.join("shared.txt")).unwrap(), "from source");
+        assert_eq!(fs::read(target.join("Plugins").join("plugin.dll")).unwrap(), vec![2u8; 64]);
+        assert_eq!(fs::read(target.join("existing.txt")).unwrap(), vec![3u8; 8192]);
+        assert_eq!(fs::read_to_string(target.join("Plugins").join("old.dll")).unwrap(), "old");
+        assert!(!MigrationService::staging_path(&target).exists());
+        let shared = result.file_actions.iter().find(|record| record.target_path.ends_with("shared.txt")).unwrap().clone();
+
+        // 写入的文件内容与源文件不一致时校验失败，跳过的文件不比较内容
+        fs::write(target.join("shared.txt"), "changed").unwrap();
+        let error = MigrationService::verify_merged_contents(std::slice::from_ref(&shared)).unwrap_err();
+        assert!(error.contains("不一致"), "{}", error);
+        let skipped = FileActionRecord { action: FileAction::Skipped, ..shared };
+        assert!(MigrationService::verify_merged_contents(&[skipped]).is_ok());
+
+        // 合并时跳过已存在的文件不能与删除源目录同时使用
+        let conflicting = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            merge: true,
+            conflict_policy: ConflictPolicy::Skip,
+            delete_source: true,
+            ..Default::default()
+        };
+        assert_eq!(validate_migration_options(&conflicting).unwrap_err().key, MessageKey::ValidationMergeSkipConflict);
+    }
+
+    #[tokio::test]
+    async fn test_failed_merge_rolls_back_only_new_files() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("App");
+        fs::create_dir_all(source.join("Docs")).unwrap();
+        fs::create_dir_all(source.join("NewDir")).unwrap();
+        fs::write(source.join("shared.txt"), "from source").unwrap();
+        fs::write(source.join("new1.txt"), "new1").unwrap();
+        fs::write(source.join("new2.txt"), "new2").unwrap();
+        fs::write(source.join("Docs").join("extra.txt"), "extra").unwrap();
+        fs::write(source.join("NewDir").join("inner.txt"), "inner").unwrap();
+        let target = target_root.path().join("App");
+        fs::create_dir_all(target.join("Docs")).unwrap();
+        fs::write(target.join("keep.txt"), "keep").unwrap();
+        fs::write(target.join("shared.txt"), "from target").unwrap();
+        fs::write(target.join("Docs").join("readme.txt"), "readme").unwrap();
+
+        // 第 3 个文件复制失败，此前已写入的文件需要撤销
+        let mut service = MigrationService::new();
+        service.file_operator.inject_failure_at(3, || {
+            FileOperationError::IoError(std::io::Error::new(std::io::ErrorKind::Other, "模拟读取错误"))
+        });
+        let options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            conflict_policy: ConflictPolicy::Overwrite,
+            merge: true,
+            ..Default::default()
+        };
+
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.message_key, Some(MessageKey::CopyFailed));
+
+        // 原有内容保持不变（被覆盖的文件已恢复），新写入的文件和目录全部移除
+        assert_eq!(fs::read_to_string(target.join("keep.txt")).unwrap(), "keep");
+        assert_eq!(fs::read_to_string(target.join("shared.txt")).unwrap(), "from target");
+        assert_eq!(fs::read_to_string(target.join("Docs").join("readme.txt")).unwrap(), "readme");
+        for new_entry in ["new1.txt", "new2.txt", "NewDir"] {
+            assert!(!target.join(new_entry).exists(), "{} 应已撤销", new_entry);
+        }
+        assert!(!target.join("Docs").join("extra.txt").exists());
+        assert!(!MigrationService::merge_originals_path(&target).exists());
+        assert!(source.join("new1.txt").exists());
     }
 
-    /// 测试写入空间
-    fn test_write_space(&self, temp_file: &Path, size: u64) -> Result<(), String> {
-        // 创建指定大小的临时文件
-        let data = vec![0u8; size as usize];
-        
-        match fs::write(temp_file, &data) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(format!("写入测试失败: {}", e)),
+    #[test]
+    fn test_estimate_space_freed() {
+        let drive = TempDir::new().unwrap();
+        let other_drive = TempDir::new().unwrap();
+        let mut items = Vec::new();
+        for (name, size, create_symlink, delete_source) in [
+            ("Linked", 3 * 1024 * 1024, true, false),
+            ("Deleted", 2 * 1024 * 1024, false, true),
+            ("Kept", 1024 * 1024, false, false),
+        ] {
+            let source = drive.path().join(name);
+            fs::create_dir_all(&source).unwrap();
+            fs::write(source.join("data.bin"), vec![0u8; size]).unwrap();
+            items.push(MigrationOptions {
+                source_path: source.display().to_string(),
+                target_path: other_drive.path().join(name).display().to_string(),
+                create_symlink,
+                delete_source,
+                ..Default::default()
+            });
         }
+        // 目标仍在同一驱动器上的项目不释放空间
+        let same_drive = drive.path().join("SameDrive");
+        fs::create_dir_all(&same_drive).unwrap();
+        fs::write(same_drive.join("data.bin"), vec![0u8; 4096]).unwrap();
+        items.push(MigrationOptions {
+            source_path: same_drive.display().to_string(),
+            target_path: drive.path().join("Elsewhere").display().to_string(),
+            create_symlink: true,
+            ..Default::default()
+        });
+
+        let mut service = MigrationService::new();
+        service.drive_status_detector = |_| Some(DriveStatus {
+            total_space: 100 * 1024 * 1024,
+            available_space: 10 * 1024 * 1024,
+            is_removable: false,
+        });
+        let estimate = service.estimate_space_freed(&items, drive.path());
+
+        assert_eq!(estimate.counted_items, 2);
+        assert_eq!(estimate.moved_bytes, 5 * 1024 * 1024);
+        assert_eq!(estimate.symlink_overhead_bytes, SYMLINK_OVERHEAD_BYTES);
+        assert_eq!(estimate.freed_bytes, 5 * 1024 * 1024 - SYMLINK_OVERHEAD_BYTES);
+        assert_eq!(estimate.projected_free_bytes, Some(15 * 1024 * 1024 - SYMLINK_OVERHEAD_BYTES));
+        assert_eq!(estimate.skipped.len(), 2);
     }
 
-    /// 检查是否有写入权限（增强版）
-    fn has_write_permission(&self, path: &Path) -> bool {
-        if !path.exists() {
-            // 如果路径不存在，检查父目录
-            if let Some(parent) = path.parent() {
-                return self.has_write_permission(parent);
+    #[tokio::test]
+    async fn test_slow_copy_times_out_and_invokes_recovery() {
+        use crate::error_recovery::{ErrorRecoveryConfig, ErrorType, RecoveryType};
+
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("App");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("data.bin"), vec![1u8; 1024]).unwrap();
+        let target = target_root.path().join("App");
+
+        // 模拟无响应的网络驱动器：每个文件复制前等待远超时限的时间
+        let mut service = MigrationService::new();
+        service.file_operator.set_copy_delay(Some(Duration::from_millis(500)));
+        let options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            operation_timeout_ms: Some(50),
+            ..Default::default()
+        };
+
+        let started = Instant::now();
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(400), "超时后应立即返回");
+        assert!(!result.success);
+        assert_eq!(result.message_key, Some(MessageKey::CopyTimedOut));
+        assert!(source.join("data.bin").exists());
+        assert!(!MigrationService::staging_path(&target).exists());
+
+        let recovery_manager = tokio::sync::Mutex::new(ErrorRecoveryManager::new(ErrorRecoveryConfig::default()));
+        assert!(matches!(
+            recovery_manager.lock().await.classify_error(&FileOperationError::Timeout(result.message.clone())),
+            ErrorType::Timeout(_)
+        ));
+        let mut result = result;
+        service.recover_timed_out_migration(&mut result, &recovery_manager).await;
+        let recovery = result.recovery.expect("超时结果应进入恢复流程");
+        assert!(matches!(recovery.recovery_type, RecoveryType::Manual));
+        assert_eq!(recovery_manager.lock().await.get_recovery_statistics().total_operations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_batch_continues_after_failure() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let item = |name: &str| {
+            let source = source_root.path().join(name);
+            MigrationOptions {
+                source_path: source.display().to_string(),
+                target_path: target_root.path().join(name).display().to_string(),
+                ..Default::default()
             }
-            return false;
-        }
-        
-        // 尝试创建一个临时文件来测试写入权限
-        let temp_file = path.join(".write_test.tmp");
-        match fs::write(&temp_file, b"test") {
-            Ok(_) => {
-                // 删除临时文件
-                let _ = fs::remove_file(&temp_file);
-                true
-            },
-            Err(_) => false
+        };
+        for name in ["first", "third"] {
+            fs::create_dir_all(source_root.path().join(name)).unwrap();
+            fs::write(source_root.path().join(name).join("data.txt"), name).unwrap();
         }
+        let mut invalid = item("second");
+        invalid.target_path = String::new();
+
+        let locks = Arc::new(MigrationLockRegistry::new());
+        let service = Arc::new(MigrationService::new());
+        let (worker, item_locks) = (Arc::clone(&service), Arc::clone(&locks));
+        let batch = service
+            .migrate_batch(vec![item("first"), invalid, item("third")], 2, move |options| {
+                validate_migration_options(&options)?;
+                let _lock = item_locks.try_acquire_migration_lock(Path::new(&options.source_path), Path::new(&options.target_path))
+                    .map_err(|e| KeyedError::with_detail(MessageKey::PreCheckFailed, e))?;
+                tokio::runtime::Handle::current().block_on(worker.migrate_folder(options))
+                    .map_err(|e| KeyedError::with_detail(MessageKey::MigrationFailed, e))
+            })
+            .await;
+
+        assert_eq!(batch.results.len(), 3);
+        assert_eq!((batch.succeeded, batch.failed), (2, 1));
+        assert!(batch.results[0].success, "{}", batch.results[0].message);
+        assert!(!batch.results[1].success);
+        assert_eq!(batch.results[1].message_key, Some(MessageKey::ValidationTargetEmpty));
+        assert!(batch.results[2].success, "{}", batch.results[2].message);
+        assert_eq!(fs::read_to_string(target_root.path().join("third").join("data.txt")).unwrap(), "third");
+        assert_eq!(locks.active_count(), 0);
     }
-}
 
-impl Default for MigrationService {
-    fn default() -> Self {
-        Self::new()
+    #[tokio::test]
+    async fn test_run_bounded_limits_parallelism() {
+        let in_flight = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let outputs = run_bounded((0..12).collect(), 3, |index, item: usize| {
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            async move {
+                let running = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(running, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                (index, item * 10)
+            }
+        }).await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        let outputs: Vec<_> = outputs.into_iter().map(|output| output.unwrap()).collect();
+        assert_eq!(outputs, (0..12).map(|i| (i, i * 10)).collect::<Vec<_>>());
+        assert!((1..=4).contains(&default_max_parallel_migrations()));
     }
-}
 
-/// 验证迁移选项
-pub fn validate_migration_options(options: &MigrationOptions) -> Result<(), String> {
-    let source = Path::new(&options.source_path);
-    let target = Path::new(&options.target_path);
+    #[tokio::test]
+    async fn test_migration_result_localization() {
+        let target_root = TempDir::new().unwrap();
+        let options = MigrationOptions {
+            source_path: target_root.path().join("missing").to_string_lossy().to_string(),
+            target_path: target_root.path().join("target").to_string_lossy().to_string(),
+            ..Default::default()
+        };
 
-    // 检查源路径
-    if options.source_path.is_empty() {
-        return Err("源路径不能为空".to_string());
+        let mut result = MigrationService::new().migrate_folder(options).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.message_key, Some(MessageKey::PreCheckFailed));
+        let detail = result.message_detail.clone().unwrap();
+        assert_eq!(result.message, format!("预检查失败: {}", detail));
+
+        // 详情只有中文，英文消息不拼接，详情仍保留在 message_detail 中
+        result.localize(Locale::EnUs);
+        assert_eq!(result.message, "Pre-migration check failed");
+        assert_eq!(result.message_detail.as_deref(), Some(detail.as_str()));
+
+        result.localize(Locale::ZhCn);
+        assert_eq!(result.message, format!("预检查失败: {}", detail));
     }
 
-    // 检查目标路径
-    if options.target_path.is_empty() {
-        return Err("目标路径不能为空".to_string());
+    #[tokio::test]
+    async fn test_verify_migration_integrity_flags_deleted_target() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        let target = target_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("file.txt"), "内容").unwrap();
+
+        let service = MigrationService::new();
+        service.create_symlink_after_migration(&source, &target, LinkType::default()).await.unwrap();
+
+        let report = service.verify_migration_integrity(&source, true);
+        assert!(report.healthy, "{:?}", report.issues);
+        assert!(report.link_exists && report.link_resolves && report.target_exists);
+        assert!(report.content_checksum.is_some());
+
+        // 模拟卸载程序删除了迁移目标，只留下悬空链接
+        fs::remove_dir_all(&target).unwrap();
+        let report = service.verify_migration_integrity(&source, true);
+        assert!(!report.healthy);
+        assert!(report.link_exists);
+        assert!(!report.target_exists);
+        assert!(!report.link_resolves);
+        assert!(report.content_checksum.is_none());
     }
 
-    // 检查路径是否相同
-    if source == target {
-        return Err("源路径和目标路径不能相同".to_string());
+    #[tokio::test]
+    async fn test_verify_migration_integrity_compares_baseline() {
+        let (_source_root, _target_root, source, target, options) = setup_swap_migration();
+        let baselines_dir = TempDir::new().unwrap();
+        let baselines_file = baselines_dir.path().join(INTEGRITY_BASELINES_FILE);
+        let service = MigrationService::new().with_integrity_baselines_file(baselines_file.clone());
+
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        let report = service.verify_migration_integrity(&source, true);
+        assert!(report.healthy, "{:?}", report.issues);
+        assert_eq!(report.baseline_checksum, report.content_checksum);
+        assert_eq!(report.content_checksum.as_ref().map(String::len), Some(64));
+
+        // 重启后仍能比对：目标中的文件被改写时报告内容不一致
+        fs::write(target.join("a.txt"), "changed").unwrap();
+        let restarted = MigrationService::new().with_integrity_baselines_file(baselines_file);
+        let report = restarted.verify_migration_integrity(&source, true);
+        assert!(!report.healthy);
+        assert!(report.baseline_checksum.is_some());
+        assert!(report.issues.iter().any(|issue| issue.contains("校验值不一致")), "{:?}", report.issues);
     }
 
-    // 检查路径是否有效
-    if let Ok(source_canonical) = std::fs::canonicalize(source) {
-        if let Ok(target_canonical) = std::fs::canonicalize(target) {
-            if source_canonical == target_canonical {
-                return Err("源路径和目标路径指向同一位置".to_string());
-            }
-        }
+    #[test]
+    fn test_checksum_tree_separates_path_and_content() {
+        let first = TempDir::new().unwrap();
+        fs::write(first.path().join("ab"), "c").unwrap();
+        let second = TempDir::new().unwrap();
+        fs::write(second.path().join("a"), "bc").unwrap();
+
+        assert_ne!(MigrationService::checksum_tree(first.path()).0, MigrationService::checksum_tree(second.path()).0);
     }
 
-    Ok(())
-}
+    #[cfg(unix)]
+    #[test]
+    #[ignore = "需以非 root 用户运行：root 不受权限位限制，无法构造不可读目录"]
+    fn test_scan_for_inaccessible_paths() {
+        use std::os::unix::fs::PermissionsExt;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
-    use std::fs::{self, File};
-    use std::io::Write;
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked");
+        fs::create_dir_all(locked.join("inner")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("open")).unwrap();
+        fs::write(temp_dir.path().join("open").join("file.txt"), "内容").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let inaccessible = MigrationService::new().scan_for_inaccessible_paths(temp_dir.path());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(inaccessible, vec![locked]);
+    }
+
+    #[tokio::test]
+    async fn test_link_refused_on_unsupported_filesystem() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("file.txt"), "内容").unwrap();
+        let target = target_root.path().join("data");
+
+        let mut service = MigrationService::new();
+        service.filesystem_detector = |_| FilesystemKind::Fat32;
+
+        let result = service.migrate_folder(MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            create_symlink: true,
+            ..Default::default()
+        }).await.unwrap();
+
+        assert!(!result.success);
+        assert!(result.message.contains("FAT32"), "{}", result.message);
+        assert!(!target.exists());
+        assert!(source.join("file.txt").exists());
+
+        // 仅不支持所请求的链接类型时给出警告而非拒绝
+        service.filesystem_detector = |_| FilesystemKind::Ext;
+        let warnings = service.check_link_support(&source, LinkType::Junction).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(FilesystemKind::from_name("vfat"), FilesystemKind::Fat32);
+    }
+
+    #[test]
+    fn test_inspect_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("data");
+        fs::create_dir_all(folder.join("sub")).unwrap();
+        fs::write(folder.join("a.txt"), vec![0u8; 100]).unwrap();
+        fs::write(folder.join("sub").join("b.txt"), vec![0u8; 50]).unwrap();
+
+        let mut service = MigrationService::new();
+        service.drive_status_detector = |_| Some(DriveStatus { total_space: 100, available_space: 50, is_removable: true });
+
+        // 普通目录；检查不应在目录中留下任何文件
+        let entries_before = fs::read_dir(&folder).unwrap().count();
+        let inspection = service.inspect_path(&folder, true);
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), entries_before);
+        assert!(inspection.exists && inspection.is_dir && !inspection.is_symlink);
+        assert!(!inspection.is_system_protected);
+        assert!(inspection.readable && inspection.writable);
+        assert_eq!(inspection.estimated_size, Some(150));
+        assert!(inspection.on_removable_drive);
+        assert!(inspection.reasons.is_empty(), "{:?}", inspection.reasons);
+
+        // 未要求统计大小时不遍历目录
+        assert_eq!(service.inspect_path(&folder, false).estimated_size, None);
+
+        // 系统保护目录及其子路径
+        service.add_protected_path(folder.clone());
+        let inspection = service.inspect_path(&folder.join("sub"), true);
+        assert!(inspection.exists && inspection.is_system_protected);
+        assert!(inspection.reasons.iter().any(|reason| reason.contains("系统保护目录")));
+
+        // 不存在的路径
+        let inspection = service.inspect_path(&temp_dir.path().join("missing"), true);
+        assert!(!inspection.exists && !inspection.is_dir && !inspection.readable && !inspection.writable);
+        assert_eq!(inspection.estimated_size, None);
+        assert!(inspection.reasons.iter().any(|reason| reason.contains("不存在")));
+    }
+
+    #[tokio::test]
+    async fn test_custom_protected_path_refuses_migration() {
+        let source_root = TempDir::new().unwrap();
+        let protected_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("file.txt"), "内容").unwrap();
+        let target = protected_root.path().join("corp").join("data");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+
+        let service = MigrationService::new();
+        assert_eq!(service.protected_paths(), MigrationService::default_protected_paths());
+        assert!(service.add_protected_path(protected_root.path().join("corp")));
+        assert!(!service.add_protected_path(protected_root.path().join("corp")), "重复添加应被忽略");
+
+        let options = MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            create_symlink: false,
+            ..Default::default()
+        };
+        let result = service.migrate_folder(options.clone()).await.unwrap();
+        assert!(!result.success);
+        assert!(result.message.contains("系统保护目录"), "{}", result.message);
+        assert!(!target.exists());
+        assert!(source.join("file.txt").exists());
+
+        // 移除后可以正常迁移
+        assert!(service.remove_protected_path(&protected_root.path().join("corp")));
+        assert!(!service.remove_protected_path(&protected_root.path().join("corp")));
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+    }
+
+    #[tokio::test]
+    async fn test_min_target_free_space_after_migration() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("file.bin"), vec![0u8; 2000]).unwrap();
+        let target = target_root.path().join("data");
+
+        let mut service = MigrationService::new();
+        service.filesystem_detector = |_| FilesystemKind::Ntfs;
+        service.drive_status_detector = |_| Some(DriveStatus {
+            total_space: 100_000,
+            available_space: 10_000,
+            is_removable: false,
+        });
+        let options = |min_free_after| MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            min_target_free_bytes_after: min_free_after,
+            ..Default::default()
+        };
+
+        // 迁移后剩余 8000 字节，低于要求保留的 9000 字节
+        let refused = service.migrate_folder(options(9_000)).await.unwrap();
+        assert!(!refused.success);
+        assert_eq!(refused.message_key, Some(MessageKey::PreCheckFailed));
+        assert!(refused.message.contains("保留"), "{}", refused.message);
+        assert!(!target.exists());
+
+        let allowed = service.migrate_folder(options(5_000)).await.unwrap();
+        assert!(allowed.success, "{}", allowed.message);
+        assert!(target.join("file.bin").exists());
+    }
+
+    #[tokio::test]
+    async fn test_target_health_warnings() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("file.txt"), "内容").unwrap();
+        let target = target_root.path().join("data");
+
+        let mut service = MigrationService::new();
+        service.filesystem_detector = |_| FilesystemKind::Ntfs;
+
+        // 剩余空间充足的固定磁盘没有警告
+        service.drive_status_detector = |_| Some(DriveStatus {
+            total_space: 100 * 1024 * 1024 * 1024,
+            available_space: 50 * 1024 * 1024 * 1024,
+            is_removable: false,
+        });
+        assert!(service.check_target_health(&target, None).is_empty());
+
+        // 剩余空间低于5%
+        service.drive_status_detector = |_| Some(DriveStatus {
+            total_space: 100 * 1024 * 1024 * 1024,
+            available_space: 4 * 1024 * 1024 * 1024,
+            is_removable: false,
+        });
+        let warnings = service.check_target_health(&target, None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("不足5%"), "{}", warnings[0]);
+
+        // 可移动磁盘：警告出现在迁移结果中，但不阻止迁移
+        service.drive_status_detector = |_| Some(DriveStatus {
+            total_space: 64 * 1024 * 1024 * 1024,
+            available_space: 32 * 1024 * 1024 * 1024,
+            is_removable: true,
+        });
+        let result = service.migrate_folder(MigrationOptions {
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            create_symlink: false,
+            ..Default::default()
+        }).await.unwrap();
+        assert!(result.success, "{}", result.message);
+        assert!(result.warnings.iter().any(|w| w.contains("可移动磁盘")), "{:?}", result.warnings);
+
+        // 网络驱动器只给出一条警告，创建链接时附带链接可能失效的提示
+        service.filesystem_detector = |_| FilesystemKind::Network;
+        service.drive_status_detector = |_| None;
+        let warnings = service.check_target_health(&target, Some(LinkType::Symlink));
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("网络驱动器") && warnings[0].contains(LinkType::Symlink.description()), "{}", warnings[0]);
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_migrations_are_rejected() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source = source_root.path().join("data");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("sub").join("file.txt"), "内容").unwrap();
+
+        let registry = Arc::new(MigrationLockRegistry::new());
+        let service = Arc::new(MigrationService::new());
+        let attempted = Arc::new(tokio::sync::Barrier::new(2));
+
+        // 第二个迁移的源位于第一个迁移的源目录内
+        let jobs = [
+            (source.clone(), target_root.path().join("a")),
+            (source.join("sub"), target_root.path().join("b")),
+        ];
+        let handles: Vec<_> = jobs.into_iter().map(|(source, target)| {
+            let registry = registry.clone();
+            let service = service.clone();
+            let attempted = attempted.clone();
+            tokio::spawn(async move {
+                let lock = registry.try_acquire_migration_lock(&source, &target);
+                // 两个迁移都尝试加锁后，获得锁的一方才开始迁移
+                attempted.wait().await;
+                let _guard = lock?;
+                service.migrate_folder(MigrationOptions {
+                    source_path: source.display().to_string(),
+                    target_path: target.display().to_string(),
+                    ..Default::default()
+                }).await
+            })
+        }).collect();
+
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap());
+        }
+
+        let rejected: Vec<_> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].contains("正在进行"));
+        assert!(results.iter().any(|r| r.as_ref().map(|m| m.success).unwrap_or(false)));
+        assert_eq!(registry.active_count(), 0);
+    }
 
     #[tokio::test]
     async fn test_migrate_folder() {
@@ -680,6 +3782,7 @@ mod tests {
             target_path: target_dir.display().to_string(),
             create_symlink: true,
             delete_source: false, // 测试中不删除源目录
+            ..Default::default()
         };
 
         let result = service.migrate_folder(options).await.unwrap();
@@ -698,6 +3801,7 @@ mod tests {
             target_path: "/path/to/target".to_string(),
             create_symlink: true,
             delete_source: true,
+            ..Default::default()
         };
 
         assert!(validate_migration_options(&options).is_ok());
@@ -707,8 +3811,310 @@ mod tests {
             target_path: "/path/to/target".to_string(),
             create_symlink: true,
             delete_source: true,
+            ..Default::default()
+        };
+
+        assert_eq!(validate_migration_options(&invalid_options).unwrap_err().key, MessageKey::ValidationSourceEmpty);
+    }
+
+    #[test]
+    fn test_cancel_migration_mid_copy() {
+        let source_dir = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let target_dir = target_root.path().join("target");
+
+        let total_files = 1000;
+        for i in 0..total_files {
+            fs::write(source_dir.path().join(format!("file_{:04}.bin", i)), vec![0u8; 16 * 1024]).unwrap();
+        }
+
+        let service = MigrationService::new();
+
+        let (copy_result, cancelled_at) = std::thread::scope(|scope| {
+            // 一旦目标目录出现文件即发出取消请求
+            let canceller = scope.spawn(|| loop {
+                let copied = fs::read_dir(&target_dir).map(|entries| entries.count()).unwrap_or(0);
+                if copied > 0 {
+                    service.cancel_migration(CancellationReason::UserRequested);
+                    return std::time::Instant::now();
+                }
+                std::thread::yield_now();
+            });
+
+            let result = service.file_operator().copy_path(source_dir.path(), &target_dir);
+            (result, canceller.join().unwrap())
+        });
+
+        assert!(matches!(copy_result, Err(FileOperationError::OperationCancelled(_))));
+        assert!(cancelled_at.elapsed() < std::time::Duration::from_secs(1));
+
+        let copied = fs::read_dir(&target_dir).unwrap().count();
+        assert!(copied < total_files, "取消后不应复制全部文件: {}", copied);
+
+        // 重置后可以再次执行复制
+        assert_eq!(service.cancellation_reason(), Some(CancellationReason::UserRequested));
+        service.reset_cancel_flag();
+        assert!(!service.is_cancelled());
+        assert_eq!(service.cancellation_reason(), None);
+    }
+
+    #[test]
+    fn test_resume_migration_from_checkpoint() {
+        use crate::file_operations::FileAction;
+
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source_dir = source_root.path().join("source");
+        let target_dir = target_root.path().join("target");
+        fs::create_dir_all(&source_dir).unwrap();
+
+        let total_files = 500;
+        for i in 0..total_files {
+            fs::write(source_dir.join(format!("file_{:04}.bin", i)), vec![i as u8; 8 * 1024]).unwrap();
+        }
+
+        let options = MigrationOptions {
+            source_path: source_dir.display().to_string(),
+            target_path: target_dir.display().to_string(),
+            resume: true,
+            ..Default::default()
+        };
+        let checkpoint_path = MigrationService::checkpoint_path(&source_dir, &target_dir);
+        let _ = fs::remove_file(&checkpoint_path);
+        let recorded = || fs::read_to_string(&checkpoint_path).map(|c| c.lines().count()).unwrap_or(0);
+
+        let service = MigrationService::new();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        // 第一次运行：检查点记录到 10 个文件后中断
+        let finished = AtomicBool::new(false);
+        let first = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while !finished.load(Ordering::Relaxed) {
+                    if recorded() >= 10 {
+                        service.cancel_migration(CancellationReason::Shutdown);
+                        return;
+                    }
+                    std::thread::yield_now();
+                }
+            });
+            let result = runtime.block_on(service.migrate_folder(options.clone())).unwrap();
+            finished.store(true, Ordering::Relaxed);
+            result
+        });
+        assert!(!first.success);
+        assert_eq!(first.cancellation_reason, Some(CancellationReason::Shutdown));
+
+        let completed_before = recorded();
+        assert!(completed_before >= 10 && completed_before < total_files, "已完成 {}", completed_before);
+        assert!(MigrationService::staging_path(&target_dir).exists(), "续传模式下中断后应保留已复制的内容");
+
+        // 模拟上次中断时写了一半、未记入检查点的文件，续传时应直接覆盖而不是按冲突策略失败
+        let checkpoint_content = fs::read_to_string(&checkpoint_path).unwrap();
+        let partial_name = (0..total_files)
+            .map(|i| format!("file_{:04}.bin", i))
+            .find(|name| !checkpoint_content.contains(name.as_str()))
+            .unwrap();
+        let partial_index: usize = partial_name[5..9].parse().unwrap();
+        fs::write(MigrationService::staging_path(&target_dir).join(&partial_name), vec![0xFFu8; 100]).unwrap();
+
+        // 第二次运行：续传剩余文件
+        service.reset_cancel_flag();
+        let second = runtime.block_on(service.migrate_folder(options)).unwrap();
+        assert!(second.success, "{}", second.message);
+
+        let count = |action: FileAction| second.file_actions.iter().filter(|r| r.action == action).count();
+        assert_eq!(count(FileAction::Resumed), completed_before);
+        assert!(count(FileAction::Overwritten) >= 1);
+        assert_eq!(count(FileAction::Copied) + count(FileAction::Overwritten), total_files - completed_before);
+        assert_eq!(fs::read(target_dir.join(&partial_name)).unwrap(), vec![partial_index as u8; 8 * 1024]);
+
+        for i in [0, total_files / 2, total_files - 1] {
+            let name = format!("file_{:04}.bin", i);
+            assert_eq!(fs::read(target_dir.join(&name)).unwrap(), vec![i as u8; 8 * 1024]);
+        }
+        assert!(!checkpoint_path.exists(), "成功完成后应删除检查点文件");
+    }
+
+    #[test]
+    fn test_checkpoint_path_uses_sha256_of_both_paths() {
+        let path = MigrationService::checkpoint_path(Path::new("/data/a"), Path::new("/backup/b"));
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let digest = name.strip_suffix(".checkpoint").unwrap();
+        assert_eq!(digest.len(), 64);
+        assert!(digest.chars().all(|c| c.is_ascii_hexdigit()));
+
+        assert_eq!(path, MigrationService::checkpoint_path(Path::new("/data/a"), Path::new("/backup/b")));
+        // 路径边界不同的组合不应得到相同的检查点文件
+        assert_ne!(path, MigrationService::checkpoint_path(Path::new("/data/a/backup"), Path::new("b")));
+        assert_ne!(path, MigrationService::checkpoint_path(Path::new("/backup/b"), Path::new("/data/a")));
+    }
+
+    #[test]
+    fn test_fresh_migration_discards_stale_staging() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source_dir = source_root.path().join("source");
+        let target_dir = target_root.path().join("target");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join("data.txt"), b"new").unwrap();
+
+        // 模拟上次非续传迁移中断后遗留的暂存目录
+        let staging = MigrationService::staging_path(&target_dir);
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("data.txt"), b"old").unwrap();
+        fs::write(staging.join("stale.txt"), b"stale").unwrap();
+
+        let options = MigrationOptions {
+            source_path: source_dir.display().to_string(),
+            target_path: target_dir.display().to_string(),
+            ..Default::default()
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let result = runtime.block_on(MigrationService::new().migrate_folder(options)).unwrap();
+        assert!(result.success, "{}", result.message);
+
+        assert_eq!(fs::read(target_dir.join("data.txt")).unwrap(), b"new");
+        assert!(!target_dir.join("stale.txt").exists(), "遗留暂存目录中的文件不应混入新目标");
+        assert!(!staging.exists());
+        assert_eq!(result.copied_bytes, 3);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_folder_preserves_metadata() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+        let source_dir = source_root.path().join("source");
+        let target_dir = target_root.path().join("target");
+
+        fs::create_dir_all(source_dir.join("subdir")).unwrap();
+        let source_file = source_dir.join("subdir").join("config.ini");
+        fs::write(&source_file, "readonly=true").unwrap();
+
+        // 设置特殊的修改时间与只读属性
+        let mtime = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+        filetime::set_file_mtime(&source_file, mtime).unwrap();
+        filetime::set_file_mtime(source_dir.join("subdir"), mtime).unwrap();
+        let mut permissions = fs::metadata(&source_file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&source_file, permissions).unwrap();
+
+        let service = MigrationService::new();
+        let options = MigrationOptions {
+            source_path: source_dir.display().to_string(),
+            target_path: target_dir.display().to_string(),
+            create_symlink: false,
+            delete_source: false,
+            preserve_metadata: true,
+            ..Default::default()
+        };
+
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+
+        let target_file = target_dir.join("subdir").join("config.ini");
+        let target_metadata = fs::metadata(&target_file).unwrap();
+        assert_eq!(filetime::FileTime::from_last_modification_time(&target_metadata), mtime);
+        assert!(target_metadata.permissions().readonly());
+
+        let target_subdir_metadata = fs::metadata(target_dir.join("subdir")).unwrap();
+        assert_eq!(filetime::FileTime::from_last_modification_time(&target_subdir_metadata), mtime);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[tokio::test]
+    async fn test_migrate_path_longer_than_max_path() {
+        let source_root = TempDir::new().unwrap();
+        let target_root = TempDir::new().unwrap();
+
+        // 嵌套目录使源文件路径超过 MAX_PATH
+        let mut nested = source_root.path().join("LongApp");
+        for i in 0..6 {
+            nested = nested.join(format!("nested_directory_level_{}_with_a_fairly_long_name", i));
+        }
+        let source_file = nested.join("settings.json");
+        assert!(source_file.to_string_lossy().len() > MAX_PATH);
+        fs::create_dir_all(extended_length_path(&nested)).unwrap();
+        fs::write(extended_length_path(&source_file), "{}").unwrap();
+
+        let source_dir = source_root.path().join("LongApp");
+        let target_dir = target_root.path().join("LongApp");
+        let service = MigrationService::new();
+        let options = MigrationOptions {
+            source_path: source_dir.display().to_string(),
+            target_path: target_dir.display().to_string(),
+            create_symlink: false,
+            delete_source: false,
+            ..Default::default()
         };
 
-        assert!(validate_migration_options(&invalid_options).is_err());
+        let result = service.migrate_folder(options).await.unwrap();
+        assert!(result.success, "{}", result.message);
+
+        let target_file = target_dir.join(source_file.strip_prefix(&source_dir).unwrap());
+        assert_eq!(fs::read_to_string(extended_length_path(&target_file)).unwrap(), "{}");
+        // 返回给用户的路径不带扩展长度前缀
+        assert!(!result.file_actions.is_empty());
+        assert!(result.file_actions.iter().all(|record| {
+            !record.source_path.starts_with(r"\\?\") && !record.target_path.starts_with(r"\\?\")
+        }));
+        assert!(result.file_actions.iter().any(|record| record.source_path == source_file.display().to_string()));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[tokio::test]
+    async fn test_locked_file_is_reported_instead_of_aborting() {
+        use std::os::windows::fs::OpenOptionsExt;
+        use crate::file_operations::FileStatus;
+
+        let (_source_root, _target_root, source, target, options) = setup_swap_migration();
+        let locked_path = source.join("a.txt");
+        // 不允许任何共享访问地打开文件，模拟被其他程序占用
+        let _holder = fs::OpenOptions::new().read(true).share_mode(0).open(&locked_path).unwrap();
+
+        let service = MigrationService::new();
+        let result = service.migrate_folder(options).await.unwrap();
+
+        assert!(!result.success);
+        let locked = result.file_results.iter()
+            .find(|r| r.path == locked_path.display().to_string())
+            .expect("被占用的文件应出现在逐文件结果中");
+        assert_eq!(locked.status, FileStatus::Locked);
+        assert!(result.file_results.iter().any(|r| r.status == FileStatus::Succeeded));
+        assert!(!fs::symlink_metadata(&source).unwrap().file_type().is_symlink());
+        assert!(!target.exists());
+    }
+
+    /// 创建源目录与多出一个小文件的目标目录（大小差异约 5%，文件数量差 1）
+    fn setup_copy_with_extra_file() -> (TempDir, PathBuf, PathBuf) {
+        let root = TempDir::new().unwrap();
+        let source = root.path().join("source");
+        let target = root.path().join("target");
+        for dir in [&source, &target] {
+            fs::create_dir_all(dir).unwrap();
+            fs::write(dir.join("data.bin"), vec![1u8; 2000]).unwrap();
+        }
+        fs::write(target.join("Thumbs.db"), vec![0u8; 100]).unwrap();
+        (root, source, target)
+    }
+
+    #[tokio::test]
+    async fn test_verify_copy_result_size_tolerance() {
+        let (_root, source, target) = setup_copy_with_extra_file();
+        let service = MigrationService::new();
+
+        assert!(service.verify_copy_result(&source, &target, DEFAULT_SIZE_TOLERANCE_PERCENT, None, &ExcludedSubpaths::default(), None).await.is_ok());
+        let strict = service.verify_copy_result(&source, &target, 0.0, None, &ExcludedSubpaths::default(), None).await;
+        assert!(strict.unwrap_err().contains("大小差异过大"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_copy_result_file_count_tolerance() {
+        let (_root, source, target) = setup_copy_with_extra_file();
+        let service = MigrationService::new();
+
+        assert!(service.verify_copy_result(&source, &target, DEFAULT_SIZE_TOLERANCE_PERCENT, Some(1), &ExcludedSubpaths::default(), None).await.is_ok());
+        let strict = service.verify_copy_result(&source, &target, DEFAULT_SIZE_TOLERANCE_PERCENT, Some(0), &ExcludedSubpaths::default(), None).await;
+        assert!(strict.unwrap_err().contains("文件数量差异过大"));
     }
-}
\ No newline at end of file
+}
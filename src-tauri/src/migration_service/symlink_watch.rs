@@ -0,0 +1,180 @@
+//! 迁移链接健康监测
+//!
+//! 记录迁移时在源位置创建的链接，之后可随时检查它们是否仍能解析到迁移目标，
+//! 以便发现目标被卸载程序或手动操作删除后留下的失效链接
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde::{Serialize, Deserialize};
+
+use crate::util::same_path;
+
+/// 日志目录中保存链接监测列表的文件名
+pub const SYMLINK_WATCH_FILE: &str = "symlink_watch.json";
+
+/// 一条被监测的链接
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymlinkWatch {
+    pub link_path: String,
+    /// 链接应当解析到的迁移目标
+    pub target_path: String,
+    pub registered_at: DateTime<Local>,
+}
+
+/// 检查发现的失效链接
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrokenSymlink {
+    pub link_path: String,
+    pub target_path: String,
+    pub reason: String,
+}
+
+/// 一次全量检查的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymlinkCheckReport {
+    pub checked: usize,
+    pub broken: Vec<BrokenSymlink>,
+}
+
+/// 持久化的链接监测列表，每次修改后立即写回文件
+pub struct SymlinkWatchList {
+    path: PathBuf,
+    entries: Mutex<Vec<SymlinkWatch>>,
+}
+
+impl SymlinkWatchList {
+    /// 从文件加载监测列表，文件不存在或无法解析时从空列表开始
+    pub fn open(path: PathBuf) -> Self {
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("解析链接监测列表失败，将重新记录 {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self { path, entries: Mutex::new(entries) }
+    }
+
+    /// 记录一条链接，同一链接路径重复登记时更新其目标
+    pub fn register(&self, link_path: &Path, target: &Path) -> Result<SymlinkWatch, String> {
+        let watch = SymlinkWatch {
+            link_path: link_path.display().to_string(),
+            target_path: target.display().to_string(),
+            registered_at: Local::now(),
+        };
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|entry| !same_path(Path::new(&entry.link_path), link_path));
+        entries.push(watch.clone());
+        self.save(&entries)?;
+
+        info!("已登记链接监测: {} -> {}", watch.link_path, watch.target_path);
+        Ok(watch)
+    }
+
+    /// 取消监测一条链接，不存在时返回 false
+    pub fn unregister(&self, link_path: &Path) -> Result<bool, String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let before = entries.len();
+        entries.retain(|entry| !same_path(Path::new(&entry.link_path), link_path));
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.save(&entries)?;
+        Ok(true)
+    }
+
+    /// 获取全部被监测的链接
+    pub fn list(&self) -> Vec<SymlinkWatch> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 检查每条链接是否仍存在并解析到登记的目标
+    pub fn check_all(&self) -> SymlinkCheckReport {
+        let entries = self.list();
+        let broken: Vec<BrokenSymlink> = entries.iter()
+            .filter_map(|entry| {
+                check_link(Path::new(&entry.link_path), Path::new(&entry.target_path)).map(|reason| BrokenSymlink {
+                    link_path: entry.link_path.clone(),
+                    target_path: entry.target_path.clone(),
+                    reason,
+                })
+            })
+            .collect();
+
+        for link in &broken {
+            warn!("迁移链接失效: {} ({})", link.link_path, link.reason);
+        }
+        SymlinkCheckReport { checked: entries.len(), broken }
+    }
+
+    fn save(&self, entries: &[SymlinkWatch]) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("序列化链接监测列表失败: {}", e))?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        fs::write(&self.path, content)
+            .map_err(|e| format!("保存链接监测列表失败 {}: {}", self.path.display(), e))
+    }
+}
+
+/// 检查单条链接，正常时返回 None，否则返回失效原因
+fn check_link(link_path: &Path, target: &Path) -> Option<String> {
+    match fs::symlink_metadata(link_path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {}
+        Ok(_) => return Some("链接已被普通文件或目录替换".to_string()),
+        Err(_) => return Some("链接已不存在".to_string()),
+    }
+
+    let resolved = match fs::canonicalize(link_path) {
+        Ok(resolved) => resolved,
+        Err(_) => return Some("链接目标不存在".to_string()),
+    };
+    match fs::canonicalize(target) {
+        Ok(canonical_target) if same_path(&resolved, &canonical_target) => None,
+        Ok(_) => Some(format!("链接指向了其他位置: {}", resolved.display())),
+        Err(_) => Some("链接目标不存在".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_all_reports_only_broken_link() {
+        let temp_dir = TempDir::new().unwrap();
+        let watch_file = temp_dir.path().join("logs").join(SYMLINK_WATCH_FILE);
+        let mut links = Vec::new();
+        for name in ["AppA", "AppB"] {
+            let target = temp_dir.path().join("targets").join(name);
+            fs::create_dir_all(&target).unwrap();
+            let link = temp_dir.path().join(name);
+            std::os::unix::fs::symlink(&target, &link).unwrap();
+            links.push((link, target));
+        }
+
+        let watches = SymlinkWatchList::open(watch_file.clone());
+        for (link, target) in &links {
+            watches.register(link, target).unwrap();
+        }
+        assert_eq!(watches.check_all().broken, Vec::new());
+
+        // 模拟卸载程序删除了其中一个迁移目标
+        fs::remove_dir_all(&links[1].1).unwrap();
+
+        // 重新加载持久化的列表后检查
+        let report = SymlinkWatchList::open(watch_file).check_all();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.broken.len(), 1);
+        assert_eq!(report.broken[0].link_path, links[1].0.display().to_string());
+        assert_eq!(report.broken[0].reason, "链接目标不存在");
+    }
+}
@@ -3,6 +3,9 @@ use serde::{Serialize, Deserialize};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use chrono::TimeZone;
+use rusqlite::{params, params_from_iter, Connection};
 use uuid::Uuid;
 
 /// 操作类型枚举
@@ -15,6 +18,7 @@ pub enum OperationType {
     Validate,
     Cancel,
     Error,
+    Undo,
 }
 
 /// 操作状态枚举
@@ -110,30 +114,97 @@ impl OperationLog {
     }
 }
 
+/// 日志存储后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LoggerBackend {
+    /// 按月分文件的 JSON Lines 日志
+    #[default]
+    File,
+    /// SQLite 数据库，适合大量日志的查询
+    Sqlite,
+}
+
+/// SQLite 日志表结构
+const SQLITE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS operation_logs (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        operation_type TEXT NOT NULL,
+        status TEXT NOT NULL,
+        source_path TEXT NOT NULL,
+        target_path TEXT,
+        details TEXT NOT NULL,
+        error_message TEXT,
+        duration_ms INTEGER,
+        file_count INTEGER,
+        total_size INTEGER,
+        user TEXT NOT NULL,
+        session_id TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_operation_logs_timestamp ON operation_logs(timestamp);
+    CREATE INDEX IF NOT EXISTS idx_operation_logs_operation_type ON operation_logs(operation_type);
+    CREATE INDEX IF NOT EXISTS idx_operation_logs_status ON operation_logs(status);
+";
+
+/// SQLite 查询的列顺序，与 `row_to_log` 保持一致
+const SQLITE_COLUMNS: &str = "id, timestamp, operation_type, status, source_path, target_path, details, \
+    error_message, duration_ms, file_count, total_size, user, session_id";
+
 /// 操作日志管理器
 #[derive(Clone)]
 pub struct OperationLogger {
     log_file: PathBuf,
     session_id: String,
     current_user: String,
+    backend: LoggerBackend,
+    connection: Option<Arc<Mutex<Connection>>>, // 仅 SQLite 后端使用
 }
 
 impl OperationLogger {
     pub fn new(log_dir: PathBuf, session_id: String, current_user: String) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_backend(log_dir, session_id, current_user, LoggerBackend::File)
+    }
+
+    /// 使用指定的存储后端创建日志管理器
+    pub fn with_backend(
+        log_dir: PathBuf,
+        session_id: String,
+        current_user: String,
+        backend: LoggerBackend,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // 创建日志目录
         std::fs::create_dir_all(&log_dir)?;
         
-        // 生成操作日志文件名
-        let timestamp = Local::now().format("%Y-%m");
-        let log_file = log_dir.join(format!("operations-{}.log", timestamp));
+        let (log_file, connection) = match backend {
+            LoggerBackend::File => {
+                // 生成操作日志文件名
+                let timestamp = Local::now().format("%Y-%m");
+                (log_dir.join(format!("operations-{}.log", timestamp)), None)
+            }
+            LoggerBackend::Sqlite => {
+                let db_file = log_dir.join("operations.db");
+                let connection = Connection::open(&db_file)?;
+                connection.execute_batch(SQLITE_SCHEMA)?;
+                (db_file, Some(Arc::new(Mutex::new(connection))))
+            }
+        };
         
         Ok(Self {
             log_file,
             session_id,
             current_user,
+            backend,
+            connection,
         })
     }
 
+    /// 获取 SQLite 连接
+    fn sqlite(&self) -> Result<std::sync::MutexGuard<'_, Connection>, Box<dyn std::error::Error>> {
+        let connection = self.connection.as_ref().ok_or("SQLite后端未初始化")?;
+        connection.lock().map_err(|_| "SQLite连接锁已损坏".into())
+    }
+
     /// 记录操作开始
     pub fn log_operation_start(
         &self,
@@ -219,8 +290,16 @@ impl OperationLogger {
         Ok(())
     }
 
-    /// 写入日志文件
+    /// 写入日志
     fn write_log(&self, log: &OperationLog) -> Result<(), Box<dyn std::error::Error>> {
+        match self.backend {
+            LoggerBackend::File => self.write_log_file(log),
+            LoggerBackend::Sqlite => self.write_log_sqlite(log),
+        }
+    }
+
+    /// 写入日志文件
+    fn write_log_file(&self, log: &OperationLog) -> Result<(), Box<dyn std::error::Error>> {
         let log_entry = serde_json::to_string(log)? + "\n";
         
         let mut file = OpenOptions::new()
@@ -234,8 +313,52 @@ impl OperationLogger {
         Ok(())
     }
 
-    /// 读取最近的日志
-    pub fn get_recent_logs(&self, limit: usize) -> Result<Vec<OperationLog>, Box<dyn std::error::Error>> {
+    /// 写入一行 SQLite 日志（与文件后端一样，状态更新会追加新记录）
+    fn write_log_sqlite(&self, log: &OperationLog) -> Result<(), Box<dyn std::error::Error>> {
+        let connection = self.sqlite()?;
+        connection.execute(
+            "INSERT INTO operation_logs (id, timestamp, operation_type, status, source_path, target_path, details, \
+             error_message, duration_ms, file_count, total_size, user, session_id) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                log.id,
+                log.timestamp.timestamp_micros(),
+                enum_to_sql(&log.operation_type)?,
+                enum_to_sql(&log.status)?,
+                log.source_path,
+                log.target_path,
+                log.details,
+                log.error_message,
+                log.duration_ms.map(|v| v as i64),
+                log.file_count.map(|v| v as i64),
+                log.total_size.map(|v| v as i64),
+                log.user,
+                log.session_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 执行 SQLite 查询并转换为日志列表
+    fn query_sqlite(&self, sql: &str, values: Vec<rusqlite::types::Value>) -> Result<Vec<OperationLog>, Box<dyn std::error::Error>> {
+        let connection = self.sqlite()?;
+        let mut statement = connection.prepare(sql)?;
+        let rows = statement.query_map(params_from_iter(values), row_to_log)?;
+
+        let mut logs = Vec::new();
+        for row in rows {
+            logs.push(row?);
+        }
+        Ok(logs)
+    }
+
+    /// 读取全部日志
+    fn read_all_logs(&self) -> Result<Vec<OperationLog>, Box<dyn std::error::Error>> {
+        if self.backend == LoggerBackend::Sqlite {
+            let sql = format!("SELECT {} FROM operation_logs ORDER BY seq", SQLITE_COLUMNS); // 与文件一致，按写入顺序
+            return self.query_sqlite(&sql, Vec::new());
+        }
+
         if !self.log_file.exists() {
             return Ok(Vec::new());
         }
@@ -244,7 +367,6 @@ impl OperationLogger {
         let reader = BufReader::new(file);
         let mut logs = Vec::new();
 
-        // 读取所有日志
         for line in reader.lines() {
             if let Ok(line) = line {
                 if let Ok(log) = serde_json::from_str::<OperationLog>(&line) {
@@ -253,6 +375,23 @@ impl OperationLogger {
             }
         }
 
+        Ok(logs)
+    }
+
+    /// 读取最近的日志
+    pub fn get_recent_logs(&self, limit: usize) -> Result<Vec<OperationLog>, Box<dyn std::error::Error>> {
+        if self.backend == LoggerBackend::Sqlite {
+            let sql = format!(
+                "SELECT {} FROM operation_logs ORDER BY timestamp DESC, seq DESC LIMIT ?1",
+                SQLITE_COLUMNS
+            );
+            let mut logs = self.query_sqlite(&sql, vec![(limit as i64).into()])?;
+            logs.reverse(); // 恢复时间顺序
+            return Ok(logs);
+        }
+
+        let mut logs = self.read_all_logs()?;
+
         // 按时间排序并取最近的
         logs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
         logs.truncate(limit);
@@ -261,6 +400,54 @@ impl OperationLogger {
         Ok(logs)
     }
 
+    /// 按条件查询日志（结果按时间顺序排列）
+    pub fn query_logs(&self, filter: &LogQueryFilter) -> Result<Vec<OperationLog>, Box<dyn std::error::Error>> {
+        if self.backend == LoggerBackend::Sqlite {
+            // 类型、状态和时间范围走索引，路径匹配在内存中完成以保持与文件后端一致
+            let mut conditions = Vec::new();
+            let mut values: Vec<rusqlite::types::Value> = Vec::new();
+            if let Some(operation_type) = &filter.operation_type {
+                values.push(enum_to_sql(operation_type)?.into());
+                conditions.push(format!("operation_type = ?{}", values.len()));
+            }
+            if let Some(status) = &filter.status {
+                values.push(enum_to_sql(status)?.into());
+                conditions.push(format!("status = ?{}", values.len()));
+            }
+            if let Some(after) = &filter.after {
+                values.push(after.timestamp_micros().into());
+                conditions.push(format!("timestamp >= ?{}", values.len()));
+            }
+            if let Some(before) = &filter.before {
+                values.push(before.timestamp_micros().into());
+                conditions.push(format!("timestamp <= ?{}", values.len()));
+            }
+
+            let where_clause = if conditions.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", conditions.join(" AND "))
+            };
+            let sql = format!(
+                "SELECT {} FROM operation_logs {} ORDER BY timestamp, seq",
+                SQLITE_COLUMNS, where_clause
+            );
+
+            return Ok(self.query_sqlite(&sql, values)?
+                .into_iter()
+                .filter(|log| filter.matches(log))
+                .collect());
+        }
+
+        let mut logs: Vec<_> = self.read_all_logs()?
+            .into_iter()
+            .filter(|log| filter.matches(log))
+            .collect();
+
+        logs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(logs)
+    }
+
     /// 按类型筛选日志
     pub fn get_logs_by_type(&self, operation_type: OperationType, limit: usize) -> Result<Vec<OperationLog>, Box<dyn std::error::Error>> {
         let all_logs = self.get_recent_logs(1000)?; // 获取足够多的日志
@@ -288,42 +475,51 @@ impl OperationLogger {
         let logs = self.get_recent_logs(10000)?; // 获取最近10000条日志
         
         let mut stats = OperationStatistics::default();
-        
-        for log in logs {
-            stats.total_operations += 1;
-            
-            match log.status {
-                OperationStatus::Completed => stats.completed_operations += 1,
-                OperationStatus::Failed => stats.failed_operations += 1,
-                OperationStatus::Cancelled => stats.cancelled_operations += 1,
-                _ => {}
-            }
-            
-            if let Some(size) = log.total_size {
-                stats.total_bytes_transferred += size;
-            }
-            
-            if let Some(files) = log.file_count {
-                stats.total_files_processed += files;
-            }
-            
-            if let Some(duration) = log.duration_ms {
-                stats.total_duration_ms += duration;
-            }
+        for log in &logs {
+            stats.record(log);
         }
-        
-        stats.average_duration_ms = if stats.completed_operations > 0 {
-            stats.total_duration_ms / stats.completed_operations
-        } else {
-            0
-        };
+        stats.finalize();
         
         Ok(stats)
     }
 
+    /// 按操作类型分别统计（口径与 get_statistics 相同），按类型首次出现的顺序排列
+    pub fn get_statistics_by_type(&self) -> Result<Vec<OperationTypeStatistics>, Box<dyn std::error::Error>> {
+        let logs = self.get_recent_logs(10000)?;
+
+        let mut by_type: Vec<OperationTypeStatistics> = Vec::new();
+        for log in &logs {
+            let index = match by_type.iter().position(|entry| entry.operation_type == log.operation_type) {
+                Some(index) => index,
+                None => {
+                    by_type.push(OperationTypeStatistics {
+                        operation_type: log.operation_type.clone(),
+                        statistics: OperationStatistics::default(),
+                    });
+                    by_type.len() - 1
+                }
+            };
+            by_type[index].statistics.record(log);
+        }
+        for entry in &mut by_type {
+            entry.statistics.finalize();
+        }
+
+        Ok(by_type)
+    }
+
     /// 清理旧的操作日志
     pub fn cleanup_old_logs(&self, days_to_keep: u32) -> Result<(), Box<dyn std::error::Error>> {
         let cutoff_date = Local::now() - chrono::Duration::days(days_to_keep as i64);
+
+        if self.backend == LoggerBackend::Sqlite {
+            let connection = self.sqlite()?;
+            connection.execute(
+                "DELETE FROM operation_logs WHERE timestamp < ?1",
+                params![cutoff_date.timestamp_micros()],
+            )?;
+            return Ok(());
+        }
         
         if !self.log_file.exists() {
             return Ok(());
@@ -356,6 +552,94 @@ impl OperationLogger {
     }
 }
 
+/// 将枚举转换为 SQLite 中存储的名称（与 JSON 序列化一致）
+fn enum_to_sql<T: Serialize>(value: &T) -> Result<String, Box<dyn std::error::Error>> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => Err(format!("无法转换为SQLite字段: {}", other).into()),
+    }
+}
+
+/// 将 SQLite 行转换为操作日志
+fn row_to_log(row: &rusqlite::Row<'_>) -> rusqlite::Result<OperationLog> {
+    fn enum_from_sql<T: serde::de::DeserializeOwned>(index: usize, name: String) -> rusqlite::Result<T> {
+        serde_json::from_value(serde_json::Value::String(name))
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+    }
+
+    let timestamp_micros: i64 = row.get(1)?;
+    let timestamp = Local.timestamp_micros(timestamp_micros).single()
+        .ok_or(rusqlite::Error::IntegralValueOutOfRange(1, timestamp_micros))?;
+
+    Ok(OperationLog {
+        id: row.get(0)?,
+        timestamp,
+        operation_type: enum_from_sql(2, row.get(2)?)?,
+        status: enum_from_sql(3, row.get(3)?)?,
+        source_path: row.get(4)?,
+        target_path: row.get(5)?,
+        details: row.get(6)?,
+        error_message: row.get(7)?,
+        duration_ms: row.get::<_, Option<i64>>(8)?.map(|v| v as u64),
+        file_count: row.get::<_, Option<i64>>(9)?.map(|v| v as u64),
+        total_size: row.get::<_, Option<i64>>(10)?.map(|v| v as u64),
+        user: row.get(11)?,
+        session_id: row.get(12)?,
+    })
+}
+
+/// 日志查询条件，未设置的字段不参与筛选
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQueryFilter {
+    pub operation_type: Option<OperationType>,
+    pub status: Option<OperationStatus>,
+    pub path_contains: Option<String>, // 匹配源路径或目标路径（不区分大小写）
+    pub after: Option<DateTime<Local>>,
+    pub before: Option<DateTime<Local>>,
+}
+
+impl LogQueryFilter {
+    /// 判断日志是否满足所有已设置的条件
+    pub fn matches(&self, log: &OperationLog) -> bool {
+        if let Some(operation_type) = &self.operation_type {
+            if &log.operation_type != operation_type {
+                return false;
+            }
+        }
+
+        if let Some(status) = &self.status {
+            if &log.status != status {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.path_contains {
+            let pattern = pattern.to_lowercase();
+            let in_source = log.source_path.to_lowercase().contains(&pattern);
+            let in_target = log.target_path.as_ref()
+                .map(|target| target.to_lowercase().contains(&pattern))
+                .unwrap_or(false);
+            if !in_source && !in_target {
+                return false;
+            }
+        }
+
+        if let Some(after) = &self.after {
+            if log.timestamp < *after {
+                return false;
+            }
+        }
+
+        if let Some(before) = &self.before {
+            if log.timestamp > *before {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 /// 操作统计信息
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct OperationStatistics {
@@ -369,7 +653,48 @@ pub struct OperationStatistics {
     pub average_duration_ms: u64,
 }
 
+/// 单一操作类型的统计信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperationTypeStatistics {
+    pub operation_type: OperationType,
+    #[serde(flatten)]
+    pub statistics: OperationStatistics,
+}
+
 impl OperationStatistics {
+    /// 累计一条日志
+    fn record(&mut self, log: &OperationLog) {
+        self.total_operations += 1;
+
+        match log.status {
+            OperationStatus::Completed => self.completed_operations += 1,
+            OperationStatus::Failed => self.failed_operations += 1,
+            OperationStatus::Cancelled => self.cancelled_operations += 1,
+            _ => {}
+        }
+
+        if let Some(size) = log.total_size {
+            self.total_bytes_transferred += size;
+        }
+
+        if let Some(files) = log.file_count {
+            self.total_files_processed += files;
+        }
+
+        if let Some(duration) = log.duration_ms {
+            self.total_duration_ms += duration;
+        }
+    }
+
+    /// 累计完成后计算平均耗时（按已完成的操作平均）
+    fn finalize(&mut self) {
+        self.average_duration_ms = if self.completed_operations > 0 {
+            self.total_duration_ms / self.completed_operations
+        } else {
+            0
+        };
+    }
+
     pub fn success_rate(&self) -> f64 {
         if self.total_operations > 0 {
             (self.completed_operations as f64 / self.total_operations as f64) * 100.0
@@ -421,4 +746,270 @@ pub fn export_logs_to_csv(
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_logger(temp_dir: &TempDir) -> OperationLogger {
+        OperationLogger::new(
+            temp_dir.path().to_path_buf(),
+            "test_session".to_string(),
+            "test_user".to_string(),
+        ).unwrap()
+    }
+
+    /// 写入一组时间、类型、状态各不相同的日志
+    fn seed_logs(logger: &OperationLogger) {
+        let now = Local::now();
+        let entries = [
+            (OperationType::Migrate, OperationStatus::Failed, "C:\\Users\\a\\AppData\\Local\\Chrome", 3),
+            (OperationType::Migrate, OperationStatus::Completed, "C:\\Users\\a\\AppData\\Roaming\\Code", 2),
+            (OperationType::Scan, OperationStatus::Failed, "C:\\Users\\a\\AppData", 1),
+            (OperationType::Migrate, OperationStatus::Failed, "C:\\Games\\Steam", 10),
+        ];
+
+        for (operation_type, status, source, days_ago) in entries {
+            let mut log = OperationLog::new(
+                operation_type,
+                source.to_string(),
+                Some("D:\\Moved".to_string()),
+                "test_user".to_string(),
+                "test_session".to_string(),
+            ).with_status(status);
+            log.timestamp = now - chrono::Duration::days(days_ago);
+            logger.write_log(&log).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_query_logs_by_operation_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = create_logger(&temp_dir);
+        seed_logs(&logger);
+
+        let filter = LogQueryFilter {
+            operation_type: Some(OperationType::Scan),
+            ..Default::default()
+        };
+        let logs = logger.query_logs(&filter).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].operation_type, OperationType::Scan);
+    }
+
+    #[test]
+    fn test_query_logs_by_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = create_logger(&temp_dir);
+        seed_logs(&logger);
+
+        let filter = LogQueryFilter {
+            status: Some(OperationStatus::Failed),
+            ..Default::default()
+        };
+        let logs = logger.query_logs(&filter).unwrap();
+        assert_eq!(logs.len(), 3);
+        assert!(logs.iter().all(|log| log.status == OperationStatus::Failed));
+    }
+
+    #[test]
+    fn test_query_logs_by_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = create_logger(&temp_dir);
+        seed_logs(&logger);
+
+        // 不区分大小写匹配源路径
+        let filter = LogQueryFilter {
+            path_contains: Some("appdata".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(logger.query_logs(&filter).unwrap().len(), 3);
+
+        // 目标路径同样参与匹配
+        let filter = LogQueryFilter {
+            path_contains: Some("D:\\Moved".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(logger.query_logs(&filter).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_query_logs_by_time_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = create_logger(&temp_dir);
+        seed_logs(&logger);
+
+        let now = Local::now();
+        let filter = LogQueryFilter {
+            after: Some(now - chrono::Duration::days(7)),
+            ..Default::default()
+        };
+        assert_eq!(logger.query_logs(&filter).unwrap().len(), 3);
+
+        let filter = LogQueryFilter {
+            before: Some(now - chrono::Duration::days(2)),
+            ..Default::default()
+        };
+        let logs = logger.query_logs(&filter).unwrap();
+        assert_eq!(logs.len(), 3);
+        // 结果按时间顺序排列
+        assert!(logs[0].timestamp <= logs[1].timestamp && logs[1].timestamp <= logs[2].timestamp);
+    }
+
+    #[test]
+    fn test_query_logs_combined() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = create_logger(&temp_dir);
+        seed_logs(&logger);
+
+        // 上周内所有涉及AppData的失败迁移操作
+        let filter = LogQueryFilter {
+            operation_type: Some(OperationType::Migrate),
+            status: Some(OperationStatus::Failed),
+            path_contains: Some("AppData".to_string()),
+            after: Some(Local::now() - chrono::Duration::days(7)),
+            before: Some(Local::now()),
+        };
+        let logs = logger.query_logs(&filter).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].source_path.ends_with("Chrome"));
+
+        // 空条件返回全部日志
+        assert_eq!(logger.query_logs(&LogQueryFilter::default()).unwrap().len(), 4);
+    }
+
+    /// 向文件后端和 SQLite 后端写入相同的一批日志
+    fn create_parity_loggers(file_dir: &TempDir, sqlite_dir: &TempDir) -> (OperationLogger, OperationLogger) {
+        let file_logger = create_logger(file_dir);
+        let sqlite_logger = OperationLogger::with_backend(
+            sqlite_dir.path().to_path_buf(),
+            "test_session".to_string(),
+            "test_user".to_string(),
+            LoggerBackend::Sqlite,
+        ).unwrap();
+
+        let now = Local::now();
+        let types = [OperationType::Scan, OperationType::Migrate, OperationType::Delete, OperationType::CreateSymlink];
+        let statuses = [OperationStatus::Completed, OperationStatus::Failed, OperationStatus::Cancelled, OperationStatus::Started];
+
+        for i in 0..300i64 {
+            let mut log = OperationLog::new(
+                types[(i % 4) as usize].clone(),
+                format!("C:\\Users\\a\\AppData\\Local\\App{}", i % 7),
+                if i % 3 == 0 { None } else { Some(format!("D:\\Moved\\App{}", i)) },
+                "test_user".to_string(),
+                "test_session".to_string(),
+            ).with_status(statuses[(i % 5 % 4) as usize].clone());
+            if log.status == OperationStatus::Completed {
+                log = log.with_metrics(i as u64, (i * 1024) as u64, (i * 10) as u64);
+            }
+            if log.status == OperationStatus::Failed {
+                log = log.with_error(format!("错误 {}", i));
+            }
+            log.timestamp = now - chrono::Duration::hours(i * 5);
+
+            file_logger.write_log(&log).unwrap();
+            sqlite_logger.write_log(&log).unwrap();
+        }
+
+        (file_logger, sqlite_logger)
+    }
+
+    fn ids(logs: &[OperationLog]) -> Vec<String> {
+        logs.iter().map(|log| log.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_sqlite_backend_query_parity() {
+        let (file_dir, sqlite_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let (file_logger, sqlite_logger) = create_parity_loggers(&file_dir, &sqlite_dir);
+
+        assert_eq!(ids(&file_logger.get_recent_logs(50).unwrap()), ids(&sqlite_logger.get_recent_logs(50).unwrap()));
+        assert_eq!(ids(&file_logger.get_failed_operations(20).unwrap()), ids(&sqlite_logger.get_failed_operations(20).unwrap()));
+
+        let filters = [
+            LogQueryFilter::default(),
+            LogQueryFilter { operation_type: Some(OperationType::Migrate), ..Default::default() },
+            LogQueryFilter { status: Some(OperationStatus::Failed), ..Default::default() },
+            LogQueryFilter { path_contains: Some("app3".to_string()), ..Default::default() },
+            LogQueryFilter {
+                operation_type: Some(OperationType::Scan),
+                status: Some(OperationStatus::Completed),
+                after: Some(Local::now() - chrono::Duration::days(20)),
+                before: Some(Local::now() - chrono::Duration::days(2)),
+                ..Default::default()
+            },
+        ];
+        for filter in &filters {
+            let file_logs = file_logger.query_logs(filter).unwrap();
+            let sqlite_logs = sqlite_logger.query_logs(filter).unwrap();
+            assert!(!file_logs.is_empty(), "{:?}", filter);
+            assert_eq!(ids(&file_logs), ids(&sqlite_logs), "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_sqlite_backend_statistics_and_cleanup_parity() {
+        let (file_dir, sqlite_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let (file_logger, sqlite_logger) = create_parity_loggers(&file_dir, &sqlite_dir);
+
+        let file_stats = serde_json::to_value(file_logger.get_statistics().unwrap()).unwrap();
+        let sqlite_stats = serde_json::to_value(sqlite_logger.get_statistics().unwrap()).unwrap();
+        assert_eq!(file_stats["total_operations"], 300);
+        assert_eq!(file_stats, sqlite_stats);
+
+        file_logger.cleanup_old_logs(30).unwrap();
+        sqlite_logger.cleanup_old_logs(30).unwrap();
+
+        let file_remaining = file_logger.read_all_logs().unwrap();
+        let sqlite_remaining = sqlite_logger.read_all_logs().unwrap();
+        assert!(file_remaining.len() < 300);
+        assert_eq!(ids(&file_remaining), ids(&sqlite_remaining));
+    }
+
+    #[test]
+    fn test_statistics_by_operation_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = create_logger(&temp_dir);
+
+        let entries = [
+            (OperationType::Migrate, 10, 1000, 200),
+            (OperationType::Scan, 500, 0, 50),
+            (OperationType::Migrate, 30, 3000, 400),
+            (OperationType::Delete, 5, 500, 10),
+        ];
+        for (operation_type, files, bytes, duration) in entries {
+            let mut log = logger.log_operation_start(operation_type, "C:\\Data".to_string(), None, String::new()).unwrap();
+            logger.complete_operation(&mut log, files, bytes, duration, None).unwrap();
+        }
+        let mut failed = logger.log_operation_start(OperationType::Migrate, "C:\\Locked".to_string(), None, String::new()).unwrap();
+        logger.fail_operation(&mut failed, "拒绝访问".to_string(), None).unwrap();
+
+        let by_type = logger.get_statistics_by_type().unwrap();
+        let types: Vec<OperationType> = by_type.iter().map(|entry| entry.operation_type.clone()).collect();
+        assert_eq!(types, vec![OperationType::Migrate, OperationType::Scan, OperationType::Delete]);
+
+        let migrate = &by_type[0].statistics;
+        assert_eq!(migrate.completed_operations, 2);
+        assert_eq!(migrate.failed_operations, 1);
+        assert_eq!(migrate.total_files_processed, 40);
+        assert_eq!(migrate.total_bytes_transferred, 4000);
+        assert_eq!(migrate.average_duration_ms, 300);
+
+        let scan = &by_type[1].statistics;
+        assert_eq!((scan.completed_operations, scan.total_files_processed, scan.average_duration_ms), (1, 500, 50));
+        let delete = &by_type[2].statistics;
+        assert_eq!((delete.completed_operations, delete.total_bytes_transferred), (1, 500));
+
+        // 各类型汇总与整体统计一致
+        let overall = logger.get_statistics().unwrap();
+        assert_eq!(by_type.iter().map(|entry| entry.statistics.total_operations).sum::<u64>(), overall.total_operations);
+        assert_eq!(by_type.iter().map(|entry| entry.statistics.total_bytes_transferred).sum::<u64>(), overall.total_bytes_transferred);
+
+        let json = serde_json::to_value(&by_type[0]).unwrap();
+        assert_eq!(json["operation_type"], "Migrate");
+        assert_eq!(json["completed_operations"], 2);
+    }
+}
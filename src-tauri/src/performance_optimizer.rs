@@ -2,13 +2,17 @@
 //!
 //! 提供内存优化、缓存管理、并发控制等性能优化功能
 
+use std::any::Any;
 use std::sync::{Arc, Mutex, RwLock};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
 use std::time::{Duration, Instant};
 use std::path::{Path, PathBuf};
 use std::fs;
 use log::{info, warn, debug};
+use serde::{Serialize, Deserialize};
 use lru::LruCache;
+use sysinfo::{Pid, ProcessExt, System, SystemExt};
 use tokio::sync::Semaphore;
 
 /// 性能优化配置
@@ -17,7 +21,10 @@ pub struct PerformanceConfig {
     pub max_memory_usage_mb: usize,
     pub cache_size: usize,
     pub max_concurrent_operations: usize,
+    pub worker_threads: usize, // 新增：并行扫描的工作线程数
     pub batch_size: usize,
+    pub migration_throughput_bytes_per_sec: u64, // 新增：尚无实测数据时预估迁移耗时所用的吞吐量
+    pub copy_buffer_size: usize, // 新增：迁移时分块复制文件的缓冲区大小（字节）
     pub cleanup_interval_seconds: u64,
     pub enable_memory_monitoring: bool,
     pub enable_caching: bool,
@@ -30,7 +37,12 @@ impl Default for PerformanceConfig {
             max_memory_usage_mb: 500, // 500MB
             cache_size: 1000, // 缓存1000个目录信息
             max_concurrent_operations: 5, // 最多5个并发操作
+            worker_threads: std::thread::available_parallelism()
+                .map(|n| n.get().min(4))
+                .unwrap_or(1), // 最多4个扫描线程
             batch_size: 100, // 每批处理100个文件
+            migration_throughput_bytes_per_sec: 50 * 1024 * 1024, // 50MB/s
+            copy_buffer_size: 1024 * 1024, // 1MB
             cleanup_interval_seconds: 300, // 5分钟清理一次
             enable_memory_monitoring: true,
             enable_caching: true,
@@ -39,6 +51,99 @@ impl Default for PerformanceConfig {
     }
 }
 
+/// 扫描并发设置（可在运行时调整）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ScanSettings {
+    pub worker_threads: usize,
+    pub batch_size: usize,
+}
+
+/// 目录缓存项的有效期，超过后即使修改时间未变化也重新扫描（目录修改时间无法反映文件内容的原地修改）
+const DIRECTORY_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// 分块复制缓冲区的最小值
+const MIN_COPY_BUFFER_SIZE: usize = 4 * 1024;
+
+/// 迁移吞吐量滑动平均所用的样本数
+const THROUGHPUT_SAMPLE_WINDOW: usize = 10;
+
+/// 日志目录下保存迁移吞吐量统计的文件名
+pub const THROUGHPUT_STATS_FILE: &str = "throughput_stats.json";
+
+/// 跨会话保存的迁移吞吐量统计
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ThroughputStats {
+    /// 滑动平均吞吐量（字节/秒）
+    pub average_bytes_per_sec: f64,
+    /// 参与平均的样本数
+    pub sample_count: usize,
+}
+
+/// 驱动器测速时写入并读回的临时文件大小
+pub const DRIVE_BENCHMARK_SIZE: usize = 8 * 1024 * 1024;
+
+/// 驱动器读写测速结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriveBenchmark {
+    pub path: PathBuf,
+    pub bytes_tested: u64,
+    /// 写入吞吐量（字节/秒）
+    pub write_bytes_per_sec: f64,
+    /// 读取吞吐量（字节/秒）
+    pub read_bytes_per_sec: f64,
+}
+
+/// 在目标目录写入并读回一个临时文件以测量读写吞吐量，结束后删除临时文件
+pub fn benchmark_drive(path: &Path, size: usize) -> Result<DriveBenchmark, String> {
+    if !path.is_dir() {
+        return Err(format!("测速路径不存在或不是目录: {}", path.display()));
+    }
+
+    let size = size.max(MIN_COPY_BUFFER_SIZE);
+    let temp_file = path.join(format!(".dir_mover_benchmark_{}.tmp", uuid::Uuid::new_v4()));
+    let result = run_drive_benchmark(&temp_file, size);
+    if let Err(e) = fs::remove_file(&temp_file) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!("删除测速临时文件失败 {}: {}", temp_file.display(), e);
+        }
+    }
+
+    let (write_elapsed, read_elapsed) = result.map_err(|e| format!("驱动器测速失败: {}", e))?;
+    // 极快的设备上耗时可能趋近 0，取下限避免得到无穷大
+    let throughput = |elapsed: Duration| size as f64 / elapsed.as_secs_f64().max(1e-6);
+    let benchmark = DriveBenchmark {
+        path: path.to_path_buf(),
+        bytes_tested: size as u64,
+        write_bytes_per_sec: throughput(write_elapsed),
+        read_bytes_per_sec: throughput(read_elapsed),
+    };
+    info!("驱动器测速 {}: 写入 {:.1} MB/s, 读取 {:.1} MB/s", path.display(),
+          benchmark.write_bytes_per_sec / 1_048_576.0, benchmark.read_bytes_per_sec / 1_048_576.0);
+    Ok(benchmark)
+}
+
+/// 写入并同步临时文件，再完整读回，返回（写入耗时, 读取耗时）
+fn run_drive_benchmark(temp_file: &Path, size: usize) -> std::io::Result<(Duration, Duration)> {
+    let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+
+    let write_start = Instant::now();
+    let mut file = fs::File::create(temp_file)?;
+    file.write_all(&data)?;
+    file.sync_all()?;
+    drop(file);
+    let write_elapsed = write_start.elapsed();
+
+    let read_start = Instant::now();
+    let mut read_back = Vec::with_capacity(size);
+    fs::File::open(temp_file)?.read_to_end(&mut read_back)?;
+    let read_elapsed = read_start.elapsed();
+
+    if read_back != data {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "读回的数据与写入的不一致"));
+    }
+    Ok((write_elapsed, read_elapsed))
+}
+
 /// 内存使用监控器
 pub struct MemoryMonitor {
     current_usage: Arc<Mutex<usize>>,
@@ -108,6 +213,88 @@ impl MemoryMonitor {
     }
 }
 
+/// 进程内存采样器
+///
+/// 通过 sysinfo 读取当前进程的实际常驻内存（RSS），并记录运行期间的峰值。
+pub struct ProcessMemorySampler {
+    system: Mutex<System>,
+    pid: Option<Pid>,
+    peak_bytes: Mutex<u64>,
+    last_bytes: Mutex<Option<u64>>, // 新增：最近一次成功采样的值，采样失败时沿用
+    #[cfg(test)]
+    reader: fn(&ProcessMemorySampler) -> Option<u64>, // 新增：读取常驻内存（测试中可替换以模拟采样失败）
+}
+
+impl ProcessMemorySampler {
+    pub fn new() -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+            pid: sysinfo::get_current_pid().ok(),
+            peak_bytes: Mutex::new(0),
+            last_bytes: Mutex::new(None),
+            #[cfg(test)]
+            reader: Self::read_process_rss,
+        }
+    }
+
+    /// 通过 sysinfo 读取当前进程的常驻内存
+    fn read_process_rss(&self) -> Option<u64> {
+        let pid = self.pid?;
+        let mut system = self.system.lock().ok()?;
+
+        if !system.refresh_process(pid) {
+            return None;
+        }
+
+        Some(system.process(pid)?.memory())
+    }
+
+    /// 读取常驻内存（测试中可替换以模拟采样失败）
+    #[cfg(test)]
+    fn read_rss(&self) -> Option<u64> {
+        (self.reader)(self)
+    }
+
+    #[cfg(not(test))]
+    fn read_rss(&self) -> Option<u64> {
+        self.read_process_rss()
+    }
+
+    /// 采样当前进程的常驻内存（字节），无法获取时返回 None
+    ///
+    /// 受限环境（如容器）中 sysinfo 可能失败或返回 0，两者都视为采样失败。
+    pub fn sample(&self) -> Option<u64> {
+        let rss = self.read_rss().filter(|rss| *rss > 0)?;
+
+        if let Ok(mut peak) = self.peak_bytes.lock() {
+            if rss > *peak {
+                *peak = rss;
+            }
+        }
+        if let Ok(mut last) = self.last_bytes.lock() {
+            *last = Some(rss);
+        }
+
+        Some(rss)
+    }
+
+    /// 最近一次成功采样的常驻内存（字节）
+    pub fn last_sample(&self) -> Option<u64> {
+        self.last_bytes.lock().ok().and_then(|last| *last)
+    }
+
+    /// 获取采样到的峰值内存（字节）
+    pub fn peak(&self) -> u64 {
+        self.peak_bytes.lock().map(|peak| *peak).unwrap_or(0)
+    }
+}
+
+impl Default for ProcessMemorySampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// 目录信息缓存
 pub struct DirectoryCache {
     cache: Arc<RwLock<LruCache<PathBuf, CachedDirectoryInfo>>>,
@@ -122,6 +309,7 @@ pub struct CachedDirectoryInfo {
     pub total_size: u64,
     pub last_modified: Instant,
     pub is_large_folder: bool,
+    pub scan: Option<Arc<dyn Any + Send + Sync>>, // 新增：完整的扫描结果（仅由磁盘扫描写入，类型由写入方决定）
 }
 
 impl DirectoryCache {
@@ -135,15 +323,29 @@ impl DirectoryCache {
 
     /// 获取缓存的目录信息
     pub fn get(&self, path: &Path) -> Option<CachedDirectoryInfo> {
-        if let Ok(mut cache) = self.cache.write() {
-            if let Some(cached_info) = cache.get(path) {
-                // 检查缓存是否过期（5分钟）
-                if cached_info.last_modified.elapsed() < Duration::from_secs(300) {
-                    if let Ok(mut hit_count) = self.hit_count.lock() {
-                        *hit_count += 1;
-                    }
-                    return Some(cached_info.clone());
+        self.get_if(path, |_| true)
+    }
+
+    /// 获取缓存的目录信息，未过期且通过 `is_valid` 校验时才计为命中，校验失败的缓存项会被移除
+    ///
+    /// 校验在释放缓存锁之后进行，可以访问文件系统。
+    pub fn get_if<F>(&self, path: &Path, is_valid: F) -> Option<CachedDirectoryInfo>
+    where
+        F: FnOnce(&CachedDirectoryInfo) -> bool,
+    {
+        let cached = self.cache.write().ok()
+            .and_then(|mut cache| cache.get(path).cloned())
+            .filter(|cached_info| cached_info.last_modified.elapsed() < DIRECTORY_CACHE_TTL);
+
+        if let Some(cached_info) = cached {
+            if is_valid(&cached_info) {
+                if let Ok(mut hit_count) = self.hit_count.lock() {
+                    *hit_count += 1;
                 }
+                return Some(cached_info);
+            }
+            if let Ok(mut cache) = self.cache.write() {
+                cache.pop(path);
             }
         }
 
@@ -268,9 +470,14 @@ impl BatchProcessor {
 pub struct PerformanceOptimizer {
     config: PerformanceConfig,
     memory_monitor: MemoryMonitor,
+    memory_sampler: ProcessMemorySampler,
     directory_cache: DirectoryCache,
     batch_processor: BatchProcessor,
     last_cleanup: Arc<Mutex<Instant>>,
+    scan_settings: RwLock<ScanSettings>,
+    throughput_samples: Mutex<VecDeque<f64>>,
+    throughput_stats_path: Option<PathBuf>, // 新增：吞吐量统计的持久化文件
+    drive_benchmarks: Mutex<HashMap<PathBuf, DriveBenchmark>>, // 新增：按测速路径保存的驱动器测速结果
 }
 
 impl PerformanceOptimizer {
@@ -282,14 +489,175 @@ impl PerformanceOptimizer {
         
         let directory_cache = DirectoryCache::new(config.cache_size);
         let batch_processor = BatchProcessor::new(config.clone());
+        let scan_settings = ScanSettings {
+            worker_threads: config.worker_threads.max(1),
+            batch_size: config.batch_size.max(1),
+        };
         
         Self {
             config,
             memory_monitor,
+            memory_sampler: ProcessMemorySampler::new(),
             directory_cache,
             batch_processor,
             last_cleanup: Arc::new(Mutex::new(Instant::now())),
+            scan_settings: RwLock::new(scan_settings),
+            throughput_samples: Mutex::new(VecDeque::with_capacity(THROUGHPUT_SAMPLE_WINDOW)),
+            throughput_stats_path: None,
+            drive_benchmarks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 使用统计文件持久化迁移吞吐量：加载上次会话保存的滑动平均，之后每次记录后写回
+    pub fn with_throughput_stats_file(mut self, path: PathBuf) -> Self {
+        match fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<ThroughputStats>(&content) {
+                Ok(stats) if stats.average_bytes_per_sec > 0.0 && stats.sample_count > 0 => {
+                    // 以保存的平均值填充样本窗口，新样本按原有权重参与滑动平均
+                    let count = stats.sample_count.min(THROUGHPUT_SAMPLE_WINDOW);
+                    if let Ok(mut samples) = self.throughput_samples.lock() {
+                        samples.clear();
+                        samples.extend(std::iter::repeat(stats.average_bytes_per_sec).take(count));
+                    }
+                    info!("已加载迁移吞吐量统计: {:.0} B/s (样本数: {})", stats.average_bytes_per_sec, count);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("吞吐量统计文件格式无效 {}: {}", path.display(), e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("读取吞吐量统计文件失败 {}: {}", path.display(), e),
+        }
+
+        self.throughput_stats_path = Some(path);
+        self
+    }
+
+    /// 当前的实测吞吐量统计（无样本时平均值为 0）
+    pub fn throughput_stats(&self) -> ThroughputStats {
+        self.throughput_samples.lock()
+            .ok()
+            .filter(|samples| !samples.is_empty())
+            .map(|samples| ThroughputStats {
+                average_bytes_per_sec: samples.iter().sum::<f64>() / samples.len() as f64,
+                sample_count: samples.len(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// 将吞吐量统计写入统计文件（未配置时跳过）
+    fn save_throughput_stats(&self) {
+        let Some(path) = &self.throughput_stats_path else {
+            return;
+        };
+
+        let result = serde_json::to_string_pretty(&self.throughput_stats())
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                fs::write(path, content).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!("保存吞吐量统计失败 {}: {}", path.display(), e);
+        }
+    }
+
+    /// 迁移复制文件时使用的缓冲区大小（字节），至少 4KB
+    pub fn copy_buffer_size(&self) -> usize {
+        self.config.copy_buffer_size.max(MIN_COPY_BUFFER_SIZE)
+    }
+
+    /// 记录一次实际迁移的吞吐量，用于改进后续的耗时预估
+    pub fn record_migration_throughput(&self, bytes: u64, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        if bytes == 0 || seconds <= 0.0 {
+            return;
+        }
+
+        if let Ok(mut samples) = self.throughput_samples.lock() {
+            if samples.len() >= THROUGHPUT_SAMPLE_WINDOW {
+                samples.pop_front();
+            }
+            samples.push_back(bytes as f64 / seconds);
+            debug!("记录迁移吞吐量: {:.0} B/s (样本数: {})", bytes as f64 / seconds, samples.len());
+        }
+        self.save_throughput_stats();
+    }
+
+    /// 当前的迁移吞吐量（字节/秒）：有实测样本时取滑动平均，否则取配置值
+    pub fn migration_throughput(&self) -> f64 {
+        let stats = self.throughput_stats();
+        if stats.sample_count > 0 {
+            stats.average_bytes_per_sec
+        } else {
+            self.config.migration_throughput_bytes_per_sec.max(1) as f64
+        }
+    }
+
+    /// 预估迁移指定字节数所需的秒数（向上取整）
+    pub fn estimate_migration_seconds(&self, total_bytes: u64) -> u64 {
+        (total_bytes as f64 / self.migration_throughput()).ceil() as u64
+    }
+
+    /// 保存驱动器测速结果，供迁移到该驱动器时的耗时预估使用
+    pub fn record_drive_benchmark(&self, benchmark: DriveBenchmark) {
+        if let Ok(mut benchmarks) = self.drive_benchmarks.lock() {
+            benchmarks.insert(benchmark.path.clone(), benchmark);
+        }
+    }
+
+    /// 查找覆盖目标路径的测速结果（取最近的上级测速路径）
+    pub fn drive_benchmark_for(&self, target: &Path) -> Option<DriveBenchmark> {
+        let benchmarks = self.drive_benchmarks.lock().ok()?;
+        benchmarks.values()
+            .filter(|benchmark| target.starts_with(&benchmark.path))
+            .max_by_key(|benchmark| benchmark.path.components().count())
+            .cloned()
+    }
+
+    /// 预估迁移到指定目标所需的秒数：目标驱动器测过速时，以其写入吞吐量为上限
+    pub fn estimate_migration_seconds_to(&self, total_bytes: u64, target: &Path) -> u64 {
+        let mut throughput = self.migration_throughput();
+        if let Some(benchmark) = self.drive_benchmark_for(target) {
+            throughput = throughput.min(benchmark.write_bytes_per_sec);
         }
+        (total_bytes as f64 / throughput.max(1.0)).ceil() as u64
+    }
+
+    /// 获取当前的扫描并发设置
+    pub fn scan_settings(&self) -> ScanSettings {
+        self.scan_settings.read()
+            .map(|settings| *settings)
+            .unwrap_or(ScanSettings {
+                worker_threads: 1,
+                batch_size: self.config.batch_size.max(1),
+            })
+    }
+
+    /// 更新扫描并发设置，未提供的项保持不变
+    pub fn update_scan_settings(
+        &self,
+        worker_threads: Option<usize>,
+        batch_size: Option<usize>,
+    ) -> Result<ScanSettings, String> {
+        if worker_threads == Some(0) {
+            return Err("工作线程数必须大于0".to_string());
+        }
+        if batch_size == Some(0) {
+            return Err("批处理大小必须大于0".to_string());
+        }
+
+        let mut settings = self.scan_settings.write()
+            .map_err(|_| "无法获取扫描设置锁".to_string())?;
+        if let Some(worker_threads) = worker_threads {
+            settings.worker_threads = worker_threads;
+        }
+        if let Some(batch_size) = batch_size {
+            settings.batch_size = batch_size;
+        }
+        info!("扫描设置已更新: 工作线程 {}, 批大小 {}", settings.worker_threads, settings.batch_size);
+        Ok(*settings)
     }
 
     /// 优化文件扫描操作
@@ -315,6 +683,51 @@ impl PerformanceOptimizer {
         result
     }
 
+    /// 是否启用了缓存
+    pub fn caching_enabled(&self) -> bool {
+        self.config.enable_caching
+    }
+
+    /// 读取缓存的扫描结果
+    ///
+    /// 缓存项类型为 `T` 且通过 `is_valid` 校验（由写入方判断结果是否仍与磁盘一致）时命中；
+    /// 命中与未命中都计入缓存统计。
+    pub fn cached_scan<T>(&self, path: &Path, is_valid: impl FnOnce(&T) -> bool) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        if !self.config.enable_caching {
+            return None;
+        }
+
+        let cached = self.directory_cache.get_if(path, |cached_info| {
+            cached_info.scan.as_ref()
+                .and_then(|scan| scan.downcast_ref::<T>())
+                .is_some_and(is_valid)
+        })?;
+        debug!("使用缓存的扫描结果: {}", path.display());
+        cached.scan?.downcast::<T>().ok()
+    }
+
+    /// 缓存一次完整扫描的结果
+    pub fn cache_scan<T>(&self, path: &Path, file_count: u64, total_size: u64, is_large_folder: bool, scan: T)
+    where
+        T: Any + Send + Sync,
+    {
+        if !self.config.enable_caching {
+            return;
+        }
+
+        self.directory_cache.insert(path.to_path_buf(), CachedDirectoryInfo {
+            path: path.to_path_buf(),
+            file_count: file_count as usize,
+            total_size,
+            last_modified: Instant::now(),
+            is_large_folder,
+            scan: Some(Arc::new(scan)),
+        });
+    }
+
     /// 优化文件迁移操作
     pub async fn optimize_migration_operation<F, Fut, R>(&self, items: Vec<BatchItem>, operation: F) -> Vec<R>
     where
@@ -370,18 +783,37 @@ impl PerformanceOptimizer {
     }
 
     /// 获取性能统计
+    ///
+    /// 内存数据取自当前进程的实际 RSS；平台不支持采样时回退为内部估算值。
     pub fn get_performance_stats(&self) -> PerformanceStats {
         let cache_stats = self.directory_cache.get_stats();
+
+        // 采样失败时沿用上次的有效值；从未成功过则退回到内部记账的用量
+        let (memory_usage_bytes, memory_peak_bytes, metrics_available) = match self.memory_sampler.sample() {
+            Some(rss) => (rss, self.memory_sampler.peak(), true),
+            None => match self.memory_sampler.last_sample() {
+                Some(last) => {
+                    debug!("进程内存采样失败，沿用上次的值: {} 字节", last);
+                    (last, self.memory_sampler.peak(), false)
+                }
+                None => (
+                    self.memory_monitor.get_current_usage() as u64,
+                    self.memory_monitor.get_peak_usage() as u64,
+                    false,
+                ),
+            },
+        };
         
         PerformanceStats {
-            memory_usage_mb: self.memory_monitor.get_current_usage() as f64 / (1024.0 * 1024.0),
-            memory_peak_mb: self.memory_monitor.get_peak_usage() as f64 / (1024.0 * 1024.0),
+            memory_usage_mb: memory_usage_bytes as f64 / (1024.0 * 1024.0),
+            memory_peak_mb: memory_peak_bytes as f64 / (1024.0 * 1024.0),
             cache_hit_rate: cache_stats.hit_rate,
             cache_size: cache_stats.size,
             batch_queue_size: self.batch_processor.queue_size(),
             last_cleanup_seconds_ago: self.last_cleanup.lock()
                 .map(|instant| instant.elapsed().as_secs())
                 .unwrap_or(0),
+            metrics_available,
         }
     }
 
@@ -404,6 +836,9 @@ pub struct PerformanceStats {
     pub cache_size: usize,
     pub batch_queue_size: usize,
     pub last_cleanup_seconds_ago: u64,
+    // 新增：本次内存数据是否来自成功的系统采样（为 false 时界面应隐藏或标注内存数值）
+    #[serde(default)]
+    pub metrics_available: bool,
 }
 
 /// 内存优化的目录扫描器
@@ -668,6 +1103,161 @@ mod tests {
         assert_eq!(stats.cache_hit_rate, 0.0);
     }
 
+    #[test]
+    fn test_performance_stats_reflect_process_memory() {
+        let optimizer = PerformanceOptimizer::new(PerformanceConfig::default());
+        let baseline = optimizer.get_performance_stats();
+        if !baseline.metrics_available {
+            // 受限环境中无法读取进程内存，由采样失败的测试覆盖
+            return;
+        }
+
+        // 分配 128MB 并逐页写入，确保页面真正驻留内存
+        let mut buffer = vec![0u8; 128 * 1024 * 1024];
+        for (i, page) in buffer.chunks_mut(4096).enumerate() {
+            page[0] = (i % 251) as u8 + 1;
+        }
+        std::hint::black_box(&mut buffer);
+        let stats = optimizer.get_performance_stats();
+        let fresh_mb = ProcessMemorySampler::new().sample().unwrap() as f64 / 1024.0 / 1024.0;
+        std::hint::black_box(&buffer);
+
+        // 只要求明显增加：其他并行测试释放的内存不会抵消大部分写入的页面
+        assert!(
+            stats.memory_usage_mb > baseline.memory_usage_mb + 32.0,
+            "内存使用应明显增加: {} -> {}",
+            baseline.memory_usage_mb,
+            stats.memory_usage_mb
+        );
+        assert!(stats.memory_peak_mb >= stats.memory_usage_mb);
+        assert!(
+            (stats.memory_usage_mb - fresh_mb).abs() < 64.0,
+            "统计值应与独立采样一致: {} vs {}",
+            stats.memory_usage_mb,
+            fresh_mb
+        );
+    }
+
+    #[test]
+    fn test_failed_memory_sample_falls_back_to_last_value() {
+        let mut optimizer = PerformanceOptimizer::new(PerformanceConfig::default());
+        optimizer.memory_sampler.reader = |_| Some(64 * 1024 * 1024);
+        let stats = optimizer.get_performance_stats();
+        assert!(stats.metrics_available);
+        assert_eq!(stats.memory_usage_mb, 64.0);
+
+        // 采样失败或返回 0：沿用上次的值并标记为不可用
+        optimizer.memory_sampler.reader = |_| None;
+        let stats = optimizer.get_performance_stats();
+        assert!(!stats.metrics_available);
+        assert_eq!((stats.memory_usage_mb, stats.memory_peak_mb), (64.0, 64.0));
+        optimizer.memory_sampler.reader = |_| Some(0);
+        assert!(!optimizer.get_performance_stats().metrics_available);
+
+        // 从未采样成功时退回到内部记账的用量
+        let mut fresh = PerformanceOptimizer::new(PerformanceConfig::default());
+        fresh.memory_sampler.reader = |_| None;
+        let stats = fresh.get_performance_stats();
+        assert!(!stats.metrics_available);
+        assert_eq!(stats.memory_usage_mb, 0.0);
+    }
+
+    #[test]
+    fn test_update_scan_settings() {
+        let optimizer = PerformanceOptimizer::new(PerformanceConfig {
+            worker_threads: 2,
+            batch_size: 10,
+            ..PerformanceConfig::default()
+        });
+        assert_eq!(optimizer.scan_settings(), ScanSettings { worker_threads: 2, batch_size: 10 });
+
+        let updated = optimizer.update_scan_settings(Some(8), None).unwrap();
+        assert_eq!(updated, ScanSettings { worker_threads: 8, batch_size: 10 });
+        assert!(optimizer.update_scan_settings(Some(0), None).is_err());
+        assert!(optimizer.update_scan_settings(None, Some(0)).is_err());
+        assert_eq!(optimizer.scan_settings(), updated);
+    }
+
+    #[test]
+    fn test_estimate_migration_seconds() {
+        const MB: u64 = 1024 * 1024;
+        let optimizer = PerformanceOptimizer::new(PerformanceConfig {
+            migration_throughput_bytes_per_sec: 20 * MB,
+            ..PerformanceConfig::default()
+        });
+
+        // 无实测数据时使用配置的吞吐量
+        assert_eq!(optimizer.estimate_migration_seconds(100 * MB), 5);
+        assert_eq!(optimizer.estimate_migration_seconds(101 * MB), 6);
+
+        // 实测 40MB/s 与 60MB/s，滑动平均为 50MB/s
+        optimizer.record_migration_throughput(80 * MB, Duration::from_secs(2));
+        optimizer.record_migration_throughput(120 * MB, Duration::from_secs(2));
+        assert_eq!(optimizer.migration_throughput(), (50 * MB) as f64);
+        assert_eq!(optimizer.estimate_migration_seconds(500 * MB), 10);
+
+        // 无效样本被忽略
+        optimizer.record_migration_throughput(0, Duration::from_secs(1));
+        optimizer.record_migration_throughput(MB, Duration::ZERO);
+        assert_eq!(optimizer.migration_throughput(), (50 * MB) as f64);
+    }
+
+    #[test]
+    fn test_benchmark_drive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let benchmark = benchmark_drive(temp_dir.path(), 1024 * 1024).unwrap();
+
+        assert_eq!(benchmark.bytes_tested, 1024 * 1024);
+        assert!(benchmark.write_bytes_per_sec > 0.0 && benchmark.write_bytes_per_sec.is_finite());
+        assert!(benchmark.read_bytes_per_sec > 0.0 && benchmark.read_bytes_per_sec.is_finite());
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0, "测速后不应留下临时文件");
+        assert!(benchmark_drive(&temp_dir.path().join("missing"), 1024).is_err());
+
+        // 目标驱动器较慢时按其写入速度预估
+        const MB: u64 = 1024 * 1024;
+        let optimizer = PerformanceOptimizer::new(PerformanceConfig {
+            migration_throughput_bytes_per_sec: 100 * MB,
+            ..PerformanceConfig::default()
+        });
+        optimizer.record_drive_benchmark(DriveBenchmark {
+            path: temp_dir.path().to_path_buf(),
+            bytes_tested: MB,
+            write_bytes_per_sec: (10 * MB) as f64,
+            read_bytes_per_sec: (20 * MB) as f64,
+        });
+        assert_eq!(optimizer.estimate_migration_seconds_to(100 * MB, &temp_dir.path().join("target")), 10);
+        assert_eq!(optimizer.estimate_migration_seconds_to(100 * MB, Path::new("/elsewhere")), 1);
+    }
+
+    #[test]
+    fn test_throughput_stats_persist_across_sessions() {
+        const MB: u64 = 1024 * 1024;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let stats_path = temp_dir.path().join("logs").join(THROUGHPUT_STATS_FILE);
+
+        let optimizer = PerformanceOptimizer::new(PerformanceConfig::default())
+            .with_throughput_stats_file(stats_path.clone());
+        optimizer.record_migration_throughput(60 * MB, Duration::from_secs(2));
+        optimizer.record_migration_throughput(100 * MB, Duration::from_secs(2));
+        assert!(stats_path.exists());
+
+        // 新会话从统计文件恢复滑动平均
+        let restored = PerformanceOptimizer::new(PerformanceConfig::default())
+            .with_throughput_stats_file(stats_path.clone());
+        assert_eq!(restored.throughput_stats(), ThroughputStats {
+            average_bytes_per_sec: (40 * MB) as f64,
+            sample_count: 2,
+        });
+        assert_eq!(restored.migration_throughput(), (40 * MB) as f64);
+
+        // 损坏的统计文件被忽略，回退到配置值
+        fs::write(&stats_path, "not json").unwrap();
+        let fallback = PerformanceOptimizer::new(PerformanceConfig::default())
+            .with_throughput_stats_file(stats_path);
+        assert_eq!(fallback.throughput_stats().sample_count, 0);
+        assert_eq!(fallback.migration_throughput(), (50 * MB) as f64);
+    }
+
     #[tokio::test]
     async fn test_memory_monitor() {
         let monitor = MemoryMonitor::new(100, 200); // 100MB warning, 200MB critical
@@ -689,6 +1279,7 @@ mod tests {
             total_size: 1024 * 1024,
             last_modified: Instant::now(),
             is_large_folder: false,
+            scan: None,
         };
         
         cache.insert(PathBuf::from("/test/path"), test_info.clone());
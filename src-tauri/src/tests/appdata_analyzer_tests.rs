@@ -73,6 +73,7 @@ fn test_appdata_analyzer_config() {
         min_size_threshold: 500 * 1024 * 1024, // 500MB
         max_depth: 3,
         sort_order: SortOrder::Asc,
+        ..Default::default()
     };
     
     analyzer.set_config(custom_config.clone());
@@ -214,6 +215,7 @@ fn test_memory_usage() {
             min_size_threshold: i * 1024 * 1024,
             max_depth: (i % 10) as usize,
             sort_order: if i % 2 == 0 { SortOrder::Asc } else { SortOrder::Desc },
+            ..Default::default()
         };
         
         // 创建新的分析器实例来测试内存使用
@@ -245,6 +247,7 @@ fn test_concurrent_safety() {
                 min_size_threshold: (i + 1) * 100 * 1024 * 1024,
                 max_depth: 2,
                 sort_order: SortOrder::Desc,
+                ..Default::default()
             };
             
             // 注意：由于AppDataAnalyzer没有内部可变状态，这个测试主要是验证线程安全
@@ -486,6 +489,7 @@ fn test_dynamic_sorting_functionality() {
             parent_type: "Local".to_string(),
             is_large: false,
             size_percentage: 10.0,
+            ..Default::default()
         },
         AppDataFirstLevelItem {
             path: "path2".to_string(),
@@ -495,6 +499,7 @@ fn test_dynamic_sorting_functionality() {
             parent_type: "Roaming".to_string(),
             is_large: true,
             size_percentage: 50.0,
+            ..Default::default()
         },
         AppDataFirstLevelItem {
             path: "path3".to_string(),
@@ -504,6 +509,7 @@ fn test_dynamic_sorting_functionality() {
             parent_type: "LocalLow".to_string(),
             is_large: false,
             size_percentage: 25.0,
+            ..Default::default()
         },
     ];
     
@@ -542,6 +548,7 @@ fn test_migration_support() {
         target_drive: "D:".to_string(),
         create_symlink: true,
         delete_source: false,
+        ..Default::default()
     };
     
     // 验证迁移选项结构
@@ -556,6 +563,7 @@ fn test_migration_support() {
         target_drive: "E:".to_string(),
         create_symlink: false,
         delete_source: true,
+        ..Default::default()
     };
     
     assert_eq!(empty_migration.source_items.len(), 0, "空源项目列表");
@@ -593,6 +601,7 @@ fn test_error_scenarios() {
         min_size_threshold: 0, // 无效的最小阈值
         max_depth: 0,          // 无效的最大深度
         sort_order: SortOrder::Desc,
+        ..Default::default()
     };
     
     // 验证配置值
@@ -616,6 +625,7 @@ fn test_one_gb_filtering() {
             parent_type: "Local".to_string(),
             is_large: true,
             size_percentage: 66.7,
+            ..Default::default()
         },
         AppDataFirstLevelItem {
             path: "medium_app".to_string(),
@@ -625,6 +635,7 @@ fn test_one_gb_filtering() {
             parent_type: "Roaming".to_string(),
             is_large: false,
             size_percentage: 16.7,
+            ..Default::default()
         },
         AppDataFirstLevelItem {
             path: "small_app".to_string(),
@@ -634,6 +645,7 @@ fn test_one_gb_filtering() {
             parent_type: "LocalLow".to_string(),
             is_large: false,
             size_percentage: 3.3,
+            ..Default::default()
         },
     ];
     
@@ -706,6 +718,7 @@ fn test_performance_requirements_nfr2() {
             parent_type: if i % 3 == 0 { "Local".to_string() } else if i % 3 == 1 { "Roaming".to_string() } else { "LocalLow".to_string() },
             is_large,
             size_percentage: (i as f64 / 1000.0) * 100.0,
+            ..Default::default()
         });
     }
     
@@ -756,6 +769,7 @@ fn test_memory_usage_limits() {
             min_size_threshold: (i + 1) * 10 * 1024 * 1024, // 10MB递增
             max_depth: (i as usize % 5) + 1, // 1-5层深度
             sort_order: if i % 2 == 0 { SortOrder::Asc } else { SortOrder::Desc },
+            ..Default::default()
         };
         
         analyzer.set_config(config);
@@ -772,6 +786,7 @@ fn test_memory_usage_limits() {
             parent_type: "Local".to_string(),
             is_large: i >= 1000, // 大约9000个项目会大于1GB
             size_percentage: (i as f64 / 10000.0) * 100.0,
+            ..Default::default()
         });
     }
     
@@ -819,6 +834,7 @@ fn test_code_coverage_sc4() {
         parent_type: "Local".to_string(),
         is_large: false,
         size_percentage: 50.0,
+        ..Default::default()
     };
     
     assert_eq!(item.name, "test_name");
@@ -830,6 +846,7 @@ fn test_code_coverage_sc4() {
         target_drive: "D:".to_string(),
         create_symlink: true,
         delete_source: false,
+        ..Default::default()
     };
     
     assert_eq!(migration_options.source_items.len(), 2);
@@ -19,6 +19,7 @@ pub fn test_appdata_scan_performance() -> Result<(), crate::tests::TestError> {
         min_size_threshold: 1024 * 1024 * 1024, // 1GB
         max_depth: 2,
         sort_order: crate::appdata_analyzer::SortOrder::Desc,
+        ..Default::default()
     };
     
     // 注意：由于我们不能在实际环境中测试，这里测试配置和初始化性能
@@ -87,6 +88,7 @@ pub fn test_appdata_memory_usage() -> Result<(), crate::tests::TestError> {
             } else { 
                 crate::appdata_analyzer::SortOrder::Desc 
             },
+            ..Default::default()
         };
         analyzer.set_config(config);
         analyzers.push(analyzer);
@@ -137,6 +139,7 @@ pub fn test_appdata_concurrent_performance() -> Result<(), crate::tests::TestErr
                 min_size_threshold: (i + 1) * 100 * 1024 * 1024,
                 max_depth: 2,
                 sort_order: crate::appdata_analyzer::SortOrder::Desc,
+                ..Default::default()
             };
             
             let _formatted_size = AppDataAnalyzer::format_size(1024 * 1024 * 1024);
@@ -3,7 +3,7 @@
 //! 测试完整的用户场景和工作流程
 
 use crate::disk_analyzer::DiskAnalyzer;
-use crate::migration_service::{MigrationService, MigrationOptions};
+use crate::migration_service::{MigrationService, MigrationOptions, default_max_parallel_migrations, run_bounded};
 use crate::error_recovery::{ErrorRecoveryManager, ErrorRecoveryConfig, RecoveryContext};
 use crate::operation_logger::{OperationLogger, OperationType, OperationStatus};
 use crate::tests::test_utils::create_test_directory_structure;
@@ -63,6 +63,7 @@ pub async fn test_complete_migration_workflow() -> Result<(), crate::tests::Test
         target_path: target_dir.display().to_string(),
         create_symlink: true,  // 用户选择创建符号链接
         delete_source: false,  // 用户选择保留源文件
+        ..Default::default()
     };
 
     let migrate_result = service.migrate_folder(options).await
@@ -147,6 +148,7 @@ pub async fn test_large_folder_handling() -> Result<(), crate::tests::TestError>
         target_path: target_dir.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let migrate_result = service.migrate_folder(options).await
@@ -213,6 +215,7 @@ pub async fn test_error_recovery_workflow() -> Result<(), crate::tests::TestErro
         target_path: problematic_target.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     // 这个迁移应该会因为权限问题而失败
@@ -277,6 +280,7 @@ pub async fn test_error_recovery_workflow() -> Result<(), crate::tests::TestErro
         target_path: backup_target.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let backup_result = service.migrate_folder(backup_options).await
@@ -337,7 +341,7 @@ pub async fn test_ui_interactions() -> Result<(), crate::tests::TestError> {
     let service = MigrationService::new();
     let target_path = temp_dir.path().join("UITarget");
     
-    let (valid, message) = service.file_operator().validate_migration_path(&test_dir, &target_path)
+    let (valid, message, _) = service.file_operator().validate_migration_path(&test_dir, &target_path)
         .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("路径验证失败: {}", e)))?;
     
     let validation_duration = validation_start.elapsed();
@@ -355,6 +359,7 @@ pub async fn test_ui_interactions() -> Result<(), crate::tests::TestError> {
         target_path: target_path.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let migrate_result = service.migrate_folder(options).await
@@ -384,6 +389,7 @@ pub async fn test_ui_interactions() -> Result<(), crate::tests::TestError> {
         target_path: invalid_target.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let invalid_result = service.migrate_folder(invalid_options).await
@@ -450,32 +456,28 @@ pub async fn test_concurrent_operations() -> Result<(), crate::tests::TestError>
     info!("测试并发迁移...");
     let migrate_start = Instant::now();
     
-    let service = MigrationService::new();
-    let mut migrate_handles = vec![];
-    
-    for (i, source_dir) in dirs.iter().enumerate() {
-        let service_clone = service.clone();
-        let source_clone = source_dir.clone();
-        let target_clone = temp_dir.path().join(format!("ConcurrentTarget{}", i));
-        
-        let handle = tokio::spawn(async move {
+    // 迁移通过有限并发的执行器进行，避免同时读写过多目录
+    let service = std::sync::Arc::new(MigrationService::new());
+    let migrate_outputs = run_bounded(dirs.clone(), default_max_parallel_migrations(), |i, source_dir| {
+        let service = service.clone();
+        let target_dir = temp_dir.path().join(format!("ConcurrentTarget{}", i));
+        async move {
             let options = MigrationOptions {
-                source_path: source_clone.display().to_string(),
-                target_path: target_clone.display().to_string(),
+                source_path: source_dir.display().to_string(),
+                target_path: target_dir.display().to_string(),
                 create_symlink: false,
                 delete_source: false,
+                ..Default::default()
             };
-            
-            service_clone.migrate_folder(options).await
-        });
-        
-        migrate_handles.push(handle);
-    }
+
+            service.migrate_folder(options).await
+        }
+    }).await;
     
     // 等待所有迁移完成
     let mut migrate_results = vec![];
-    for handle in migrate_handles {
-        let result = handle.await
+    for output in migrate_outputs {
+        let result = output
             .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("并发迁移任务失败: {}", e)))?
             .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("迁移失败: {}", e)))?;
         
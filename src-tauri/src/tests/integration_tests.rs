@@ -4,7 +4,7 @@
 
 use crate::disk_analyzer::DiskAnalyzer;
 use crate::file_operations::FileOperator;
-use crate::migration_service::{MigrationService, MigrationOptions};
+use crate::migration_service::{MigrationService, MigrationOptions, MigrationLockRegistry};
 use crate::error_recovery::{ErrorRecoveryManager, ErrorRecoveryConfig, RecoveryContext};
 use crate::operation_logger::{OperationLogger, OperationType, OperationStatus};
 use crate::tests::test_utils::create_test_directory_structure;
@@ -39,6 +39,7 @@ pub async fn test_scan_and_migrate() -> Result<(), crate::tests::TestError> {
         target_path: target_dir.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let migrate_result = service.migrate_folder(options).await
@@ -345,7 +346,7 @@ async fn test_valid_path_validation(operator: &FileOperator, temp_dir: &TempDir)
     create_test_directory_structure(&source_dir)?;
 
     // 验证路径
-    let (valid, message) = operator.validate_migration_path(&source_dir, &target_dir)
+    let (valid, message, _) = operator.validate_migration_path(&source_dir, &target_dir)
         .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("路径验证失败: {}", e)))?;
 
     assert!(valid, "有效路径应该通过验证");
@@ -363,7 +364,7 @@ async fn test_invalid_path_validation(operator: &FileOperator, temp_dir: &TempDi
     // 不创建源目录，使其不存在
 
     // 验证路径
-    let (valid, message) = operator.validate_migration_path(&source_dir, &target_dir)
+    let (valid, message, _) = operator.validate_migration_path(&source_dir, &target_dir)
         .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("路径验证失败: {}", e)))?;
 
     assert!(!valid, "无效路径应该验证失败");
@@ -382,7 +383,7 @@ async fn test_dangerous_path_validation(operator: &FileOperator, temp_dir: &Temp
     create_test_directory_structure(&source_dir)?;
 
     // 验证路径
-    let (valid, message) = operator.validate_migration_path(&source_dir, &target_dir)
+    let (valid, message, _) = operator.validate_migration_path(&source_dir, &target_dir)
         .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("路径验证失败: {}", e)))?;
 
     assert!(!valid, "危险路径应该验证失败");
@@ -415,6 +416,119 @@ pub async fn test_backup_and_rollback() -> Result<(), crate::tests::TestError> {
     Ok(())
 }
 
+/// 迁移（删除源目录）后撤销的集成测试
+pub async fn test_migrate_and_undo() -> Result<(), crate::tests::TestError> {
+    let source_root = TempDir::new()
+        .map_err(|e| crate::tests::TestError::SetupFailed(format!("创建临时目录失败: {}", e)))?;
+    let target_root = TempDir::new()
+        .map_err(|e| crate::tests::TestError::SetupFailed(format!("创建临时目录失败: {}", e)))?;
+
+    let source_dir = source_root.path().join("undo_source");
+    create_complex_test_structure(&source_dir)?;
+    let original_files = count_files_in_directory(&source_dir)?;
+    let target_dir = target_root.path().join("undo_target");
+
+    let migration_service = MigrationService::new();
+
+    // 没有完成的迁移时无可撤销
+    assert!(migration_service.last_completed_migration().is_none());
+    assert!(migration_service.undo_migration(&source_dir, &target_dir).is_err());
+
+    for create_symlink in [false, true] {
+        let result = migration_service.migrate_folder(MigrationOptions {
+            source_path: source_dir.to_string_lossy().to_string(),
+            target_path: target_dir.to_string_lossy().to_string(),
+            create_symlink,
+            delete_source: true,
+            ..Default::default()
+        }).await.map_err(crate::tests::TestError::ExecutionFailed)?;
+        assert!(result.success, "迁移应该成功: {:?}", result.message);
+        assert!(!source_dir.is_dir() || create_symlink, "迁移后源目录应已删除");
+
+        let (source, target) = migration_service.last_completed_migration()
+            .ok_or_else(|| crate::tests::TestError::ExecutionFailed("迁移未登记为可撤销".to_string()))?;
+        let undone = migration_service.undo_migration(&source, &target)
+            .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("撤销失败: {}", e)))?;
+        assert_eq!(undone.source_path, source_dir.display().to_string());
+        assert!(!fs::symlink_metadata(&source_dir).unwrap().file_type().is_symlink(), "撤销后源位置不应再是链接");
+        assert!(source_dir.is_dir(), "撤销后源目录应已恢复");
+        assert_eq!(count_files_in_directory(&source_dir)?, original_files, "恢复的文件数量应与迁移前一致");
+        assert!(!target_dir.exists(), "撤销后不应残留迁移目标");
+
+        // 同一迁移只能撤销一次
+        assert!(migration_service.undo_migration(&source, &target).is_err());
+    }
+
+    info!("迁移撤销集成测试完成");
+    Ok(())
+}
+
+/// 迁移进行中取消并回滚的集成测试
+pub async fn test_cancel_and_rollback_mid_migration() -> Result<(), crate::tests::TestError> {
+    use std::sync::Arc;
+
+    let source_root = TempDir::new()
+        .map_err(|e| crate::tests::TestError::SetupFailed(format!("创建临时目录失败: {}", e)))?;
+    let target_root = TempDir::new()
+        .map_err(|e| crate::tests::TestError::SetupFailed(format!("创建临时目录失败: {}", e)))?;
+
+    let source_dir = source_root.path().join("rollback_source");
+    fs::create_dir_all(&source_dir)
+        .map_err(|e| crate::tests::TestError::SetupFailed(e.to_string()))?;
+    let total_files = 1000;
+    for i in 0..total_files {
+        fs::write(source_dir.join(format!("file_{:04}.bin", i)), vec![7u8; 16 * 1024])
+            .map_err(|e| crate::tests::TestError::SetupFailed(e.to_string()))?;
+    }
+    let target_dir = target_root.path().join("rollback_target");
+    let staging = MigrationService::staging_path(&target_dir);
+
+    let service = Arc::new(MigrationService::new());
+    let locks = Arc::new(MigrationLockRegistry::new());
+
+    // 在独立线程中执行迁移，迁移期间持有路径锁
+    let migration = {
+        let service = service.clone();
+        let locks = locks.clone();
+        let options = MigrationOptions {
+            source_path: source_dir.to_string_lossy().to_string(),
+            target_path: target_dir.to_string_lossy().to_string(),
+            create_symlink: true,
+            delete_source: true,
+            ..Default::default()
+        };
+        std::thread::spawn(move || {
+            let _lock = locks.try_acquire_migration_lock(std::path::Path::new(&options.source_path), std::path::Path::new(&options.target_path));
+            tokio::runtime::Runtime::new().unwrap().block_on(service.migrate_folder(options))
+        })
+    };
+
+    // 暂存目录出现文件（或迁移已结束）后发出取消并回滚
+    while !migration.is_finished()
+        && fs::read_dir(&staging).map(|entries| entries.count()).unwrap_or(0) == 0
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+    }
+
+    let result = service
+        .cancel_and_rollback(&source_dir, &target_dir, &locks, std::time::Duration::from_secs(30))
+        .await
+        .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("取消并回滚失败: {}", e)))?;
+    migration.join().map_err(|_| crate::tests::TestError::ExecutionFailed("迁移线程异常退出".to_string()))?
+        .map_err(crate::tests::TestError::ExecutionFailed)?;
+
+    assert!(result.success && result.stopped, "取消并回滚应成功: {:?}", result);
+    assert!(!fs::symlink_metadata(&source_dir).unwrap().file_type().is_symlink(), "源目录不应被链接替换");
+    assert_eq!(count_files_in_directory(&source_dir)?, total_files, "源目录文件应完整");
+    assert_eq!(fs::read(source_dir.join("file_0000.bin")).unwrap(), vec![7u8; 16 * 1024]);
+    assert!(!staging.exists(), "不应残留未完成的迁移数据");
+    assert!(!target_dir.exists(), "不应残留迁移目标");
+    assert_eq!(locks.active_count(), 0);
+
+    info!("取消并回滚集成测试完成");
+    Ok(())
+}
+
 /// 测试备份创建和验证
 async fn test_backup_creation_and_verification(
     recovery_manager: &mut ErrorRecoveryManager,
@@ -497,10 +611,10 @@ async fn test_backup_cleanup_integration(
     info!("清理前备份统计: {:?}", stats_before);
 
     // 执行清理（设置很短的保留时间）
-    let cleaned_count = recovery_manager.cleanup_expired_backups()
+    let report = recovery_manager.cleanup_expired_backups()
         .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("备份清理失败: {}", e)))?;
 
-    info!("清理了 {} 个过期备份", cleaned_count);
+    info!("清理了 {} 个过期备份", report.cleaned.len());
 
     // 获取清理后的统计
     let stats_after = recovery_manager.get_recovery_statistics();
@@ -598,6 +712,12 @@ mod tests {
         
         info!("运行备份和回滚集成测试");
         test_backup_and_rollback().await?;
+
+        info!("运行迁移撤销集成测试");
+        test_migrate_and_undo().await?;
+
+        info!("运行取消并回滚集成测试");
+        test_cancel_and_rollback_mid_migration().await?;
         
         // AppData专项集成测试
         info!("运行AppData扫描集成测试");
@@ -632,12 +752,30 @@ mod tests {
         let result = test_backup_and_rollback().await;
         assert!(result.is_ok(), "备份和回滚集成测试应该通过");
         
+        let result = test_migrate_and_undo().await;
+        assert!(result.is_ok(), "迁移撤销集成测试应该通过");
+
+        let result = test_cancel_and_rollback_mid_migration().await;
+        assert!(result.is_ok(), "取消并回滚集成测试应该通过");
+        
         let result = test_appdata_scan_integration().await;
         assert!(result.is_ok(), "AppData扫描集成测试应该通过");
         
         let result = test_appdata_complete_workflow().await;
         assert!(result.is_ok(), "AppData完整工作流集成测试应该通过");
     }
+
+    #[tokio::test]
+    async fn test_migrate_and_undo_restores_source() {
+        let result = test_migrate_and_undo().await;
+        assert!(result.is_ok(), "迁移撤销集成测试应该通过: {:?}", result.err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_rollback_keeps_source_intact() {
+        let result = test_cancel_and_rollback_mid_migration().await;
+        assert!(result.is_ok(), "取消并回滚集成测试应该通过: {:?}", result.err());
+    }
 }
 
 /// AppData扫描集成测试
@@ -736,6 +874,7 @@ async fn test_large_folder_filtering_integration(appdata_base: &std::path::Path)
         min_size_threshold: 1024 * 1024 * 1024, // 1GB
         max_depth: 2,
         sort_order: crate::appdata_analyzer::SortOrder::Desc,
+        ..Default::default()
     };
     analyzer.set_config(config);
     
@@ -1003,6 +1142,7 @@ async fn test_appdata_scanning_workflow(appdata_base: &std::path::Path) -> Resul
         min_size_threshold: 1024 * 1024 * 1024, // 1GB
         max_depth: 2,
         sort_order: SortOrder::Desc,
+        ..Default::default()
     };
     analyzer.set_config(config);
 
@@ -1128,6 +1268,7 @@ async fn test_appdata_migration_workflow(
         target_drive: target_drive.to_string_lossy().to_string(),
         create_symlink: true,
         delete_source: false,
+        ..Default::default()
     };
 
     // 验证迁移选项
@@ -1136,6 +1277,7 @@ async fn test_appdata_migration_workflow(
         target_path: target_drive.to_string_lossy().to_string(),
         create_symlink: migration_options.create_symlink,
         delete_source: migration_options.delete_source,
+        ..Default::default()
     });
 
     assert!(validation_result.is_ok(), "迁移选项验证应该通过");
@@ -1168,6 +1310,7 @@ async fn test_appdata_migration_workflow(
             target_path: target_path.to_string_lossy().to_string(),
             create_symlink: migration_options.create_symlink,
             delete_source: migration_options.delete_source,
+            ..Default::default()
         };
 
         // 执行迁移
@@ -1205,6 +1348,7 @@ async fn test_appdata_migration_workflow(
         source_path: format!("{}个项目", migration_options.source_items.len()),
         target_path: target_drive.to_string_lossy().to_string(),
         symlink_path: if migration_options.create_symlink { Some(format!("创建了{}个符号链接", success_count)) } else { None },
+        ..Default::default()
     };
 
     Ok(migration_result)
@@ -1277,6 +1421,7 @@ async fn test_performance_benchmarks(appdata_base: &std::path::Path) -> Result<(
         min_size_threshold: 1024 * 1024 * 1024, // 1GB
         max_depth: 2,
         sort_order: crate::appdata_analyzer::SortOrder::Desc,
+        ..Default::default()
     };
     analyzer.set_config(config);
     
@@ -135,6 +135,8 @@ impl TestRunner {
         self.run_test("操作日志集成测试", || integration_tests::test_operation_logging()).await;
         self.run_test("路径验证集成测试", || integration_tests::test_path_validation()).await;
         self.run_test("备份和回滚集成测试", || integration_tests::test_backup_and_rollback()).await;
+        self.run_test("迁移撤销集成测试", || integration_tests::test_migrate_and_undo()).await;
+        self.run_test("取消并回滚集成测试", || integration_tests::test_cancel_and_rollback_mid_migration()).await;
         self.run_test("AppData扫描集成测试", || integration_tests::test_appdata_scan_integration()).await;
     }
 
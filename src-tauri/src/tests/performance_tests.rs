@@ -123,6 +123,7 @@ pub async fn test_migration_performance() -> Result<(), crate::tests::TestError>
             target_path: target_dir.display().to_string(),
             create_symlink: false,
             delete_source: false,
+            ..Default::default()
         };
 
         let result = service.migrate_folder(options).await
@@ -207,6 +208,7 @@ pub async fn test_memory_usage() -> Result<(), crate::tests::TestError> {
         target_path: target_dir.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let _migrate_result = service.migrate_folder(options).await
@@ -239,6 +241,7 @@ pub async fn test_memory_usage() -> Result<(), crate::tests::TestError> {
             target_path: temp_target.display().to_string(),
             create_symlink: false,
             delete_source: true,
+            ..Default::default()
         };
         
         service.migrate_folder(options).await
@@ -306,6 +309,7 @@ pub async fn test_large_file_performance() -> Result<(), crate::tests::TestError
             target_path: target_dir.display().to_string(),
             create_symlink: false,
             delete_source: false,
+            ..Default::default()
         };
 
         let result = service.migrate_folder(options).await
@@ -425,6 +429,7 @@ pub async fn test_concurrent_performance() -> Result<(), crate::tests::TestError
                 target_path: target_clone.display().to_string(),
                 create_symlink: false,
                 delete_source: false,
+                ..Default::default()
             };
             
             service_clone.migrate_folder(options).await
@@ -477,6 +482,7 @@ pub async fn test_concurrent_performance() -> Result<(), crate::tests::TestError
             target_path: target_clone.display().to_string(),
             create_symlink: false,
             delete_source: false,
+            ..Default::default()
         };
         
         service.migrate_folder(options).await
@@ -64,7 +64,7 @@ pub async fn test_disk_analyzer() -> Result<(), crate::tests::TestError> {
     });
     
     // 立即取消原始分析器
-    analyzer.cancel_scan();
+    analyzer.cancel_scan(crate::types::CancellationReason::UserRequested);
     
     // 等待任务完成
     let _result = scan_task.await
@@ -293,6 +293,7 @@ async fn test_basic_migration(service: &MigrationService, temp_dir: &TempDir) ->
         target_path: target_dir.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let result = service.migrate_folder(options).await
@@ -321,6 +322,7 @@ async fn test_migration_with_symlink(service: &MigrationService, temp_dir: &Temp
         target_path: target_dir.display().to_string(),
         create_symlink: true,
         delete_source: false,
+        ..Default::default()
     };
 
     let result = service.migrate_folder(options).await
@@ -351,6 +353,7 @@ async fn test_migration_validation(service: &MigrationService, temp_dir: &TempDi
         target_path: target_dir.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let result = service.migrate_folder(options).await
@@ -376,6 +379,7 @@ async fn test_migration_error_handling(service: &MigrationService, temp_dir: &Te
         target_path: target_dir.display().to_string(),
         create_symlink: false,
         delete_source: false,
+        ..Default::default()
     };
 
     let result = service.migrate_folder(options).await
@@ -497,10 +501,10 @@ async fn test_backup_cleanup(recovery_manager: &mut ErrorRecoveryManager, temp_d
     }
 
     // 清理过期备份（设置很短的保留时间）
-    let cleaned_count = recovery_manager.cleanup_expired_backups()
+    let report = recovery_manager.cleanup_expired_backups()
         .map_err(|e| crate::tests::TestError::ExecutionFailed(format!("备份清理失败: {}", e)))?;
 
-    info!("备份清理测试完成，清理了 {} 个备份", cleaned_count);
+    info!("备份清理测试完成，清理了 {} 个备份", report.cleaned.len());
 
     Ok(())
 }
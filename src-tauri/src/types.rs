@@ -1,5 +1,8 @@
 use serde::{Serialize, Deserialize};
 use std::time::SystemTime;
+use crate::messages::MessageKey;
+use crate::file_operations::FileOperationError;
+use crate::error_recovery::{BackupError, RecoveryError};
 
 /// 目录信息结构体（与前端共享）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,8 +25,59 @@ pub struct ScanProgress {
     pub total_directories: u64,     // 新增：总目录数
     pub current_directory: String,  // 新增：当前处理的目录
     pub estimated_time_remaining: u64, // 新增：预计剩余时间（秒）
-    pub scan_speed: f64,            // 新增：扫描速度（文件/秒）
+    pub scan_speed: f64,            // 新增：扫描速度（文件/秒，最近几次更新的滑动平均）
     pub large_folders_found: u64,   // 新增：发现的大文件夹数量
+    pub start_time: Option<SystemTime>, // 新增：扫描开始时间
+    #[serde(default)]
+    pub cancellation_reason: Option<CancellationReason>, // 新增：扫描被取消的原因（未取消时为 None）
+}
+
+/// 扫描或迁移被取消的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancellationReason {
+    /// 用户主动取消
+    #[default]
+    UserRequested,
+    /// 操作超时
+    Timeout,
+    /// 磁盘空间不足
+    SpaceExhausted,
+    /// 应用正在退出
+    Shutdown,
+}
+
+impl CancellationReason {
+    /// 原因的中文描述，拼接在取消错误信息之后
+    pub fn description(&self) -> &'static str {
+        match self {
+            CancellationReason::UserRequested => "用户取消",
+            CancellationReason::Timeout => "操作超时",
+            CancellationReason::SpaceExhausted => "磁盘空间不足",
+            CancellationReason::Shutdown => "应用正在退出",
+        }
+    }
+
+    /// 编码为原子变量中保存的值，0 保留给“未取消”
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            CancellationReason::UserRequested => 1,
+            CancellationReason::Timeout => 2,
+            CancellationReason::SpaceExhausted => 3,
+            CancellationReason::Shutdown => 4,
+        }
+    }
+
+    /// 从原子变量中保存的值解码，0 或未知值返回 None
+    pub(crate) fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(CancellationReason::UserRequested),
+            2 => Some(CancellationReason::Timeout),
+            3 => Some(CancellationReason::SpaceExhausted),
+            4 => Some(CancellationReason::Shutdown),
+            _ => None,
+        }
+    }
 }
 
 /// 磁盘信息
@@ -35,6 +89,34 @@ pub struct DiskInfo {
     pub used_space: u64,
 }
 
+/// 迁移目标盘建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriveSuggestion {
+    pub name: String,
+    pub mount_point: String,
+    pub total_space: u64,
+    pub free_space: u64,
+    pub recommended: bool, // 可用空间是否足以容纳所需大小（含20%缓冲）
+}
+
+/// 按可用空间降序排列候选盘，并根据所需大小标记推荐项
+pub fn rank_drive_suggestions(mut drives: Vec<DriveSuggestion>, required_bytes: u64) -> Vec<DriveSuggestion> {
+    let required_with_buffer = required_bytes.saturating_add(required_bytes / 5);
+    for drive in &mut drives {
+        drive.recommended = drive.free_space > 0 && drive.free_space >= required_with_buffer;
+    }
+    drives.sort_by(|a, b| b.free_space.cmp(&a.free_space));
+    drives
+}
+
+/// 迁移耗时预估
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationEstimate {
+    pub total_bytes: u64,
+    pub file_count: u64,
+    pub estimated_seconds: u64,
+}
+
 /// 迁移选项
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MigrationOptions {
@@ -124,6 +206,30 @@ impl ErrorInfo {
     }
 }
 
+/// 命令以 `{ code, message }` 的形式返回错误，前端可按 code 分支处理
+impl From<FileOperationError> for ErrorInfo {
+    fn from(error: FileOperationError) -> Self {
+        let info = Self::new(error.code(), &error.to_string());
+        match &error {
+            // IO 错误附带具体的错误类型（如 NotFound、StorageFull）
+            FileOperationError::IoError(e) => info.with_details(&format!("{:?}", e.kind())),
+            _ => info,
+        }
+    }
+}
+
+impl From<RecoveryError> for ErrorInfo {
+    fn from(error: RecoveryError) -> Self {
+        Self::new(error.code(), &error.to_string())
+    }
+}
+
+impl From<BackupError> for ErrorInfo {
+    fn from(error: BackupError) -> Self {
+        Self::new(error.code(), &error.to_string())
+    }
+}
+
 /// 进度信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgressInfo {
@@ -168,6 +274,11 @@ pub struct PathValidationResult {
     pub valid: bool,
     pub message: String,
     pub suggestions: Vec<String>,
+    // 新增：消息的稳定键与验证未通过的原因，前端可据此识别结果或切换语言
+    #[serde(default)]
+    pub message_key: Option<MessageKey>,
+    #[serde(default)]
+    pub message_detail: Option<String>,
 }
 
 impl PathValidationResult {
@@ -176,6 +287,8 @@ impl PathValidationResult {
             valid: true,
             message: message.to_string(),
             suggestions: Vec::new(),
+            message_key: None,
+            message_detail: None,
         }
     }
 
@@ -184,6 +297,8 @@ impl PathValidationResult {
             valid: false,
             message: message.to_string(),
             suggestions: Vec::new(),
+            message_key: None,
+            message_detail: None,
         }
     }
 
@@ -290,6 +405,23 @@ mod tests {
         assert!(error_response.error.is_some());
     }
 
+    #[test]
+    fn test_error_info_from_error_enums() {
+        let error = ErrorInfo::from(FileOperationError::PermissionDenied("C:\\Windows".to_string()));
+        assert_eq!(error.code, "PERMISSION_DENIED");
+        assert_eq!(error.message, "权限被拒绝: C:\\Windows");
+
+        let json = serde_json::to_value(ErrorInfo::from(BackupError::BackupTooLarge("10 GB".to_string()))).unwrap();
+        assert_eq!(json["code"], "BACKUP_TOO_LARGE");
+        assert_eq!(json["message"], "备份过大: 10 GB");
+
+        assert_eq!(ErrorInfo::from(RecoveryError::UserCancelled(String::new())).code, "USER_CANCELLED");
+
+        let io_error = ErrorInfo::from(FileOperationError::IoError(std::io::Error::from(std::io::ErrorKind::NotFound)));
+        assert_eq!(io_error.code, "IO_ERROR");
+        assert_eq!(io_error.details.as_deref(), Some("NotFound"));
+    }
+
     #[test]
     fn test_progress_info() {
         let mut progress = ProgressInfo::new("扫描", "/test/path");
@@ -302,6 +434,32 @@ mod tests {
         assert_eq!(progress.total, 100);
     }
 
+    #[test]
+    fn test_rank_drive_suggestions() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let drive = |name: &str, free_space: u64| DriveSuggestion {
+            name: name.to_string(),
+            mount_point: format!("{}\\", name),
+            total_space: 500 * GB,
+            free_space,
+            recommended: false,
+        };
+
+        let ranked = rank_drive_suggestions(
+            vec![drive("C:", 20 * GB), drive("E:", 300 * GB), drive("D:", 11 * GB)],
+            10 * GB,
+        );
+
+        let names: Vec<&str> = ranked.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["E:", "C:", "D:"]);
+        assert!(ranked.windows(2).all(|w| w[0].free_space >= w[1].free_space));
+
+        // 需要 10GB（含缓冲 12GB）：E 与 C 推荐，D 只剩 11GB 不推荐
+        assert!(ranked[0].recommended);
+        assert!(ranked[1].recommended);
+        assert!(!ranked[2].recommended);
+    }
+
     #[test]
     fn test_path_validation_result() {
         let valid = PathValidationResult::valid("路径有效");
@@ -0,0 +1,20 @@
+use std::path::{Path, PathBuf};
+
+/// 路径比较用的规范形式：Windows 路径不区分大小写
+fn comparable_path(path: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// 判断 path 是否等于 base 或位于 base 之下（按路径组件比较）
+pub(crate) fn is_within(path: &Path, base: &Path) -> bool {
+    comparable_path(path).starts_with(comparable_path(base))
+}
+
+/// 判断两个路径是否相同（Windows 下不区分大小写）
+pub(crate) fn same_path(a: &Path, b: &Path) -> bool {
+    comparable_path(a) == comparable_path(b)
+}
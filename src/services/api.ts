@@ -1,12 +1,12 @@
 import { invoke } from "@tauri-apps/api/core";
-import { DirectoryInfo, ScanProgress, MigrationOptions, MigrationResult } from "../types/directory";
-import { AppDataInfo, AppDataScanOptions, AppDataScanResult, AppDataConfig, AppDataMigrationOptions, AppDataMigrationResult, AppDataDriveInfo } from "../types/appdata";
+import { DirectoryInfo, ScanProgress, MigrationOptions, MigrationResult, CancellationReason } from "../types/directory";
+import { AppDataInfo, AppDataFirstLevelItem, AppDataItemsPage, AppDataScanOptions, AppDataScanResult, AppDataConfig, AppDataMigrationOptions, AppDataMigrationResult, AppDataDriveInfo } from "../types/appdata";
 
 // 操作日志类型定义
 export interface OperationLog {
   id: string;
   timestamp: string;
-  operation_type: 'Scan' | 'Migrate' | 'Delete' | 'CreateSymlink' | 'Validate' | 'Cancel' | 'Error';
+  operation_type: 'Scan' | 'Migrate' | 'Delete' | 'CreateSymlink' | 'Validate' | 'Cancel' | 'Error' | 'Undo';
   status: 'Started' | 'InProgress' | 'Completed' | 'Failed' | 'Cancelled';
   source_path: string;
   target_path?: string;
@@ -30,6 +30,37 @@ export interface OperationStatistics {
   average_duration_ms: number;
 }
 
+// 过期备份清理报告：failed 中的备份保留登记，下次清理时重试
+export interface BackupCleanupReport {
+  cleaned: string[];
+  already_gone: string[];
+  failed: { backup_id: string; backup_path: string; error: string }[];
+}
+
+// 单一操作类型的统计（字段与 OperationStatistics 相同）
+export interface OperationTypeStatistics extends OperationStatistics {
+  operation_type: string;
+}
+
+// 命令返回的结构化错误（code 为稳定的错误码，如 PERMISSION_DENIED）
+export interface ErrorInfo {
+  code: string;
+  message: string;
+  details?: string;
+}
+
+/** 判断命令错误是否为结构化错误 */
+export function isErrorInfo(error: unknown): error is ErrorInfo {
+  return typeof error === 'object' && error !== null
+    && typeof (error as ErrorInfo).code === 'string'
+    && typeof (error as ErrorInfo).message === 'string';
+}
+
+/** 提取命令错误的文案，兼容字符串错误和结构化错误 */
+export function errorMessage(error: unknown): string {
+  return isErrorInfo(error) ? error.message : String(error);
+}
+
 // 错误恢复类型定义
 export interface RecoveryStatistics {
   total_operations: number;
@@ -66,14 +97,16 @@ export const diskAPI = {
    * 扫描指定路径的目录结构
    * @param path 要扫描的路径
    * @param cDriveMode 是否启用C盘专项扫描模式
+   * @param computeSizeOnDisk 是否额外统计实际占用的磁盘空间
    * @returns 目录信息
    */
-  async scanDirectory(path: string, cDriveMode: boolean = false): Promise<DirectoryInfo> {
+  async scanDirectory(path: string, cDriveMode: boolean = false, computeSizeOnDisk: boolean = false): Promise<DirectoryInfo> {
     console.log('API: 开始扫描目录', path, 'C盘模式:', cDriveMode);
     try {
       const result = await invoke<DirectoryInfo>("scan_directory", {
         path,
-        cDriveMode
+        cDriveMode,
+        computeSizeOnDisk
       });
       console.log('API: 扫描目录成功', result);
       return result;
@@ -83,6 +116,45 @@ export const diskAPI = {
     }
   },
 
+  /**
+   * 扫描目录并返回紧凑结果，适合体积很大的目录树
+   * @param path 要扫描的路径
+   * @param cDriveMode 是否启用C盘专项扫描模式
+   * @param pruneSmallFolders 是否把小目录合并为父目录中的汇总
+   * @returns 紧凑形式的目录信息
+   */
+  async scanDirectoryCompact(path: string, cDriveMode: boolean = false, pruneSmallFolders: boolean = false): Promise<CompactDirectoryInfo> {
+    try {
+      return await invoke<CompactDirectoryInfo>("scan_directory", {
+        path,
+        cDriveMode,
+        pruneSmallFolders,
+        compact: true
+      });
+    } catch (error) {
+      console.error("API: 扫描目录失败:", error);
+      throw new Error(`扫描目录失败: ${error}`);
+    }
+  },
+
+  /**
+   * 扫描目录并返回汇总统计
+   * @param path 要扫描的路径
+   * @param cDriveMode 是否启用C盘专项扫描模式
+   * @returns 目录信息及汇总统计
+   */
+  async scanDirectoryWithSummary(path: string, cDriveMode: boolean = false): Promise<DirectoryScanWithSummary> {
+    try {
+      return await invoke<DirectoryScanWithSummary>("scan_directory_with_summary", {
+        path,
+        cDriveMode
+      });
+    } catch (error) {
+      console.error("API: 扫描目录失败:", error);
+      throw new Error(`扫描目录失败: ${error}`);
+    }
+  },
+
   /**
    * 获取扫描进度
    * @returns 扫描进度信息
@@ -100,9 +172,9 @@ export const diskAPI = {
   /**
    * 停止扫描
    */
-  async stopScan(): Promise<void> {
+  async stopScan(reason?: CancellationReason): Promise<void> {
     try {
-      await invoke("stop_scan");
+      await invoke("stop_scan", { reason: reason ?? null });
     } catch (error) {
       console.error("停止扫描失败:", error);
       throw new Error(`停止扫描失败: ${error}`);
@@ -170,9 +242,32 @@ export const performanceAPI = {
       console.error("获取性能基准失败:", error);
       throw new Error(`获取性能基准失败: ${error}`);
     }
+  },
+
+  /**
+   * 测试目标驱动器的读写速度（结果会用于迁移耗时预估）
+   * @param path 目标驱动器上的目录
+   * @returns 读写吞吐量（字节/秒）
+   */
+  async benchmarkDrive(path: string): Promise<DriveBenchmark> {
+    try {
+      const result = await invoke<DriveBenchmark>("benchmark_drive", { path });
+      return result;
+    } catch (error) {
+      console.error("驱动器测速失败:", error);
+      throw new Error(`驱动器测速失败: ${error}`);
+    }
   }
 };
 
+// 驱动器测速结果
+export interface DriveBenchmark {
+  path: string;
+  bytes_tested: number;
+  write_bytes_per_sec: number;
+  read_bytes_per_sec: number;
+}
+
 // 性能基准类型定义
 export interface PerformanceBenchmark {
   memory_usage_score: number;
@@ -182,6 +277,82 @@ export interface PerformanceBenchmark {
   recommendations: string[];
 }
 
+// 迁移后源位置链接的解析结果
+export interface LinkResolution {
+  link_path: string;
+  link_type: 'Symlink' | 'Junction';
+  link_target: string;
+  resolved_path: string | null;
+}
+
+// 已迁移目标改换磁盘的结果
+export interface RelocationResult {
+  link_path: string;
+  link_type: 'Symlink' | 'Junction';
+  previous_target: string;
+  new_target: string;
+  copied: boolean;
+  warnings: string[];
+}
+
+// 被监测的迁移链接
+export interface SymlinkWatch {
+  link_path: string;
+  target_path: string;
+  registered_at: string;
+}
+
+// 迁移链接健康检查结果
+export interface SymlinkCheckReport {
+  checked: number;
+  broken: Array<{ link_path: string; target_path: string; reason: string }>;
+}
+
+// 迁移计划预计释放的C盘空间
+export interface SpaceFreedEstimate {
+  drive: string;
+  counted_items: number;
+  moved_bytes: number;
+  symlink_overhead_bytes: number;
+  freed_bytes: number;
+  current_free_bytes: number | null;
+  projected_free_bytes: number | null;
+  skipped: string[];
+}
+
+// 扫描汇总统计
+export interface ScanSummary {
+  total_directories: number;
+  total_files: number;
+  max_depth_reached: number;
+  directories_by_depth: number[];
+  largest_file: { path: string; size: number } | null;
+  large_folders_found: number;
+  /** 按大小区间统计的文件数：0-1KB、1-10KB、10-100KB、100KB-1MB、1-10MB、10-100MB、100MB-1GB、>1GB */
+  size_histogram: number[];
+}
+
+export interface DirectoryScanWithSummary {
+  directory: DirectoryInfo;
+  summary: ScanSummary;
+}
+
+/** 紧凑形式的目录信息，默认值字段被省略 */
+export interface CompactDirectoryInfo {
+  path: string;
+  name: string;
+  size: number;
+  file_count: number;
+  subdirectories?: CompactDirectoryInfo[];
+  last_modified?: unknown;
+  is_large_folder?: boolean;
+  size_percentage: number;
+  budget_exceeded?: boolean;
+  size_on_disk?: number;
+  omitted_subdirectories?: number;
+  omitted_size?: number;
+}
+
 /**
  * 测试API
  */
@@ -251,11 +422,11 @@ export const errorRecoveryAPI = {
 
   /**
    * 清理过期备份
-   * @returns 清理的备份数量
+   * @returns 清理报告（已清理、已不存在、清理失败的备份）
    */
-  async cleanupExpiredBackups(): Promise<number> {
+  async cleanupExpiredBackups(): Promise<BackupCleanupReport> {
     try {
-      const result = await invoke<number>("cleanup_expired_backups");
+      const result = await invoke<BackupCleanupReport>("cleanup_expired_backups");
       return result;
     } catch (error) {
       console.error("清理过期备份失败:", error);
@@ -263,6 +434,22 @@ export const errorRecoveryAPI = {
     }
   },
 
+  /**
+   * 从备份中只恢复指定的相对路径
+   * @param backupId 备份ID
+   * @param relativePaths 相对备份根目录的文件或子目录
+   * @returns 已恢复的路径
+   */
+  async restoreBackupPaths(backupId: string, relativePaths: string[]): Promise<string[]> {
+    try {
+      const result = await invoke<string[]>("restore_backup_paths", { backupId, relativePaths });
+      return result;
+    } catch (error) {
+      console.error("部分恢复失败:", error);
+      throw new Error(`部分恢复失败: ${error}`);
+    }
+  },
+
   /**
    * 测试错误恢复功能
    * @returns 是否成功
@@ -290,10 +477,13 @@ export const migrationAPI = {
   async migrateDirectory(options: MigrationOptions): Promise<MigrationResult> {
     try {
       const result = await invoke<MigrationResult>("migrate_directory", {
-        sourcePath: options.sourcePath,
-        targetPath: options.targetPath,
-        createSymlink: options.createSymlink,
-        deleteSource: options.deleteSource
+        options: {
+          source_path: options.sourcePath,
+          target_path: options.targetPath,
+          create_symlink: options.createSymlink,
+          delete_source: options.deleteSource,
+          operation_timeout_ms: options.operationTimeoutMs
+        }
       });
       return result;
     } catch (error) {
@@ -302,11 +492,115 @@ export const migrationAPI = {
     }
   },
 
+  /**
+   * 解析迁移后源位置的链接
+   * @param linkPath 链接路径（迁移前的源路径）
+   * @returns 链接类型及其真实指向
+   */
+  async resolveMigrationTarget(linkPath: string): Promise<LinkResolution> {
+    try {
+      return await invoke<LinkResolution>("resolve_migration_target", { linkPath });
+    } catch (error) {
+      console.error("解析迁移链接失败:", error);
+      throw new Error(`解析迁移链接失败: ${error}`);
+    }
+  },
+
+  /**
+   * 把已迁移的目标移到另一个磁盘，并更新源位置的链接
+   * @param linkPath 链接路径（迁移前的源路径）
+   * @param newTargetDrive 新的目标盘（如"E:"）
+   * @returns 新旧目标路径，失败时原目标和原链接保持不变
+   */
+  async relocateMigratedTarget(linkPath: string, newTargetDrive: string): Promise<RelocationResult> {
+    try {
+      return await invoke<RelocationResult>("relocate_migrated_target", { linkPath, newTargetDrive });
+    } catch (error) {
+      console.error("改换迁移目标失败:", error);
+      throw new Error(`改换迁移目标失败: ${error}`);
+    }
+  },
+
+  /**
+   * 登记需要监测的迁移链接（迁移创建的链接会自动登记）
+   * @param linkPath 源位置的链接
+   * @param targetPath 链接应指向的迁移目标
+   */
+  async registerSymlinkWatch(linkPath: string, targetPath: string): Promise<SymlinkWatch> {
+    try {
+      return await invoke<SymlinkWatch>("register_symlink_watch", { linkPath, targetPath });
+    } catch (error) {
+      console.error("登记链接监测失败:", error);
+      throw new Error(`登记链接监测失败: ${error}`);
+    }
+  },
+
+  /**
+   * 取消监测迁移链接
+   * @param linkPath 源位置的链接
+   * @returns 是否存在该登记
+   */
+  async unregisterSymlinkWatch(linkPath: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("unregister_symlink_watch", { linkPath });
+    } catch (error) {
+      console.error("取消链接监测失败:", error);
+      throw new Error(`取消链接监测失败: ${error}`);
+    }
+  },
+
+  /**
+   * 获取所有被监测的迁移链接
+   */
+  async listSymlinkWatches(): Promise<SymlinkWatch[]> {
+    try {
+      return await invoke<SymlinkWatch[]>("list_symlink_watches");
+    } catch (error) {
+      console.error("获取链接监测列表失败:", error);
+      throw new Error(`获取链接监测列表失败: ${error}`);
+    }
+  },
+
+  /**
+   * 检查所有被监测的链接是否仍指向迁移目标
+   * @returns 检查数量及失效的链接
+   */
+  async checkAllSymlinks(): Promise<SymlinkCheckReport> {
+    try {
+      return await invoke<SymlinkCheckReport>("check_all_symlinks");
+    } catch (error) {
+      console.error("检查迁移链接失败:", error);
+      throw new Error(`检查迁移链接失败: ${error}`);
+    }
+  },
+
+  /**
+   * 估算迁移计划能为C盘释放多少空间
+   * @param items 计划迁移的项目
+   * @returns 预计释放的空间及迁移后C盘的剩余空间
+   */
+  async estimateSpaceFreed(items: MigrationOptions[]): Promise<SpaceFreedEstimate> {
+    try {
+      return await invoke<SpaceFreedEstimate>("estimate_space_freed", {
+        items: items.map(item => ({
+          source_path: item.sourcePath,
+          target_path: item.targetPath,
+          create_symlink: item.createSymlink,
+          delete_source: item.deleteSource
+        }))
+      });
+    } catch (error) {
+      console.error("估算释放空间失败:", error);
+      throw new Error(`估算释放空间失败: ${error}`);
+    }
+  },
+
   /**
    * 验证迁移路径
    * @param sourcePath 源路径
    * @param targetPath 目标路径
    * @returns 验证结果
+   * @throws ErrorInfo 路径无法验证时抛出命令返回的结构化错误，可按 code 分支处理
    */
   async validateMigrationPath(sourcePath: string, targetPath: string): Promise<{
     valid: boolean;
@@ -320,6 +614,10 @@ export const migrationAPI = {
       return result;
     } catch (error) {
       console.error("验证迁移路径失败:", error);
+      // 保留错误码，供调用方按 code 分支处理
+      if (isErrorInfo(error)) {
+        throw error;
+      }
       throw new Error(`验证迁移路径失败: ${error}`);
     }
   }
@@ -442,6 +740,19 @@ export const operationAPI = {
     }
   },
 
+  /**
+   * 按操作类型获取统计信息
+   * @returns 各操作类型的统计信息
+   */
+  async getOperationStatisticsByType(): Promise<OperationTypeStatistics[]> {
+    try {
+      return await invoke<OperationTypeStatistics[]>("get_operation_statistics_by_type");
+    } catch (error) {
+      console.error("获取分类操作统计失败:", error);
+      throw new Error(`获取分类操作统计失败: ${error}`);
+    }
+  },
+
   /**
    * 导出操作日志
    * @param outputPath 输出路径
@@ -501,6 +812,33 @@ export const appDataAPI = {
     }
   },
 
+  /**
+   * 分页读取最近一次AppData扫描的一级项目
+   * @param offset 起始位置
+   * @param limit 每页数量（可选，默认50）
+   */
+  async getAppDataItemsPage(offset: number, limit?: number): Promise<AppDataItemsPage> {
+    try {
+      return await invoke<AppDataItemsPage>("get_appdata_items_page", { offset, limit: limit ?? null });
+    } catch (error) {
+      console.error("API: 获取AppData项目分页失败:", error);
+      throw new Error(`获取AppData项目分页失败: ${error}`);
+    }
+  },
+
+  /**
+   * 列出已迁移（原位置是指向其他磁盘的链接）的AppData项目
+   * @param basePath AppData根目录（可选，默认当前用户）
+   */
+  async listMigratedAppDataItems(basePath?: string): Promise<AppDataFirstLevelItem[]> {
+    try {
+      return await invoke<AppDataFirstLevelItem[]>("list_migrated_appdata_items", { basePath: basePath ?? null });
+    } catch (error) {
+      console.error("API: 获取已迁移的AppData项目失败:", error);
+      throw new Error(`获取已迁移的AppData项目失败: ${error}`);
+    }
+  },
+
   /**
    * 流式扫描AppData目录 - 支持实时进度推送
    * @param options 扫描选项（可选）
@@ -628,6 +966,20 @@ export const appDataAPI = {
     }
   },
 
+  /**
+   * 获取指定用户的AppData路径
+   * @param username 用户名或用户配置文件目录
+   * @returns AppData路径
+   */
+  async getAppDataPathForUser(username: string): Promise<string> {
+    try {
+      return await invoke<string>("get_appdata_path_for_user", { username });
+    } catch (error) {
+      console.error("API: 获取用户AppData路径失败:", error);
+      throw new Error(`获取用户AppData路径失败: ${error}`);
+    }
+  },
+
   /**
    * 迁移AppData项目（增强版本，支持进度报告）
    * @param options 迁移选项
@@ -781,10 +1133,16 @@ export const appDataAPI = {
       path: string;
       valid: boolean;
       message?: string;
+      size: number;
     }>;
     summary: string;
     targetDriveValid: boolean;
     targetDrive: string;
+    totalBytes: number;
+    requiredBytes: number;
+    availableBytes: number | null;
+    reasons: string[];
+    warnings: string[];
   }> {
     try {
       const result = await invoke<{
@@ -793,10 +1151,16 @@ export const appDataAPI = {
           path: string;
           valid: boolean;
           message?: string;
+          size: number;
         }>;
         summary: string;
         target_drive_valid: boolean;
         target_drive: string;
+        total_bytes: number;
+        required_bytes: number;
+        available_bytes: number | null;
+        reasons: string[];
+        warnings?: string[];
       }>("validate_appdata_migration_options", { options });
       
       return {
@@ -804,7 +1168,12 @@ export const appDataAPI = {
         items: result.items,
         summary: result.summary,
         targetDriveValid: result.target_drive_valid,
-        targetDrive: result.target_drive
+        targetDrive: result.target_drive,
+        totalBytes: result.total_bytes,
+        requiredBytes: result.required_bytes,
+        availableBytes: result.available_bytes,
+        reasons: result.reasons,
+        warnings: result.warnings ?? []
       };
     } catch (error) {
       console.error('API: 验证迁移选项失败:', error);
@@ -902,7 +1271,22 @@ export const appDataAPI = {
    * @returns 格式化的错误消息
    */
   formatMigrationError(error: unknown): string {
-    if (error instanceof Error) {
+    if (isErrorInfo(error)) {
+      switch (error.code) {
+        case 'PERMISSION_DENIED':
+          return '迁移失败：权限不足，请确保有足够的权限访问源文件和目标位置';
+        case 'PATH_NOT_FOUND':
+          return '迁移失败：源文件或目标盘符不存在';
+        case 'PATH_ALREADY_EXISTS':
+          return '迁移失败：目标位置已存在同名文件或文件夹';
+        case 'OPERATION_CANCELLED':
+          return `迁移已取消：${error.message}`;
+        case 'TIMEOUT':
+          return '迁移失败：文件复制超时，请检查目标驱动器（如网络驱动器）是否可以访问';
+        default:
+          return `迁移失败：${error.message}`;
+      }
+    } else if (error instanceof Error) {
       // 常见的迁移错误类型
       const errorMessage = error.message.toLowerCase();
       
@@ -19,10 +19,29 @@ export type SortOrder = 'asc' | 'desc';
 export interface AppDataConfig {
   /** 最小大小阈值（默认1GB）- 用于筛选大文件 */
   minSizeThreshold: number;
-  /** 最大扫描深度（默认2层）- 限制扫描范围 */
+  /** 最大扫描深度（默认2层）- 从 Local/LocalLow/Roaming 算起，一级项目为第1层；项目大小总是完整统计，该深度只限制查找最近修改时间的范围 */
   maxDepth: number;
   /** 排序方式（默认desc）- 控制结果展示顺序 */
   sortOrder: SortOrder;
+  /** 按子树（Local/LocalLow/Roaming）设置的大文件夹阈值，未指定时使用 minSizeThreshold */
+  thresholds?: Record<string, number>;
+  /** 只保留超过指定天数未修改的项目，未指定时不筛选 */
+  minDaysSinceModified?: number;
+  /** 迁移推荐分中大小、闲置时间和分类三项的权重，未指定时使用默认值 */
+  scoreWeights?: MigrationScoreWeights;
+}
+
+/**
+ * 迁移推荐分权重
+ * 按权重之和归一化，全部为 0 时分数为 0
+ */
+export interface MigrationScoreWeights {
+  /** 大小权重（默认0.5） */
+  size: number;
+  /** 闲置时间权重（默认0.3） */
+  staleness: number;
+  /** 分类权重（默认0.2） */
+  category: number;
 }
 
 /**
@@ -45,6 +64,32 @@ export interface AppDataFirstLevelItem {
   isLarge: boolean;
   /** 占总大小百分比 - 用于可视化展示 */
   sizePercentage: number;
+  /** 最后修改时间：项目本身及扫描深度内所有条目中最新的修改时间（Rust SystemTime 序列化格式） */
+  lastModified?: { secs_since_epoch: number; nanos_since_epoch: number } | null;
+  /** 最后访问时间（Rust SystemTime 序列化格式） */
+  lastAccessed?: { secs_since_epoch: number; nanos_since_epoch: number } | null;
+  /** 距今未修改的天数 - 用于发现废弃数据 */
+  daysSinceModified?: number | null;
+  /** 按文件夹名称推断的分类（Browser、Developer、Games 等，未知为 Other） */
+  category?: string;
+  /** 是否已迁移（原位置是指向其他磁盘的链接），已迁移的项目不会出现在候选列表中 */
+  isSymlink?: boolean;
+  /** 已迁移项目的链接目标 */
+  linkTarget?: string | null;
+  /** 迁移推荐分（0~1）- 越大、越久未修改、越不像系统数据的项目越高，可用于按推荐程度排序 */
+  migrationScore?: number;
+}
+
+/**
+ * AppData 分类汇总 - 用于分类占比图表
+ */
+export interface AppDataCategorySummary {
+  /** 分类名称 */
+  category: string;
+  /** 分类下项目的总大小（字节） */
+  size: number;
+  /** 分类下的项目数 */
+  itemCount: number;
 }
 
 /**
@@ -61,8 +106,16 @@ export interface AppDataMigrationOptions {
   createSymlink: boolean;
   /** 是否删除源文件 - 空间清理选项 */
   deleteSource: boolean;
+  /** 目标盘上的目录布局，默认保留 Local/LocalLow/Roaming 结构 */
+  targetLayout?: TargetLayout;
 }
 
+/** AppData 项目在目标盘上的目录布局 */
+export type TargetLayout =
+  | "flat"
+  | "preserveSubtree"
+  | { underNamedFolder: string };
+
 /**
  * AppData 信息接口（更新版本）
  * 包含完整的AppData扫描结果和统计信息
@@ -89,6 +142,22 @@ export interface AppDataInfo {
   largeItems: AppDataFirstLevelItem[];
   /** 扫描耗时（毫秒）- 性能指标 */
   scanTimeMs: number;
+  /** 已迁移项目的总大小（字节）- 这部分数据已不占用C盘空间 */
+  alreadyFreedBytes?: number;
+}
+
+/**
+ * 一页AppData一级项目 - 用于懒加载大量项目
+ */
+export interface AppDataItemsPage {
+  /** 本页项目（按扫描时的排序） */
+  items: AppDataFirstLevelItem[];
+  /** 本页第一个项目的位置 */
+  offset: number;
+  /** 最近一次扫描的项目总数 */
+  total: number;
+  /** 之后是否还有项目 */
+  hasMore: boolean;
 }
 
 /**
@@ -99,7 +168,7 @@ export interface AppDataInfo {
 export interface AppDataScanOptions {
   /** 最小大小阈值（默认1GB）- 用于筛选大文件 */
   minSizeThreshold?: number;
-  /** 最大扫描深度（默认2层）- 限制扫描范围 */
+  /** 最大扫描深度（默认2层）- 从 Local/LocalLow/Roaming 算起，一级项目为第1层；项目大小总是完整统计，该深度只限制查找最近修改时间的范围 */
   maxDepth?: number;
   /** 排序方式（默认desc）- 控制结果展示顺序 */
   sortOrder?: SortOrder;
@@ -8,6 +8,8 @@ export interface DirectoryInfo {
   isExpanded?: boolean;
   isLargeFolder?: boolean;      // 新增：标识大文件夹
   sizePercentage?: number;      // 新增：相对父目录的占比
+  budgetExceeded?: boolean;     // 新增：扫描预算用尽，结果不完整
+  sizeOnDisk?: number | null;  // 新增：实际占用的磁盘空间（未启用统计时为空）
 }
 
 // 扫描进度接口
@@ -20,16 +22,22 @@ export interface ScanProgress {
   totalDirectories?: number;         // 新增：总目录数
   currentDirectory?: string;         // 新增：当前处理的目录
   estimatedTimeRemaining?: number;   // 新增：预计剩余时间（秒）
-  scanSpeed?: number;                // 新增：扫描速度（文件/秒）
+  scanSpeed?: number;                // 新增：扫描速度（文件/秒，滑动平均）
   largeFoldersFound?: number;        // 新增：发现的大文件夹数量
+  startTime?: { secs_since_epoch: number; nanos_since_epoch: number } | null; // 新增：扫描开始时间
+  cancellationReason?: CancellationReason | null; // 新增：扫描被取消的原因
 }
 
+// 扫描或迁移被取消的原因
+export type CancellationReason = 'user_requested' | 'timeout' | 'space_exhausted' | 'shutdown';
+
 // 迁移选项接口
 export interface MigrationOptions {
   sourcePath: string;
   targetPath: string;
   createSymlink: boolean;
   deleteSource: boolean;
+  operationTimeoutMs?: number;  // 新增：单个文件复制的时限（毫秒），不设置表示不限制
 }
 
 // 迁移结果接口